@@ -0,0 +1,134 @@
+//! Timers that run out at a time of day instead of after a duration.
+
+use crate::recurrence::Weekday;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use windows::Win32::System::Time::{
+    GetTimeZoneInformation, TIME_ZONE_ID_DAYLIGHT, TIME_ZONE_INFORMATION,
+};
+
+const DAY: i64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Repeat {
+    /// Armed by starting the timer, for the next time the time of day comes around
+    #[default]
+    Once,
+    Daily,
+    Weekly(Weekday),
+}
+
+impl Repeat {
+    pub fn is_repeating(self) -> bool {
+        self != Self::Once
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Alarm {
+    pub hour: u8,
+    pub minute: u8,
+    /// The time is in UTC rather than local time, like the game's schedules
+    pub utc: bool,
+    pub repeat: Repeat,
+}
+
+/// Seconds local time is ahead of UTC right now
+pub fn local_offset() -> i64 {
+    let mut info = TIME_ZONE_INFORMATION::default();
+    let id = unsafe { GetTimeZoneInformation(&mut info) };
+    let bias = info.Bias
+        + if id == TIME_ZONE_ID_DAYLIGHT {
+            info.DaylightBias
+        } else {
+            info.StandardBias
+        };
+    -(bias as i64) * 60
+}
+
+impl Alarm {
+    pub fn sanitize(&mut self) {
+        self.hour = self.hour.min(23);
+        self.minute = self.minute.min(59);
+    }
+
+    /// `20:00` or `20:00 UTC`
+    pub fn label(&self) -> String {
+        format!(
+            "{:02}:{:02}{}",
+            self.hour,
+            self.minute,
+            if self.utc { " UTC" } else { "" }
+        )
+    }
+
+    /// The first time after `now` the alarm goes off
+    pub fn next(&self, now: SystemTime) -> SystemTime {
+        let offset = if self.utc { 0 } else { local_offset() };
+        self.next_with_offset(now, offset)
+    }
+
+    /// Like [`Self::next`] with local time `offset` seconds ahead of UTC
+    fn next_with_offset(&self, now: SystemTime, offset: i64) -> SystemTime {
+        let utc = now.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as i64;
+        let local = utc + offset;
+        let day = local.div_euclid(DAY);
+        let mut next = day * DAY + self.hour as i64 * 3600 + self.minute as i64 * 60;
+        let period = match self.repeat {
+            Repeat::Once | Repeat::Daily => DAY,
+            Repeat::Weekly(weekday) => {
+                // the epoch was a thursday
+                let today = (day + 3).rem_euclid(7);
+                next += (weekday as i64 - today).rem_euclid(7) * DAY;
+                7 * DAY
+            }
+        };
+        if next <= local {
+            next += period;
+        }
+        UNIX_EPOCH + Duration::from_secs((next - offset).max(0) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: u64 = 3600;
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    fn alarm(hour: u8, repeat: Repeat) -> Alarm {
+        Alarm {
+            hour,
+            minute: 0,
+            utc: true,
+            repeat,
+        }
+    }
+
+    #[test]
+    fn next_time_of_day() {
+        let daily = alarm(20, Repeat::Daily);
+        // thursday 1970-01-01 10:00
+        assert_eq!(daily.next(at(10 * HOUR)), at(20 * HOUR));
+        assert_eq!(daily.next(at(20 * HOUR)), at(44 * HOUR));
+        // 20:00 in UTC+2 is 18:00 UTC
+        assert_eq!(
+            daily.next_with_offset(at(10 * HOUR), 2 * 3600),
+            at(18 * HOUR)
+        );
+    }
+
+    #[test]
+    fn next_weekday() {
+        let weekly = alarm(20, Repeat::Weekly(Weekday::Friday));
+        assert_eq!(weekly.next(at(10 * HOUR)), at(44 * HOUR));
+        let weekly = alarm(8, Repeat::Weekly(Weekday::Thursday));
+        assert_eq!(weekly.next(at(10 * HOUR)), at((7 * 24 + 8) * HOUR));
+    }
+}
@@ -0,0 +1,113 @@
+//! Timer windows placed relative to an edge or corner of the screen, so a layout
+//! survives changes of resolution and UI scale.
+
+use serde::{Deserialize, Serialize};
+
+/// Offsets further than this off the anchor are surely a typo
+pub const MAX_OFFSET: f32 = 10000.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AnchorPoint {
+    TopLeft,
+    #[default]
+    TopCenter,
+    TopRight,
+    LeftMiddle,
+    Center,
+    RightMiddle,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+impl AnchorPoint {
+    pub const ALL: [AnchorPoint; 9] = [
+        Self::TopLeft,
+        Self::TopCenter,
+        Self::TopRight,
+        Self::LeftMiddle,
+        Self::Center,
+        Self::RightMiddle,
+        Self::BottomLeft,
+        Self::BottomCenter,
+        Self::BottomRight,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::TopLeft => "Top left",
+            Self::TopCenter => "Top center",
+            Self::TopRight => "Top right",
+            Self::LeftMiddle => "Left middle",
+            Self::Center => "Center",
+            Self::RightMiddle => "Right middle",
+            Self::BottomLeft => "Bottom left",
+            Self::BottomCenter => "Bottom center",
+            Self::BottomRight => "Bottom right",
+        }
+    }
+
+    /// Where on the screen and on the window the point is, 0 to 1 along each axis
+    fn fraction(self) -> [f32; 2] {
+        let i = self as usize;
+        [(i % 3) as f32 / 2.0, (i / 3) as f32 / 2.0]
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Anchor {
+    pub point: AnchorPoint,
+    /// Pixels from the anchor point, positive is right and down
+    #[serde(default)]
+    pub offset: [f32; 2],
+}
+
+impl Anchor {
+    pub fn sanitize(&mut self) {
+        for o in &mut self.offset {
+            *o = if o.is_finite() {
+                o.clamp(-MAX_OFFSET, MAX_OFFSET)
+            } else {
+                0.0
+            };
+        }
+    }
+
+    /// Window position and pivot on a display of `display` size, the same point of the
+    /// window as of the screen sits at the anchor so it stays on screen
+    pub fn place(&self, display: [f32; 2]) -> ([f32; 2], [f32; 2]) {
+        let pivot = self.point.fraction();
+        let position = [
+            display[0] * pivot[0] + self.offset[0],
+            display[1] * pivot[1] + self.offset[1],
+        ];
+        (position, pivot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn places_relative_to_display() {
+        let anchor = Anchor {
+            point: AnchorPoint::BottomRight,
+            offset: [-10.0, -20.0],
+        };
+        assert_eq!(
+            anchor.place([1920.0, 1080.0]),
+            ([1910.0, 1060.0], [1.0, 1.0])
+        );
+        assert_eq!(
+            anchor.place([2560.0, 1440.0]),
+            ([2550.0, 1420.0], [1.0, 1.0])
+        );
+        let anchor = Anchor {
+            point: AnchorPoint::TopCenter,
+            offset: [0.0, 50.0],
+        };
+        assert_eq!(anchor.place([1920.0, 1080.0]), ([960.0, 50.0], [0.5, 0.0]));
+    }
+}
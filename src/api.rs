@@ -0,0 +1,49 @@
+//! Events other addons can raise to drive timers.
+//!
+//! All events take the timer name as a nul-terminated string payload:
+//! - `EV_TIMERS_START`: starts (or restarts) the timer
+//! - `EV_TIMERS_STOP`: stops the timer
+
+use crate::eventlog;
+use crate::inbox::{self, Request};
+use crate::timer::StartSource;
+use nexus::event::event_subscribe;
+use nexus::event_consume;
+use std::ffi::{c_char, CStr};
+
+pub const EV_START: &str = "EV_TIMERS_START";
+pub const EV_STOP: &str = "EV_TIMERS_STOP";
+
+fn payload_name(data: Option<&c_char>) -> Option<String> {
+    let data = data?;
+    // SAFETY: our events are documented to carry a nul-terminated string
+    let name = unsafe { CStr::from_ptr(data) };
+    Some(name.to_string_lossy().into_owned())
+}
+
+pub fn register() {
+    event_subscribe(
+        EV_START,
+        event_consume!(<c_char> |data| {
+            let Some(name) = payload_name(data) else {
+                log::warn!("{} raised without a timer name", EV_START);
+                eventlog::error("", format!("{} raised without a timer name", EV_START));
+                return;
+            };
+            inbox::send(Request::Start(name, StartSource::Api));
+        }),
+    )
+    .revert_on_unload();
+    event_subscribe(
+        EV_STOP,
+        event_consume!(<c_char> |data| {
+            let Some(name) = payload_name(data) else {
+                log::warn!("{} raised without a timer name", EV_STOP);
+                eventlog::error("", format!("{} raised without a timer name", EV_STOP));
+                return;
+            };
+            inbox::send(Request::Stop(name));
+        }),
+    )
+    .revert_on_unload();
+}
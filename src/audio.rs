@@ -0,0 +1,370 @@
+//! Synthesized alert tones, distinguishable by pitch and rhythm alone, and sounds
+//! picked from the `sounds` folder of the addon.
+
+use crate::sync::LockExt;
+use crate::worker::Worker;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use windows::core::{HSTRING, PCWSTR};
+use windows::Win32::Foundation::HMODULE;
+use windows::Win32::Media::Audio::{PlaySoundW, SND_FILENAME, SND_MEMORY, SND_NODEFAULT, SND_SYNC};
+
+const SAMPLE_RATE: u32 = 22050;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Critical,
+}
+
+impl Priority {
+    pub const ALL: [Priority; 4] = [Self::Low, Self::Normal, Self::High, Self::Critical];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Low => "Low",
+            Self::Normal => "Normal",
+            Self::High => "High",
+            Self::Critical => "Critical",
+        }
+    }
+
+    /// (frequency in Hz, length in ms) segments, a frequency of 0 is a pause.
+    /// Higher priorities are higher pitched and have more, faster beeps.
+    fn pattern(self) -> &'static [(f32, u32)] {
+        match self {
+            Self::Low => &[(440.0, 250)],
+            Self::Normal => &[(660.0, 150), (0.0, 100), (660.0, 150)],
+            Self::High => &[
+                (880.0, 100),
+                (0.0, 60),
+                (880.0, 100),
+                (0.0, 60),
+                (880.0, 100),
+            ],
+            Self::Critical => &[
+                (990.0, 80),
+                (1320.0, 80),
+                (0.0, 40),
+                (990.0, 80),
+                (1320.0, 80),
+                (0.0, 40),
+                (990.0, 80),
+                (1320.0, 160),
+            ],
+        }
+    }
+}
+
+/// What a sound is played for, each has its own volume
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundKind {
+    Start,
+    Warning,
+    Finish,
+    Mark,
+}
+
+/// Volumes of the addon's sounds from 0 to 100, each kind is scaled by the master one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Volumes {
+    pub master: u8,
+    pub start: u8,
+    pub warning: u8,
+    pub finish: u8,
+    pub mark: u8,
+}
+
+const FULL_VOLUME: Volumes = Volumes {
+    master: 100,
+    start: 100,
+    warning: 100,
+    finish: 100,
+    mark: 100,
+};
+
+impl Default for Volumes {
+    fn default() -> Self {
+        FULL_VOLUME
+    }
+}
+
+impl Volumes {
+    /// Volume of `kind` from 0 to 1
+    pub fn of(&self, kind: SoundKind) -> f32 {
+        let volume = match kind {
+            SoundKind::Start => self.start,
+            SoundKind::Warning => self.warning,
+            SoundKind::Finish => self.finish,
+            SoundKind::Mark => self.mark,
+        };
+        f32::from(self.master.min(100)) / 100.0 * f32::from(volume.min(100)) / 100.0
+    }
+
+    pub fn sanitize(&mut self) {
+        for volume in [
+            &mut self.master,
+            &mut self.start,
+            &mut self.warning,
+            &mut self.finish,
+            &mut self.mark,
+        ] {
+            *volume = (*volume).min(100);
+        }
+    }
+}
+
+// Set from the settings every tick, read when a sound is sent to be played
+static VOLUMES: Mutex<(Volumes, bool)> = Mutex::new((FULL_VOLUME, false));
+
+/// Volumes to play sounds at from now on, nothing is played while `muted`
+pub fn set_volumes(volumes: Volumes, muted: bool) {
+    *VOLUMES.locked() = (volumes, muted);
+}
+
+/// Renders a pattern as a 16 bit mono WAV file
+fn synthesize(pattern: &[(f32, u32)]) -> Vec<u8> {
+    // short fades at every edge so the tones don't click
+    let fade = SAMPLE_RATE as f32 * 0.005;
+    let mut samples = Vec::new();
+    for &(freq, ms) in pattern {
+        let len = SAMPLE_RATE * ms / 1000;
+        for i in 0..len {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let envelope = (i as f32 / fade).min((len - i) as f32 / fade).min(1.0);
+            let value = if freq > 0.0 {
+                (t * freq * std::f32::consts::TAU).sin() * envelope * 0.5
+            } else {
+                0.0
+            };
+            samples.push((value * i16::MAX as f32) as i16);
+        }
+    }
+    let data_len = samples.len() as u32 * 2;
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // mono
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for s in samples {
+        wav.extend_from_slice(&s.to_le_bytes());
+    }
+    wav
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Tone {
+    Alert(Priority),
+    /// Short tick for countdown announcements
+    Mark,
+}
+
+#[derive(Debug)]
+enum Sound {
+    Tone(Tone),
+    /// WAV file, e.g. one from the sounds folder
+    File(PathBuf),
+}
+
+#[derive(Debug)]
+struct Playback {
+    sound: Sound,
+    /// From 0 to 1
+    volume: f32,
+}
+
+/// A 16 bit PCM WAV file with its samples scaled by `volume`, `None` for other formats,
+/// which PlaySound can't be told a volume for
+fn scaled(wav: &[u8], volume: f32) -> Option<Vec<u8>> {
+    let mut out = wav.to_vec();
+    let (mut at, mut pcm16) = (12, false);
+    while at + 8 <= out.len() {
+        let id = &out[at..at + 4];
+        let len = u32::from_le_bytes(out[at + 4..at + 8].try_into().ok()?) as usize;
+        let body = at + 8;
+        if id == b"fmt " && body + 16 <= out.len() {
+            let format = u16::from_le_bytes([out[body], out[body + 1]]);
+            let bits = u16::from_le_bytes([out[body + 14], out[body + 15]]);
+            pcm16 = format == 1 && bits == 16;
+        } else if id == b"data" {
+            if !pcm16 {
+                return None;
+            }
+            let end = (body + len).min(out.len());
+            for sample in out[body..end].chunks_exact_mut(2) {
+                let value = i16::from_le_bytes([sample[0], sample[1]]);
+                let value = (f32::from(value) * volume) as i16;
+                sample.copy_from_slice(&value.to_le_bytes());
+            }
+            return Some(out);
+        }
+        // chunks are padded to an even length
+        at = body + len + len % 2;
+    }
+    None
+}
+
+const MARK_PATTERN: &[(f32, u32)] = &[(1200.0, 40)];
+
+fn tone(tone: Tone) -> &'static [u8] {
+    static TONES: OnceLock<Vec<Vec<u8>>> = OnceLock::new();
+    let tones = TONES.get_or_init(|| {
+        Priority::ALL
+            .iter()
+            .map(|p| synthesize(p.pattern()))
+            .chain([synthesize(MARK_PATTERN)])
+            .collect()
+    });
+    match tone {
+        Tone::Alert(priority) => &tones[priority as usize],
+        Tone::Mark => &tones[Priority::ALL.len()],
+    }
+}
+
+fn play_wav(wav: &[u8]) -> bool {
+    // synchronous so cues fired together are queued instead of cutting each other off
+    unsafe {
+        PlaySoundW(
+            PCWSTR(wav.as_ptr().cast()),
+            HMODULE::default(),
+            SND_MEMORY | SND_SYNC | SND_NODEFAULT,
+        )
+    }
+    .as_bool()
+}
+
+fn play(playback: Playback) {
+    let Playback { sound, volume } = playback;
+    let full = volume >= 1.0;
+    let played = match &sound {
+        Sound::Tone(which) if full => play_wav(tone(*which)),
+        Sound::Tone(which) => scaled(tone(*which), volume).is_some_and(|wav| play_wav(&wav)),
+        Sound::File(path) if full => unsafe {
+            PlaySoundW(
+                &HSTRING::from(path.to_string_lossy().as_ref()),
+                HMODULE::default(),
+                SND_FILENAME | SND_SYNC | SND_NODEFAULT,
+            )
+        }
+        .as_bool(),
+        Sound::File(path) => match std::fs::read(path) {
+            Ok(wav) => match scaled(&wav, volume) {
+                Some(scaled) => play_wav(&scaled),
+                None => {
+                    log::debug!(
+                        "Playing {} at full volume, it isn't 16 bit PCM",
+                        path.display()
+                    );
+                    play_wav(&wav)
+                }
+            },
+            Err(_) => false,
+        },
+    };
+    if !played {
+        log::warn!("Failed to play {:?}", sound);
+        crate::eventlog::error("", format!("failed to play {:?}", sound));
+    }
+}
+
+static AUDIO: Worker<Playback> = Worker::new("audio", Duration::from_secs(30), play);
+
+fn send(sound: Sound, kind: SoundKind) {
+    let (volumes, muted) = *VOLUMES.locked();
+    let volume = volumes.of(kind);
+    if muted || volume <= 0.0 {
+        return;
+    }
+    AUDIO.send(Playback { sound, volume });
+}
+
+/// Plays the tone of `priority` in the background
+pub fn cue(priority: Priority, kind: SoundKind) {
+    send(Sound::Tone(Tone::Alert(priority)), kind);
+}
+
+/// Plays the countdown announcement tick in the background
+pub fn mark() {
+    send(Sound::Tone(Tone::Mark), SoundKind::Mark);
+}
+
+fn sounds_dir() -> PathBuf {
+    crate::config::addon_dir().join("sounds")
+}
+
+/// The WAV files in the sounds folder, by file name
+pub fn sound_files() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(sounds_dir()) else {
+        return Vec::new();
+    };
+    let mut files: Vec<String> = entries
+        .filter_map(|e| e.ok()?.file_name().into_string().ok())
+        .filter(|name| name.to_lowercase().ends_with(".wav"))
+        .collect();
+    files.sort();
+    files
+}
+
+/// Plays `file` from the sounds folder if one is picked, otherwise the tone of
+/// `priority`, in the background
+pub fn cue_or_file(priority: Priority, file: &str, kind: SoundKind) {
+    if file.is_empty() {
+        cue(priority, kind);
+    } else {
+        play_file(file, kind);
+    }
+}
+
+/// Plays a file from the sounds folder in the background
+pub fn play_file(file: &str, kind: SoundKind) {
+    send(Sound::File(sounds_dir().join(file)), kind);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples(wav: &[u8]) -> Vec<i16> {
+        wav[44..]
+            .chunks_exact(2)
+            .map(|s| i16::from_le_bytes([s[0], s[1]]))
+            .collect()
+    }
+
+    #[test]
+    fn volume_scales_the_samples() {
+        let wav = synthesize(&[(440.0, 10)]);
+        let half = scaled(&wav, 0.5).expect("a 16 bit PCM file");
+        assert_eq!(half.len(), wav.len());
+        assert_eq!(half[..44], wav[..44]);
+        for (full, half) in samples(&wav).into_iter().zip(samples(&half)) {
+            assert_eq!(half, (f32::from(full) * 0.5) as i16);
+        }
+        let mut eight_bit = wav.clone();
+        eight_bit[34] = 8;
+        assert_eq!(scaled(&eight_bit, 0.5), None);
+
+        let volumes = Volumes {
+            master: 50,
+            mark: 50,
+            ..Volumes::default()
+        };
+        assert_eq!(volumes.of(SoundKind::Start), 0.5);
+        assert_eq!(volumes.of(SoundKind::Mark), 0.25);
+    }
+}
@@ -0,0 +1,195 @@
+//! Snapshots of timers.json and layout.json in `backups/`, so a bad bulk edit or import
+//! can be rolled back from the options.
+//!
+//! One is taken on load, every quarter hour the timers changed in, on unload and before a
+//! restore. Each is `<unix secs>-<timer count>.json`, listing them doesn't read them.
+
+use crate::config::{self, StoredConfig};
+use crate::layout::Layout;
+use crate::sync::LockExt;
+use crate::timer::{Timer, Timers};
+use crate::worker::Worker;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+pub const DEFAULT_KEEP: u32 = 10;
+pub const MAX_KEEP: u32 = 100;
+const INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    /// As in timers.json
+    timers: Value,
+    layout: Layout,
+}
+
+/// A snapshot on disk, newest first from [`list`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backup {
+    pub path: PathBuf,
+    /// Unix time in seconds
+    pub at: u64,
+    pub timers: usize,
+}
+
+fn dir() -> PathBuf {
+    config::addon_dir().join("backups")
+}
+
+fn file_name(at: u64, timers: usize) -> String {
+    format!("{}-{}.json", at, timers)
+}
+
+fn parse_name(name: &str) -> Option<(u64, usize)> {
+    let (at, timers) = name.strip_suffix(".json")?.split_once('-')?;
+    Some((at.parse().ok()?, timers.parse().ok()?))
+}
+
+pub fn list() -> Vec<Backup> {
+    let Ok(entries) = std::fs::read_dir(dir()) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<Backup> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let (at, timers) = parse_name(&entry.file_name().to_string_lossy())?;
+            Some(Backup {
+                path: entry.path(),
+                at,
+                timers,
+            })
+        })
+        .collect();
+    backups.sort_by_key(|b| std::cmp::Reverse(b.at));
+    backups
+}
+
+/// What a snapshot is taken of, copied out holding the timers and serialized off them
+pub struct Pending {
+    include: Vec<String>,
+    /// Packs too, for their layout
+    timers: Vec<Timer>,
+}
+
+impl Pending {
+    pub fn of(timers: &Timers) -> Self {
+        Self {
+            include: timers.include.clone(),
+            timers: timers
+                .list
+                .iter()
+                .filter(|t| t.system.is_none())
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+struct Job {
+    pending: Pending,
+    keep: u32,
+}
+
+// The contents of the latest snapshot, not to write the same one again
+static LATEST: Mutex<Option<String>> = Mutex::new(None);
+// When the latest snapshot was taken, or found unchanged
+static TAKEN_AT: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Writes the snapshot of `job` unless it is the same as the latest, then removes those
+/// past the `keep` newest
+fn write(job: Job) {
+    let Job { pending, keep } = job;
+    if keep == 0 {
+        return;
+    }
+    let json = config::timers_json(&pending.include, &pending.timers);
+    let snapshot = Snapshot {
+        timers: serde_json::from_str(&json).expect("Timers to be parsed back"),
+        layout: Layout::of(&pending.timers),
+    };
+    let contents = serde_json::to_string(&snapshot).expect("Snapshot to be serialized");
+    let mut latest = LATEST.locked();
+    if latest.is_none() {
+        // the one written on the last unload, the timers are usually unchanged since
+        *latest = list()
+            .first()
+            .and_then(|b| std::fs::read_to_string(&b.path).ok());
+    }
+    if latest.as_ref() == Some(&contents) {
+        return;
+    }
+    let count = pending.timers.iter().filter(|t| t.pack.is_none()).count();
+    let at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let dir = dir();
+    let path = dir.join(file_name(at, count));
+    let result = std::fs::create_dir_all(&dir).and_then(|()| std::fs::write(&path, &contents));
+    if let Err(e) = result {
+        log::warn!("Failed to write backup {}: {}", path.display(), e);
+        return;
+    }
+    *latest = Some(contents);
+    drop(latest);
+    for old in list().into_iter().skip(keep as usize) {
+        if let Err(e) = std::fs::remove_file(&old.path) {
+            log::warn!("Failed to remove backup {}: {}", old.path.display(), e);
+        }
+    }
+}
+
+static WRITER: Worker<Job> = Worker::new("backup", Duration::from_secs(10), write);
+
+/// Snapshots `pending` in the background, keeping the latest `keep`
+pub fn take(pending: Pending, keep: u32) {
+    WRITER.send(Job { pending, keep });
+}
+
+/// Snapshots `pending` right away, for when the workers are already stopped or the
+/// timers are about to be replaced
+pub fn take_now(pending: Pending, keep: u32) {
+    write(Job { pending, keep });
+}
+
+/// What to snapshot once `INTERVAL` passed since the latest, called every tick holding
+/// the timers. Handed to [`take`] after letting go of them.
+pub fn collect(timers: &Timers, keep: u32) -> Option<Pending> {
+    if keep == 0 {
+        return None;
+    }
+    let mut taken = TAKEN_AT.locked();
+    if taken.is_some_and(|at| at.elapsed() < INTERVAL) {
+        return None;
+    }
+    // unchanged timers aren't written, wait for the next interval all the same
+    *taken = Some(Instant::now());
+    Some(Pending::of(timers))
+}
+
+/// Our own timers of the snapshot at `path`, with their layout
+pub fn read(path: &Path) -> Result<Vec<Timer>, String> {
+    let contents = std::fs::read(path).map_err(|e| e.to_string())?;
+    let snapshot: Snapshot = serde_json::from_slice(&contents).map_err(|e| e.to_string())?;
+    let stored = StoredConfig::try_from(snapshot.timers)?;
+    let mut timers = stored.config.timers;
+    snapshot.layout.apply(&mut timers);
+    Ok(timers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_of_snapshots() {
+        let name = file_name(1_700_000_000, 42);
+        assert_eq!(name, "1700000000-42.json");
+        assert_eq!(parse_name(&name), Some((1_700_000_000, 42)));
+        assert_eq!(parse_name("1700000000-42.json.tmp"), None);
+        assert_eq!(parse_name("notes.json"), None);
+    }
+}
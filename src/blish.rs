@@ -0,0 +1,296 @@
+//! Conversion from and to the timer files of the Blish HUD timers module (`.bhtimer`).
+//!
+//! Those describe whole encounters: phases started by triggers at map positions, each
+//! with alerts at timestamps into the phase. Here every alert timestamp becomes a timer
+//! of its own, grouped by encounter. Triggers, directions and world markers have no
+//! equivalent and are dropped, and exported files have no triggers, so they have to be
+//! added in Blish HUD before the timers start on their own.
+
+use crate::import::{self, Import, ImportError};
+use crate::timer::Timer;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const EXTENSION: &str = "bhtimer";
+/// Length of the alert Blish HUD shows once a timestamp is reached
+const ALERT_DURATION: f32 = 5.0;
+/// `extra` key of the encounter id, so exported files keep the id they were imported with
+const ID_KEY: &str = "blish_id";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BlishTimer {
+    pub id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub category: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub author: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub map: Option<u32>,
+    pub enabled: bool,
+    pub phases: Vec<Phase>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Phase {
+    pub name: String,
+    /// Trigger starting the phase, only carried along
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<serde_json::Value>,
+    pub alerts: Vec<Alert>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub directions: Vec<serde_json::Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub markers: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Alert {
+    /// Shown for `warning_duration` seconds before each timestamp
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub warning: String,
+    pub warning_duration: f32,
+    /// Shown once a timestamp is reached
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub alert: String,
+    pub alert_duration: f32,
+    /// Seconds into the phase
+    pub timestamps: Vec<f32>,
+}
+
+fn secs(s: f32) -> Duration {
+    Duration::try_from_secs_f32(s).unwrap_or_default()
+}
+
+/// Our timers for one encounter, with warnings about what couldn't be converted
+pub fn convert(encounter: BlishTimer) -> (Vec<Timer>, Vec<String>) {
+    let mut timers = Vec::new();
+    let mut warnings = Vec::new();
+    let several_phases = encounter.phases.len() > 1;
+    for (i, phase) in encounter.phases.iter().enumerate() {
+        if !phase.directions.is_empty() || !phase.markers.is_empty() {
+            warnings.push(format!(
+                "Dropped directions and markers of {} {}, they are placed in the world",
+                encounter.name, phase.name
+            ));
+        }
+        if i > 0 && !phase.alerts.is_empty() {
+            warnings.push(format!(
+                "Timers of {} {} count from the start of that phase, start them by hand",
+                encounter.name, phase.name
+            ));
+        }
+        for alert in &phase.alerts {
+            let text = [&alert.alert, &alert.warning]
+                .into_iter()
+                .find(|t| !t.is_empty())
+                .unwrap_or(&phase.name);
+            let name = if several_phases {
+                format!("{}: {}", phase.name, text)
+            } else {
+                text.clone()
+            };
+            for (n, &at) in alert.timestamps.iter().enumerate() {
+                let name = if n == 0 {
+                    name.clone()
+                } else {
+                    format!("{} #{}", name, n + 1)
+                };
+                let mut timer = Timer::new(0, name, secs(at));
+                timer.group = encounter.name.clone();
+                timer.note = encounter.description.clone();
+                if alert.warning_duration > 0.0 {
+                    timer.marks = vec![secs(alert.warning_duration)];
+                }
+                if !encounter.id.is_empty() {
+                    timer
+                        .extra
+                        .insert(ID_KEY.to_string(), encounter.id.clone().into());
+                }
+                timers.push(timer);
+            }
+        }
+    }
+    (timers, warnings)
+}
+
+/// Reads every `.bhtimer` file in `dir`
+pub fn import_dir(dir: &Path) -> Result<Import, ImportError> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(ImportError::Io)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == EXTENSION))
+        .collect();
+    paths.sort();
+    let mut timers = Vec::new();
+    let mut warnings = Vec::new();
+    for path in paths {
+        let bytes = import::read_file(&path)?;
+        let encounter = match serde_json::from_slice(&bytes) {
+            Ok(e) => e,
+            Err(e) => {
+                warnings.push(format!("Skipped {}: {}", path.display(), e));
+                continue;
+            }
+        };
+        let (converted, converted_warnings) = convert(encounter);
+        timers.extend(converted);
+        warnings.extend(converted_warnings);
+    }
+    import::validate(timers, warnings)
+}
+
+/// One encounter per group, a single phase with an alert per timer
+pub fn export<'a>(timers: impl IntoIterator<Item = &'a Timer>) -> Vec<BlishTimer> {
+    let mut encounters: Vec<BlishTimer> = Vec::new();
+    for timer in timers.into_iter().filter(|t| t.system.is_none()) {
+        let name = if timer.group.is_empty() {
+            "Nexus Timers"
+        } else {
+            timer.group.as_str()
+        };
+        let index = match encounters.iter().position(|e| e.name == name) {
+            Some(i) => i,
+            None => {
+                let id = timer
+                    .extra
+                    .get(ID_KEY)
+                    .and_then(|id| id.as_str())
+                    .map_or_else(|| slug(name), str::to_string);
+                encounters.push(BlishTimer {
+                    id,
+                    name: name.to_string(),
+                    category: "Nexus Timers".to_string(),
+                    description: "Exported from Nexus Timers, add triggers to start it".to_string(),
+                    enabled: true,
+                    phases: vec![Phase {
+                        name: "Start".to_string(),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                });
+                encounters.len() - 1
+            }
+        };
+        let warning = timer.marks.iter().max().copied().unwrap_or_default();
+        encounters[index].phases[0].alerts.push(Alert {
+            warning: format!("{} soon", timer.name),
+            warning_duration: warning.as_secs_f32(),
+            alert: timer.name.clone(),
+            alert_duration: ALERT_DURATION,
+            timestamps: vec![(timer.delay + timer.duration).as_secs_f32()],
+        });
+    }
+    encounters
+}
+
+/// Writes each exported encounter to `dir`, returns how many files were written
+pub fn export_dir<'a>(
+    dir: &Path,
+    timers: impl IntoIterator<Item = &'a Timer>,
+) -> std::io::Result<usize> {
+    std::fs::create_dir_all(dir)?;
+    let encounters = export(timers);
+    for encounter in &encounters {
+        let json = serde_json::to_string_pretty(encounter).map_err(std::io::Error::other)?;
+        // ids of imported files could be anything
+        let path = dir.join(slug(&encounter.id)).with_extension(EXTENSION);
+        std::fs::write(path, json)?;
+    }
+    Ok(encounters.len())
+}
+
+/// File name safe id, `Vale Guardian` becomes `vale_guardian`
+fn slug(name: &str) -> String {
+    let slug: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    match slug.trim_matches('_') {
+        "" => "timers".to_string(),
+        slug => slug.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEC: Duration = Duration::from_secs(1);
+
+    fn encounter() -> BlishTimer {
+        BlishTimer {
+            id: "vg".to_string(),
+            name: "Vale Guardian".to_string(),
+            phases: vec![
+                Phase {
+                    name: "Phase 1".to_string(),
+                    alerts: vec![Alert {
+                        warning: "Split soon".to_string(),
+                        warning_duration: 5.0,
+                        alert: "Split".to_string(),
+                        timestamps: vec![30.0, 60.0],
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                },
+                Phase {
+                    name: "Phase 2".to_string(),
+                    alerts: vec![Alert {
+                        warning: "Seekers".to_string(),
+                        timestamps: vec![10.0],
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn converts_alert_timestamps_to_timers() {
+        let (timers, warnings) = convert(encounter());
+        let summary: Vec<_> = timers
+            .iter()
+            .map(|t| (t.name.as_str(), t.duration, t.marks.clone()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Phase 1: Split", 30 * SEC, vec![5 * SEC]),
+                ("Phase 1: Split #2", 60 * SEC, vec![5 * SEC]),
+                ("Phase 2: Seekers", 10 * SEC, vec![]),
+            ]
+        );
+        assert!(timers.iter().all(|t| t.group == "Vale Guardian"));
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn exports_one_encounter_per_group() {
+        let (mut timers, _) = convert(encounter());
+        timers.push(Timer::new(0, "Greens".to_string(), 20 * SEC));
+        let encounters = export(&timers);
+        assert_eq!(encounters.len(), 2);
+        assert_eq!(encounters[0].id, "vg");
+        assert_eq!(encounters[0].phases[0].alerts.len(), 3);
+        assert_eq!(encounters[1].id, "nexus_timers");
+        let alert = &encounters[1].phases[0].alerts[0];
+        assert_eq!(alert.alert, "Greens");
+        assert_eq!(alert.timestamps, vec![20.0]);
+    }
+}
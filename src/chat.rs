@@ -0,0 +1,182 @@
+//! Timers started by squad and party chat, e.g. the commander typing "burn in 30".
+//!
+//! Nexus itself has no chat events, messages come from the Unofficial Extras addon
+//! which raises `EV_UNOFFICIAL_EXTRAS_SQUAD_CHAT_MESSAGE` for each squad or party
+//! message. Without it installed no trigger ever fires.
+
+use crate::inbox::{self, Request};
+use crate::timer::Timer;
+use nexus::event::event_subscribe;
+use nexus::event_consume;
+use regex::RegexBuilder;
+use serde::{Deserialize, Serialize};
+use std::ffi::c_char;
+
+pub const EV_SQUAD_CHAT_MESSAGE: &str = "EV_UNOFFICIAL_EXTRAS_SQUAD_CHAT_MESSAGE";
+
+/// Keeps a pattern from an imported file from taking up a lot of memory
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatChannel {
+    /// Squad and party
+    #[default]
+    Any,
+    Squad,
+    Party,
+}
+
+impl ChatChannel {
+    pub const ALL: [ChatChannel; 3] = [Self::Any, Self::Squad, Self::Party];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Any => "Squad and party",
+            Self::Squad => "Squad only",
+            Self::Party => "Party only",
+        }
+    }
+
+    fn accepts(self, channel: ChatChannel) -> bool {
+        self == Self::Any || self == channel
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatTrigger {
+    /// Found anywhere in a message, ignoring case
+    pub keyword: String,
+    /// `keyword` is a regular expression
+    pub regex: bool,
+    pub channel: ChatChannel,
+}
+
+impl ChatTrigger {
+    /// Why the regex of the trigger can't be used
+    pub fn error(&self) -> Option<String> {
+        if !self.regex {
+            return None;
+        }
+        self.build().err()
+    }
+
+    fn build(&self) -> Result<regex::Regex, String> {
+        RegexBuilder::new(&self.keyword)
+            .case_insensitive(true)
+            .size_limit(REGEX_SIZE_LIMIT)
+            .build()
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn matches(&self, channel: ChatChannel, text: &str) -> bool {
+        if self.keyword.is_empty() || !self.channel.accepts(channel) {
+            return false;
+        }
+        if self.regex {
+            return self.build().is_ok_and(|r| r.is_match(text));
+        }
+        text.to_lowercase().contains(&self.keyword.to_lowercase())
+    }
+}
+
+/// The timers to start for a message
+pub fn triggered<'a>(
+    timers: impl IntoIterator<Item = &'a Timer>,
+    channel: ChatChannel,
+    text: &str,
+) -> Vec<String> {
+    timers
+        .into_iter()
+        .filter(|t| t.enabled)
+        .filter(|t| t.chat.as_ref().is_some_and(|c| c.matches(channel, text)))
+        .map(|t| t.name.clone())
+        .collect()
+}
+
+/// `SquadMessageInfo` of Unofficial Extras, not every field is read
+#[repr(C)]
+struct SquadMessageInfo {
+    channel_id: u32,
+    /// 0 party, 1 squad, anything else isn't chat
+    channel_type: u8,
+    subgroup: u8,
+    is_broadcast: u8,
+    _unused: u8,
+    timestamp: *const c_char,
+    timestamp_length: u64,
+    account_name: *const c_char,
+    account_name_length: u64,
+    character_name: *const c_char,
+    character_name_length: u64,
+    text: *const c_char,
+    text_length: u64,
+}
+
+impl SquadMessageInfo {
+    fn channel(&self) -> Option<ChatChannel> {
+        match self.channel_type {
+            0 => Some(ChatChannel::Party),
+            1 => Some(ChatChannel::Squad),
+            _ => None,
+        }
+    }
+
+    fn text(&self) -> String {
+        if self.text.is_null() {
+            return String::new();
+        }
+        // SAFETY: Extras passes the text with its length, valid for the event
+        let bytes = unsafe {
+            std::slice::from_raw_parts(self.text as *const u8, self.text_length as usize)
+        };
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+}
+
+pub fn register() {
+    event_subscribe(
+        EV_SQUAD_CHAT_MESSAGE,
+        event_consume!(<SquadMessageInfo> |data| {
+            let Some(message) = data else {
+                return;
+            };
+            let Some(channel) = message.channel() else {
+                return;
+            };
+            inbox::send(Request::Chat(channel, message.text()));
+        }),
+    )
+    .revert_on_unload();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn keywords_and_channels() {
+        let mut burn = Timer::new(1, "Burn".to_string(), Duration::from_secs(30));
+        burn.chat = Some(ChatTrigger {
+            keyword: "burn in".to_string(),
+            regex: false,
+            channel: ChatChannel::Squad,
+        });
+        let mut split = Timer::new(2, "Split".to_string(), Duration::from_secs(10));
+        split.chat = Some(ChatTrigger {
+            keyword: r"^split\s+\d+$".to_string(),
+            regex: true,
+            channel: ChatChannel::Any,
+        });
+        let timers = [burn, split];
+        assert_eq!(
+            triggered(&timers, ChatChannel::Squad, "BURN IN 30"),
+            ["Burn"]
+        );
+        assert!(triggered(&timers, ChatChannel::Party, "burn in 30").is_empty());
+        assert_eq!(triggered(&timers, ChatChannel::Party, "split 2"), ["Split"]);
+        assert!(triggered(&timers, ChatChannel::Party, "split now").is_empty());
+    }
+}
@@ -0,0 +1,197 @@
+use crate::sync::LockExt;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Source of the current time for the timer engine, so it can be driven by tests
+pub trait Clock: fmt::Debug + Send {
+    fn now(&self) -> Instant;
+    /// The current wall clock time
+    fn wall(&self) -> SystemTime;
+}
+
+#[derive(Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn wall(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// A clock that only moves when told to, for tests and session replay.
+/// Its wall clock starts at the unix epoch.
+#[derive(Debug, Clone)]
+pub struct ManualClock(std::sync::Arc<Mutex<(Instant, SystemTime)>>);
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self(std::sync::Arc::new(Mutex::new((
+            Instant::now(),
+            UNIX_EPOCH,
+        ))))
+    }
+
+    pub fn advance(&self, by: Duration) {
+        let mut times = self.0.locked();
+        times.0 += by;
+        times.1 += by;
+    }
+
+    /// Moves only the wall clock, like a clock change or `Instant`s not counting standby
+    #[cfg(test)]
+    pub fn shift_wall(&self, by: Duration) {
+        self.0.locked().1 += by;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.0.locked().0
+    }
+
+    fn wall(&self) -> SystemTime {
+        self.0.locked().1
+    }
+}
+
+pub const MIN_SCALE: f32 = 0.1;
+pub const MAX_SCALE: f32 = 10.0;
+
+/// Runs another clock faster or slower, to go through long timelines in practice
+/// without waiting. Changing the speed carries on from the current time. The wall
+/// clock isn't scaled, it always tells the real time.
+#[derive(Debug)]
+pub struct Scaled {
+    inner: Box<dyn Clock>,
+    scale: f32,
+    // Time of the inner clock when the speed last changed, and ours at that point
+    base: Instant,
+    scaled: Instant,
+}
+
+impl Scaled {
+    pub fn new(inner: Box<dyn Clock>) -> Self {
+        let now = inner.now();
+        Self {
+            inner,
+            scale: 1.0,
+            base: now,
+            scaled: now,
+        }
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    pub fn set_scale(&mut self, scale: f32) {
+        let scale = scale.clamp(MIN_SCALE, MAX_SCALE);
+        if scale == self.scale {
+            return;
+        }
+        self.scaled = self.now();
+        self.base = self.inner.now();
+        self.scale = scale;
+    }
+}
+
+impl Clock for Scaled {
+    fn now(&self) -> Instant {
+        let passed = self.inner.now() - self.base;
+        self.scaled + passed.mul_f64(f64::from(self.scale))
+    }
+
+    fn wall(&self) -> SystemTime {
+        self.inner.wall()
+    }
+}
+
+/// Jumps of the wall clock smaller than this are treated as noise
+const JUMP_THRESHOLD: f64 = 2.0;
+/// Going this long without a single poll means the machine was in standby
+const SUSPEND_THRESHOLD: Duration = Duration::from_secs(30);
+
+/// Compares the wall clock against the monotonic clock to notice when the
+/// system time is changed underneath us (NTP corrections, DST, manual changes)
+/// or the machine was suspended.
+struct ClockWatch {
+    instant: Instant,
+    system: SystemTime,
+}
+
+static WATCH: Mutex<Option<ClockWatch>> = Mutex::new(None);
+
+pub enum ClockEvent {
+    /// The wall clock jumped by this many seconds
+    Jump(f64),
+    /// Nothing ran for `away`, of which `counted` is included in `Instant`s
+    /// (whether time spent suspended counts depends on the platform)
+    Suspended { away: Duration, counted: Duration },
+}
+
+fn signed_secs(a: SystemTime, b: SystemTime) -> f64 {
+    match a.duration_since(b) {
+        Ok(d) => d.as_secs_f64(),
+        Err(e) => -e.duration().as_secs_f64(),
+    }
+}
+
+/// Checks the clocks against the last call.
+///
+/// Anything based on `Instant` is unaffected by clock jumps, only deadlines that
+/// are defined in wall-clock time need to be recomputed.
+pub fn poll() -> Option<ClockEvent> {
+    let now = ClockWatch {
+        instant: Instant::now(),
+        system: SystemTime::now(),
+    };
+    let mut watch = WATCH.locked();
+    let event = watch.as_ref().and_then(|last| {
+        let wall = signed_secs(now.system, last.system);
+        let mono = now.instant - last.instant;
+        let away = mono.max(Duration::from_secs_f64(wall.max(0.0)));
+        if away >= SUSPEND_THRESHOLD {
+            return Some(ClockEvent::Suspended {
+                away,
+                counted: mono,
+            });
+        }
+        let jump = wall - mono.as_secs_f64();
+        (jump.abs() >= JUMP_THRESHOLD).then_some(ClockEvent::Jump(jump))
+    });
+    *watch = Some(now);
+    event
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scaled_clock_carries_on() {
+        let manual = ManualClock::new();
+        let mut clock = Scaled::new(Box::new(manual.clone()));
+        let (start, wall) = (clock.now(), clock.wall());
+        manual.advance(Duration::from_secs(10));
+        assert_eq!(clock.now() - start, Duration::from_secs(10));
+        clock.set_scale(2.0);
+        manual.advance(Duration::from_secs(10));
+        assert_eq!(clock.now() - start, Duration::from_secs(30));
+        clock.set_scale(0.5);
+        manual.advance(Duration::from_secs(10));
+        assert_eq!(clock.now() - start, Duration::from_secs(35));
+        // the wall clock keeps to real time
+        assert_eq!(clock.wall(), wall + Duration::from_secs(30));
+        clock.set_scale(1.0);
+        manual.advance(Duration::from_secs(10));
+        assert_eq!(clock.now() - start, Duration::from_secs(45));
+        assert_eq!(clock.wall(), wall + Duration::from_secs(40));
+        clock.set_scale(100.0);
+        assert_eq!(clock.scale(), MAX_SCALE);
+    }
+}
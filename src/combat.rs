@@ -0,0 +1,209 @@
+//! Timers started by skill casts and buff applications seen in combat, e.g. a boss
+//! casting a mechanic.
+//!
+//! Combat data comes from the ArcDPS bridge of Nexus, which raises
+//! `EV_ARCDPS_COMBATEVENT_LOCAL_RAW` for every event arcdps sees in the area. Without
+//! arcdps installed no trigger ever fires.
+
+use crate::inbox::{self, Request};
+use crate::store::Store;
+use crate::sync::LockExt;
+use crate::timer::Timer;
+use nexus::event::event_subscribe;
+use nexus::event_consume;
+use serde::{Deserialize, Serialize};
+use std::ffi::c_char;
+use std::sync::Mutex;
+
+pub const EV_COMBAT_LOCAL: &str = "EV_ARCDPS_COMBATEVENT_LOCAL_RAW";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CombatKind {
+    #[default]
+    SkillCast,
+    BuffApply,
+}
+
+impl CombatKind {
+    pub const ALL: [CombatKind; 2] = [Self::SkillCast, Self::BuffApply];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::SkillCast => "Skill cast",
+            Self::BuffApply => "Buff applied",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CombatTrigger {
+    pub kind: CombatKind,
+    /// Skill or buff ids, any of them starts the timer
+    pub ids: Vec<u32>,
+}
+
+// What some timer is started by, kept up to date by `watch` so the flood of combat
+// events nobody waits for doesn't lock the timers
+static WATCHED: Mutex<Vec<(CombatKind, u32)>> = Mutex::new(Vec::new());
+
+/// Keeps track of the triggers of `timers`, called every tick
+pub fn watch(timers: &Store) {
+    let wanted = || {
+        timers
+            .iter()
+            .filter(|t| t.enabled)
+            .filter_map(|t| t.combat.as_ref())
+            .flat_map(|c| c.ids.iter().map(move |id| (c.kind, *id)))
+    };
+    let mut watched = WATCHED.locked();
+    if wanted().count() == watched.len() && wanted().all(|w| watched.contains(&w)) {
+        return;
+    }
+    *watched = wanted().collect();
+    log::debug!("Watching {} skills and buffs in combat", watched.len());
+}
+
+/// The timers to start for a skill cast or buff application
+pub fn triggered<'a>(
+    timers: impl IntoIterator<Item = &'a Timer>,
+    kind: CombatKind,
+    id: u32,
+) -> Vec<String> {
+    timers
+        .into_iter()
+        .filter(|t| t.enabled)
+        .filter(|t| {
+            t.combat
+                .as_ref()
+                .is_some_and(|c| c.kind == kind && c.ids.contains(&id))
+        })
+        .map(|t| t.name.clone())
+        .collect()
+}
+
+/// `cbtevent` of arcdps, not every field is read
+#[repr(C)]
+#[derive(Default)]
+struct CombatEvent {
+    time: u64,
+    src_agent: u64,
+    dst_agent: u64,
+    value: i32,
+    buff_dmg: i32,
+    overstack_value: u32,
+    skill_id: u32,
+    src_instid: u16,
+    dst_instid: u16,
+    src_master_instid: u16,
+    dst_master_instid: u16,
+    iff: u8,
+    buff: u8,
+    result: u8,
+    is_activation: u8,
+    is_buffremove: u8,
+    is_ninety: u8,
+    is_fifty: u8,
+    is_moving: u8,
+    is_statechange: u8,
+    is_flanking: u8,
+    is_shields: u8,
+    is_offcycle: u8,
+    pad: [u8; 4],
+}
+
+impl CombatEvent {
+    /// What the event is for the triggers, if anything
+    fn trigger(&self) -> Option<(CombatKind, u32)> {
+        if self.is_statechange != 0 {
+            return None;
+        }
+        match self.is_activation {
+            // a normal or quickness-affected start of a cast
+            1 | 2 => return Some((CombatKind::SkillCast, self.skill_id)),
+            0 => {}
+            _ => return None,
+        }
+        // an application carries its duration, buff damage has none
+        let applied = self.buff != 0 && self.is_buffremove == 0 && self.buff_dmg == 0;
+        (applied && self.value != 0).then_some((CombatKind::BuffApply, self.skill_id))
+    }
+}
+
+/// `EvCombatData` of the ArcDPS bridge
+#[repr(C)]
+struct CombatData {
+    ev: *const CombatEvent,
+    src: *const std::ffi::c_void,
+    dst: *const std::ffi::c_void,
+    skill_name: *const c_char,
+    id: u64,
+    revision: u64,
+}
+
+pub fn register() {
+    event_subscribe(
+        EV_COMBAT_LOCAL,
+        event_consume!(<CombatData> |data| {
+            let Some(data) = data else {
+                return;
+            };
+            // SAFETY: the bridge passes arcdps' event, valid for the callback, or null
+            let Some(ev) = (unsafe { data.ev.as_ref() }) else {
+                return;
+            };
+            let Some((kind, id)) = ev.trigger() else {
+                return;
+            };
+            if !WATCHED.locked().contains(&(kind, id)) {
+                return;
+            }
+            inbox::send(Request::Combat(kind, id));
+        }),
+    )
+    .revert_on_unload();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn casts_and_applications() {
+        let cast = CombatEvent {
+            skill_id: 34_664,
+            is_activation: 1,
+            ..Default::default()
+        };
+        assert_eq!(cast.trigger(), Some((CombatKind::SkillCast, 34_664)));
+        let cancel = CombatEvent {
+            is_activation: 4,
+            ..cast
+        };
+        assert_eq!(cancel.trigger(), None);
+        let applied = CombatEvent {
+            skill_id: 740,
+            buff: 1,
+            value: 5000,
+            ..Default::default()
+        };
+        assert_eq!(applied.trigger(), Some((CombatKind::BuffApply, 740)));
+        let damage = CombatEvent {
+            buff_dmg: 120,
+            value: 0,
+            ..applied
+        };
+        assert_eq!(damage.trigger(), None);
+
+        let mut timer = Timer::new(1, "Split".to_string(), Duration::from_secs(40));
+        timer.combat = Some(CombatTrigger {
+            kind: CombatKind::SkillCast,
+            ids: vec![34_664],
+        });
+        let timers = [timer];
+        assert_eq!(triggered(&timers, CombatKind::SkillCast, 34_664), ["Split"]);
+        assert!(triggered(&timers, CombatKind::BuffApply, 34_664).is_empty());
+    }
+}
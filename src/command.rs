@@ -0,0 +1,182 @@
+//! Commands typed into the palette, like `start greens` or `add 30 greens`, for timers
+//! that are used too rarely to bind a key to.
+
+use crate::duration::parse_duration;
+use crate::timer::{StartSource, Timers};
+use std::time::Duration;
+
+pub const HELP: &str = "start <timer>, stop <timer>, stop all, pause <timer>, \
+                        add <time> <timer>, new <name> <time>";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Start(String),
+    Stop(String),
+    StopAll,
+    Pause(String),
+    Add(Duration, String),
+    New(String, Duration),
+}
+
+/// The name `query` is closest to: the same name, then one starting with it, then one
+/// containing it, then one with its letters in order. Shorter names win ties.
+fn best_match<'a>(query: &str, names: &[&'a str]) -> Option<&'a str> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return None;
+    }
+    let rank = |name: &str| {
+        let name = name.to_lowercase();
+        if name == query {
+            Some(0)
+        } else if name.starts_with(&query) {
+            Some(1)
+        } else if name.contains(&query) {
+            Some(2)
+        } else {
+            let mut letters = name.chars();
+            query
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .all(|c| letters.any(|l| l == c))
+                .then_some(3)
+        }
+    };
+    names
+        .iter()
+        .filter_map(|&name| Some((rank(name)?, name.len(), name)))
+        .min()
+        .map(|(_, _, name)| name)
+}
+
+fn timer(query: &str, names: &[&str]) -> Result<String, String> {
+    if query.trim().is_empty() {
+        return Err("Which timer?".to_string());
+    }
+    best_match(query, names)
+        .map(str::to_string)
+        .ok_or_else(|| format!("No timer matches {}", query.trim()))
+}
+
+fn duration(text: &str) -> Result<Duration, String> {
+    match parse_duration(text) {
+        Ok(d) if d.is_zero() => Err("The time can't be zero".to_string()),
+        Ok(d) => Ok(d),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Reads a command, matching timer names against `names`
+pub fn parse(input: &str, names: &[&str]) -> Result<Command, String> {
+    let input = input.trim();
+    let (verb, rest) = input.split_once(' ').unwrap_or((input, ""));
+    let rest = rest.trim();
+    match verb.to_lowercase().as_str() {
+        "start" => Ok(Command::Start(timer(rest, names)?)),
+        "stop" if rest.eq_ignore_ascii_case("all") => Ok(Command::StopAll),
+        "stop" => Ok(Command::Stop(timer(rest, names)?)),
+        "pause" | "resume" => Ok(Command::Pause(timer(rest, names)?)),
+        "add" => {
+            let (time, query) = rest.split_once(' ').unwrap_or((rest, ""));
+            Ok(Command::Add(duration(time)?, timer(query, names)?))
+        }
+        "new" => {
+            let Some((name, time)) = rest.rsplit_once(' ') else {
+                return Err("new <name> <time>".to_string());
+            };
+            let name = name.trim();
+            if names.iter().any(|n| n.eq_ignore_ascii_case(name)) {
+                return Err(format!("There already is a timer {}", name));
+            }
+            Ok(Command::New(name.to_string(), duration(time)?))
+        }
+        "" => Err(HELP.to_string()),
+        other => Err(format!("Unknown command {}, try {}", other, HELP)),
+    }
+}
+
+/// Runs a command, returns what it did
+pub fn run(command: Command, timers: &mut Timers) -> String {
+    match command {
+        Command::Start(name) => {
+            timers.start(&name, StartSource::Ui);
+            format!("Started {}", name)
+        }
+        Command::Stop(name) => {
+            timers.stop(&name);
+            format!("Stopped {}", name)
+        }
+        Command::StopAll => {
+            let active: Vec<String> = timers
+                .list
+                .iter()
+                .filter(|t| t.state.is_active())
+                .map(|t| t.name.clone())
+                .collect();
+            for name in &active {
+                timers.stop(name);
+            }
+            format!("Stopped {} timers", active.len())
+        }
+        Command::Pause(name) => {
+            timers.toggle_pause(&name);
+            format!("Paused or resumed {}", name)
+        }
+        Command::Add(by, name) => {
+            timers.add_time(&name, by);
+            format!("Added {}s to {}", by.as_secs_f32(), name)
+        }
+        // disabled and archived timers aren't offered for matching, the name is still theirs
+        Command::New(name, _) if timers.contains_name(&name) => {
+            format!("There already is a timer {}", name)
+        }
+        Command::New(name, duration) => {
+            crate::keybinds::register(timers.add(name.clone(), duration));
+            format!("Added timer {}", name)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAMES: &[&str] = &["Greens", "Green Circle", "Pull", "Sabetha Cannons"];
+
+    #[test]
+    fn fuzzy_names() {
+        assert_eq!(best_match("greens", NAMES), Some("Greens"));
+        assert_eq!(best_match("gre", NAMES), Some("Greens"));
+        assert_eq!(best_match("circle", NAMES), Some("Green Circle"));
+        assert_eq!(best_match("sbcan", NAMES), Some("Sabetha Cannons"));
+        assert_eq!(best_match("xyz", NAMES), None);
+    }
+
+    #[test]
+    fn commands() {
+        let parse = |input| parse(input, NAMES);
+        assert_eq!(
+            parse("start greens"),
+            Ok(Command::Start("Greens".to_string()))
+        );
+        assert_eq!(parse("Stop All"), Ok(Command::StopAll));
+        assert_eq!(
+            parse("add 30 cannons"),
+            Ok(Command::Add(
+                Duration::from_secs(30),
+                "Sabetha Cannons".to_string()
+            ))
+        );
+        assert_eq!(
+            parse("new Second pull 1:30"),
+            Ok(Command::New(
+                "Second pull".to_string(),
+                Duration::from_secs(90)
+            ))
+        );
+        assert!(parse("new pull 10").is_err());
+        assert!(parse("start").is_err());
+        assert!(parse("add 0 greens").is_err());
+        assert!(parse("jump greens").is_err());
+    }
+}
@@ -0,0 +1,736 @@
+use crate::audio::Volumes;
+use crate::backup;
+use crate::control;
+use crate::format::{Rounding, TimeFormat, TimeStyle, DEFAULT_DECIMALS, MAX_DECIMALS};
+use crate::history::History;
+use crate::import;
+use crate::layout::Layout;
+use crate::maps::MapRule;
+use crate::notice;
+use crate::recurrence::ResetSchedule;
+use crate::stream::{self, StreamFormat};
+use crate::sync::LockExt;
+use crate::theme::{Palette, Preset};
+use crate::timer::{Timer, TimerOrder, Timers};
+use crate::ui::{ClickModifier, Easing, FinishTime};
+use nexus::paths::get_addon_dir;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub fn addon_dir() -> PathBuf {
+    get_addon_dir("timers").expect("Addon dir to exist")
+}
+
+pub fn config_path() -> PathBuf {
+    addon_dir().join("timers.json")
+}
+
+pub fn settings_path() -> PathBuf {
+    addon_dir().join("settings.json")
+}
+
+pub fn layout_path() -> PathBuf {
+    addon_dir().join("layout.json")
+}
+
+pub fn history_path() -> PathBuf {
+    addon_dir().join("history.json")
+}
+
+fn loading_marker_path() -> PathBuf {
+    addon_dir().join("loading")
+}
+
+/// Marks a load as in progress, returns how many loads before it never finished
+pub fn begin_load() -> u32 {
+    let path = loading_marker_path();
+    let failed = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(0);
+    let result = std::fs::create_dir_all(addon_dir())
+        .and_then(|()| std::fs::write(&path, (failed + 1).to_string()));
+    if let Err(e) = result {
+        log::warn!("Failed to write {}: {}", path.display(), e);
+    }
+    failed
+}
+
+pub fn end_load() {
+    let _ = std::fs::remove_file(loading_marker_path());
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Render cost per frame in milliseconds above which the overlay degrades
+    pub frame_budget_ms: f32,
+    /// Only show the overlay while in combat
+    pub combat_only: bool,
+    /// Timer windows let go snap to a grid this fine, 0 for none
+    pub snap_grid: u32,
+    /// Timer windows let go next to another one line up with it
+    pub snap_to_windows: bool,
+    /// Pause running timers while the game is in the background or loading
+    pub pause_when_away: bool,
+    /// Pause running timers on loading screens and the character select, still showing
+    /// them dimmed
+    pub freeze_while_loading: bool,
+    /// Seconds before the end of a critical timer from which its banner is shown
+    pub critical_warning_secs: f32,
+    /// Show the strip of buttons for favorite timers
+    pub hotbar: bool,
+    /// Timers in the up next window, 0 to not show it
+    pub up_next: usize,
+    /// Ask before deleting timers
+    pub confirm_delete: bool,
+    /// Shortcut in the Nexus quick access bar that lists the next timers and opens the
+    /// timers window
+    pub quick_access: bool,
+    /// Hide timer windows that sat finished or paused for this many minutes until they
+    /// start again, 0 for never
+    pub hide_idle_mins: u32,
+    /// Keybind presses that start or stop timers of at least this many minutes ask
+    /// first, 0 to never ask
+    pub confirm_long_mins: u32,
+    /// Show all timers in one list sorted by remaining time once at least this many are
+    /// up, 0 to always use a window per timer
+    pub compact_after: usize,
+    /// How remaining times are shown, unless a timer has its own
+    pub time_format: TimeFormat,
+    /// Decimals of the seconds formats, unless a timer has its own
+    pub decimals: u8,
+    /// How remaining times are rounded to the last digit shown, unless a timer has its own
+    pub rounding: Rounding,
+    /// How often per second the overlay text is refreshed, 0 for every frame
+    pub refresh_hz: u32,
+    /// Left click on a timer in the overlay pauses it and middle click resets it, off
+    /// to click through the overlay
+    pub overlay_clicks: bool,
+    /// Key to hold for those clicks
+    pub click_modifier: ClickModifier,
+    /// Where the time of day a running timer finishes at is shown in the overlay
+    pub finish_time: FinishTime,
+    /// How the flash of a timer in the overlay fades out
+    pub flash_easing: Easing,
+    /// Colors of the timer states
+    pub theme: Preset,
+    /// Colors of the `Custom` theme
+    pub custom_theme: Palette,
+    /// Scale of the overlay, 0 to follow the interface size and resolution of the game
+    pub ui_scale: f32,
+    /// How fast the timers run for trying out timelines, always real time after a restart
+    #[serde(skip)]
+    pub time_scale: f32,
+    /// Order of the active timers in the overlay
+    pub timer_order: TimerOrder,
+    /// Days without a start after which a timer is suggested for the archive, 0 to not
+    /// suggest any
+    pub archive_after_days: u32,
+    /// Snapshots of the timers kept in `backups/`, 0 to take none
+    pub backups: u32,
+    /// Volume of spoken announcements, 0 to 100
+    pub voice_volume: u8,
+    /// Volume of the tones and sound files
+    pub volumes: Volumes,
+    /// Nothing is played or spoken, toggled by keybind too
+    pub muted: bool,
+    /// File the running timers are written to for stream overlays
+    pub stream_file: StreamFormat,
+    /// How often the stream file is rewritten in milliseconds
+    pub stream_interval_ms: u32,
+    /// Share the running timers with other addons and tools through the data link
+    pub data_link: bool,
+    /// Take requests from tools on this computer, see `control`
+    pub control: bool,
+    pub control_port: u16,
+    /// Requests without it are turned away, generated once the endpoint is turned on
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub control_token: String,
+    /// Buttons for Righteous Indignation timers of WvW objectives, on WvW maps
+    pub wvw_panel: bool,
+    /// Length of the pull timer in seconds
+    pub pull_secs: u32,
+    /// Timer started once the pull timer runs out, empty for none
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub pull_then: String,
+    /// Set once the first-run wizard was completed or skipped
+    pub onboarded: bool,
+    /// Record timer actions to `sessions/` so timing bugs can be replayed
+    pub record_sessions: bool,
+    /// Day and week boundaries for anything recurring
+    pub resets: ResetSchedule,
+    /// GW2 API key with the `account` and `progression` permissions, empty to not use the API
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub api_key: String,
+    /// https URL of the relay shared timer starts go through, empty to not share
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub squad_relay: String,
+    /// Room on the relay, shared with the squad
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub squad_room: String,
+    /// Groups of timers shown on specific maps, the others are hidden there
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub maps: Vec<MapRule>,
+    /// World boss and meta events to show the next spawn of
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<String>,
+    /// Groups with a keybind that starts or stops all of their timers
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub group_keybinds: Vec<String>,
+}
+
+impl Settings {
+    /// Volume speech is spoken at, the master volume applies to it as well
+    pub fn spoken_volume(&self) -> u8 {
+        if self.muted {
+            return 0;
+        }
+        (u32::from(self.voice_volume.min(100)) * u32::from(self.volumes.master.min(100)) / 100)
+            as u8
+    }
+
+    /// How times are shown, unless a timer has its own format, decimals or rounding
+    pub fn time_style(&self) -> TimeStyle {
+        self.time_format
+            .with_decimals(self.decimals)
+            .rounded(self.rounding)
+    }
+
+    /// Colors of the selected theme
+    pub fn palette(&self) -> Palette {
+        match self.theme {
+            Preset::Custom => self.custom_theme,
+            preset => preset.palette(),
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            frame_budget_ms: 1.0,
+            combat_only: false,
+            snap_grid: 0,
+            snap_to_windows: false,
+            pause_when_away: false,
+            freeze_while_loading: false,
+            critical_warning_secs: 10.0,
+            hotbar: false,
+            up_next: 0,
+            confirm_delete: true,
+            quick_access: true,
+            hide_idle_mins: 0,
+            confirm_long_mins: 0,
+            compact_after: 10,
+            time_format: TimeFormat::default(),
+            decimals: DEFAULT_DECIMALS,
+            rounding: Rounding::default(),
+            refresh_hz: 0,
+            overlay_clicks: true,
+            click_modifier: ClickModifier::None,
+            finish_time: FinishTime::default(),
+            flash_easing: Easing::default(),
+            theme: Preset::default(),
+            custom_theme: Palette::default(),
+            ui_scale: 0.0,
+            time_scale: 1.0,
+            timer_order: TimerOrder::default(),
+            archive_after_days: 30,
+            backups: backup::DEFAULT_KEEP,
+            voice_volume: 100,
+            volumes: Volumes::default(),
+            muted: false,
+            stream_file: StreamFormat::Off,
+            stream_interval_ms: stream::DEFAULT_INTERVAL_MS,
+            data_link: false,
+            control: false,
+            control_port: control::DEFAULT_PORT,
+            control_token: String::new(),
+            wvw_panel: false,
+            pull_secs: DEFAULT_PULL_SECS,
+            pull_then: String::new(),
+            record_sessions: false,
+            onboarded: false,
+            resets: ResetSchedule::default(),
+            api_key: String::new(),
+            squad_relay: String::new(),
+            squad_room: String::new(),
+            maps: Vec::new(),
+            events: Vec::new(),
+            group_keybinds: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ConfigFile {
+    /// Additional files (relative to the addon dir) whose timers are merged in on load,
+    /// `*` is supported in the file name, e.g. `packs/*.json`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub timers: Vec<Timer>,
+}
+
+/// Layout of timers.json written by this version. Bumped with a step in [`MIGRATIONS`]
+/// whenever a change can't be covered by serde defaults, e.g. a field changing meaning.
+pub const CONFIG_VERSION: u64 = 1;
+
+/// `MIGRATIONS[v]` upgrades a file of version `v` to `v + 1`
+const MIGRATIONS: [fn(Value) -> Value; CONFIG_VERSION as usize] = [from_legacy];
+
+/// Version 0: timers.json used to be a plain list of timers, then an object without a
+/// version
+fn from_legacy(value: Value) -> Value {
+    match value {
+        Value::Array(timers) => {
+            let mut map = serde_json::Map::new();
+            map.insert("timers".to_string(), Value::Array(timers));
+            Value::Object(map)
+        }
+        other => other,
+    }
+}
+
+/// Upgrades a timers.json of any earlier version to the current layout, returns it with
+/// the version it had. Newer files are left as they are.
+pub fn migrate(mut value: Value) -> Result<(Value, u64), String> {
+    let version = match &value {
+        Value::Array(_) => 0,
+        Value::Object(map) => match map.get("version") {
+            Some(v) => v.as_u64().ok_or("version isn't a number")?,
+            None => 0,
+        },
+        _ => return Err("expected an object or a list of timers".to_string()),
+    };
+    for step in MIGRATIONS.iter().skip(version as usize) {
+        value = step(value);
+    }
+    if version < CONFIG_VERSION {
+        if let Value::Object(map) = &mut value {
+            map.insert("version".to_string(), CONFIG_VERSION.into());
+        }
+    }
+    Ok((value, version))
+}
+
+/// timers.json or a pack as it was read, migrated to the current layout
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "Value")]
+pub struct StoredConfig {
+    pub config: ConfigFile,
+    /// The version the file had before it was migrated
+    pub version: u64,
+}
+
+impl Default for StoredConfig {
+    fn default() -> Self {
+        Self {
+            config: ConfigFile::default(),
+            version: CONFIG_VERSION,
+        }
+    }
+}
+
+impl TryFrom<Value> for StoredConfig {
+    type Error = String;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let (value, version) = migrate(value)?;
+        let config = serde_json::from_value(value).map_err(|e| e.to_string())?;
+        Ok(Self { config, version })
+    }
+}
+
+impl From<StoredConfig> for ConfigFile {
+    fn from(stored: StoredConfig) -> Self {
+        stored.config
+    }
+}
+
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        Some((prefix, suffix)) => {
+            name.len() >= prefix.len() + suffix.len()
+                && name.starts_with(prefix)
+                && name.ends_with(suffix)
+        }
+        None => pattern == name,
+    }
+}
+
+fn resolve_include(base: &Path, pattern: &str) -> Vec<PathBuf> {
+    let (dir, file) = match pattern.rsplit_once(['/', '\\']) {
+        Some((dir, file)) => (base.join(dir), file),
+        None => (base.to_path_buf(), pattern),
+    };
+    if !file.contains('*') {
+        return vec![dir.join(file)];
+    }
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        log::warn!("Include directory {} does not exist", dir.display());
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name()
+                .is_some_and(|n| wildcard_match(file, &n.to_string_lossy()))
+        })
+        .collect();
+    paths.sort();
+    paths
+}
+
+fn load_includes(base: &Path, include: &[String], timers: &mut Vec<Timer>) {
+    for pattern in include {
+        for path in resolve_include(base, pattern) {
+            let pack = match import::from_file(&path) {
+                Ok(c) => c,
+                Err(e) => {
+                    notice(format!("Failed to load include {}: {}", path.display(), e));
+                    continue;
+                }
+            };
+            for warning in &pack.warnings {
+                log::warn!("{}: {}", path.display(), warning);
+            }
+            if !pack.include.is_empty() {
+                log::warn!("Nested includes in {} are ignored", path.display());
+            }
+            let source = path
+                .strip_prefix(base)
+                .unwrap_or(&path)
+                .display()
+                .to_string();
+            for mut timer in pack.timers {
+                if timers.iter().any(|t| t.name == timer.name) {
+                    log::warn!("Skipping duplicate timer {} from {}", timer.name, source);
+                    continue;
+                }
+                timer.pack = Some(source.clone());
+                timers.push(timer);
+            }
+        }
+    }
+}
+
+/// How many previous versions of each config are kept, `.bak` the newest of them
+const BACKUPS: usize = 3;
+
+/// The `generation`th previous version of a config, 0 the newest
+fn backup_path(path: &Path, generation: usize) -> PathBuf {
+    match generation {
+        0 => path.with_extension("json.bak"),
+        n => path.with_extension(format!("json.bak{}", n + 1)),
+    }
+}
+
+/// Where a config that couldn't be parsed at `at` is moved, named after when so one moved
+/// aside before isn't overwritten
+fn corrupt_path(path: &Path, at: SystemTime) -> PathBuf {
+    let secs = at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    path.with_extension(format!("{}.json.corrupt", secs))
+}
+
+// Configs that couldn't be read for another reason than their contents, saving would lose them
+static UNREADABLE: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Parse errors come back as `InvalidData`, anything else is from reading the file
+fn parse_file<T: DeserializeOwned>(path: &Path) -> std::io::Result<T> {
+    let contents = std::fs::read(path)?;
+    serde_json::from_slice(&contents)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+/// Reads a config file, falling back to its backups if it can't be parsed.
+/// A broken file is moved aside so it is neither lost nor overwritten on save. One that
+/// can't be read at all, like while another program holds it, is left alone and not
+/// saved over until it was read.
+fn read_config<T: DeserializeOwned + Default>(path: &Path) -> T {
+    read_config_from(path).0
+}
+
+/// [`read_config`] along with the file it was read from, None with the defaults
+fn read_config_from<T: DeserializeOwned + Default>(path: &Path) -> (T, Option<PathBuf>) {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let err = match parse_file(path) {
+        Ok(c) => {
+            UNREADABLE.locked().retain(|p| p != path);
+            return (c, Some(path.to_path_buf()));
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return (T::default(), None),
+        Err(e) if e.kind() == std::io::ErrorKind::InvalidData => e,
+        Err(e) => {
+            UNREADABLE.locked().push(path.to_path_buf());
+            notice(format!(
+                "Failed to read {}: {}. Using the defaults, it won't be saved over",
+                name, e
+            ));
+            return (T::default(), None);
+        }
+    };
+    let corrupt = corrupt_path(path, SystemTime::now());
+    let _ = std::fs::rename(path, &corrupt);
+    let restored = (0..BACKUPS).find_map(|generation| {
+        let backup = backup_path(path, generation);
+        Some((parse_file(&backup).ok()?, backup))
+    });
+    match restored {
+        Some((c, backup)) => {
+            notice(format!(
+                "Failed to parse {}: {}. Restored the last backup, the broken file was kept as {}",
+                name,
+                err,
+                corrupt.display()
+            ));
+            (c, Some(backup))
+        }
+        None => {
+            notice(format!(
+                "Failed to parse {}: {}. No usable backup was found, the broken file was kept as {}",
+                name,
+                err,
+                corrupt.display()
+            ));
+            (T::default(), None)
+        }
+    }
+}
+
+/// Writes through a temporary file and a rename so a crash can't leave a half-written
+/// config behind. The previous `BACKUPS` versions are kept as `.bak`, `.bak2` and so on.
+fn write_config(path: &Path, contents: &str) -> std::io::Result<()> {
+    if UNREADABLE.locked().iter().any(|p| p == path) {
+        return Err(std::io::Error::other(
+            "it couldn't be read on load, not saving over it",
+        ));
+    }
+    std::fs::create_dir_all(path.parent().expect("Config to be in a directory"))?;
+    let tmp = path.with_extension("json.tmp");
+    {
+        let mut f = std::fs::File::create(&tmp)?;
+        f.write_all(contents.as_bytes())?;
+        f.sync_all()?;
+    }
+    if path.exists() {
+        for generation in (1..BACKUPS).rev() {
+            let older = backup_path(path, generation - 1);
+            if older.exists() {
+                std::fs::rename(older, backup_path(path, generation))?;
+            }
+        }
+        std::fs::copy(path, backup_path(path, 0))?;
+    }
+    std::fs::rename(&tmp, path)
+}
+
+pub fn load_timers() -> Timers {
+    let path = config_path();
+    let (stored, source) = read_config_from::<StoredConfig>(&path);
+    if stored.version < CONFIG_VERSION {
+        // the upgraded file is only written on the next save, keep the original around
+        let original = path.with_extension(format!("v{}.json", stored.version));
+        log::info!(
+            "Upgrading timers.json from version {} to {}, the original is kept as {}",
+            stored.version,
+            CONFIG_VERSION,
+            original.display()
+        );
+        // a broken timers.json is moved aside, the backup the timers came from is copied
+        let source = source.as_deref().unwrap_or(&path);
+        if !original.exists() {
+            if let Err(e) = std::fs::copy(source, &original) {
+                log::warn!("Failed to keep the original timers.json: {}", e);
+            }
+        }
+    } else if stored.version > CONFIG_VERSION {
+        notice(format!(
+            "timers.json is from a newer version of the addon (layout {}, this one knows {}), \
+             settings this version doesn't know are lost once it saves",
+            stored.version, CONFIG_VERSION
+        ));
+    }
+    let ConfigFile {
+        include,
+        mut timers,
+    } = stored.into();
+    load_includes(
+        path.parent().expect("Config to be in a directory"),
+        &include,
+        &mut timers,
+    );
+    // timers.json had the layout before layout.json, it is kept where there is none
+    let layout_path = layout_path();
+    if layout_path.exists() {
+        read_config::<Layout>(&layout_path).apply(&mut timers);
+    }
+    let mut timers = Timers::new(timers, include);
+    timers.seed_last_used();
+    timers
+}
+
+/// timers.json of our own timers, without the layout
+pub fn timers_json<'a>(include: &[String], timers: impl IntoIterator<Item = &'a Timer>) -> String {
+    #[derive(Serialize)]
+    struct ConfigFileRef<'a> {
+        version: u64,
+        #[serde(skip_serializing_if = "<[String]>::is_empty")]
+        include: &'a [String],
+        timers: Vec<&'a Timer>,
+    }
+    let mut sorted: Vec<&Timer> = timers
+        .into_iter()
+        .filter(|t| t.pack.is_none() && t.system.is_none())
+        .collect();
+    sorted.sort_by_key(|t| t.id);
+    serde_json::to_string_pretty(&ConfigFileRef {
+        version: CONFIG_VERSION,
+        include,
+        timers: sorted,
+    })
+    .expect("Timers to be serialized")
+}
+
+/// Writes the timer definitions to `path` for sharing, returns how many there were.
+/// Includes are left out as their paths are local.
+pub fn export_timers(path: &Path, timers: &Timers) -> std::io::Result<usize> {
+    let json = timers_json(&[], &timers.list);
+    std::fs::write(path, json)?;
+    Ok(timers
+        .list
+        .iter()
+        .filter(|t| t.pack.is_none() && t.system.is_none())
+        .count())
+}
+
+/// A config file that couldn't be written
+#[derive(Debug)]
+pub struct SaveError {
+    pub file: &'static str,
+    pub error: std::io::Error,
+}
+
+impl std::fmt::Display for SaveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Failed to save {}: {}", self.file, self.error)
+    }
+}
+
+fn save(file: &'static str, path: &Path, contents: &str) -> Result<(), SaveError> {
+    write_config(path, contents).map_err(|error| SaveError { file, error })
+}
+
+/// Saves timers.json and layout.json, the layout even if the timers failed
+pub fn save_timers(timers: &Timers) -> Result<(), SaveError> {
+    let json = timers_json(&timers.include, &timers.list);
+    let config = config_path();
+    log::info!("Saving timers to {}", config.display());
+    let saved = save("timers.json", &config, &json);
+    let json =
+        serde_json::to_string_pretty(&Layout::of(&timers.list)).expect("Layout to be serialized");
+    let layout = save("layout.json", &layout_path(), &json);
+    saved.and(layout)
+}
+
+pub const MAX_UI_SCALE: f32 = 4.0;
+pub const MAX_SNAP_GRID: u32 = 200;
+pub const DEFAULT_PULL_SECS: u32 = 10;
+pub const MAX_PULL_SECS: u32 = 600;
+
+pub fn load_settings() -> Settings {
+    let mut settings: Settings = read_config(&settings_path());
+    settings.resets.sanitize();
+    settings.snap_grid = settings.snap_grid.min(MAX_SNAP_GRID);
+    settings.volumes.sanitize();
+    settings.custom_theme.sanitize();
+    settings.decimals = settings.decimals.min(MAX_DECIMALS);
+    settings.refresh_hz = settings.refresh_hz.min(import::MAX_REFRESH_HZ);
+    settings.ui_scale = settings.ui_scale.clamp(0.0, MAX_UI_SCALE);
+    settings.backups = settings.backups.min(backup::MAX_KEEP);
+    settings.pull_secs = settings.pull_secs.clamp(1, MAX_PULL_SECS);
+    settings.control_port = settings.control_port.max(control::MIN_PORT);
+    settings.stream_interval_ms = settings
+        .stream_interval_ms
+        .clamp(stream::MIN_INTERVAL_MS, stream::MAX_INTERVAL_MS);
+    settings
+}
+
+pub fn save_settings(settings: &Settings) -> Result<(), SaveError> {
+    let json = serde_json::to_string_pretty(settings).expect("Settings to be serialized");
+    save("settings.json", &settings_path(), &json)
+}
+
+pub fn load_history() -> History {
+    read_config(&history_path())
+}
+
+pub fn save_history(history: &History) -> Result<(), SaveError> {
+    let json = serde_json::to_string(history).expect("History to be serialized");
+    save("history.json", &history_path(), &json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn keeps_generations_of_backups() {
+        let dir = std::env::temp_dir().join(format!("timers-backups-{}", std::process::id()));
+        let path = dir.join("settings.json");
+        for n in 0..=BACKUPS {
+            write_config(&path, &n.to_string()).unwrap();
+        }
+        let read = |path: &Path| std::fs::read_to_string(path).unwrap();
+        assert_eq!(read(&path), BACKUPS.to_string());
+        for generation in 0..BACKUPS {
+            let expected = (BACKUPS - 1 - generation).to_string();
+            assert_eq!(read(&backup_path(&path, generation)), expected);
+        }
+        assert!(!backup_path(&path, BACKUPS).exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn broken_configs_are_kept_apart() {
+        let path = Path::new("addons/timers/timers.json");
+        let at = |secs| UNIX_EPOCH + std::time::Duration::from_secs(secs);
+        assert_eq!(
+            corrupt_path(path, at(1_700_000_000)),
+            Path::new("addons/timers/timers.1700000000.json.corrupt")
+        );
+        assert_ne!(corrupt_path(path, at(1)), corrupt_path(path, at(2)));
+    }
+
+    #[test]
+    fn migrates_legacy_list() {
+        let legacy = json!([{"name": "a", "duration": {"secs": 10, "nanos": 0}}]);
+        let (value, version) = migrate(legacy.clone()).unwrap();
+        assert_eq!(version, 0);
+        assert_eq!(
+            value,
+            json!({"version": CONFIG_VERSION, "timers": legacy.clone()})
+        );
+        let stored = StoredConfig::try_from(legacy).unwrap();
+        assert_eq!(stored.config.timers[0].name, "a");
+    }
+
+    #[test]
+    fn keeps_current_and_newer_files() {
+        let unversioned = json!({"include": ["packs/*.json"], "timers": []});
+        let (value, version) = migrate(unversioned).unwrap();
+        assert_eq!(version, 0);
+        assert_eq!(value["version"], json!(CONFIG_VERSION));
+        assert_eq!(value["include"], json!(["packs/*.json"]));
+        let newer = json!({"version": CONFIG_VERSION + 1, "timers": []});
+        assert_eq!(migrate(newer.clone()).unwrap(), (newer, CONFIG_VERSION + 1));
+        assert!(migrate(json!("timers")).is_err());
+    }
+}
@@ -0,0 +1,458 @@
+//! Optional HTTP endpoint on localhost for Stream Deck plugins and scripts, off by default.
+//!
+//! Every request has to carry the token from the options as `Authorization: Bearer ..`:
+//! - `GET /timers`: the timers as `[{"name": .., "state": .., "remaining_secs": ..}]`
+//! - `POST /timers/{name}/start`: starts (or restarts) the timer
+//! - `POST /timers/{name}/stop`: stops the timer
+//!
+//! Names are percent-encoded in the path. Starts and stops are queued like the keybinds,
+//! so they are answered with 202 before they are applied.
+
+use crate::inbox::{self, Request};
+use crate::sync::LockExt;
+use crate::timer::{StartSource, TimerState, Timers};
+use serde::Serialize;
+use std::hash::{BuildHasher, RandomState};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime};
+
+pub const DEFAULT_PORT: u16 = 52_380;
+/// Lower ports need admin rights or belong to system services
+pub const MIN_PORT: u16 = 1024;
+/// How often the listing the endpoint answers with is renewed
+const INTERVAL: Duration = Duration::from_millis(250);
+/// How often the listener looks for connections and whether it should stop
+const ACCEPT_POLL: Duration = Duration::from_millis(50);
+/// Anything longer isn't a request of ours
+const MAX_HEAD: usize = 8 * 1024;
+/// How long a connection has for its whole request and the answer
+const TIMEOUT: Duration = Duration::from_secs(1);
+/// Connections served at once, more wait to be accepted
+const MAX_CONNECTIONS: usize = 8;
+
+#[derive(Debug, Serialize)]
+struct Entry {
+    name: String,
+    state: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remaining_secs: Option<f64>,
+}
+
+/// What the endpoint answers with, renewed by the tick so it never waits on the timers
+#[derive(Debug, Default)]
+pub struct Listing {
+    names: Vec<String>,
+    json: String,
+    at: Option<Instant>,
+}
+
+static LISTING: Mutex<Listing> = Mutex::new(Listing {
+    names: Vec::new(),
+    json: String::new(),
+    at: None,
+});
+
+struct Listener {
+    port: u16,
+    /// Changed in place, a new token doesn't need a new socket
+    token: Arc<Mutex<String>>,
+    /// `None` if the port couldn't be listened on, tried again once the settings change
+    running: Option<(Sender<()>, JoinHandle<()>)>,
+}
+
+static LISTENER: Mutex<Option<Listener>> = Mutex::new(None);
+/// Listeners told to stop, finishing the connections they had. Joined on unload.
+static STOPPING: Mutex<Vec<JoinHandle<()>>> = Mutex::new(Vec::new());
+
+/// A new random token, 32 hex digits
+pub fn new_token() -> String {
+    let seed = SystemTime::now();
+    let [a, b] = [0u8, 1].map(|i| RandomState::new().hash_one((seed, i)));
+    format!("{:016x}{:016x}", a, b)
+}
+
+fn state_name(state: TimerState) -> &'static str {
+    match state {
+        TimerState::Idle => "idle",
+        TimerState::Running { .. } => "running",
+        TimerState::Paused { .. } => "paused",
+        TimerState::Finished { .. } => "finished",
+    }
+}
+
+fn listing(timers: &Timers) -> Listing {
+    let now = timers.now();
+    let entries: Vec<_> = timers
+        .list
+        .iter()
+        .filter(|t| t.enabled && t.system.is_none())
+        .map(|t| Entry {
+            name: t.name.clone(),
+            state: state_name(t.state),
+            remaining_secs: t.remaining(now).map(|d| d.as_secs_f64()),
+        })
+        .collect();
+    Listing {
+        names: entries.iter().map(|e| e.name.clone()).collect(),
+        json: serde_json::to_string(&entries).expect("Control entries to be serialized"),
+        at: Some(Instant::now()),
+    }
+}
+
+/// The listing to answer with once the current one is `INTERVAL` old, called every tick
+/// holding the timers
+pub fn collect(timers: &Timers) -> Option<Listing> {
+    let listening = LISTENER
+        .locked()
+        .as_ref()
+        .is_some_and(|l| l.running.is_some());
+    let due = LISTING
+        .locked()
+        .at
+        .is_none_or(|at| at.elapsed() >= INTERVAL);
+    (listening && due).then(|| listing(timers))
+}
+
+/// Listens on `port` with `token` if given and answers with `listing` from [`collect`]
+/// from now on, called every tick. Never waits on a listener to stop.
+pub fn poll(config: Option<(u16, &str)>, listing: Option<Listing>) {
+    STOPPING.locked().retain(|thread| !thread.is_finished());
+    let mut listener = LISTENER.locked();
+    match (listener.as_mut(), config) {
+        (Some(l), Some((port, token))) if l.port == port => {
+            let mut current = l.token.locked();
+            if *current != token {
+                token.clone_into(&mut current);
+            }
+        }
+        (None, None) => {}
+        _ => {
+            if let Some(old) = listener.take() {
+                stop_listener(old);
+            }
+            *listener = config.map(|(port, token)| listen(port, token));
+        }
+    }
+    if listener.as_ref().is_none_or(|l| l.running.is_none()) {
+        return;
+    }
+    drop(listener);
+    if let Some(listing) = listing {
+        *LISTING.locked() = listing;
+    }
+}
+
+/// Stops listening and waits for every listener to finish, on unload
+pub fn stop() {
+    if let Some(listener) = LISTENER.locked().take() {
+        stop_listener(listener);
+    }
+    let stopping = std::mem::take(&mut *STOPPING.locked());
+    for thread in stopping {
+        if thread.join().is_err() {
+            log::warn!("Control endpoint thread panicked");
+        }
+    }
+}
+
+/// Tells the listener to stop, it winds down on its own
+fn stop_listener(listener: Listener) {
+    if let Some((tx, thread)) = listener.running {
+        drop(tx);
+        STOPPING.locked().push(thread);
+        log::info!("Stopped listening on port {}", listener.port);
+    }
+}
+
+fn listen(port: u16, token: &str) -> Listener {
+    let token = Arc::new(Mutex::new(token.to_string()));
+    let running = match bind(port) {
+        Ok(socket) => {
+            log::info!("Listening for control requests on 127.0.0.1:{}", port);
+            let (tx, rx) = channel::<()>();
+            let token = Arc::clone(&token);
+            let thread = std::thread::Builder::new()
+                .name("timers-control".to_string())
+                .spawn(move || {
+                    let mut serving = Vec::new();
+                    // until the sender is dropped
+                    while let Err(RecvTimeoutError::Timeout) = rx.recv_timeout(ACCEPT_POLL) {
+                        accept_all(&socket, &token, &mut serving);
+                    }
+                    // each has its deadline, so this doesn't take long
+                    for connection in serving {
+                        let _ = connection.join();
+                    }
+                })
+                .expect("Control thread to spawn");
+            Some((tx, thread))
+        }
+        Err(e) => {
+            log::warn!("Failed to listen on port {}: {}", port, e);
+            let text = format!("couldn't listen on port {}: {}", port, e);
+            crate::eventlog::error("", text);
+            None
+        }
+    };
+    Listener {
+        port,
+        token,
+        running,
+    }
+}
+
+fn bind(port: u16) -> std::io::Result<TcpListener> {
+    let socket = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+    socket.set_nonblocking(true)?;
+    Ok(socket)
+}
+
+/// Serves each new connection on a thread of its own, so a slow one doesn't hold up the
+/// others
+fn accept_all(socket: &TcpListener, token: &Arc<Mutex<String>>, serving: &mut Vec<JoinHandle<()>>) {
+    loop {
+        serving.retain(|connection| !connection.is_finished());
+        if serving.len() >= MAX_CONNECTIONS {
+            return;
+        }
+        match socket.accept() {
+            Ok((stream, _)) => {
+                let token = Arc::clone(token);
+                let connection = std::thread::Builder::new()
+                    .name("timers-control-connection".to_string())
+                    .spawn(move || {
+                        if let Err(e) = serve(stream, &token) {
+                            log::debug!("Control request failed: {}", e);
+                        }
+                    });
+                match connection {
+                    Ok(connection) => serving.push(connection),
+                    Err(e) => log::warn!("Failed to serve a control connection: {}", e),
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => return,
+            Err(e) => {
+                log::warn!("Failed to accept a control connection: {}", e);
+                return;
+            }
+        }
+    }
+}
+
+/// What is left until `deadline`, timing out once it passed
+fn left(deadline: Instant) -> std::io::Result<Duration> {
+    let left = deadline.saturating_duration_since(Instant::now());
+    if left.is_zero() {
+        return Err(ErrorKind::TimedOut.into());
+    }
+    Ok(left)
+}
+
+fn serve(mut stream: TcpStream, token: &Mutex<String>) -> std::io::Result<()> {
+    let deadline = Instant::now() + TIMEOUT;
+    stream.set_nonblocking(false)?;
+    let mut head = Vec::new();
+    let mut buf = [0; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        stream.set_read_timeout(Some(left(deadline)?))?;
+        let read = stream.read(&mut buf)?;
+        if read == 0 || head.len() + read > MAX_HEAD {
+            return Err(ErrorKind::InvalidData.into());
+        }
+        head.extend_from_slice(&buf[..read]);
+    }
+    let head = String::from_utf8_lossy(&head);
+    let token = token.locked().clone();
+    let reply = {
+        let listing = LISTING.locked();
+        respond(&head, &token, &listing.names, &listing.json)
+    };
+    if let Some(request) = reply.request {
+        inbox::send(request);
+    }
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        reply.status,
+        reply.body.len(),
+        reply.body
+    );
+    stream.set_write_timeout(Some(left(deadline)?))?;
+    stream.write_all(response.as_bytes())
+}
+
+#[derive(Debug, PartialEq)]
+struct Reply {
+    status: &'static str,
+    body: String,
+    request: Option<Request>,
+}
+
+impl Reply {
+    fn error(status: &'static str, text: &str) -> Self {
+        Self {
+            status,
+            body: serde_json::json!({ "error": text }).to_string(),
+            request: None,
+        }
+    }
+}
+
+/// `%xx` in a path segment, `None` if it isn't valid UTF-8 after
+fn percent_decode(segment: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(segment.len());
+    let mut rest = segment.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        if b == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(b);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Compares in the same time however much of the token matches
+fn token_matches(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// The answer to the request `head`, `names` being the timers there are and `json` the
+/// listing of them
+fn respond(head: &str, token: &str, names: &[String], json: &str) -> Reply {
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split(' ');
+    let (method, target) = (request_line.next(), request_line.next());
+    let (Some(method), Some(target)) = (method, target) else {
+        return Reply::error("400 Bad Request", "malformed request");
+    };
+    let authorized = lines
+        .filter_map(|line| line.split_once(':'))
+        .filter(|(name, _)| name.trim().eq_ignore_ascii_case("authorization"))
+        .filter_map(|(_, value)| value.trim().strip_prefix("Bearer "))
+        .any(|given| !token.is_empty() && token_matches(given.trim(), token));
+    if !authorized {
+        return Reply::error("401 Unauthorized", "missing or wrong token");
+    }
+    let path = target.split('?').next().unwrap_or_default();
+    let segments: Vec<_> = path.trim_matches('/').split('/').collect();
+    match (method, segments.as_slice()) {
+        ("GET", ["timers"]) => Reply {
+            status: "200 OK",
+            body: json.to_string(),
+            request: None,
+        },
+        ("POST", ["timers", name, action @ ("start" | "stop")]) => {
+            let Some(name) = percent_decode(name).filter(|n| names.contains(n)) else {
+                return Reply::error("404 Not Found", "no such timer");
+            };
+            let request = if *action == "start" {
+                Request::Start(name, StartSource::Control)
+            } else {
+                Request::Stop(name)
+            };
+            Reply {
+                status: "202 Accepted",
+                body: serde_json::json!({ "queued": action }).to_string(),
+                request: Some(request),
+            }
+        }
+        (_, ["timers"] | ["timers", _, "start" | "stop"]) => {
+            Reply::error("405 Method Not Allowed", "wrong method")
+        }
+        _ => Reply::error("404 Not Found", "no such endpoint"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers_with_the_token_only() {
+        let names = ["Boss: phase 2".to_string()];
+        let ask = |head: &str| respond(head, "secret", &names, "[]");
+        let auth = "\r\nAuthorization: Bearer secret\r\n\r\n";
+        let list = ask(&format!("GET /timers HTTP/1.1{}", auth));
+        assert_eq!((list.status, list.body.as_str()), ("200 OK", "[]"));
+        assert_eq!(
+            ask("GET /timers HTTP/1.1\r\n\r\n").status,
+            "401 Unauthorized"
+        );
+        assert_eq!(
+            ask("GET /timers HTTP/1.1\r\nauthorization: Bearer secre\r\n\r\n").status,
+            "401 Unauthorized"
+        );
+        let start = ask(&format!(
+            "POST /timers/Boss%3A%20phase%202/start HTTP/1.1{}",
+            auth
+        ));
+        assert_eq!(start.status, "202 Accepted");
+        assert_eq!(
+            start.request,
+            Some(Request::Start(
+                "Boss: phase 2".to_string(),
+                StartSource::Control
+            ))
+        );
+        let stop = ask(&format!(
+            "POST /timers/Boss%3A%20phase%202/stop HTTP/1.1{}",
+            auth
+        ));
+        assert_eq!(
+            stop.request,
+            Some(Request::Stop("Boss: phase 2".to_string()))
+        );
+        let missing = ask(&format!("POST /timers/Other/start HTTP/1.1{}", auth));
+        assert_eq!((missing.status, missing.request), ("404 Not Found", None));
+        let get = ask(&format!("GET /timers/Other/start HTTP/1.1{}", auth));
+        assert_eq!(get.status, "405 Method Not Allowed");
+        // no token set lets nothing in
+        assert_eq!(
+            respond(
+                "GET /timers HTTP/1.1\r\nAuthorization: Bearer \r\n\r\n",
+                "",
+                &names,
+                "[]"
+            )
+            .status,
+            "401 Unauthorized"
+        );
+        assert_eq!(percent_decode("a%2"), None);
+        assert_eq!(new_token().len(), 32);
+    }
+
+    #[test]
+    fn slow_requests_run_out_of_time() {
+        let socket = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let addr = socket.local_addr().unwrap();
+        let client = std::thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            // a byte well within every read's time, never the whole head
+            for _ in 0..10 {
+                if stream.write_all(b"G").is_err() {
+                    return;
+                }
+                std::thread::sleep(TIMEOUT / 4);
+            }
+        });
+        let (stream, _) = socket.accept().unwrap();
+        let started = Instant::now();
+        let served = serve(stream, &Mutex::new("secret".to_string()));
+        assert_eq!(served.unwrap_err().kind(), ErrorKind::TimedOut);
+        assert!(started.elapsed() < 2 * TIMEOUT);
+        client.join().unwrap();
+    }
+}
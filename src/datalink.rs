@@ -0,0 +1,197 @@
+//! The running timers shared through the Nexus data link `DL_TIMERS`, for addons and
+//! tools on a second monitor that want the remaining times without reading a file.
+//!
+//! The link holds a [`SharedTimers`], `#[repr(C)]` and without implicit padding:
+//!
+//! | offset | type     | field                                       |
+//! |--------|----------|---------------------------------------------|
+//! | 0      | u32      | `version`, [`VERSION`]                      |
+//! | 4      | u32      | `sequence`, odd while it is being written   |
+//! | 8      | u64      | `updated_ms`, unix time of the last update  |
+//! | 16     | u32      | `count` of the timers that follow           |
+//! | 20     | u32      | reserved                                    |
+//! | 24     | 96 × 32  | `timers`, the first `count` of them set     |
+//!
+//! Each [`SharedTimer`] is `id: u64`, `remaining_ms: u64`, `duration_ms: u64`,
+//! `state: u32` ([`RUNNING`], [`PAUSED`] or [`COUNT_IN`]), a reserved u32 and the name as
+//! 64 bytes of zero padded UTF-8. Timers are ordered by when they finish, paused ones
+//! last.
+//!
+//! It is rewritten every [`INTERVAL`] while enabled and emptied when turned off. Readers
+//! copy it, then retry if `sequence` was odd or changed in the meantime.
+
+use crate::sync::LockExt;
+use crate::timer::{Timer, TimerState, Timers};
+use nexus::data_link::share_data_link;
+use std::sync::atomic::{fence, AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+pub const ID: &str = "DL_TIMERS";
+/// Raised whenever the layout changes
+pub const VERSION: u32 = 1;
+pub const MAX_TIMERS: usize = 32;
+pub const NAME_LEN: usize = 64;
+pub const INTERVAL: Duration = Duration::from_millis(50);
+
+pub const RUNNING: u32 = 0;
+pub const PAUSED: u32 = 1;
+pub const COUNT_IN: u32 = 2;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SharedTimer {
+    pub id: u64,
+    /// Until the end of the count-in for `COUNT_IN`
+    pub remaining_ms: u64,
+    pub duration_ms: u64,
+    pub state: u32,
+    pub _reserved: u32,
+    /// Cut at a character boundary if longer
+    pub name: [u8; NAME_LEN],
+}
+
+#[repr(C)]
+pub struct SharedTimers {
+    pub version: u32,
+    pub sequence: AtomicU32,
+    pub updated_ms: u64,
+    pub count: u32,
+    pub _reserved: u32,
+    pub timers: [SharedTimer; MAX_TIMERS],
+}
+
+fn name_bytes(name: &str) -> [u8; NAME_LEN] {
+    let mut end = name.len().min(NAME_LEN);
+    while !name.is_char_boundary(end) {
+        end -= 1;
+    }
+    let mut bytes = [0; NAME_LEN];
+    bytes[..end].copy_from_slice(&name.as_bytes()[..end]);
+    bytes
+}
+
+fn shared(timer: &Timer, now: Instant) -> Option<SharedTimer> {
+    let (state, remaining) = match (timer.state, timer.count_in(now)) {
+        (TimerState::Paused { .. }, _) => (PAUSED, timer.remaining(now)?),
+        (TimerState::Running { .. }, Some(count_in)) => (COUNT_IN, count_in),
+        (TimerState::Running { .. }, None) => (RUNNING, timer.remaining(now)?),
+        _ => return None,
+    };
+    Some(SharedTimer {
+        id: timer.id,
+        remaining_ms: remaining.as_millis() as u64,
+        duration_ms: timer.run_duration().as_millis() as u64,
+        state,
+        _reserved: 0,
+        name: name_bytes(&timer.name),
+    })
+}
+
+/// The running and paused timers in the order they are shared in
+fn entries(timers: &Timers) -> Vec<SharedTimer> {
+    let now = timers.now();
+    let mut entries: Vec<_> = timers
+        .list
+        .iter()
+        .filter(|t| t.enabled)
+        .filter_map(|t| shared(t, now))
+        .collect();
+    entries.sort_by_key(|e| (e.state == PAUSED, e.remaining_ms));
+    entries.truncate(MAX_TIMERS);
+    entries
+}
+
+struct Link {
+    /// Null if Nexus couldn't share it, tried again once turned off and on
+    shared: *mut SharedTimers,
+    written: Instant,
+}
+
+// SAFETY: the memory is owned by Nexus until the game closes, only written under the lock
+unsafe impl Send for Link {}
+
+static LINK: Mutex<Option<Link>> = Mutex::new(None);
+
+fn write(shared: &mut SharedTimers, entries: &[SharedTimer]) {
+    let sequence = shared.sequence.load(Ordering::Relaxed);
+    shared
+        .sequence
+        .store(sequence.wrapping_add(1), Ordering::Relaxed);
+    fence(Ordering::Release);
+    shared.version = VERSION;
+    shared.updated_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    shared.count = entries.len() as u32;
+    shared.timers[..entries.len()].copy_from_slice(entries);
+    shared
+        .sequence
+        .store(sequence.wrapping_add(2), Ordering::Release);
+}
+
+/// The timers to share once `INTERVAL` passed since the last time if `enabled`, called
+/// every tick holding the timers
+pub fn collect(enabled: bool, timers: &Timers) -> Option<Vec<SharedTimer>> {
+    let due = LINK
+        .locked()
+        .as_ref()
+        .is_none_or(|l| l.written.elapsed() >= INTERVAL);
+    (enabled && due).then(|| entries(timers))
+}
+
+/// Writes `entries` from [`collect`] to the link, empties it once no longer `enabled`
+pub fn publish(enabled: bool, entries: Option<Vec<SharedTimer>>) {
+    let mut link = LINK.locked();
+    if !enabled {
+        // readers shouldn't keep counting down from the last update
+        if let Some(link) = link.take().filter(|l| !l.shared.is_null()) {
+            // SAFETY: see `Link`
+            write(unsafe { &mut *link.shared }, &[]);
+        }
+        return;
+    }
+    let Some(entries) = entries else {
+        return;
+    };
+    let shared = match link.as_ref() {
+        Some(link) => link.shared,
+        None => {
+            // SAFETY: sized by Nexus for `SharedTimers`, which is valid zeroed
+            let shared = unsafe { share_data_link::<SharedTimers>(ID) };
+            if shared.is_null() {
+                log::warn!("Failed to share the data link {}", ID);
+                crate::eventlog::error("", format!("couldn't share the data link {}", ID));
+            }
+            shared
+        }
+    };
+    if !shared.is_null() {
+        // SAFETY: see `Link`
+        write(unsafe { &mut *shared }, &entries);
+    }
+    *link = Some(Link {
+        shared,
+        written: Instant::now(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem::{offset_of, size_of};
+
+    #[test]
+    fn layout_matches_the_docs() {
+        assert_eq!(size_of::<SharedTimer>(), 96);
+        assert_eq!(offset_of!(SharedTimer, name), 32);
+        assert_eq!(offset_of!(SharedTimers, count), 16);
+        assert_eq!(offset_of!(SharedTimers, timers), 24);
+        assert_eq!(size_of::<SharedTimers>(), 24 + 96 * MAX_TIMERS);
+        assert_eq!(&name_bytes("Greens")[..7], b"Greens\0");
+        // "é" is two bytes and would be cut in half
+        let long = "a".repeat(NAME_LEN - 1) + "é";
+        assert_eq!(name_bytes(&long)[NAME_LEN - 1], 0);
+    }
+}
@@ -0,0 +1,157 @@
+//! Parsing of human-written durations like `90`, `1:30`, `2m30s` or `1 std 30 min`.
+
+use crate::import::MAX_DURATION;
+use std::fmt;
+use std::time::Duration;
+
+// Unit words (lowercase) of every language we localize keybinds for
+#[rustfmt::skip]
+const HOURS: &[&str] = &[
+    "h", "hr", "hrs", "hour", "hours", // en
+    "std", "stunde", "stunden", // de
+    "heure", "heures", // fr
+    "hora", "horas", // es, br
+    "ora", "ore", // it
+    "godz", "godzina", "godziny", "godzin", // pl
+    "hod", "hodina", "hodiny", "hodin", // cz
+    "ч", "час", "часа", "часов", // ru
+    "小时", "时", "時", // cn
+];
+#[rustfmt::skip]
+const MINUTES: &[&str] = &[
+    "m", "min", "mins", "minute", "minutes", // en, fr
+    "minuten", // de
+    "minuto", "minutos", // es, br
+    "minuti", // it
+    "minuta", "minuty", "minut", // pl, cz
+    "м", "мин", "минута", "минуты", "минут", // ru
+    "分", "分钟", "分鐘", // cn
+];
+#[rustfmt::skip]
+const SECONDS: &[&str] = &[
+    "s", "sec", "secs", "second", "seconds", // en
+    "sek", "sekunde", "sekunden", // de
+    "seconde", "secondes", // fr
+    "seg", "segundo", "segundos", // es, br
+    "secondo", "secondi", // it
+    "sekunda", "sekundy", "sekund", // pl, cz
+    "с", "сек", "секунда", "секунды", "секунд", // ru
+    "秒", // cn
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseDurationError {
+    Empty,
+    InvalidNumber(String),
+    UnknownUnit(String),
+    /// Longer than any timer can run, see `MAX_DURATION`
+    TooLong,
+}
+
+impl fmt::Display for ParseDurationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "no duration given"),
+            Self::InvalidNumber(n) => write!(f, "{:?} is not a number", n),
+            Self::UnknownUnit(u) => write!(f, "unknown unit {:?}", u),
+            Self::TooLong => write!(f, "longer than {} days", MAX_DURATION.as_secs() / 86400),
+        }
+    }
+}
+
+fn unit_seconds(unit: &str) -> Option<f64> {
+    if unit.is_empty() || SECONDS.contains(&unit) {
+        Some(1.0)
+    } else if MINUTES.contains(&unit) {
+        Some(60.0)
+    } else if HOURS.contains(&unit) {
+        Some(3600.0)
+    } else if unit == "ms" {
+        Some(0.001)
+    } else {
+        None
+    }
+}
+
+fn parse_number(n: &str) -> Result<f64, ParseDurationError> {
+    // accept decimal commas as used in most of the supported languages
+    n.replace(',', ".")
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite() && *v >= 0.0)
+        .ok_or_else(|| ParseDurationError::InvalidNumber(n.to_string()))
+}
+
+/// Rounded to whole milliseconds, finer than that only shows up as float noise
+fn to_duration(secs: f64) -> Result<Duration, ParseDurationError> {
+    if secs > MAX_DURATION.as_secs_f64() {
+        return Err(ParseDurationError::TooLong);
+    }
+    Duration::try_from_secs_f64((secs * 1000.0).round() / 1000.0)
+        .map_err(|_| ParseDurationError::InvalidNumber(secs.to_string()))
+}
+
+/// `h:mm:ss`, `m:ss` (the last component may have a fraction)
+fn parse_clock(input: &str) -> Result<Duration, ParseDurationError> {
+    let mut secs = 0.0;
+    for part in input.split(':') {
+        secs = secs * 60.0 + parse_number(part.trim())?;
+    }
+    to_duration(secs)
+}
+
+pub fn parse_duration(input: &str) -> Result<Duration, ParseDurationError> {
+    let input = input.trim().to_lowercase();
+    if input.is_empty() {
+        return Err(ParseDurationError::Empty);
+    }
+    if input.contains(':') {
+        return parse_clock(&input);
+    }
+    let mut secs = 0.0;
+    let mut rest = input.as_str();
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == ','))
+            .unwrap_or(rest.len());
+        let (number, tail) = rest.split_at(number_end);
+        if number.is_empty() {
+            return Err(ParseDurationError::InvalidNumber(rest.to_string()));
+        }
+        let tail = tail.trim_start();
+        let unit_end = tail
+            .find(|c: char| !c.is_alphabetic())
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_end);
+        let factor =
+            unit_seconds(unit).ok_or_else(|| ParseDurationError::UnknownUnit(unit.to_string()))?;
+        secs += parse_number(number)? * factor;
+        rest = tail.trim_start();
+    }
+    to_duration(secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_add_row_formats() {
+        let ms = |ms| Ok(Duration::from_millis(ms));
+        assert_eq!(parse_duration("90"), ms(90_000));
+        assert_eq!(parse_duration("1:30"), ms(90_000));
+        assert_eq!(parse_duration("0.5"), ms(500));
+        assert_eq!(parse_duration("2m30s"), ms(150_000));
+        assert_eq!(parse_duration("0.1s 0.2s"), ms(300));
+        assert_eq!(parse_duration("1.5 ms"), ms(2));
+        assert_eq!(parse_duration(" "), Err(ParseDurationError::Empty));
+        assert!(parse_duration("1:x").is_err());
+        assert!(parse_duration("5 parsecs").is_err());
+        assert_eq!(parse_duration("720h"), Ok(MAX_DURATION));
+        assert_eq!(parse_duration("720h 1s"), Err(ParseDurationError::TooLong));
+        assert_eq!(
+            parse_duration("18000000000000000000"),
+            Err(ParseDurationError::TooLong)
+        );
+    }
+}
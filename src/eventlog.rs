@@ -0,0 +1,131 @@
+//! Recent starts, stops, finishes and failures, kept in memory for the log in the
+//! options. Users can look into why a trigger or keybind didn't fire there without
+//! digging through the Nexus log.
+//!
+//! Entries are recorded before whatever they describe is acted on, so a panic on the way
+//! still leaves them behind.
+
+use crate::format::time_of_day;
+use crate::sync::LockExt;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Entries kept, older ones are dropped
+pub const CAPACITY: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Start,
+    Stop,
+    Finish,
+    /// A keybind was pressed, with what it did
+    Keybind,
+    Error,
+}
+
+impl Kind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Start => "start",
+            Self::Stop => "stop",
+            Self::Finish => "finish",
+            Self::Keybind => "keybind",
+            Self::Error => "error",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    /// Unix time in seconds
+    pub at: u64,
+    pub kind: Kind,
+    /// Empty if it isn't about one timer
+    pub timer: String,
+    pub text: String,
+}
+
+impl Entry {
+    /// `12:04:31 start Greens: by keybind`, in local time `offset` seconds ahead of UTC
+    pub fn line(&self, offset: i64) -> String {
+        let mut line = format!("{} {}", time_of_day(self.at, offset), self.kind.name());
+        if !self.timer.is_empty() {
+            line.push(' ');
+            line.push_str(&self.timer);
+        }
+        if !self.text.is_empty() {
+            line.push_str(": ");
+            line.push_str(&self.text);
+        }
+        line
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct EventLog {
+    pub entries: VecDeque<Entry>,
+}
+
+impl EventLog {
+    pub const fn new() -> Self {
+        Self {
+            entries: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, entry: Entry) {
+        if self.entries.len() >= CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+}
+
+pub static LOG: Mutex<EventLog> = Mutex::new(EventLog::new());
+
+/// Adds an entry about `timer`, empty if it is about none
+pub fn record(kind: Kind, timer: &str, text: impl Into<String>) {
+    let at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    LOG.locked().push(Entry {
+        at,
+        kind,
+        timer: timer.to_string(),
+        text: text.into(),
+    });
+}
+
+pub fn error(timer: &str, text: impl Into<String>) {
+    record(Kind::Error, timer, text);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_keeps_the_latest() {
+        let mut log = EventLog::new();
+        for i in 0..CAPACITY + 5 {
+            log.push(Entry {
+                at: i as u64,
+                kind: Kind::Start,
+                timer: "Greens".to_string(),
+                text: String::new(),
+            });
+        }
+        assert_eq!(log.entries.len(), CAPACITY);
+        assert_eq!(log.entries.front().map(|e| e.at), Some(5));
+        let entry = Entry {
+            at: 45_296,
+            kind: Kind::Error,
+            timer: String::new(),
+            text: "no sound".to_string(),
+        };
+        assert_eq!(entry.line(0), "12:34:56 error: no sound");
+        assert_eq!(entry.line(-13 * 3600), "23:34:56 error: no sound");
+    }
+}
@@ -0,0 +1,271 @@
+//! How remaining and overtime durations are displayed.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::time::Duration;
+
+/// Below this the adaptive format shows decimals
+const ADAPTIVE_THRESHOLD: Duration = Duration::from_secs(10);
+
+/// Decimals of the seconds formats, unless set otherwise
+pub const DEFAULT_DECIMALS: u8 = 2;
+pub const MAX_DECIMALS: u8 = 2;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeFormat {
+    /// `92.41`
+    #[default]
+    Seconds,
+    /// `1:32`
+    MinSec,
+    /// `0:01:32`
+    HourMinSec,
+    /// `1:32` and `9.41` in the final seconds
+    Adaptive,
+}
+
+impl TimeFormat {
+    pub const ALL: [TimeFormat; 4] = [
+        Self::Seconds,
+        Self::MinSec,
+        Self::HourMinSec,
+        Self::Adaptive,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Seconds => "Seconds (92.41)",
+            Self::MinSec => "Minutes (1:32)",
+            Self::HourMinSec => "Hours (0:01:32)",
+            Self::Adaptive => "Adaptive (1:32, 9.41)",
+        }
+    }
+
+    pub fn with_decimals(self, decimals: u8) -> TimeStyle {
+        TimeStyle {
+            format: self,
+            decimals: decimals.min(MAX_DECIMALS),
+            rounding: Rounding::default(),
+        }
+    }
+
+    /// The fixed format `d` is shown in
+    fn resolve(self, d: Duration) -> Self {
+        match self {
+            Self::Adaptive if d < ADAPTIVE_THRESHOLD => Self::Seconds,
+            Self::Adaptive if d.as_secs() >= 3600 => Self::HourMinSec,
+            Self::Adaptive => Self::MinSec,
+            format => format,
+        }
+    }
+}
+
+/// How a time is rounded to the last digit shown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rounding {
+    /// `2.9` is shown as `2`, `0` only at the very end
+    #[default]
+    Down,
+    /// `2.1` is shown as `3`, `2` once it reaches 2.0
+    Up,
+    /// `2.4` is shown as `2` and `2.5` as `3`
+    Nearest,
+}
+
+impl Rounding {
+    pub const ALL: [Rounding; 3] = [Self::Down, Self::Up, Self::Nearest];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Down => "Down",
+            Self::Up => "Up",
+            Self::Nearest => "Nearest",
+        }
+    }
+
+    /// `d` rounded to a multiple of `step`
+    fn round(self, d: Duration, step: Duration) -> Duration {
+        let (nanos, step) = (d.as_nanos(), step.as_nanos());
+        let steps = match self {
+            Self::Down => nanos / step,
+            Self::Up => nanos.div_ceil(step),
+            Self::Nearest => (nanos + step / 2) / step,
+        };
+        Duration::from_nanos((steps * step) as u64)
+    }
+}
+
+/// A format, how many decimals its seconds show, where it shows any, and how the last
+/// digit is rounded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeStyle {
+    pub format: TimeFormat,
+    pub decimals: u8,
+    pub rounding: Rounding,
+}
+
+impl Default for TimeStyle {
+    fn default() -> Self {
+        TimeFormat::default().with_decimals(DEFAULT_DECIMALS)
+    }
+}
+
+impl TimeStyle {
+    pub fn rounded(self, rounding: Rounding) -> Self {
+        Self { rounding, ..self }
+    }
+
+    /// The format `d` is shown in and `d` rounded to its last digit
+    fn resolve(self, d: Duration) -> (TimeFormat, Duration) {
+        let format = self.format.resolve(d);
+        let step = match format {
+            TimeFormat::Seconds => {
+                Duration::from_millis(10u64.pow(3 - self.decimals.min(MAX_DECIMALS) as u32))
+            }
+            _ => Duration::from_secs(1),
+        };
+        (format, self.rounding.round(d, step))
+    }
+
+    pub fn format(self, d: Duration) -> String {
+        let mut text = String::new();
+        self.format_into(d, &mut text);
+        text
+    }
+
+    /// Like [`Self::format`] into an existing buffer, which is cleared first
+    pub fn format_into(self, d: Duration, out: &mut String) {
+        out.clear();
+        // picked by the time before it is rounded, so rounding up the final seconds of
+        // the adaptive format reads `10.00` rather than jumping to `0:10` and back
+        let (format, d) = self.resolve(d);
+        let secs = d.as_secs();
+        // writing to a String can't fail
+        let _ = match format {
+            TimeFormat::Seconds => match self.decimals {
+                0 => write!(out, "{}", secs),
+                1 => write!(out, "{}.{}", secs, d.subsec_millis() / 100),
+                _ => write!(out, "{}.{:02}", secs, d.subsec_millis() / 10),
+            },
+            TimeFormat::MinSec => write!(out, "{}:{:02}", secs / 60, secs % 60),
+            _ => write!(
+                out,
+                "{}:{:02}:{:02}",
+                secs / 3600,
+                secs / 60 % 60,
+                secs % 60
+            ),
+        };
+    }
+
+    /// Changes exactly when the text of [`Self::format`] does, so it is only formatted
+    /// again when it would read differently
+    pub fn tick(self, d: Duration) -> u64 {
+        let (format, d) = self.resolve(d);
+        // in hundredths, apart from the format when the adaptive one switches
+        (d.as_millis() as u64 / 10) * 4 + format as u64
+    }
+}
+
+/// `21:43:10` at unix time `unix`, in local time `offset` seconds ahead of UTC
+pub fn time_of_day(unix: u64, offset: i64) -> String {
+    let secs = (unix as i64 + offset).rem_euclid(24 * 60 * 60);
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    impl TimeFormat {
+        fn format(self, d: Duration) -> String {
+            self.with_decimals(DEFAULT_DECIMALS).format(d)
+        }
+    }
+
+    #[test]
+    fn formats_seconds() {
+        assert_eq!(TimeFormat::Seconds.format(ms(92_414)), "92.41");
+        assert_eq!(TimeFormat::Seconds.format(Duration::ZERO), "0.00");
+    }
+
+    #[test]
+    fn formats_times_of_day() {
+        assert_eq!(time_of_day(78_190, 0), "21:43:10");
+        assert_eq!(time_of_day(78_190, 3 * 3600), "00:43:10");
+        assert_eq!(time_of_day(0, -3600), "23:00:00");
+    }
+
+    #[test]
+    fn formats_clock_styles() {
+        assert_eq!(TimeFormat::MinSec.format(ms(92_414)), "1:32");
+        assert_eq!(TimeFormat::MinSec.format(ms(3_723_000)), "62:03");
+        assert_eq!(TimeFormat::HourMinSec.format(ms(92_414)), "0:01:32");
+        assert_eq!(TimeFormat::HourMinSec.format(ms(3_723_999)), "1:02:03");
+    }
+
+    #[test]
+    fn adaptive_switches_to_decimals() {
+        assert_eq!(TimeFormat::Adaptive.format(ms(3_723_000)), "1:02:03");
+        assert_eq!(TimeFormat::Adaptive.format(ms(92_414)), "1:32");
+        assert_eq!(TimeFormat::Adaptive.format(ms(10_000)), "0:10");
+        assert_eq!(TimeFormat::Adaptive.format(ms(9_414)), "9.41");
+    }
+
+    #[test]
+    fn decimals_of_seconds() {
+        let style = |decimals| TimeFormat::Seconds.with_decimals(decimals);
+        assert_eq!(style(0).format(ms(92_414)), "92");
+        assert_eq!(style(1).format(ms(92_414)), "92.4");
+        assert_eq!(style(2).format(ms(92_414)), "92.41");
+        assert_eq!(style(9).format(ms(92_414)), "92.41");
+        assert_eq!(
+            TimeFormat::Adaptive.with_decimals(1).format(ms(9_414)),
+            "9.4"
+        );
+        assert_eq!(
+            TimeFormat::MinSec.with_decimals(0).format(ms(92_414)),
+            "1:32"
+        );
+    }
+
+    #[test]
+    fn rounds_the_last_digit() {
+        let style = |rounding| TimeFormat::Seconds.with_decimals(0).rounded(rounding);
+        assert_eq!(style(Rounding::Down).format(ms(2_900)), "2");
+        assert_eq!(style(Rounding::Up).format(ms(2_100)), "3");
+        assert_eq!(style(Rounding::Up).format(ms(2_000)), "2");
+        assert_eq!(style(Rounding::Up).format(Duration::from_nanos(1)), "1");
+        assert_eq!(style(Rounding::Nearest).format(ms(2_499)), "2");
+        assert_eq!(style(Rounding::Nearest).format(ms(2_500)), "3");
+        let up = |format: TimeFormat| format.with_decimals(2).rounded(Rounding::Up);
+        assert_eq!(up(TimeFormat::Seconds).format(ms(92_411)), "92.42");
+        assert_eq!(up(TimeFormat::MinSec).format(ms(59_001)), "1:00");
+        assert_eq!(up(TimeFormat::Adaptive).format(ms(9_999)), "10.00");
+        assert_eq!(up(TimeFormat::Adaptive).format(ms(10_001)), "0:11");
+    }
+
+    #[test]
+    fn tick_follows_the_text() {
+        for (format, rounding) in TimeFormat::ALL
+            .into_iter()
+            .flat_map(|f| Rounding::ALL.map(|r| (f, r)))
+        {
+            for decimals in 0..=MAX_DECIMALS {
+                let style = format.with_decimals(decimals).rounded(rounding);
+                let mut last = (style.tick(ms(0)), style.format(ms(0)));
+                for t in (5..20_000).step_by(5) {
+                    let now = (style.tick(ms(t)), style.format(ms(t)));
+                    assert_eq!(now.0 == last.0, now.1 == last.1, "{:?} at {}ms", style, t);
+                    last = now;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! What the game is currently doing, read from the MumbleLink.
+
+use crate::sync::LockExt;
+use nexus::data_link::{get_mumble_link, mumble::UiState};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The game updates the link every frame, except on loading screens
+const STALL: Duration = Duration::from_millis(500);
+
+// Last ui tick of the link and when it changed, polled from the tick thread too
+static LAST_TICK: Mutex<Option<(u32, Instant)>> = Mutex::new(None);
+
+pub fn in_combat() -> bool {
+    get_mumble_link().is_some_and(|link| link.context.ui_state.contains(UiState::IN_COMBAT))
+}
+
+/// The map the player is on, `None` without a link or before one is loaded
+pub fn map_id() -> Option<u32> {
+    get_mumble_link()
+        .map(|link| link.context.map_id)
+        .filter(|&id| id != 0)
+}
+
+/// JSON the game writes into the link with the character name and interface settings
+fn identity() -> Option<String> {
+    let link = get_mumble_link()?;
+    let len = link
+        .identity
+        .iter()
+        .position(|&c| c == 0)
+        .unwrap_or(link.identity.len());
+    Some(String::from_utf16_lossy(&link.identity[..len]))
+}
+
+/// Interface size setting of the game, 0 for small up to 3 for larger
+pub fn ui_size() -> Option<u8> {
+    ui_size_of(&identity()?)
+}
+
+fn ui_size_of(identity: &str) -> Option<u8> {
+    let (_, rest) = identity.split_once("\"uisz\":")?;
+    let digits = rest.trim_start();
+    let end = digits
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(digits.len());
+    digits[..end].parse().ok().filter(|&size| size <= 3)
+}
+
+/// Name of the character being played, `None` on the character select and without a link
+pub fn character() -> Option<String> {
+    character_of(&identity()?)
+}
+
+fn character_of(identity: &str) -> Option<String> {
+    let (_, rest) = identity.split_once("\"name\":")?;
+    let mut chars = rest.trim_start().strip_prefix('"')?.chars();
+    let mut name = String::new();
+    loop {
+        match chars.next()? {
+            '"' => break,
+            // names are letters and spaces, nothing that would need a \u escape
+            '\\' => name.push(chars.next()?),
+            c => name.push(c),
+        }
+    }
+    (!name.is_empty()).then_some(name)
+}
+
+/// Whether a loading screen or the character select is up, the game doesn't update the
+/// link on either. Without a link there is no telling, that counts as playing.
+pub fn is_loading() -> bool {
+    let Some(link) = get_mumble_link() else {
+        return false;
+    };
+    let mut last = LAST_TICK.locked();
+    match *last {
+        Some((tick, since)) if tick == link.ui_tick => since.elapsed() >= STALL,
+        _ => {
+            *last = Some((link.ui_tick, Instant::now()));
+            false
+        }
+    }
+}
+
+/// Whether the game window is in the background or [`is_loading`]
+pub fn is_away() -> bool {
+    let focused =
+        get_mumble_link().is_none_or(|link| link.context.ui_state.contains(UiState::GAME_FOCUS));
+    !focused || is_loading()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ui_size_from_the_identity() {
+        let identity = r#"{"name":"Char","profession":4,"map_id":1206,"fov":0.873,"uisz":2}"#;
+        assert_eq!(ui_size_of(identity), Some(2));
+        assert_eq!(ui_size_of(r#"{"uisz": 0, "name":"Char"}"#), Some(0));
+        assert_eq!(ui_size_of(r#"{"uisz":9}"#), None);
+        assert_eq!(ui_size_of(""), None);
+    }
+
+    #[test]
+    fn character_from_the_identity() {
+        let identity = r#"{"name":"Èlà Swiftblade","profession":4,"uisz":2}"#;
+        assert_eq!(character_of(identity).as_deref(), Some("Èlà Swiftblade"));
+        assert_eq!(
+            character_of(r#"{"name": "Odd \"One\""}"#).as_deref(),
+            Some("Odd \"One\"")
+        );
+        assert_eq!(character_of(r#"{"name":"","uisz":1}"#), None);
+        assert_eq!(character_of(r#"{"name":"Cut"#), None);
+        assert_eq!(character_of(""), None);
+    }
+}
@@ -0,0 +1,120 @@
+//! Daily crafting cooldowns of the account from the official GW2 API, shown as
+//! read-only system timers counting down to the daily reset.
+
+use crate::recurrence::ResetSchedule;
+use crate::sync::LockExt;
+use crate::timer::Timers;
+use crate::worker::Worker;
+use serde::Deserialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+const HOST: &str = "api.guildwars2.com";
+/// Source of the system timers created here
+const SOURCE: &str = "GW2 API";
+const REFRESH: Duration = Duration::from_secs(5 * 60);
+
+struct Fetched {
+    key: String,
+    /// Ids of the daily crafts already done today, e.g. `charged_quartz_crystal`
+    result: Result<Vec<String>, String>,
+}
+
+struct State {
+    /// Key and time of the last request sent
+    requested: Option<(String, Instant)>,
+    fetched: Option<Fetched>,
+    status: String,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    requested: None,
+    fetched: None,
+    status: String::new(),
+});
+
+fn parse(body: &[u8]) -> Result<Vec<String>, String> {
+    #[derive(Deserialize)]
+    struct ApiError {
+        text: String,
+    }
+    serde_json::from_slice(body).map_err(|e| match serde_json::from_slice::<ApiError>(body) {
+        Ok(err) => err.text,
+        Err(_) => e.to_string(),
+    })
+}
+
+fn fetch(key: String) {
+    let result = crate::http::get(
+        HOST,
+        "/v2/account/dailycrafting",
+        &format!("Authorization: Bearer {}", key),
+    )
+    .and_then(|body| parse(&body));
+    STATE.locked().fetched = Some(Fetched { key, result });
+}
+
+static FETCHER: Worker<String> = Worker::new("gw2api", Duration::from_secs(30), fetch);
+
+/// `charged_quartz_crystal` to `Charged Quartz Crystal`
+fn display_name(id: &str) -> String {
+    id.split('_')
+        .filter(|w| !w.is_empty())
+        .map(|w| {
+            let mut chars = w.chars();
+            chars
+                .next()
+                .map(|c| c.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Applies finished requests to `timers` and sends a new one when due, called every frame
+pub fn poll(key: &str, timers: &mut Timers) {
+    let mut state = STATE.locked();
+    if key.is_empty() {
+        if state.requested.take().is_some() {
+            timers.sync_system(SOURCE, &[]);
+            state.status.clear();
+        }
+        return;
+    }
+    if let Some(Fetched { key: used, result }) = state.fetched.take() {
+        // answers for a key that was changed in the meantime are outdated
+        if used == key {
+            match result {
+                Ok(done) => {
+                    let now = SystemTime::now();
+                    let reset = ResetSchedule::default().next_daily(now);
+                    let remaining = reset.duration_since(now).unwrap_or_default();
+                    let wanted: Vec<_> = done
+                        .iter()
+                        .map(|id| (display_name(id), remaining))
+                        .collect();
+                    timers.sync_system(SOURCE, &wanted);
+                    state.status = format!("Updated, {} crafts on cooldown", wanted.len());
+                }
+                Err(e) => {
+                    log::warn!("Failed to fetch daily crafting: {}", e);
+                    state.status = format!("Update failed: {}", e);
+                }
+            }
+        }
+    }
+    let due = match &state.requested {
+        Some((used, at)) => used != key || at.elapsed() >= REFRESH,
+        None => true,
+    };
+    if due {
+        state.requested = Some((key.to_string(), Instant::now()));
+        drop(state);
+        FETCHER.send(key.to_string());
+    }
+}
+
+/// Outcome of the last update, empty if there was none
+pub fn status() -> String {
+    STATE.locked().status.clone()
+}
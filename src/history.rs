@@ -0,0 +1,317 @@
+//! Past runs of the timers, each optionally tagged with a note, and statistics on them.
+
+use crate::recurrence::ResetSchedule;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Runs kept, the oldest are dropped
+const MAX_RUNS: usize = 1000;
+
+pub static HISTORY: Mutex<History> = Mutex::new(History {
+    runs: Vec::new(),
+    started: BTreeMap::new(),
+});
+
+fn unix_secs(t: SystemTime) -> u64 {
+    t.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Finished,
+    /// Stopped by hand before running out
+    Stopped,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Run {
+    pub id: u64,
+    pub timer: String,
+    /// Unix time in seconds
+    #[serde(default)]
+    pub started: u64,
+    /// Unix time in seconds
+    pub ended: u64,
+    /// Time spent counting, without pauses
+    pub length: Duration,
+    pub outcome: Outcome,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note: String,
+    /// Time into the run at each lap
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub laps: Vec<Duration>,
+}
+
+/// Runs of one timer
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub timer: String,
+    pub runs: usize,
+    pub finished: usize,
+    pub average: Duration,
+}
+
+/// Finished runs of one timer since the daily and the weekly reset
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Completions {
+    pub today: usize,
+    pub week: usize,
+    /// Days in a row with a finished run, up to today or yesterday. Only as far back as
+    /// the runs that are kept.
+    pub streak: u32,
+}
+
+impl Completions {
+    /// Like `2× today, 5× this week, 3 days in a row`
+    pub fn label(self) -> String {
+        let mut label = format!("{}× today, {}× this week", self.today, self.week);
+        if self.streak > 1 {
+            label.push_str(&format!(", {} days in a row", self.streak));
+        }
+        label
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct History {
+    #[serde(default)]
+    pub runs: Vec<Run>,
+    // When the current run of each timer started, by the name of the timer running it
+    #[serde(skip)]
+    started: BTreeMap<String, u64>,
+}
+
+impl History {
+    /// Remembers when timer `run` started a run, to be recorded once it ends
+    pub fn begin(&mut self, run: &str, at: SystemTime) {
+        self.started.insert(run.to_string(), unix_secs(at));
+    }
+
+    /// Adds a run of timer `run` that ended at `ended` as one of `timer`, returns its id.
+    /// The two differ for instances, their runs are counted as the spawning timer's.
+    pub fn record(
+        &mut self,
+        run: &str,
+        timer: &str,
+        length: Duration,
+        outcome: Outcome,
+        ended: SystemTime,
+    ) -> u64 {
+        let id = self.runs.last().map_or(1, |r| r.id + 1);
+        let ended = unix_secs(ended);
+        let started = self
+            .started
+            .remove(run)
+            .unwrap_or_else(|| ended.saturating_sub(length.as_secs()));
+        self.runs.push(Run {
+            id,
+            timer: timer.to_string(),
+            started,
+            ended,
+            length,
+            outcome,
+            note: String::new(),
+            laps: Vec::new(),
+        });
+        let excess = self.runs.len().saturating_sub(MAX_RUNS);
+        self.runs.drain(..excess);
+        id
+    }
+
+    pub fn set_note(&mut self, id: u64, note: String) {
+        if let Some(run) = self.runs.iter_mut().find(|r| r.id == id) {
+            run.note = note;
+        }
+    }
+
+    pub fn set_laps(&mut self, id: u64, laps: Vec<Duration>) {
+        if let Some(run) = self.runs.iter_mut().find(|r| r.id == id) {
+            run.laps = laps;
+        }
+    }
+
+    /// How often `timer` finished since the resets before `now`
+    pub fn completions(&self, timer: &str, resets: &ResetSchedule, now: SystemTime) -> Completions {
+        let (today, week_began) = (resets.day(unix_secs(now)), resets.week_began(now));
+        let mut days = BTreeSet::new();
+        let mut completions = Completions::default();
+        let finished = self
+            .runs
+            .iter()
+            .filter(|r| r.timer == timer && r.outcome == Outcome::Finished);
+        for run in finished {
+            let day = resets.day(run.ended);
+            completions.today += usize::from(day == today);
+            completions.week += usize::from(run.ended >= week_began);
+            days.insert(day);
+        }
+        // still going until the end of today
+        let mut day = if days.contains(&today) {
+            today
+        } else {
+            today.saturating_sub(1)
+        };
+        while days.contains(&day) {
+            completions.streak += 1;
+            day = match day.checked_sub(1) {
+                Some(day) => day,
+                None => break,
+            };
+        }
+        completions
+    }
+
+    /// Per timer, sorted by name
+    pub fn stats(&self) -> Vec<Stats> {
+        let mut by_timer: BTreeMap<&str, Vec<&Run>> = BTreeMap::new();
+        for run in &self.runs {
+            by_timer.entry(&run.timer).or_default().push(run);
+        }
+        by_timer
+            .into_iter()
+            .map(|(timer, runs)| Stats {
+                timer: timer.to_string(),
+                runs: runs.len(),
+                finished: runs
+                    .iter()
+                    .filter(|r| r.outcome == Outcome::Finished)
+                    .count(),
+                average: runs.iter().map(|r| r.length).sum::<Duration>() / runs.len() as u32,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEC: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn keeps_the_latest_runs() {
+        let mut history = History::default();
+        for _ in 0..MAX_RUNS + 5 {
+            history.record("a", "a", Duration::ZERO, Outcome::Finished, UNIX_EPOCH);
+        }
+        assert_eq!(history.runs.len(), MAX_RUNS);
+        assert_eq!(history.runs[0].id, 6);
+        let id = history.record("b", "b", Duration::ZERO, Outcome::Stopped, UNIX_EPOCH);
+        assert_eq!(id, MAX_RUNS as u64 + 6);
+        history.set_note(id, "wipe at split 2".to_string());
+        assert_eq!(history.runs.last().unwrap().note, "wipe at split 2");
+    }
+
+    #[test]
+    fn runs_keep_their_start() {
+        let mut history = History::default();
+        history.begin("a", UNIX_EPOCH + 100 * SEC);
+        history.record(
+            "a",
+            "a",
+            10 * SEC,
+            Outcome::Finished,
+            UNIX_EPOCH + 130 * SEC,
+        );
+        // paused for a while, so the start isn't the end minus the length
+        assert_eq!(history.runs[0].started, 100);
+        history.record(
+            "a",
+            "a",
+            10 * SEC,
+            Outcome::Finished,
+            UNIX_EPOCH + 200 * SEC,
+        );
+        assert_eq!(history.runs[1].started, 190);
+        // a second run of the same timer, while the first is still going
+        history.begin("a", UNIX_EPOCH + 300 * SEC);
+        history.begin("a #2", UNIX_EPOCH + 320 * SEC);
+        history.record(
+            "a #2",
+            "a",
+            10 * SEC,
+            Outcome::Finished,
+            UNIX_EPOCH + 330 * SEC,
+        );
+        history.record(
+            "a",
+            "a",
+            10 * SEC,
+            Outcome::Finished,
+            UNIX_EPOCH + 360 * SEC,
+        );
+        assert_eq!(history.runs[2].timer, "a");
+        assert_eq!(history.runs[2].started, 320);
+        assert_eq!(history.runs[3].started, 300);
+    }
+
+    #[test]
+    fn completions_since_the_resets() {
+        const DAY: u64 = 24 * 3600;
+        let resets = ResetSchedule::default();
+        let at = |secs: u64| UNIX_EPOCH + Duration::from_secs(secs);
+        let mut history = History::default();
+        // a monday, the weekly reset is at 7:30
+        let monday = 4 * DAY;
+        for ended in [
+            monday - DAY + 3600,
+            monday + 3600,
+            monday + 8 * 3600,
+            monday + DAY + 3600,
+            monday + DAY + 7200,
+        ] {
+            history.record("a", "a", SEC, Outcome::Finished, at(ended));
+        }
+        history.record("a", "a", SEC, Outcome::Stopped, at(monday + DAY + 9000));
+        history.record("b", "b", SEC, Outcome::Finished, at(monday + DAY + 9000));
+        let now = at(monday + DAY + 10_000);
+        assert_eq!(
+            history.completions("a", &resets, now),
+            Completions {
+                today: 2,
+                week: 3,
+                streak: 3,
+            }
+        );
+        // yesterday's run still counts towards the streak until today ends
+        let tomorrow = at(monday + 2 * DAY + 10);
+        assert_eq!(history.completions("a", &resets, tomorrow).streak, 3);
+        assert_eq!(history.completions("a", &resets, tomorrow).today, 0);
+        let later = at(monday + 3 * DAY + 10);
+        assert_eq!(history.completions("a", &resets, later).streak, 0);
+        assert_eq!(
+            history.completions("a", &resets, now).label(),
+            "2× today, 3× this week, 3 days in a row"
+        );
+    }
+
+    #[test]
+    fn counts_and_averages_per_timer() {
+        let mut history = History::default();
+        history.record("b", "b", 10 * SEC, Outcome::Finished, UNIX_EPOCH);
+        history.record("a", "a", 4 * SEC, Outcome::Stopped, UNIX_EPOCH);
+        history.record("b", "b", 5 * SEC, Outcome::Stopped, UNIX_EPOCH);
+        history.record("b", "b", 9 * SEC, Outcome::Finished, UNIX_EPOCH);
+        let stats = history.stats();
+        assert_eq!(
+            stats,
+            vec![
+                Stats {
+                    timer: "a".to_string(),
+                    runs: 1,
+                    finished: 0,
+                    average: 4 * SEC,
+                },
+                Stats {
+                    timer: "b".to_string(),
+                    runs: 3,
+                    finished: 2,
+                    average: 8 * SEC,
+                },
+            ]
+        );
+    }
+}
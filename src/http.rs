@@ -0,0 +1,113 @@
+//! Minimal blocking HTTPS client on top of WinHTTP, only used from worker threads.
+
+use std::ffi::c_void;
+use windows::core::{w, Error, HSTRING, PCWSTR};
+use windows::Win32::Networking::WinHttp::{
+    WinHttpCloseHandle, WinHttpConnect, WinHttpOpen, WinHttpOpenRequest, WinHttpQueryDataAvailable,
+    WinHttpReadData, WinHttpReceiveResponse, WinHttpSendRequest, WinHttpSetTimeouts,
+    INTERNET_DEFAULT_HTTPS_PORT, WINHTTP_ACCESS_TYPE_AUTOMATIC_PROXY, WINHTTP_FLAG_SECURE,
+};
+
+/// Longest wait for resolving the host and connecting, in milliseconds
+const CONNECT_TIMEOUT_MS: i32 = 5_000;
+/// Longest wait for sending the request or the next bit of the response, in milliseconds,
+/// so a stalled server can't hold up a worker and with it the unload
+const TRANSFER_TIMEOUT_MS: i32 = 10_000;
+
+/// Closes the WinHTTP handle when dropped
+struct Handle(*mut c_void);
+
+impl Handle {
+    fn new(raw: *mut c_void) -> windows::core::Result<Self> {
+        if raw.is_null() {
+            Err(Error::from_win32())
+        } else {
+            Ok(Self(raw))
+        }
+    }
+}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        let _ = unsafe { WinHttpCloseHandle(self.0) };
+    }
+}
+
+/// `GET https://{host}{path}` and returns the body, whatever the status code.
+/// `headers` are `Name: value` lines separated by `\r\n`.
+pub fn get(host: &str, path: &str, headers: &str) -> Result<Vec<u8>, String> {
+    request(w!("GET"), host, path, headers, &[]).map_err(|e| e.to_string())
+}
+
+/// `POST https://{host}{path}` with `body`, returns the response body like [`get`]
+pub fn post(host: &str, path: &str, headers: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+    request(w!("POST"), host, path, headers, body).map_err(|e| e.to_string())
+}
+
+fn request(
+    method: PCWSTR,
+    host: &str,
+    path: &str,
+    headers: &str,
+    body: &[u8],
+) -> windows::core::Result<Vec<u8>> {
+    let headers: Vec<u16> = headers.encode_utf16().collect();
+    unsafe {
+        let session = Handle::new(WinHttpOpen(
+            w!("nexus-timers"),
+            WINHTTP_ACCESS_TYPE_AUTOMATIC_PROXY,
+            PCWSTR::null(),
+            PCWSTR::null(),
+            0,
+        ))?;
+        WinHttpSetTimeouts(
+            session.0,
+            CONNECT_TIMEOUT_MS,
+            CONNECT_TIMEOUT_MS,
+            TRANSFER_TIMEOUT_MS,
+            TRANSFER_TIMEOUT_MS,
+        )?;
+        let connection = Handle::new(WinHttpConnect(
+            session.0,
+            &HSTRING::from(host),
+            INTERNET_DEFAULT_HTTPS_PORT,
+            0,
+        ))?;
+        let request = Handle::new(WinHttpOpenRequest(
+            connection.0,
+            method,
+            &HSTRING::from(path),
+            PCWSTR::null(),
+            PCWSTR::null(),
+            std::ptr::null(),
+            WINHTTP_FLAG_SECURE,
+        ))?;
+        WinHttpSendRequest(
+            request.0,
+            (!headers.is_empty()).then_some(headers.as_slice()),
+            (!body.is_empty()).then_some(body.as_ptr().cast()),
+            body.len() as u32,
+            body.len() as u32,
+            0,
+        )?;
+        WinHttpReceiveResponse(request.0, std::ptr::null_mut())?;
+        let mut body = Vec::new();
+        loop {
+            let mut available = 0;
+            WinHttpQueryDataAvailable(request.0, &mut available)?;
+            if available == 0 {
+                return Ok(body);
+            }
+            let start = body.len();
+            body.resize(start + available as usize, 0);
+            let mut read = 0;
+            WinHttpReadData(
+                request.0,
+                body[start..].as_mut_ptr().cast(),
+                available,
+                &mut read,
+            )?;
+            body.truncate(start + read as usize);
+        }
+    }
+}
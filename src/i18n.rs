@@ -0,0 +1,82 @@
+//! UI strings in every language Nexus supports, from the embedded files in `lang/`.
+//! They are handed to Nexus, which picks the one of the current language.
+
+use nexus::localization::{set_translation, translate};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+pub const LANGS: &[&str] = &["br", "cn", "cz", "de", "en", "es", "fr", "it", "pl", "ru"];
+
+const FILES: &[(&str, &str)] = &[
+    ("br", include_str!("lang/br.json")),
+    ("cn", include_str!("lang/cn.json")),
+    ("cz", include_str!("lang/cz.json")),
+    ("de", include_str!("lang/de.json")),
+    ("en", include_str!("lang/en.json")),
+    ("es", include_str!("lang/es.json")),
+    ("fr", include_str!("lang/fr.json")),
+    ("it", include_str!("lang/it.json")),
+    ("pl", include_str!("lang/pl.json")),
+    ("ru", include_str!("lang/ru.json")),
+];
+
+const PREFIX: &str = "TIMERS_UI_";
+
+type Strings = BTreeMap<&'static str, BTreeMap<String, String>>;
+
+fn strings() -> &'static Strings {
+    static STRINGS: OnceLock<Strings> = OnceLock::new();
+    STRINGS.get_or_init(|| {
+        FILES
+            .iter()
+            .map(|&(lang, json)| {
+                let strings = serde_json::from_str(json).expect("Language file to be valid");
+                (lang, strings)
+            })
+            .collect()
+    })
+}
+
+/// `key` in `lang`, English if that language doesn't have it
+pub fn text(lang: &str, key: &str) -> String {
+    let strings = strings();
+    strings
+        .get(lang)
+        .and_then(|s| s.get(key))
+        .or_else(|| strings["en"].get(key))
+        .cloned()
+        .unwrap_or_else(|| key.to_string())
+}
+
+/// Hands all strings to Nexus
+pub fn register() {
+    for &lang in LANGS {
+        for key in strings()["en"].keys() {
+            set_translation(format!("{}{}", PREFIX, key), lang, text(lang, key));
+        }
+    }
+}
+
+/// `key` in the current language
+pub fn tr(key: &str) -> String {
+    let id = format!("{}{}", PREFIX, key);
+    // Nexus hands back the identifier for strings it doesn't know
+    translate(&id)
+        .filter(|t| *t != id)
+        .unwrap_or_else(|| text("en", key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_language_has_every_string() {
+        let strings = strings();
+        assert_eq!(strings.len(), LANGS.len());
+        let keys: Vec<_> = strings["en"].keys().collect();
+        for (lang, s) in strings {
+            assert_eq!(s.keys().collect::<Vec<_>>(), keys, "keys of {}", lang);
+        }
+    }
+}
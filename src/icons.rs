@@ -0,0 +1,31 @@
+//! Timer icons, image files in the addon directory loaded as Nexus textures.
+
+use nexus::imgui::{Image, Ui};
+use nexus::texture::get_texture_or_create_from_file;
+use std::path::{Component, Path, PathBuf};
+
+const TEXTURE_PREFIX: &str = "TEX_TIMERS_ICON_";
+
+/// Resolves an icon path relative to the addon directory, refusing anything outside of it
+pub fn resolve(path: &str) -> Option<PathBuf> {
+    let relative = Path::new(path);
+    let inside = relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    (inside && !path.is_empty()).then(|| crate::config::addon_dir().join(relative))
+}
+
+/// Draws the icon as a square of `size`. Nexus loads textures in the background,
+/// so nothing is drawn for the first few frames.
+pub fn draw(ui: &Ui, path: &str, size: f32) -> bool {
+    let Some(file) = resolve(path) else {
+        return false;
+    };
+    match get_texture_or_create_from_file(format!("{}{}", TEXTURE_PREFIX, path), file) {
+        Some(texture) => {
+            Image::new(texture.id(), [size, size]).build(ui);
+            true
+        }
+        None => false,
+    }
+}
@@ -0,0 +1,329 @@
+//! Validation for timers coming from outside sources (packs, clipboard, ...).
+//!
+//! Everything that isn't our own timers.json goes through here so a malformed
+//! file can't panic the render thread or put nonsense into the live timers.
+
+use crate::config::{ConfigFile, StoredConfig};
+use crate::marker::Marker;
+use crate::timer::{Channel, ColorBand, Timer};
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+const MAX_SIZE: usize = 1024 * 1024;
+const MAX_TIMERS: usize = 1000;
+const MAX_NAME_LEN: usize = 64;
+const MAX_TEXT_LEN: usize = 1024;
+const MAX_EXTRA: usize = 64;
+pub const MAX_DURATION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+/// Shortest cap on a run, a zero one would stop every run as it starts
+pub const MIN_RUN: Duration = Duration::from_secs(1);
+pub const MAX_REFRESH_HZ: u32 = 120;
+pub const MAX_OFFSET_MS: i32 = 60_000;
+/// Smallest and largest overlay window that can be dragged to
+pub const MIN_WINDOW: f32 = 20.0;
+pub const MAX_WINDOW: f32 = 4000.0;
+
+#[derive(Debug)]
+pub enum ImportError {
+    Io(std::io::Error),
+    TooLarge(usize),
+    Parse(serde_json::Error),
+    TooMany(usize),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{}", e),
+            Self::TooLarge(size) => write!(
+                f,
+                "{} bytes is larger than the limit of {} bytes",
+                size, MAX_SIZE
+            ),
+            Self::Parse(e) => write!(
+                f,
+                "invalid timer JSON at line {}, column {}: {}",
+                e.line(),
+                e.column(),
+                e
+            ),
+            Self::TooMany(count) => write!(
+                f,
+                "{} timers is more than the limit of {}",
+                count, MAX_TIMERS
+            ),
+        }
+    }
+}
+
+pub struct Import {
+    pub timers: Vec<Timer>,
+    pub include: Vec<String>,
+    /// Entries that were dropped or changed during validation
+    pub warnings: Vec<String>,
+}
+
+pub fn from_file(path: &Path) -> Result<Import, ImportError> {
+    from_slice(&read_file(path)?)
+}
+
+/// Reads a file that is to be imported, refusing ones too large to be timers
+pub fn read_file(path: &Path) -> Result<Vec<u8>, ImportError> {
+    let size = std::fs::metadata(path).map_err(ImportError::Io)?.len() as usize;
+    if size > MAX_SIZE {
+        return Err(ImportError::TooLarge(size));
+    }
+    std::fs::read(path).map_err(ImportError::Io)
+}
+
+pub fn from_slice(bytes: &[u8]) -> Result<Import, ImportError> {
+    if bytes.len() > MAX_SIZE {
+        return Err(ImportError::TooLarge(bytes.len()));
+    }
+    let config: ConfigFile = serde_json::from_slice::<StoredConfig>(bytes)
+        .map_err(ImportError::Parse)?
+        .into();
+    let mut import = validate(config.timers, Vec::new())?;
+    import.include = config.include;
+    Ok(import)
+}
+
+/// Sanitizes timers converted from another format, `warnings` are those of the conversion
+pub fn validate(timers: Vec<Timer>, warnings: Vec<String>) -> Result<Import, ImportError> {
+    if timers.len() > MAX_TIMERS {
+        return Err(ImportError::TooMany(timers.len()));
+    }
+    let mut import = Import {
+        timers: Vec::with_capacity(timers.len()),
+        include: Vec::new(),
+        warnings,
+    };
+    for timer in timers {
+        if let Some(timer) = sanitize(timer, &mut import.warnings) {
+            if import.timers.iter().any(|t| t.name == timer.name) {
+                import
+                    .warnings
+                    .push(format!("Skipped duplicate timer {}", timer.name));
+                continue;
+            }
+            import.timers.push(timer);
+        }
+    }
+    Ok(import)
+}
+
+fn sanitize_text(text: &str, max: usize) -> String {
+    text.chars()
+        .filter(|c| !c.is_control())
+        .take(max)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+fn sanitize(mut timer: Timer, warnings: &mut Vec<String>) -> Option<Timer> {
+    let name = sanitize_text(&timer.name, MAX_NAME_LEN);
+    if name.is_empty() {
+        warnings.push("Skipped a timer without a name".to_string());
+        return None;
+    }
+    if name != timer.name {
+        warnings.push(format!("Renamed timer {:?} to {:?}", timer.name, name));
+        timer.name = name;
+    }
+    if timer.duration > MAX_DURATION {
+        warnings.push(format!("Clamped duration of {}", timer.name));
+        timer.duration = MAX_DURATION;
+    }
+    if timer.delay > MAX_DURATION {
+        warnings.push(format!("Clamped count-in of {}", timer.name));
+        timer.delay = MAX_DURATION;
+    }
+    timer.add_step = timer.add_step.min(MAX_DURATION);
+    timer.finish_text = sanitize_text(&timer.finish_text, MAX_TEXT_LEN);
+    timer.group = sanitize_text(&timer.group, MAX_NAME_LEN);
+    let mut characters = Vec::new();
+    for character in &timer.characters {
+        let character = sanitize_text(character, MAX_NAME_LEN);
+        if !character.is_empty() && !characters.contains(&character) {
+            characters.push(character);
+        }
+    }
+    timer.characters = characters;
+    timer.names = std::mem::take(&mut timer.names)
+        .into_iter()
+        .filter(|(lang, _)| crate::i18n::LANGS.contains(&lang.as_str()))
+        .map(|(lang, name)| (lang, sanitize_text(&name, MAX_NAME_LEN)))
+        .filter(|(_, name)| !name.is_empty())
+        .collect();
+    if !timer.icon.is_empty() && crate::icons::resolve(&timer.icon).is_none() {
+        warnings.push(format!(
+            "Dropped icon of {} outside the addon dir",
+            timer.name
+        ));
+        timer.icon.clear();
+    }
+    timer.icon = sanitize_text(&timer.icon, MAX_TEXT_LEN);
+    if !timer.url.is_empty() && !crate::links::is_valid(&timer.url) {
+        warnings.push(format!(
+            "Dropped link of {}, it isn't a web link",
+            timer.name
+        ));
+        timer.url.clear();
+    }
+    timer.url = sanitize_text(&timer.url, MAX_TEXT_LEN);
+    // where a timer posts or who it shares its starts with is for the user to set up
+    if !timer.webhook.is_empty() {
+        warnings.push(format!("Dropped webhook of {}", timer.name));
+        timer.webhook.clear();
+    }
+    if timer.share {
+        warnings.push(format!(
+            "Turned off sharing {} with the squad room",
+            timer.name
+        ));
+        timer.share = false;
+    }
+    timer.exclusive = sanitize_text(&timer.exclusive, MAX_NAME_LEN);
+    // notes may span several lines
+    let lines: Vec<String> = timer
+        .note
+        .lines()
+        .map(|l| sanitize_text(l, MAX_TEXT_LEN))
+        .collect();
+    timer.note = lines.join("\n").trim().chars().take(MAX_TEXT_LEN).collect();
+    if let Some(marker) = &mut timer.marker {
+        if !marker.position.iter().all(|p| p.is_finite()) {
+            marker.position = [0.0, 0.0];
+        }
+        marker.size = if marker.size.is_finite() {
+            marker.size.clamp(1.0, crate::marker::MAX_SIZE)
+        } else {
+            Marker::default().size
+        };
+        marker.label = sanitize_text(&marker.label, MAX_NAME_LEN);
+    }
+    timer.countdown = timer.countdown.min(MAX_DURATION);
+    timer.lockout = timer.lockout.min(MAX_DURATION);
+    timer.press_hold = timer.press_hold.min(MAX_DURATION);
+    timer.offset_ms = timer.offset_ms.clamp(-MAX_OFFSET_MS, MAX_OFFSET_MS);
+    // the range only makes sense for a plain duration
+    if timer.alarm.is_some() || timer.timeline.is_some() {
+        timer.earliest = None;
+    }
+    if let Some(earliest) = &mut timer.earliest {
+        *earliest = (*earliest).min(timer.duration);
+    }
+    if let Some(fade) = &mut timer.fade {
+        fade.from = fade.from.min(MAX_DURATION);
+    }
+    if let Some(hold) = &mut timer.hold {
+        *hold = (*hold).min(MAX_DURATION);
+    }
+    if let Some(max_run) = &mut timer.max_run {
+        *max_run = (*max_run).clamp(MIN_RUN, MAX_DURATION);
+    }
+    for file in [
+        &mut timer.sounds.start,
+        &mut timer.sounds.warning,
+        &mut timer.sounds.finish,
+    ] {
+        // only files right in the sounds folder
+        if file.contains(['/', '\\']) || file.contains("..") {
+            warnings.push(format!("Ignored sound {} of {}", file, timer.name));
+            file.clear();
+        }
+    }
+    if let Some(anchor) = &mut timer.anchor {
+        anchor.sanitize();
+    }
+    if let Some(alarm) = &mut timer.alarm {
+        alarm.sanitize();
+    }
+    if let Some(combat) = &mut timer.combat {
+        combat.ids.truncate(MAX_EXTRA);
+    }
+    if let Some(timeline) = &mut timer.timeline {
+        timeline.steps.truncate(MAX_EXTRA);
+        for step in &mut timeline.steps {
+            step.label = sanitize_text(&step.label, MAX_NAME_LEN);
+            step.duration = step.duration.min(MAX_DURATION);
+        }
+        timer.duration = timeline.total();
+    }
+    if let Some(chat) = &mut timer.chat {
+        chat.keyword = sanitize_text(&chat.keyword, MAX_TEXT_LEN);
+        if let Some(e) = chat.error() {
+            warnings.push(format!("Dropped chat trigger of {}: {}", timer.name, e));
+            timer.chat = None;
+        }
+    }
+    for channel in Channel::ALL {
+        let lead = timer.leads.get_mut(channel);
+        *lead = (*lead).min(MAX_DURATION);
+    }
+    sanitize_bands(&mut timer.bands);
+    sanitize_style(&mut timer.decimals, &mut timer.refresh_hz);
+    sanitize_template(&mut timer.template);
+    sanitize_hide_idle(&mut timer.hide_idle);
+    sanitize_window_size(&mut timer.window_size);
+    timer.marks.retain(|m| *m <= MAX_DURATION);
+    timer.marks.truncate(MAX_EXTRA);
+    if timer.extra.len() > MAX_EXTRA {
+        warnings.push(format!("Dropped extra metadata of {}", timer.name));
+        timer.extra.clear();
+    }
+    // ids are local to a config, imported timers always get fresh ones
+    timer.id = 0;
+    Some(timer)
+}
+
+pub fn sanitize_style(decimals: &mut Option<u8>, refresh_hz: &mut Option<u32>) {
+    if let Some(decimals) = decimals {
+        *decimals = (*decimals).min(crate::format::MAX_DECIMALS);
+    }
+    if let Some(hz) = refresh_hz {
+        *hz = (*hz).min(MAX_REFRESH_HZ);
+    }
+}
+
+/// Drops a template that can't be shown
+pub fn sanitize_template(template: &mut String) {
+    *template = sanitize_text(template, MAX_TEXT_LEN);
+    if crate::template::validate(template).is_err() {
+        template.clear();
+    }
+}
+
+pub fn sanitize_hide_idle(hide_idle: &mut Option<Duration>) {
+    if let Some(after) = hide_idle {
+        *after = (*after).min(MAX_DURATION);
+    }
+}
+
+/// Drops a size that isn't a number, fits it between the smallest and largest otherwise
+pub fn sanitize_window_size(size: &mut Option<[f32; 2]>) {
+    if size.is_some_and(|s| !s.iter().all(|v| v.is_finite())) {
+        *size = None;
+    }
+    if let Some(size) = size {
+        for v in size {
+            *v = v.clamp(MIN_WINDOW, MAX_WINDOW);
+        }
+    }
+}
+
+pub fn sanitize_bands(bands: &mut Vec<ColorBand>) {
+    bands.truncate(MAX_EXTRA);
+    for band in bands {
+        band.from = band.from.min(MAX_DURATION);
+        for c in &mut band.color {
+            *c = if c.is_finite() {
+                c.clamp(0.0, 1.0)
+            } else {
+                1.0
+            };
+        }
+    }
+}
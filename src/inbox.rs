@@ -0,0 +1,142 @@
+//! Changes to the timers asked for by keybinds, other addons and game events.
+//!
+//! Their callbacks run on threads of their own and only queue what they ask for, so a
+//! key press never waits on a frame being drawn. The tick applies the queue.
+
+use crate::chat::{self, ChatChannel};
+use crate::combat::{self, CombatKind};
+use crate::eventlog::{self, Kind};
+use crate::keybinds::{self, Scope};
+use crate::ready;
+use crate::sync::LockExt;
+use crate::timer::{StartSource, Timers};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Request {
+    /// A timer keybind was pressed, asking first for runs of at least the duration
+    Keybind(String, Option<Duration>),
+    /// A timer keybind was let go
+    Release(String),
+    PressGroup(String),
+    RestartLast,
+    /// Starts or calls off the pull, then starting the named timer
+    TogglePull(Duration, Option<String>),
+    Start(String, StartSource),
+    Stop(String),
+    Chat(ChatChannel, String),
+    Combat(CombatKind, u32),
+    ReadyCheck,
+}
+
+static QUEUE: Mutex<Vec<Request>> = Mutex::new(Vec::new());
+
+/// Queues `request` for the next tick
+pub fn send(request: Request) {
+    QUEUE.locked().push(request);
+}
+
+/// Applies the queued requests and the keybinds held long enough, returning the timers
+/// whose press is to be confirmed. Timer keybinds act in `scope`.
+pub fn apply(timers: &mut Timers, scope: &Scope) -> Vec<String> {
+    let requests = std::mem::take(&mut *QUEUE.locked());
+    let now = Instant::now();
+    let mut asks = apply_all(timers, requests, scope, now);
+    asks.extend(keybinds::poll_held(timers, now));
+    asks
+}
+
+fn apply_all(
+    timers: &mut Timers,
+    requests: Vec<Request>,
+    scope: &Scope,
+    now: Instant,
+) -> Vec<String> {
+    let mut asks = Vec::new();
+    for request in requests {
+        match request {
+            Request::Keybind(id, confirm) => {
+                asks.extend(keybinds::pressed(timers, &id, confirm, scope, now));
+            }
+            Request::Release(id) => keybinds::released(timers, &id),
+            Request::PressGroup(group) => {
+                let text = format!("starts or stops group {}", group);
+                eventlog::record(Kind::Keybind, "", text);
+                timers.press_group(&group);
+            }
+            Request::RestartLast => timers.restart_last(),
+            Request::TogglePull(duration, then) => {
+                let started = timers.toggle_pull(duration, then);
+                let text = if started { "started" } else { "called off" };
+                eventlog::record(Kind::Keybind, "Pull", text);
+            }
+            Request::Start(name, source) => {
+                if !timers.contains_name(&name) {
+                    let text = format!("started by {}, there is no such timer", source.name());
+                    eventlog::error(&name, text);
+                }
+                timers.start(&name, source);
+            }
+            Request::Stop(name) => timers.stop(&name),
+            Request::Chat(channel, text) => {
+                for name in chat::triggered(&timers.list, channel, &text) {
+                    log::info!("Starting {} from chat: {}", name, text);
+                    timers.start(&name, StartSource::Chat);
+                }
+            }
+            Request::Combat(kind, id) => {
+                for name in combat::triggered(&timers.list, kind, id) {
+                    log::info!("Starting {} from {} {}", name, kind.name(), id);
+                    timers.start(&name, StartSource::Combat);
+                }
+            }
+            Request::ReadyCheck => {
+                for name in ready::triggered(&timers.list) {
+                    timers.start(&name, StartSource::ReadyCheck);
+                }
+            }
+        }
+    }
+    asks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::store::Store;
+    use crate::timer::Timer;
+
+    #[test]
+    fn applies_requests_in_order() {
+        let mut timers = Timers::with_clock(Box::new(ManualClock::new()));
+        let burn = Timer::new(1, "Burn".to_string(), Duration::from_secs(30));
+        let push = Timer::new(2, "Push".to_string(), Duration::from_secs(60));
+        let key = burn.keybind_id.clone();
+        timers.list = Store::new(vec![burn, push]);
+        let now = Instant::now();
+        let asks = apply_all(
+            &mut timers,
+            vec![
+                Request::Start("Push".to_string(), StartSource::Api),
+                Request::Stop("Push".to_string()),
+                Request::Keybind(format!("KB_TIMER_{}", key), None),
+                Request::Start("Missing".to_string(), StartSource::Api),
+            ],
+            &Scope::default(),
+            now,
+        );
+        assert!(asks.is_empty());
+        assert!(timers.get(1).unwrap().state.is_active());
+        assert!(!timers.get(2).unwrap().state.is_active());
+        // a press that starts a long enough run waits for the confirmation
+        timers.stop("Burn");
+        let press = Request::Keybind(format!("KB_TIMER_{}", key), Some(Duration::ZERO));
+        assert_eq!(
+            apply_all(&mut timers, vec![press], &Scope::default(), now),
+            ["Burn"]
+        );
+        assert!(!timers.get(1).unwrap().state.is_active());
+    }
+}
@@ -0,0 +1,932 @@
+use crate::eventlog::{self, Kind};
+use crate::i18n::{self, LANGS};
+use crate::inbox::{self, Request};
+use crate::maps::{self, MapRule};
+use crate::sync::LockExt;
+use crate::timer::{self, Timer, Timers, KEYBIND_ID_LEN};
+use crate::worker::Worker;
+use nexus::keybind::{
+    register_keybind_with_string, register_keybind_with_struct, unregister_keybind, Keybind,
+    RawKeybindHandler,
+};
+use nexus::keybind_handler;
+use nexus::localization::set_translation;
+use nexus::paths::get_addon_dir;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use windows::Win32::UI::Input::KeyboardAndMouse::GetKeyNameTextW;
+
+/// Keybinds of a timer are this, its `keybind_id` and one of the suffixes
+const TIMER_PREFIX: &str = "KB_TIMER_";
+const START_SUFFIX: &str = "";
+const ADD_SUFFIX: &str = "_ADD";
+const SUBTRACT_SUFFIX: &str = "_SUBTRACT";
+const LAP_SUFFIX: &str = "_LAP";
+const SUFFIXES: [&str; 4] = [START_SUFFIX, ADD_SUFFIX, SUBTRACT_SUFFIX, LAP_SUFFIX];
+/// Earlier versions named the keybinds of a timer with one of these and its name, see
+/// `migrate`
+const LEGACY_PREFIXES: [(&str, &str); 4] = [
+    ("KB_TIMER_START_", START_SUFFIX),
+    ("KB_TIMER_ADD_", ADD_SUFFIX),
+    ("KB_TIMER_SUBTRACT_", SUBTRACT_SUFFIX),
+    ("KB_TIMER_LAP_", LAP_SUFFIX),
+];
+const GROUP_PREFIX: &str = "KB_TIMER_GROUP_START_";
+const RESTART_LAST: &str = "KB_TIMER_RESTART_LAST";
+const TOGGLE_OVERLAY: &str = "KB_TIMER_TOGGLE_OVERLAY";
+const QUICK_TIMER: &str = "KB_TIMER_QUICK";
+const COMMAND_PALETTE: &str = "KB_TIMER_COMMAND";
+const TOGGLE_MUTE: &str = "KB_TIMER_MUTE";
+const PULL: &str = "KB_TIMER_PULL";
+/// Also what the quick access shortcut presses
+pub const TOGGLE_WINDOW: &str = "KB_TIMER_TOGGLE_WINDOW";
+
+/// Names a keybind in every language, `text` gives the name for a language code
+fn localize(id: &str, text: impl Fn(&str) -> String) {
+    for &l in LANGS {
+        set_translation(id, l, text(l));
+    }
+}
+
+fn timer_keybind(timer: &Timer, suffix: &str) -> String {
+    format!("{}{}{}", TIMER_PREFIX, timer.keybind_id, suffix)
+}
+
+/// The keybind id of the timer and the suffix of one of its keybinds
+fn split_timer_keybind(id: &str) -> Option<(&str, &str)> {
+    let (key, suffix) = id
+        .strip_prefix(TIMER_PREFIX)?
+        .split_at_checked(KEYBIND_ID_LEN)?;
+    (timer::is_keybind_id(key) && SUFFIXES.contains(&suffix)).then_some((key, suffix))
+}
+
+/// A start keybind held down, acting once held for the timer's `press_hold`
+struct Held {
+    since: Instant,
+    confirm: Option<Duration>,
+    // Acted already, repeated presses until it's let go don't act again
+    acted: bool,
+}
+
+/// By keybind id of the timer
+static HELD: Mutex<BTreeMap<String, Held>> = Mutex::new(BTreeMap::new());
+
+/// The character and map the keybinds of timers are pressed on. Only the timers shown
+/// there act on theirs, so timers of different characters or maps can share a key.
+#[derive(Debug, Default)]
+pub struct Scope {
+    /// Groups shown on the map, `None` for all, see [`maps::groups_for`]
+    pub groups: Option<Vec<String>>,
+    pub character: Option<String>,
+}
+
+impl Scope {
+    pub fn current(rules: &[MapRule]) -> Self {
+        Self {
+            groups: maps::current_groups(rules),
+            character: crate::game::character(),
+        }
+    }
+
+    fn includes(&self, timer: &Timer) -> bool {
+        maps::shows(self.groups.as_deref(), timer) && timer.shows_for(self.character.as_deref())
+    }
+}
+
+/// Applies a press of the timer keybind `id` in `scope`. Returns the timer to ask about
+/// first if the press starts or ends a run of at least `confirm`.
+pub fn pressed(
+    timers: &mut Timers,
+    id: &str,
+    confirm: Option<Duration>,
+    scope: &Scope,
+    now: Instant,
+) -> Option<String> {
+    let (key, suffix) = split_timer_keybind(id)?;
+    let timer = timers.list.iter().find(|t| t.keybind_id == key)?;
+    if !scope.includes(timer) {
+        eventlog::record(Kind::Keybind, &timer.name, "not shown here, left alone");
+        return None;
+    }
+    let (name, step) = (timer.name.clone(), timer.add_step);
+    match suffix {
+        ADD_SUFFIX => timers.add_time(&name, step),
+        SUBTRACT_SUFFIX => timers.subtract_time(&name, step),
+        LAP_SUFFIX => timers.lap(&name),
+        _ if !timer.press_hold.is_zero() => {
+            HELD.locked().entry(key.to_string()).or_insert(Held {
+                since: now,
+                confirm,
+                acted: false,
+            });
+        }
+        _ => return press_start(timers, name, confirm),
+    }
+    None
+}
+
+/// Lets go of the timer keybind `id`, a start keybind let go too early does nothing
+pub fn released(timers: &Timers, id: &str) {
+    let Some((key, START_SUFFIX)) = split_timer_keybind(id) else {
+        return;
+    };
+    if HELD.locked().remove(key).is_some_and(|held| !held.acted) {
+        if let Some(timer) = timers.list.iter().find(|t| t.keybind_id == key) {
+            eventlog::record(
+                Kind::Keybind,
+                &timer.name,
+                "let go before it was held long enough",
+            );
+        }
+    }
+}
+
+/// Presses the timers whose start keybind is held long enough by `now`, returning those to
+/// ask about first like `pressed`
+pub fn poll_held(timers: &mut Timers, now: Instant) -> Vec<String> {
+    let mut due = Vec::new();
+    HELD.locked().retain(|key, held| {
+        let Some(timer) = timers.list.iter().find(|t| t.keybind_id == *key) else {
+            return false;
+        };
+        if !held.acted && now.saturating_duration_since(held.since) >= timer.press_hold {
+            held.acted = true;
+            due.push((timer.name.clone(), held.confirm));
+        }
+        true
+    });
+    due.into_iter()
+        .filter_map(|(name, confirm)| press_start(timers, name, confirm))
+        .collect()
+}
+
+fn press_start(timers: &mut Timers, name: String, confirm: Option<Duration>) -> Option<String> {
+    let outcome = timers.press_outcome(&name);
+    let asks = confirm.is_some_and(|long| {
+        outcome.starts_or_ends()
+            && timers
+                .list
+                .iter()
+                .any(|t| t.name == name && t.run_duration() >= long)
+    });
+    if asks {
+        eventlog::record(Kind::Keybind, &name, "waiting for the confirmation");
+        return Some(name);
+    }
+    eventlog::record(Kind::Keybind, &name, outcome.describe());
+    timers.press(&name);
+    None
+}
+
+/// Handler of every keybind of a timer, the press is applied by `pressed`
+fn timer_keybind_handler() -> RawKeybindHandler {
+    keybind_handler!(|id, is_release| {
+        if is_release {
+            inbox::send(Request::Release(id.to_string()));
+            return;
+        }
+        let confirm_mins = crate::SETTINGS
+            .get()
+            .expect("Settings to be set")
+            .locked()
+            .confirm_long_mins;
+        let confirm = (confirm_mins > 0).then(|| Duration::from_secs(u64::from(confirm_mins) * 60));
+        inbox::send(Request::Keybind(id.to_string(), confirm));
+    })
+}
+
+fn group_handler() -> RawKeybindHandler {
+    keybind_handler!(|id, is_release| {
+        if !is_release {
+            let group = id.trim_start_matches(GROUP_PREFIX);
+            inbox::send(Request::PressGroup(group.to_string()));
+        }
+    })
+}
+
+fn restart_last_handler() -> RawKeybindHandler {
+    keybind_handler!(|_id, is_release| {
+        if !is_release {
+            inbox::send(Request::RestartLast);
+        }
+    })
+}
+
+fn toggle_overlay_handler() -> RawKeybindHandler {
+    keybind_handler!(|_id, is_release| {
+        if !is_release {
+            crate::ui::toggle_overlay();
+        }
+    })
+}
+
+fn quick_timer_handler() -> RawKeybindHandler {
+    keybind_handler!(|_id, is_release| {
+        if !is_release {
+            crate::ui::open_quick_timer();
+        }
+    })
+}
+
+fn command_palette_handler() -> RawKeybindHandler {
+    keybind_handler!(|_id, is_release| {
+        if !is_release {
+            crate::ui::open_command_palette();
+        }
+    })
+}
+
+fn toggle_window_handler() -> RawKeybindHandler {
+    keybind_handler!(|_id, is_release| {
+        if !is_release {
+            crate::ui::toggle_timers_window();
+        }
+    })
+}
+
+fn pull_handler() -> RawKeybindHandler {
+    keybind_handler!(|_id, is_release| {
+        if is_release {
+            return;
+        }
+        let (secs, then) = {
+            let settings = crate::SETTINGS.get().expect("Settings to be set").locked();
+            (settings.pull_secs, settings.pull_then.clone())
+        };
+        let then = Some(then).filter(|name| !name.is_empty());
+        inbox::send(Request::TogglePull(Duration::from_secs(secs.into()), then));
+    })
+}
+
+fn toggle_mute_handler() -> RawKeybindHandler {
+    keybind_handler!(|_id, is_release| {
+        if is_release {
+            return;
+        }
+        let mut settings = crate::SETTINGS.get().expect("Settings to be set").locked();
+        settings.muted = !settings.muted;
+        log::info!(
+            "Sounds {}",
+            if settings.muted { "muted" } else { "unmuted" }
+        );
+    })
+}
+
+/// A registered timer for the conflict warning
+struct Registered {
+    name: String,
+    /// Timers of different characters don't get in each other's way on the same key
+    characters: Vec<String>,
+}
+
+impl Registered {
+    fn of(timer: &Timer) -> Self {
+        Self {
+            name: timer.name.clone(),
+            characters: timer.characters.clone(),
+        }
+    }
+
+    /// Whether both are only shown on characters and never on the same
+    fn apart(&self, characters: &[String]) -> bool {
+        !self.characters.is_empty()
+            && !characters.is_empty()
+            && !self.characters.iter().any(|c| characters.contains(c))
+    }
+}
+
+/// The registered timers by keybind id
+static NAMES: Mutex<BTreeMap<String, Registered>> = Mutex::new(BTreeMap::new());
+
+/// Handler of the timer keybinds with `suffix`, and whether a timer has that keybind
+fn timer_handler(suffix: &str) -> (RawKeybindHandler, HasKeybind) {
+    let has: HasKeybind = match suffix {
+        ADD_SUFFIX | SUBTRACT_SUFFIX => |t| t.time_keybinds,
+        LAP_SUFFIX => |t| t.lap_keybind,
+        _ => |_| true,
+    };
+    (timer_keybind_handler(), has)
+}
+
+pub fn register(timer: &Timer) {
+    if !timer.enabled {
+        return;
+    }
+    let id = timer_keybind(timer, START_SUFFIX);
+    let _ = register_keybind_with_string(&id, timer_keybind_handler(), "(null)");
+    if timer.time_keybinds {
+        let id = timer_keybind(timer, ADD_SUFFIX);
+        let _ = register_keybind_with_string(&id, timer_keybind_handler(), "(null)");
+        let id = timer_keybind(timer, SUBTRACT_SUFFIX);
+        let _ = register_keybind_with_string(&id, timer_keybind_handler(), "(null)");
+    }
+    if timer.lap_keybind {
+        let id = timer_keybind(timer, LAP_SUFFIX);
+        let _ = register_keybind_with_string(&id, timer_keybind_handler(), "(null)");
+    }
+    NAMES
+        .locked()
+        .insert(timer.keybind_id.clone(), Registered::of(timer));
+    localize_timer(timer);
+}
+
+/// Keeps the conflict warning in step with the characters of `timer` after they changed
+pub fn rescope(timer: &Timer) {
+    if let Some(registered) = NAMES.locked().get_mut(&timer.keybind_id) {
+        registered.characters.clone_from(&timer.characters);
+    }
+}
+
+/// Names the keybinds of `timer` after its display names
+pub fn localize_timer(timer: &Timer) {
+    localize(&timer_keybind(timer, START_SUFFIX), |l| {
+        timer.display_name(l).to_string()
+    });
+    if timer.time_keybinds {
+        let named = |suffix: &str, key: &'static str| {
+            localize(&timer_keybind(timer, suffix), |l| {
+                format!("{}: {}", timer.display_name(l), i18n::text(l, key))
+            });
+        };
+        named(ADD_SUFFIX, "add_time");
+        named(SUBTRACT_SUFFIX, "remove_time");
+    }
+    if timer.lap_keybind {
+        localize(&timer_keybind(timer, LAP_SUFFIX), |l| {
+            format!("{}: {}", timer.display_name(l), i18n::text(l, "lap"))
+        });
+    }
+}
+
+pub fn unregister(timer: &Timer) {
+    unregister_keybind(timer_keybind(timer, START_SUFFIX));
+    unregister_time_keybinds(timer);
+    unregister_lap_keybind(timer);
+    NAMES.locked().remove(&timer.keybind_id);
+}
+
+pub fn unregister_lap_keybind(timer: &Timer) {
+    unregister_keybind(timer_keybind(timer, LAP_SUFFIX));
+}
+
+pub fn unregister_time_keybinds(timer: &Timer) {
+    unregister_keybind(timer_keybind(timer, ADD_SUFFIX));
+    unregister_keybind(timer_keybind(timer, SUBTRACT_SUFFIX));
+}
+
+/// The keybind a keybind of timer `name` had in earlier versions if `id` is one, by the
+/// suffix it has now
+fn split_legacy(id: &str) -> Option<(&str, &'static str)> {
+    LEGACY_PREFIXES
+        .iter()
+        .find_map(|(prefix, suffix)| Some((id.strip_prefix(prefix)?, *suffix)))
+}
+
+/// The timer of `migrated` whose keybind `id` was before it got its keybind id, and the
+/// suffix the keybind has now. Named after the timer by earlier versions, or after the
+/// keybind id it had.
+fn migrated_keybind<'a>(
+    timers: &'a Timers,
+    migrated: &[(u64, String)],
+    id: &str,
+) -> Option<(&'a Timer, &'static str)> {
+    if let Some((name, suffix)) = split_legacy(id) {
+        let timer = timers.list.iter().find(|t| {
+            t.name == name && migrated.iter().any(|(m, old)| *m == t.id && old.is_empty())
+        })?;
+        return Some((timer, suffix));
+    }
+    let rest = id.strip_prefix(TIMER_PREFIX)?;
+    migrated
+        .iter()
+        .filter(|(_, old)| !old.is_empty())
+        .find_map(|(m, old)| {
+            let suffix = rest.strip_prefix(old.as_str())?;
+            let suffix = SUFFIXES.into_iter().find(|s| *s == suffix)?;
+            Some((timers.get(*m)?, suffix))
+        })
+}
+
+/// Moves the keys assigned to the keybinds of the `migrated` timers to their keybind ids,
+/// see [`migrated_keybind`]. Called before they are registered.
+pub fn migrate(timers: &Timers, migrated: &[(u64, String)]) {
+    if migrated.is_empty() {
+        return;
+    }
+    let binds = match all_assigned() {
+        Ok(binds) => binds,
+        Err(e) => {
+            log::warn!("Can't move the keys of keybinds to keybind ids: {}", e);
+            return;
+        }
+    };
+    let mut moved = 0;
+    for (old, bind) in &binds {
+        let Some((timer, suffix)) = migrated_keybind(timers, migrated, old) else {
+            continue;
+        };
+        let (handler, has) = timer_handler(suffix);
+        if timer.enabled && has(timer) {
+            let _ = register_keybind_with_struct(
+                timer_keybind(timer, suffix),
+                handler,
+                bind.to_keybind(),
+            );
+            moved += 1;
+        }
+        unregister_keybind(old);
+    }
+    if moved > 0 {
+        log::info!("Moved {} keybinds to keybind ids", moved);
+    }
+}
+
+/// Registers the keybind that starts or stops the timers of `group`
+pub fn register_group(group: &str) {
+    let id = format!("{}{}", GROUP_PREFIX, group);
+    let _ = register_keybind_with_string(&id, group_handler(), "(null)");
+    localize(&id, |l| {
+        format!("{}: {}", group, i18n::text(l, "start_stop_group"))
+    });
+}
+
+pub fn unregister_group(group: &str) {
+    unregister_keybind(format!("{}{}", GROUP_PREFIX, group));
+}
+
+pub fn register_global() {
+    let _ = register_keybind_with_string(RESTART_LAST, restart_last_handler(), "(null)");
+    localize(RESTART_LAST, |l| i18n::text(l, "restart_last"));
+
+    let _ = register_keybind_with_string(TOGGLE_OVERLAY, toggle_overlay_handler(), "(null)");
+    localize(TOGGLE_OVERLAY, |l| i18n::text(l, "toggle_overlay"));
+
+    let _ = register_keybind_with_string(QUICK_TIMER, quick_timer_handler(), "(null)");
+    localize(QUICK_TIMER, |l| i18n::text(l, "quick_timer"));
+
+    let _ = register_keybind_with_string(COMMAND_PALETTE, command_palette_handler(), "(null)");
+    localize(COMMAND_PALETTE, |l| i18n::text(l, "command_palette"));
+
+    let _ = register_keybind_with_string(TOGGLE_MUTE, toggle_mute_handler(), "(null)");
+    localize(TOGGLE_MUTE, |l| i18n::text(l, "toggle_mute"));
+
+    let _ = register_keybind_with_string(TOGGLE_WINDOW, toggle_window_handler(), "(null)");
+    localize(TOGGLE_WINDOW, |l| i18n::text(l, "toggle_window"));
+
+    let _ = register_keybind_with_string(PULL, pull_handler(), "(null)");
+    localize(PULL, |l| i18n::text(l, "pull_timer"));
+}
+
+/// A key assignment as Nexus stores it, `key` is a scan code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bind {
+    pub key: u16,
+    #[serde(default)]
+    pub alt: bool,
+    #[serde(default)]
+    pub ctrl: bool,
+    #[serde(default)]
+    pub shift: bool,
+}
+
+fn is_ours(id: &str) -> bool {
+    split_timer_keybind(id).is_some()
+        || id.starts_with(GROUP_PREFIX)
+        || id == RESTART_LAST
+        || id == TOGGLE_OVERLAY
+        || id == QUICK_TIMER
+        || id == COMMAND_PALETTE
+        || id == TOGGLE_MUTE
+        || id == TOGGLE_WINDOW
+        || id == PULL
+}
+
+fn parse_bind(value: &serde_json::Value) -> Option<Bind> {
+    let flag = |name: &str| value.get(name).and_then(|v| v.as_bool()).unwrap_or(false);
+    let key = value.get("Key")?.as_u64()?;
+    (key != 0).then(|| Bind {
+        key: key as u16,
+        alt: flag("Alt"),
+        ctrl: flag("Ctrl"),
+        shift: flag("Shift"),
+    })
+}
+
+impl Bind {
+    fn to_keybind(self) -> Keybind {
+        Keybind {
+            key: self.key,
+            alt: self.alt,
+            ctrl: self.ctrl,
+            shift: self.shift,
+        }
+    }
+
+    /// How the key is written on the keyboard, like `Ctrl+Shift+F5`
+    pub fn label(self) -> String {
+        let mut label = String::new();
+        for (held, name) in [
+            (self.ctrl, "Ctrl+"),
+            (self.alt, "Alt+"),
+            (self.shift, "Shift+"),
+        ] {
+            if held {
+                label.push_str(name);
+            }
+        }
+        // the scan code goes into bits 16-23, extended keys like the arrows have 0xE0 in
+        // the high byte and need bit 24
+        let mut lparam = i32::from(self.key & 0xFF) << 16;
+        if self.key & 0xFF00 == 0xE000 {
+            lparam |= 1 << 24;
+        }
+        let mut name = [0u16; 64];
+        // SAFETY: the buffer outlives the call, its length is passed along
+        let len = unsafe { GetKeyNameTextW(lparam, &mut name) };
+        match usize::try_from(len) {
+            Ok(len) if len > 0 => label.push_str(&String::from_utf16_lossy(&name[..len])),
+            _ => label.push_str(&format!("0x{:X}", self.key)),
+        }
+        label
+    }
+}
+
+/// The keys currently assigned to our keybinds. Nexus has no API to query them, so
+/// they are read from its own file, which is a map of identifiers or a list of entries
+/// depending on the Nexus version.
+fn assigned() -> Result<BTreeMap<String, Bind>, String> {
+    let mut binds = all_assigned()?;
+    binds.retain(|id, _| is_ours(id));
+    Ok(binds)
+}
+
+/// The keys of the keybinds of every addon
+fn all_assigned() -> Result<BTreeMap<String, Bind>, String> {
+    let dir = get_addon_dir("Nexus").ok_or("Nexus directory not found")?;
+    let contents = ["InputBinds.json", "Keybinds.json"]
+        .iter()
+        .find_map(|file| std::fs::read(dir.join(file)).ok())
+        .ok_or("Nexus keybinds file not found")?;
+    let json: serde_json::Value = serde_json::from_slice(&contents).map_err(|e| e.to_string())?;
+    let entries: Vec<(String, &serde_json::Value)> = match &json {
+        serde_json::Value::Object(map) => map.iter().map(|(id, v)| (id.clone(), v)).collect(),
+        serde_json::Value::Array(list) => list
+            .iter()
+            .filter_map(|v| Some((v.get("Identifier")?.as_str()?.to_string(), v)))
+            .collect(),
+        _ => return Err("unknown keybinds file format".to_string()),
+    };
+    Ok(entries
+        .into_iter()
+        .filter_map(|(id, v)| Some((id, parse_bind(v)?)))
+        .collect())
+}
+
+/// Names a keybind for the conflict warning, the ones of other addons by identifier.
+/// `names` are the registered timers by keybind id.
+fn describe(id: &str, names: &BTreeMap<String, Registered>) -> String {
+    if let Some(name) = split_timer_keybind(id).and_then(|(key, suffix)| {
+        let of = match suffix {
+            ADD_SUFFIX => " (add time)",
+            SUBTRACT_SUFFIX => " (remove time)",
+            LAP_SUFFIX => " (lap)",
+            _ => "",
+        };
+        Some(format!("timer {}{}", names.get(key)?.name, of))
+    }) {
+        return name;
+    }
+    if let Some(group) = id.strip_prefix(GROUP_PREFIX) {
+        return format!("group {}", group);
+    }
+    match id {
+        RESTART_LAST => "restart last timer".to_string(),
+        TOGGLE_OVERLAY => "toggle overlay".to_string(),
+        QUICK_TIMER => "quick timer".to_string(),
+        COMMAND_PALETTE => "command palette".to_string(),
+        TOGGLE_MUTE => "mute".to_string(),
+        TOGGLE_WINDOW => "timers window".to_string(),
+        PULL => "pull timer".to_string(),
+        _ => id.to_string(),
+    }
+}
+
+/// The other keybinds that are on the same key as `id`
+fn sharing<'a>(binds: &'a BTreeMap<String, Bind>, id: &str) -> Vec<&'a str> {
+    let Some(bind) = binds.get(id) else {
+        return Vec::new();
+    };
+    binds
+        .iter()
+        .filter(|(other, b)| *other != id && *b == bind)
+        .map(|(other, _)| other.as_str())
+        .collect()
+}
+
+/// The key of a start keybind and the other keybinds on the same key
+pub struct StartBind {
+    pub label: String,
+    pub conflicts: Vec<String>,
+}
+
+struct KnownBinds {
+    read_at: Instant,
+    binds: BTreeMap<String, Bind>,
+}
+
+// the options show them every frame, the file is only read every few seconds and off
+// the render thread
+static KNOWN: Mutex<Option<KnownBinds>> = Mutex::new(None);
+const REREAD: Duration = Duration::from_secs(5);
+// A read is queued, not to queue another meanwhile
+static READING: AtomicBool = AtomicBool::new(false);
+
+fn read_known((): ()) {
+    let binds = all_assigned().unwrap_or_else(|e| {
+        log::debug!("Can't read the assigned keys: {}", e);
+        BTreeMap::new()
+    });
+    *KNOWN.locked() = Some(KnownBinds {
+        read_at: Instant::now(),
+        binds,
+    });
+    READING.store(false, Ordering::Relaxed);
+}
+
+static READER: Worker<()> = Worker::new("keybinds", Duration::from_secs(10), read_known);
+
+/// Reads the assigned keys again in the background once they are `REREAD` old, called
+/// before showing them
+pub fn refresh_binds() {
+    let stale = KNOWN
+        .locked()
+        .as_ref()
+        .is_none_or(|k| k.read_at.elapsed() >= REREAD);
+    if stale && !READING.swap(true, Ordering::Relaxed) {
+        READER.send(());
+    }
+}
+
+/// The key the start keybind of `timer` is on as of the last read, `None` if it isn't
+/// bound or wasn't read yet
+pub fn start_bind(timer: &Timer) -> Option<StartBind> {
+    let known = KNOWN.locked();
+    let binds = &known.as_ref()?.binds;
+    let id = timer_keybind(timer, START_SUFFIX);
+    let bind = binds.get(&id)?;
+    let names = NAMES.locked();
+    Some(StartBind {
+        label: bind.label(),
+        conflicts: sharing(binds, &id)
+            .into_iter()
+            .filter(|other| {
+                let apart = split_timer_keybind(other)
+                    .and_then(|(key, _)| names.get(key))
+                    .is_some_and(|r| r.apart(&timer.characters));
+                !apart
+            })
+            .map(|other| describe(other, &names))
+            .collect(),
+    })
+}
+
+/// Whether a timer has the keybind of a prefix
+type HasKeybind = fn(&Timer) -> bool;
+
+/// Writes the keys of all our keybinds to `path`, returns how many there were. Those of
+/// timers are named after the timer like in earlier versions, keybind ids differ between
+/// machines.
+pub fn export(path: &Path, timers: &Timers) -> Result<usize, String> {
+    let binds: BTreeMap<String, Bind> = assigned()?
+        .into_iter()
+        .filter_map(|(id, bind)| {
+            let Some((key, suffix)) = split_timer_keybind(&id) else {
+                return Some((id, bind));
+            };
+            let timer = timers.list.iter().find(|t| t.keybind_id == key)?;
+            let (prefix, _) = LEGACY_PREFIXES.iter().find(|(_, s)| *s == suffix)?;
+            Some((format!("{}{}", prefix, timer.name), bind))
+        })
+        .collect();
+    let json = serde_json::to_string_pretty(&binds).expect("Keybinds to be serialized");
+    std::fs::write(path, json).map_err(|e| e.to_string())?;
+    Ok(binds.len())
+}
+
+/// Offers the keys from an export to Nexus, returns how many belonged to existing
+/// keybinds. Nexus keeps keys that are already assigned on this machine. `groups` are
+/// the ones with a keybind.
+pub fn import(path: &Path, timers: &Timers, groups: &[String]) -> Result<usize, String> {
+    let contents = std::fs::read(path).map_err(|e| e.to_string())?;
+    let binds: BTreeMap<String, Bind> =
+        serde_json::from_slice(&contents).map_err(|e| e.to_string())?;
+    let mut applied = 0;
+    for (id, bind) in binds {
+        let (id, handler) = match id.as_str() {
+            RESTART_LAST => (id, restart_last_handler()),
+            TOGGLE_OVERLAY => (id, toggle_overlay_handler()),
+            QUICK_TIMER => (id, quick_timer_handler()),
+            COMMAND_PALETTE => (id, command_palette_handler()),
+            TOGGLE_MUTE => (id, toggle_mute_handler()),
+            TOGGLE_WINDOW => (id, toggle_window_handler()),
+            PULL => (id, pull_handler()),
+            _ if id
+                .strip_prefix(GROUP_PREFIX)
+                .is_some_and(|g| groups.iter().any(|group| group == g)) =>
+            {
+                (id, group_handler())
+            }
+            _ => {
+                // by name from exports and earlier versions, by keybind id as Nexus has them
+                let found = split_legacy(&id)
+                    .and_then(|(name, suffix)| {
+                        Some((timers.list.iter().find(|t| t.name == name)?, suffix))
+                    })
+                    .or_else(|| {
+                        let (key, suffix) = split_timer_keybind(&id)?;
+                        Some((timers.list.iter().find(|t| t.keybind_id == key)?, suffix))
+                    });
+                let Some((timer, suffix)) = found else {
+                    continue;
+                };
+                let (handler, has) = timer_handler(suffix);
+                if !timer.enabled || !has(timer) {
+                    continue;
+                }
+                (timer_keybind(timer, suffix), handler)
+            }
+        };
+        let _ = register_keybind_with_struct(&id, handler, bind.to_keybind());
+        applied += 1;
+    }
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::Store;
+
+    #[test]
+    fn binds_on_the_same_key() {
+        let bind = |key, ctrl| Bind {
+            key,
+            alt: false,
+            ctrl,
+            shift: false,
+        };
+        let binds: BTreeMap<String, Bind> = [
+            ("KB_TIMER_START_Boss", bind(0x3F, false)),
+            ("KB_TIMER_LAP_Boss", bind(0x3F, true)),
+            ("KB_TIMER_START_Portal", bind(0x3F, false)),
+            ("KB_OTHER_ADDON", bind(0x3F, false)),
+            ("KB_TIMER_START_Food", bind(0x40, false)),
+        ]
+        .into_iter()
+        .map(|(id, b)| (id.to_string(), b))
+        .collect();
+        assert_eq!(
+            sharing(&binds, "KB_TIMER_START_Boss"),
+            ["KB_OTHER_ADDON", "KB_TIMER_START_Portal"]
+        );
+        assert!(sharing(&binds, "KB_TIMER_START_Food").is_empty());
+        assert!(sharing(&binds, "KB_TIMER_START_Unbound").is_empty());
+        let portal = Registered {
+            name: "Portal".to_string(),
+            characters: vec!["Mesmer".to_string()],
+        };
+        // only timers of other characters are out of the way
+        assert!(portal.apart(&["Guardian".to_string()]));
+        assert!(!portal.apart(&["Mesmer".to_string(), "Guardian".to_string()]));
+        assert!(!portal.apart(&[]));
+        let names = [("00000000000000ff".to_string(), portal)].into();
+        assert_eq!(
+            describe("KB_TIMER_00000000000000ff", &names),
+            "timer Portal"
+        );
+        assert_eq!(
+            describe("KB_TIMER_00000000000000ff_LAP", &names),
+            "timer Portal (lap)"
+        );
+        assert_eq!(describe("KB_TIMER_GROUP_START_Vale", &names), "group Vale");
+        assert_eq!(describe("KB_OTHER_ADDON", &names), "KB_OTHER_ADDON");
+    }
+
+    #[test]
+    fn start_keybind_held_long_enough() {
+        let mut timer = Timer::new(1, "Portal".to_string(), Duration::from_secs(60));
+        timer.press_hold = Duration::from_millis(500);
+        let id = timer_keybind(&timer, START_SUFFIX);
+        let mut timers = Timers::with_clock(Box::new(crate::clock::ManualClock::new()));
+        timers.list = Store::new(vec![timer]);
+        let now = Instant::now();
+        let running = |timers: &Timers| timers.get(1).is_some_and(|t| t.state.is_active());
+        // let go too early
+        assert_eq!(
+            pressed(&mut timers, &id, None, &Scope::default(), now),
+            None
+        );
+        released(&timers, &id);
+        assert!(poll_held(&mut timers, now + Duration::from_secs(1)).is_empty());
+        assert!(!running(&timers));
+        // held on, it starts once the hold is up rather than on the release
+        pressed(&mut timers, &id, None, &Scope::default(), now);
+        poll_held(&mut timers, now + Duration::from_millis(200));
+        assert!(!running(&timers));
+        poll_held(&mut timers, now + Duration::from_millis(500));
+        assert!(running(&timers));
+        // the key repeating while still held doesn't stop it again
+        pressed(
+            &mut timers,
+            &id,
+            None,
+            &Scope::default(),
+            now + Duration::from_millis(600),
+        );
+        poll_held(&mut timers, now + Duration::from_secs(2));
+        released(&timers, &id);
+        assert!(running(&timers));
+    }
+
+    #[test]
+    fn keybinds_act_in_their_scope_only() {
+        let mut mesmer = Timer::new(1, "Portal".to_string(), Duration::from_secs(60));
+        mesmer.characters = vec!["Mesmer".to_string()];
+        let mut raid = Timer::new(2, "Breakbar".to_string(), Duration::from_secs(60));
+        raid.group = "Vale".to_string();
+        let ids = [&mesmer, &raid].map(|t| timer_keybind(t, START_SUFFIX));
+        let mut timers = Timers::with_clock(Box::new(crate::clock::ManualClock::new()));
+        timers.list = Store::new(vec![mesmer, raid]);
+        let now = Instant::now();
+        let elsewhere = Scope {
+            groups: Some(vec!["Gorseval".to_string()]),
+            character: Some("Guardian".to_string()),
+        };
+        for id in &ids {
+            pressed(&mut timers, id, None, &elsewhere, now);
+        }
+        assert!(timers.list.iter().all(|t| !t.state.is_active()));
+        // a map without a rule shows every group
+        let here = Scope {
+            groups: None,
+            character: Some("Mesmer".to_string()),
+        };
+        for id in &ids {
+            pressed(&mut timers, id, None, &here, now);
+        }
+        assert!(timers.list.iter().all(|t| t.state.is_active()));
+    }
+
+    #[test]
+    fn timer_keybinds_by_id_and_by_name() {
+        let timer = Timer::new(1, "Boss: phase 2 (CM)".to_string(), Duration::from_secs(60));
+        let id = timer_keybind(&timer, ADD_SUFFIX);
+        assert_eq!(
+            split_timer_keybind(&id),
+            Some((timer.keybind_id.as_str(), ADD_SUFFIX))
+        );
+        assert_eq!(
+            split_timer_keybind(&timer_keybind(&timer, START_SUFFIX)),
+            Some((timer.keybind_id.as_str(), START_SUFFIX))
+        );
+        // the global ones and groups aren't a timer's
+        assert_eq!(split_timer_keybind(TOGGLE_WINDOW), None);
+        assert_eq!(
+            split_timer_keybind("KB_TIMER_GROUP_START_0123456789abcdef"),
+            None
+        );
+        assert_eq!(split_timer_keybind("KB_TIMER_0123456789abcdef_PAUSE"), None);
+        assert_eq!(
+            split_legacy("KB_TIMER_LAP_Boss: phase 2 (CM)"),
+            Some(("Boss: phase 2 (CM)", LAP_SUFFIX))
+        );
+        assert_eq!(split_legacy("KB_TIMER_GROUP_START_Vale"), None);
+    }
+
+    #[test]
+    fn keybinds_move_from_what_they_were_named() {
+        let mut timers = Timers::with_clock(Box::new(crate::clock::ManualClock::new()));
+        let mut legacy = Timer::new(1, "Boss".to_string(), Duration::from_secs(60));
+        legacy.keybind_id.clear();
+        let mut edited = Timer::new(2, "Portal".to_string(), Duration::from_secs(60));
+        edited.keybind_id = "portal".to_string();
+        timers.list = Store::new(vec![legacy, edited]);
+        let migrated = timers.assign_keybind_ids();
+        let of =
+            |id: &str| migrated_keybind(&timers, &migrated, id).map(|(t, suffix)| (t.id, suffix));
+        assert_eq!(of("KB_TIMER_START_Boss"), Some((1, START_SUFFIX)));
+        assert_eq!(of("KB_TIMER_portal"), Some((2, START_SUFFIX)));
+        assert_eq!(of("KB_TIMER_portal_LAP"), Some((2, LAP_SUFFIX)));
+        assert_eq!(of("KB_TIMER_START_Portal"), None);
+        assert_eq!(of("KB_TIMER_portal_PAUSE"), None);
+        let new = timer_keybind(timers.get(2).unwrap(), START_SUFFIX);
+        assert_eq!(
+            split_timer_keybind(&new).map(|(_, s)| s),
+            Some(START_SUFFIX)
+        );
+    }
+}
@@ -0,0 +1,196 @@
+//! Where and how the timers are drawn, kept in layout.json apart from timers.json so
+//! sharing timers doesn't share the screen arrangement along with them.
+
+use crate::anchor::Anchor;
+use crate::format::{Rounding, TimeFormat};
+use crate::import;
+use crate::timer::{ColorBand, Fade, Timer};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimerLayout {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<Anchor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<TimeFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decimals: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rounding: Option<Rounding>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_hz: Option<u32>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub template: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hide_idle: Option<Duration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window_size: Option<[f32; 2]>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub bands: Vec<ColorBand>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fade: Option<Fade>,
+    /// Screen position of the marker, its look belongs to the timer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub marker_position: Option<[f32; 2]>,
+}
+
+impl TimerLayout {
+    fn of(timer: &Timer) -> Self {
+        Self {
+            anchor: timer.anchor,
+            format: timer.format,
+            decimals: timer.decimals,
+            rounding: timer.rounding,
+            refresh_hz: timer.refresh_hz,
+            template: timer.template.clone(),
+            hide_idle: timer.hide_idle,
+            window_size: timer.window_size,
+            bands: timer.bands.clone(),
+            fade: timer.fade,
+            marker_position: timer.marker.as_ref().map(|m| m.position),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn sanitize(&mut self) {
+        if let Some(anchor) = &mut self.anchor {
+            anchor.sanitize();
+        }
+        import::sanitize_bands(&mut self.bands);
+        import::sanitize_style(&mut self.decimals, &mut self.refresh_hz);
+        import::sanitize_template(&mut self.template);
+        import::sanitize_hide_idle(&mut self.hide_idle);
+        import::sanitize_window_size(&mut self.window_size);
+        if let Some(fade) = &mut self.fade {
+            fade.from = fade.from.min(import::MAX_DURATION);
+        }
+    }
+
+    fn apply(&self, timer: &mut Timer) {
+        timer.anchor = self.anchor;
+        timer.format = self.format;
+        timer.decimals = self.decimals;
+        timer.rounding = self.rounding;
+        timer.refresh_hz = self.refresh_hz;
+        timer.template.clone_from(&self.template);
+        timer.hide_idle = self.hide_idle;
+        timer.window_size = self.window_size;
+        timer.bands = self.bands.clone();
+        timer.fade = self.fade;
+        if let (Some(marker), Some(position)) = (&mut timer.marker, self.marker_position) {
+            if position.iter().all(|p| p.is_finite()) {
+                marker.position = position;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Layout {
+    /// By timer name, which unlike the id survives removing and importing a timer again
+    #[serde(default)]
+    pub timers: BTreeMap<String, TimerLayout>,
+}
+
+impl Layout {
+    /// The layout of every timer that has one, packs included
+    pub fn of<'a>(timers: impl IntoIterator<Item = &'a Timer>) -> Self {
+        let timers = timers
+            .into_iter()
+            .filter(|t| t.system.is_none())
+            .map(|t| (t.name.clone(), TimerLayout::of(t)))
+            .filter(|(_, l)| !l.is_empty())
+            .collect();
+        Self { timers }
+    }
+
+    /// Sets the layout of the timers in it, returns how many there were.
+    /// The others keep theirs.
+    pub fn apply<'a>(&self, timers: impl IntoIterator<Item = &'a mut Timer>) -> usize {
+        let mut applied = 0;
+        for timer in timers {
+            if let Some(layout) = self.timers.get(&timer.name) {
+                layout.apply(timer);
+                applied += 1;
+            }
+        }
+        applied
+    }
+}
+
+/// Writes the layout of all timers to `path`, returns how many timers had one
+pub fn export<'a>(
+    path: &Path,
+    timers: impl IntoIterator<Item = &'a Timer>,
+) -> Result<usize, String> {
+    let layout = Layout::of(timers);
+    let json = serde_json::to_string_pretty(&layout).expect("Layout to be serialized");
+    std::fs::write(path, json).map_err(|e| e.to_string())?;
+    Ok(layout.timers.len())
+}
+
+/// Applies the layout from an export to the timers of the same name, returns how many
+/// there were
+pub fn import<'a>(
+    path: &Path,
+    timers: impl IntoIterator<Item = &'a mut Timer>,
+) -> Result<usize, String> {
+    let contents = import::read_file(path).map_err(|e| e.to_string())?;
+    let mut layout: Layout = serde_json::from_slice(&contents).map_err(|e| e.to_string())?;
+    for timer in layout.timers.values_mut() {
+        timer.sanitize();
+    }
+    Ok(layout.apply(timers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anchor::AnchorPoint;
+    use std::time::Duration;
+
+    #[test]
+    fn layout_follows_the_name() {
+        let mut timer = Timer::new(1, "Split".to_string(), Duration::from_secs(10));
+        timer.anchor = Some(Anchor {
+            point: AnchorPoint::Center,
+            offset: [0.0, 20.0],
+        });
+        timer.window_size = Some([250.0, 80.0]);
+        let plain = Timer::new(2, "Plain".to_string(), Duration::from_secs(10));
+        let layout = Layout::of(&[timer.clone(), plain]);
+        assert_eq!(layout.timers.len(), 1);
+
+        let mut timers = vec![
+            Timer::new(7, "Split".to_string(), Duration::from_secs(30)),
+            Timer::new(8, "Other".to_string(), Duration::from_secs(30)),
+        ];
+        assert_eq!(layout.apply(&mut timers), 1);
+        assert_eq!(timers[0].anchor, timer.anchor);
+        assert_eq!(timers[0].window_size, Some([250.0, 80.0]));
+        assert_eq!(timers[1].anchor, None);
+    }
+
+    #[test]
+    fn window_sizes_are_sanitized() {
+        let mut layout = TimerLayout {
+            window_size: Some([f32::NAN, 80.0]),
+            ..TimerLayout::default()
+        };
+        layout.sanitize();
+        assert_eq!(layout.window_size, None);
+        layout.window_size = Some([1.0, 1e9]);
+        layout.sanitize();
+        assert_eq!(
+            layout.window_size,
+            Some([import::MIN_WINDOW, import::MAX_WINDOW])
+        );
+    }
+}
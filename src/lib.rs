@@ -1,189 +1,493 @@
+use config::Settings;
+use nexus::alert::send_alert;
 use nexus::gui::{register_render, render, RenderType};
-use nexus::imgui::{Ui, Window};
-use nexus::keybind::{register_keybind_with_string, unregister_keybind};
-use nexus::paths::get_addon_dir;
-use nexus::{keybind_handler, localization::set_translation, AddonFlags, UpdateProvider};
-use serde::{Deserialize, Serialize};
-use std::cell::{Cell, RefCell};
-use std::path::PathBuf;
-use std::sync::Mutex;
-use std::time::{Duration, Instant};
-
-fn config_path() -> PathBuf {
-    get_addon_dir("timers")
-        .expect("Addon dir to exist")
-        .join("timers.json")
-}
+use nexus::imgui::Ui;
+use nexus::{AddonFlags, UpdateProvider};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use sync::LockExt;
+use timer::{Channel, Timer, TimerEvent, Timers};
+use webhook::HookEvent;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct Timer {
-    name: String,
-    duration: Duration,
-    // If None, start and stop is the same
-    #[serde(skip, default)]
-    started: Option<Instant>,
-}
+mod alarm;
+mod anchor;
+mod api;
+mod audio;
+mod backup;
+mod blish;
+mod chat;
+mod clock;
+mod combat;
+mod command;
+mod config;
+mod control;
+mod datalink;
+mod duration;
+mod eventlog;
+mod format;
+mod game;
+mod gw2api;
+mod history;
+mod http;
+mod i18n;
+mod icons;
+mod import;
+mod inbox;
+mod keybinds;
+mod layout;
+mod links;
+mod maps;
+mod marker;
+mod presets;
+mod profiling;
+mod quick_access;
+mod ready;
+mod recurrence;
+mod schedule;
+mod session;
+mod speech;
+mod squad;
+mod store;
+mod stream;
+mod sync;
+mod template;
+mod theme;
+mod ticker;
+mod timeline;
+mod timer;
+mod ui;
+mod webhook;
+mod worker;
+mod wvw;
 
-impl Timer {
-    fn new(name: String, duration: Duration) -> Self {
-        let new = Self {
-            name,
-            duration,
-            started: None,
-        };
+static TIMERS: OnceLock<Mutex<Timers>> = OnceLock::new();
+static SETTINGS: OnceLock<Mutex<Settings>> = OnceLock::new();
 
-        new.register_keybind();
-        new.register_localization();
-        new
-    }
+// Finish text to be copied to the clipboard, which only works on the render thread
+static CLIPBOARD: Mutex<Option<String>> = Mutex::new(None);
 
-    fn find_by_name<'a>(timers: &'a mut Vec<Self>, name: &'_ str) -> Option<&'a mut Self> {
-        timers.iter_mut().find(|t| t.name == name)
-    }
+// Messages about problems, shown on screen and in the options until dismissed
+static NOTICES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+// Why the addon runs without loading any files, set if it does
+static SAFE_MODE: OnceLock<String> = OnceLock::new();
 
-    const LANGS: &[&str] = &["br", "cn", "cz", "de", "en", "es", "fr", "it", "pl", "ru"];
-    fn register_localization(&self) {
-        for &l in Self::LANGS {
-            self.localize(l);
+// Between load and unload, a second load would register everything twice
+static LOADED: AtomicBool = AtomicBool::new(false);
+
+// Loads that didn't finish (e.g. crashed the game) before falling back to safe mode
+const MAX_FAILED_LOADS: u32 = 2;
+
+fn notice(msg: String) {
+    log::warn!("{}", msg);
+    NOTICES.locked().push(msg);
+}
+
+/// Sets `cell`, or replaces what an earlier load left there if the addon was loaded
+/// again without its statics being reset
+fn set_or_replace<T>(cell: &OnceLock<Mutex<T>>, value: T) {
+    match cell.get() {
+        Some(state) => {
+            log::warn!("Replacing the state of an earlier load");
+            *state.locked() = value;
+        }
+        None => {
+            let _ = cell.set(Mutex::new(value));
         }
     }
+}
 
-    fn localize(&self, lang: &str) {
-        set_translation(format!("KB_TIMER_START_{}", self.name), lang, &self.name);
+fn load() {
+    if LOADED.swap(true, Ordering::Relaxed) {
+        log::warn!("Loaded again without being unloaded, ignoring");
+        return;
     }
-
-    fn register_keybind(&self) {
-        let start_key_handler = keybind_handler!(|id, is_release| {
-            if is_release {
-                return;
-            }
-            let name = id.trim_start_matches("KB_TIMER_START_");
-            let mut timers = TIMERS.get().expect("Timers to be set").lock().unwrap();
-            if let Some(timer) = Timer::find_by_name(&mut *timers, name) {
-                timer.started = Some(std::time::Instant::now());
-            }
-        });
-        let _ = register_keybind_with_string(
-            format!("KB_TIMER_START_{}", self.name),
-            start_key_handler,
-            "(null)",
-        );
-        self.register_localization();
+    let failed = config::begin_load();
+    if failed >= MAX_FAILED_LOADS {
+        load_safe_mode(format!("the last {} starts did not finish", failed));
+    } else if let Err(panic) = std::panic::catch_unwind(load_normal) {
+        let msg = panic
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        load_safe_mode(format!("loading failed ({})", msg));
     }
+    config::end_load();
+}
 
-    fn unregister_keybind(&self) {
-        unregister_keybind(format!("KB_TIMER_START_{}", self.name));
+/// Starts with nothing loaded and nothing saved, so a bad config can't keep the addon
+/// from starting and the files stay as they are for the user to fix
+fn load_safe_mode(reason: String) {
+    log::error!("Starting in safe mode: {}", reason);
+    // a failed load may have gotten as far as setting these
+    set_or_replace(&TIMERS, Timers::default());
+    set_or_replace(
+        &SETTINGS,
+        Settings {
+            onboarded: true,
+            ..Settings::default()
+        },
+    );
+    if SAFE_MODE.set(reason).is_err() {
+        log::warn!("Already in safe mode from an earlier load");
     }
+    register_render(RenderType::OptionsRender, render!(ui::render_options)).revert_on_unload();
+    register_render(RenderType::Render, render!(ui::render_notices)).revert_on_unload();
 }
 
-static TIMERS: std::sync::OnceLock<Mutex<Vec<Timer>>> = std::sync::OnceLock::new();
-
-fn load() {
+fn load_normal() {
     log::info!("Loading timers");
-    let config: Vec<Timer> = if let Ok(f) = std::fs::File::open(config_path()) {
-        match serde_json::from_reader(f) {
-            Ok(c) => c,
-            Err(e) => {
-                log::warn!("Failed to parse timers.json: {}", e);
-                Vec::new()
-            }
-        }
-    } else {
-        Vec::new()
-    };
-    for timer in &config {
-        timer.register_keybind();
+    let _profile = profiling::scope("load");
+    i18n::register();
+    let mut timers = config::load_timers();
+    let migrated = timers.assign_keybind_ids();
+    keybinds::migrate(&timers, &migrated);
+    for timer in &timers.list {
+        keybinds::register(timer);
         log::info!("Loaded timer {}", timer.name);
     }
-    TIMERS
-        .set(Mutex::new(config))
-        .expect("Timers to be set only once");
+    *history::HISTORY.locked() = config::load_history();
+    let settings = config::load_settings();
+    if settings.record_sessions {
+        timers.start_recording();
+    }
+    let started = timers.autostart();
+    if !started.is_empty() {
+        log::info!("Started on load: {}", started.join(", "));
+    }
+    set_or_replace(&TIMERS, timers);
+    keybinds::register_global();
+    for group in &settings.group_keybinds {
+        keybinds::register_group(group);
+    }
+    if settings.quick_access {
+        quick_access::register();
+    }
+    api::register();
+    chat::register();
+    combat::register();
+    ready::register();
+
+    set_or_replace(&SETTINGS, settings);
+
+    register_render(RenderType::PreRender, render!(tick)).revert_on_unload();
+    ticker::start(advance);
+    register_render(RenderType::Render, render!(ui::render_overlay)).revert_on_unload();
+    register_render(RenderType::Render, render!(ui::render_banner)).revert_on_unload();
+    register_render(RenderType::Render, render!(ui::render_countdown)).revert_on_unload();
+    register_render(RenderType::Render, render!(ui::render_notices)).revert_on_unload();
+    register_render(RenderType::Render, render!(ui::render_hotbar)).revert_on_unload();
+    register_render(RenderType::Render, render!(ui::render_up_next)).revert_on_unload();
+    register_render(RenderType::Render, render!(ui::render_wvw_panel)).revert_on_unload();
+    register_render(RenderType::Render, render!(ui::render_editor_window)).revert_on_unload();
+    register_render(RenderType::Render, render!(ui::render_note_prompt)).revert_on_unload();
+    register_render(RenderType::Render, render!(ui::render_quick_timer)).revert_on_unload();
+    register_render(RenderType::Render, render!(ui::render_command_palette)).revert_on_unload();
+    register_render(RenderType::Render, render!(ui::render_press_confirmation)).revert_on_unload();
+    register_render(RenderType::Render, render!(ui::render_timers_window)).revert_on_unload();
+    register_render(RenderType::OptionsRender, render!(ui::render_options)).revert_on_unload();
+}
 
-    register_render(RenderType::Render, render!(render_fn)).revert_on_unload();
-    register_render(RenderType::OptionsRender, render!(render_options)).revert_on_unload();
+/// Advances the timers once per frame, before anything is drawn
+fn tick(ui: &Ui) {
+    advance();
+    if let Some(text) = CLIPBOARD.locked().take() {
+        ui.set_clipboard_text(text);
+    }
 }
 
-fn render_fn(ui: &Ui) {
-    let timers = TIMERS.get().expect("Timers to be set").lock().unwrap();
-    for timer in timers.iter().filter(|t| t.started.is_some()) {
-        let started = timer.started.expect("Timer to have started");
-        let elapsed = started.elapsed();
-        let rest = if elapsed >= timer.duration {
-            // action on timer finish?
-            0.0
-        } else {
-            (timer.duration - elapsed).as_secs_f32()
-        };
-        Window::new(timer.name.as_str()).build(ui, || ui.text(format!("{:.2}", rest)));
+/// Advances the timers and fires whatever that caused, called from the render thread
+/// every frame and from the ticker thread in between
+fn advance() {
+    let _profile = profiling::scope("tick");
+    let (
+        voice_volume,
+        (pause_when_away, freeze_while_loading),
+        api_key,
+        events,
+        relay,
+        room,
+        map_rules,
+        stream,
+        (backups, quick_access, data_link),
+        control,
+        time_scale,
+    ) = {
+        let settings = SETTINGS.get().expect("Settings to be set").locked();
+        audio::set_volumes(settings.volumes, settings.muted);
+        (
+            settings.spoken_volume(),
+            (settings.pause_when_away, settings.freeze_while_loading),
+            settings.api_key.clone(),
+            settings.events.clone(),
+            settings.squad_relay.clone(),
+            settings.squad_room.clone(),
+            settings.maps.clone(),
+            (
+                settings.stream_file,
+                settings.stream_interval_ms,
+                settings.time_style(),
+            ),
+            (settings.backups, settings.quick_access, settings.data_link),
+            (settings.control && !settings.control_token.is_empty())
+                .then(|| (settings.control_port, settings.control_token.clone())),
+            settings.time_scale,
+        )
+    };
+    maps::poll(game::map_id(), &map_rules);
+    let clock_event = clock::poll();
+    let scope = keybinds::Scope::current(&map_rules);
+    let mut timers = TIMERS.get().expect("Timers to be set").locked();
+    let asks = inbox::apply(&mut timers, &scope);
+    timers.set_time_scale(time_scale);
+    combat::watch(&timers.list);
+    gw2api::poll(&api_key, &mut timers);
+    schedule::poll(&events, &mut timers);
+    squad::poll(&relay, &room, &mut timers);
+    let frozen = freeze_while_loading && game::is_loading();
+    timers.set_away(frozen || (pause_when_away && game::is_away()));
+    let mut adjusted = None;
+    match clock_event {
+        Some(clock::ClockEvent::Jump(jump)) => {
+            log::warn!("System clock jumped by {:+.1}s", jump);
+            let moved = timers.resync_wall_clock();
+            if !moved.is_empty() {
+                log::info!("Moved to the new wall clock time: {}", moved.join(", "));
+            }
+        }
+        Some(clock::ClockEvent::Suspended { away, counted }) => {
+            log::info!("Resumed after {:.0}s in standby", away.as_secs_f32());
+            let mut affected = timers.resume_after_suspend(away, counted);
+            affected.extend(timers.resync_wall_clock());
+            if !affected.is_empty() {
+                adjusted = Some(format!(
+                    "Timers adjusted after standby: {}",
+                    affected.join(", ")
+                ));
+            }
+        }
+        None => {}
+    }
+    let events: Vec<(TimerEvent, Timer, Option<String>)> = timers
+        .tick()
+        .into_iter()
+        .filter_map(|event| {
+            let timer = timers.get(event.id())?;
+            Some((event, timer.clone(), timers.history_name(timer)))
+        })
+        .collect();
+    let (format, interval_ms, style) = stream;
+    let streamed = stream::collect(format, interval_ms, &timers, style);
+    let backup = backup::collect(&timers, backups);
+    let linked = datalink::collect(data_link, &timers);
+    let listing = control::collect(&timers);
+    let tooltip = quick_access
+        .then(|| quick_access::collect(&timers, style))
+        .flatten();
+    // sounds, other addons, requests and files can take their time, not holding the
+    // timers for any of it
+    drop(timers);
+    if let Some(text) = adjusted {
+        send_alert(text);
+    }
+    for (event, timer, kept_as) in events {
+        let kept_as = kept_as.as_deref();
+        dispatch(event, &timer, kept_as, voice_volume, &relay, &room);
+    }
+    if let Some(update) = streamed {
+        stream::publish(update);
+    }
+    if let Some(pending) = backup {
+        backup::take(pending, backups);
+    }
+    datalink::publish(data_link, linked);
+    control::poll(
+        control
+            .as_ref()
+            .map(|(port, token)| (*port, token.as_str())),
+        listing,
+    );
+    if let Some(text) = tooltip {
+        quick_access::publish(text);
+    }
+    // the confirmation is rendered holding the prompt before the timers
+    for name in asks {
+        ui::confirm_press(name);
     }
 }
 
-fn render_options(ui: &Ui) {
-    let mut timers = TIMERS.get().expect("Timers to be set").lock().unwrap();
-    let mut to_remove = Vec::new();
-    if let Some(_tbl) = ui.begin_table("timer_options", 3) {
-        for (idx, timer) in timers.iter_mut().enumerate() {
-            ui.table_next_row();
-            ui.table_next_column();
-            ui.text(timer.name.as_str());
-            ui.table_next_column();
-            let mut seconds = timer.duration.as_secs() as i32;
-            ui.input_int(format!("{:?}", seconds), &mut seconds)
-                .read_only(timer.started.is_some())
-                .build();
-            if seconds >= 0 {
-                timer.duration = std::time::Duration::from_secs(seconds as u64);
+/// Plays, says, shares and records what happened to `timer`, as it was right after. Its
+/// runs go into the history as ones of `kept_as`, if any.
+fn dispatch(
+    event: TimerEvent,
+    timer: &Timer,
+    kept_as: Option<&str>,
+    voice_volume: u8,
+    relay: &str,
+    room: &str,
+) {
+    match event {
+        TimerEvent::Started(_, source) => {
+            log::debug!("Timer {} started by {}", timer.name, source.name());
+            let by = format!("by {}", source.name());
+            eventlog::record(eventlog::Kind::Start, &timer.name, by);
+            if kept_as.is_some() {
+                let now = std::time::SystemTime::now();
+                history::HISTORY.locked().begin(&timer.name, now);
+            }
+            if !timer.sounds.start.is_empty() {
+                audio::play_file(&timer.sounds.start, audio::SoundKind::Start);
             }
-            ui.table_next_column();
-            if ui.button("Delete") {
-                to_remove.push(idx);
-                timer.unregister_keybind();
+            if timer.voice {
+                speech::say(timer.name.clone(), voice_volume);
+            }
+            if timer.share && source != timer::StartSource::Squad {
+                squad::broadcast(relay, room, &timer.name);
+            }
+            if !timer.webhook.is_empty() {
+                webhook::send(&timer.webhook, &timer.name, HookEvent::Start);
             }
         }
-        let tmp_timers = std::mem::take(&mut *timers);
-        *timers = tmp_timers
-            .into_iter()
-            .enumerate()
-            .filter(|(idx, _)| !to_remove.contains(idx))
-            .map(|(_, t)| t)
-            .collect();
-        ui.table_next_row();
-        ui.table_next_column();
-        thread_local! {
-            static NEW_NAME: RefCell<String> = const { RefCell::new(String::new()) };
-            static NEW_DURATION: Cell<i32> = const { Cell::new(0) };
+        TimerEvent::Mark(id, mark) => {
+            log::debug!("Timer {} at {:?}", timer.name, mark);
+            if timer.voice {
+                let text = format!("{} in {}", timer.name, speech::spoken(mark));
+                speech::say(text, voice_volume);
+            }
+            if timer.mark_cue.ping() {
+                ui::ping(id);
+            }
+            if timer.mark_cue.sound() {
+                audio::mark();
+            }
         }
-        NEW_NAME.with_borrow_mut(|nn| {
-            ui.input_text("Name", nn).build();
-        });
-        ui.table_next_column();
-        let mut new_duration = NEW_DURATION.get();
-        ui.input_int("Seconds", &mut new_duration).build();
-        NEW_DURATION.set(new_duration);
-        ui.table_next_column();
-        if ui.button("Add") {
-            NEW_NAME.with_borrow_mut(|nn| {
-                if nn.is_empty() {
-                    return;
+        TimerEvent::Warn(_, channel) => alert(timer, channel, voice_volume),
+        TimerEvent::Step(id, step) => {
+            if let Some(step) = timer.timeline.as_ref().and_then(|t| t.steps.get(step)) {
+                log::debug!("Timer {} on to {}", timer.name, step.label);
+                if timer.voice {
+                    speech::say(step.label.clone(), voice_volume);
                 }
-                timers.push(Timer::new(
-                    std::mem::take(nn),
-                    Duration::from_secs(NEW_DURATION.get() as u64),
-                ));
-                NEW_DURATION.set(0);
-            })
+                ui::ping(id);
+            }
+        }
+        TimerEvent::Possible(id) => {
+            log::debug!("Timer {} possible from now on", timer.name);
+            if timer.voice {
+                speech::say(format!("{} possible", timer.name), voice_volume);
+            }
+            ui::ping(id);
+        }
+        TimerEvent::Finished(_) => {
+            eventlog::record(eventlog::Kind::Finish, &timer.name, "");
+            on_finish(timer, voice_volume);
+            if !timer.webhook.is_empty() {
+                webhook::send(&timer.webhook, &timer.name, HookEvent::Finish);
+            }
+            if let Some(kept_as) = kept_as {
+                let length = timer.run_duration();
+                record_run(timer, kept_as, length, history::Outcome::Finished);
+            }
+        }
+        TimerEvent::Stopped(_, ran) => {
+            let after = format!("after {:.1}s", ran.as_secs_f32());
+            eventlog::record(eventlog::Kind::Stop, &timer.name, after);
+            if let Some(kept_as) = kept_as {
+                let run = record_run(timer, kept_as, ran, history::Outcome::Stopped);
+                if timer.ask_note {
+                    ui::ask_note(run, kept_as.to_string());
+                }
+            }
+        }
+    }
+}
+
+fn record_run(
+    timer: &Timer,
+    kept_as: &str,
+    length: std::time::Duration,
+    outcome: history::Outcome,
+) -> u64 {
+    let mut history = history::HISTORY.locked();
+    let now = std::time::SystemTime::now();
+    let run = history.record(&timer.name, kept_as, length, outcome, now);
+    if !timer.laps.is_empty() {
+        history.set_laps(run, timer.laps.clone());
+    }
+    run
+}
+
+fn on_finish(timer: &Timer, voice_volume: u8) {
+    log::debug!("Timer {} finished", timer.name);
+    if !timer.finish_text.is_empty() {
+        *CLIPBOARD.locked() = Some(timer.finish_text.clone());
+    }
+    for channel in Channel::ALL {
+        if timer.leads.get(channel).is_zero() {
+            alert(timer, channel, voice_volume);
         }
     }
+    // the sound channel warned ahead, the finish still gets its own sound
+    let finish = &timer.sounds.finish;
+    if timer.sound && !timer.leads.sound.is_zero() && !finish.is_empty() {
+        audio::play_file(finish, audio::SoundKind::Finish);
+    }
+}
+
+/// The finish alert of one channel, if the timer has it turned on
+fn alert(timer: &Timer, channel: Channel, voice_volume: u8) {
+    match channel {
+        Channel::Sound if timer.sound => {
+            let (file, kind) = if timer.leads.sound.is_zero() {
+                (&timer.sounds.finish, audio::SoundKind::Finish)
+            } else {
+                (&timer.sounds.warning, audio::SoundKind::Warning)
+            };
+            audio::cue_or_file(timer.priority, file, kind);
+        }
+        Channel::Voice if timer.voice => {
+            let lead = timer.leads.voice;
+            let text = if lead.is_zero() {
+                format!("{} finished", timer.name)
+            } else {
+                format!("{} in {}", timer.name, speech::spoken(lead))
+            };
+            speech::say(text, voice_volume);
+        }
+        Channel::Flash if timer.flash => ui::ping(timer.id),
+        _ => {}
+    }
 }
 
 fn unload() {
     log::info!("Unloading timers");
-    let timers = TIMERS.get().expect("Timers to be set").lock().unwrap();
-    let json = serde_json::to_string_pretty(&*timers).expect("Timers to be serialized");
-    let config = config_path();
-    log::info!("Saving timers to {}", config.display());
-    let _ = std::fs::create_dir_all(&config.parent().unwrap());
-    let _ = std::fs::write(&config, json);
+    LOADED.store(false, Ordering::Relaxed);
+    ticker::stop();
+    control::stop();
+    worker::shutdown_all();
+    if SAFE_MODE.get().is_some() {
+        return;
+    }
+    let profile = profiling::scope("save");
+    let mut timers = TIMERS.get().expect("Timers to be set").locked();
+    let saved_timers = config::save_timers(&timers);
+    if let Some(recording) = timers.take_recording() {
+        session::save(&recording.into_session());
+    }
+    let settings = SETTINGS.get().expect("Settings to be set").locked();
+    backup::take_now(backup::Pending::of(&timers), settings.backups);
+    let results = [
+        saved_timers,
+        config::save_settings(&settings),
+        config::save_history(&history::HISTORY.locked()),
+    ];
+    // there is no UI left to show them in
+    for e in results.into_iter().filter_map(Result::err) {
+        log::error!("{}", e);
+    }
+    drop(profile);
+    profiling::write_csv(&config::config_path().with_file_name("profiling.csv"));
 }
 
 nexus::export! {
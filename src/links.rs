@@ -0,0 +1,42 @@
+//! Opening web links attached to timers in the default browser.
+
+use crate::worker::Worker;
+use std::time::Duration;
+use windows::core::{w, HSTRING, PCWSTR};
+use windows::Win32::Foundation::HWND;
+use windows::Win32::UI::Shell::ShellExecuteW;
+use windows::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+
+/// Only web links, anything else would be run as a program by the shell
+pub fn is_valid(url: &str) -> bool {
+    (url.starts_with("https://") || url.starts_with("http://"))
+        && !url.contains(char::is_whitespace)
+}
+
+fn shell_open(url: String) {
+    let result = unsafe {
+        ShellExecuteW(
+            HWND::default(),
+            w!("open"),
+            &HSTRING::from(url.as_str()),
+            PCWSTR::null(),
+            PCWSTR::null(),
+            SW_SHOWNORMAL,
+        )
+    };
+    // anything up to 32 is an error code
+    if result.0 as isize <= 32 {
+        log::warn!("Failed to open {}: error {}", url, result.0 as isize);
+    }
+}
+
+// the shell can take a moment to hand the link over, keep that off the render thread
+static SHELL: Worker<String> = Worker::new("shell", Duration::from_secs(10), shell_open);
+
+pub fn open(url: &str) {
+    if !is_valid(url) {
+        log::warn!("Not opening {:?}, only web links are supported", url);
+        return;
+    }
+    SHELL.send(url.to_string());
+}
@@ -0,0 +1,97 @@
+//! Timers shown per map, so entering an instance brings up the timers of its encounters
+//! and hides the rest.
+
+use crate::sync::LockExt;
+use crate::timer::Timer;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MapRule {
+    /// Map id as the MumbleLink reports it
+    pub map: u32,
+    /// Only for telling the rules apart in the options
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    /// Groups of the timers shown on the map
+    #[serde(default)]
+    pub groups: Vec<String>,
+}
+
+/// The groups shown on `map`, `None` if there is no rule for it and everything is shown
+pub fn groups_for(rules: &[MapRule], map: Option<u32>) -> Option<Vec<String>> {
+    let map = map?;
+    let groups: Vec<String> = rules
+        .iter()
+        .filter(|r| r.map == map)
+        .flat_map(|r| r.groups.iter().cloned())
+        .collect();
+    rules.iter().any(|r| r.map == map).then_some(groups)
+}
+
+/// Whether `timer` is shown with the groups of [`groups_for`]
+pub fn shows(groups: Option<&[String]>, timer: &Timer) -> bool {
+    groups.is_none_or(|groups| groups.contains(&timer.group))
+}
+
+// Polled from the tick thread too
+static CURRENT: Mutex<Option<u32>> = Mutex::new(None);
+
+/// Notices map changes, called every tick with the map the game is on
+pub fn poll(map: Option<u32>, rules: &[MapRule]) {
+    let mut current = CURRENT.locked();
+    if *current == map {
+        return;
+    }
+    *current = map;
+    drop(current);
+    let Some(id) = map else {
+        return;
+    };
+    match groups_for(rules, map) {
+        Some(groups) => log::info!("Entered map {}, showing {}", id, groups.join(", ")),
+        None => log::debug!("Entered map {}", id),
+    }
+}
+
+/// The map seen by the last [`poll`]
+pub fn current() -> Option<u32> {
+    *CURRENT.locked()
+}
+
+/// The groups shown on the current map, see [`groups_for`]
+pub fn current_groups(rules: &[MapRule]) -> Option<Vec<String>> {
+    groups_for(rules, current())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn rules_pick_groups() {
+        let rules = vec![
+            MapRule {
+                map: 1062,
+                name: "Spirit Vale".to_string(),
+                groups: vec!["Vale Guardian".to_string()],
+            },
+            MapRule {
+                map: 1062,
+                name: String::new(),
+                groups: vec!["Gorseval".to_string()],
+            },
+        ];
+        let groups = groups_for(&rules, Some(1062)).unwrap();
+        assert_eq!(groups, ["Vale Guardian", "Gorseval"]);
+        assert_eq!(groups_for(&rules, Some(15)), None);
+        assert_eq!(groups_for(&rules, None), None);
+
+        let mut timer = Timer::new(1, "Split".to_string(), Duration::from_secs(10));
+        assert!(!shows(Some(&groups), &timer));
+        assert!(shows(None, &timer));
+        timer.group = "Gorseval".to_string();
+        assert!(shows(Some(&groups), &timer));
+    }
+}
@@ -0,0 +1,103 @@
+//! On-screen markers a running timer draws to point at part of the screen.
+
+use nexus::imgui::Ui;
+use serde::{Deserialize, Serialize};
+
+const MARKER_COLOR: [f32; 4] = [1.0, 0.85, 0.2, 1.0];
+const OUTLINE_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.8];
+pub const MAX_SIZE: f32 = 500.0;
+
+fn default_size() -> f32 {
+    40.0
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarkerShape {
+    #[default]
+    Circle,
+    /// Pointing down at the position
+    Arrow,
+}
+
+impl MarkerShape {
+    pub const ALL: [MarkerShape; 2] = [Self::Circle, Self::Arrow];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Circle => "Circle",
+            Self::Arrow => "Arrow",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Marker {
+    #[serde(default)]
+    pub shape: MarkerShape,
+    /// Screen position in pixels that is marked
+    pub position: [f32; 2],
+    #[serde(default = "default_size")]
+    pub size: f32,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub label: String,
+}
+
+impl Default for Marker {
+    fn default() -> Self {
+        Self {
+            shape: MarkerShape::default(),
+            position: [0.0, 0.0],
+            size: default_size(),
+            label: String::new(),
+        }
+    }
+}
+
+impl Marker {
+    /// Draws on top of everything else, outside of any window
+    pub fn draw(&self, ui: &Ui) {
+        let draw = ui.get_foreground_draw_list();
+        let [x, y] = self.position;
+        let size = self.size.clamp(1.0, MAX_SIZE);
+        let thickness = (size / 10.0).max(2.0);
+        let label_at = match self.shape {
+            MarkerShape::Circle => {
+                let radius = size / 2.0;
+                draw.add_circle([x, y], radius, OUTLINE_COLOR)
+                    .thickness(thickness + 2.0)
+                    .build();
+                draw.add_circle([x, y], radius, MARKER_COLOR)
+                    .thickness(thickness)
+                    .build();
+                [x + radius + thickness, y - ui.text_line_height() / 2.0]
+            }
+            MarkerShape::Arrow => {
+                let head = [
+                    [x, y],
+                    [x - size / 2.0, y - size],
+                    [x + size / 2.0, y - size],
+                ];
+                draw.add_triangle(head[0], head[1], head[2], OUTLINE_COLOR)
+                    .thickness(2.0)
+                    .build();
+                draw.add_triangle(head[0], head[1], head[2], MARKER_COLOR)
+                    .filled(true)
+                    .build();
+                draw.add_line([x, y - size], [x, y - 2.0 * size], MARKER_COLOR)
+                    .thickness(thickness)
+                    .build();
+                let [width, height] = ui.calc_text_size(&self.label);
+                [x - width / 2.0, y - 2.0 * size - height]
+            }
+        };
+        if !self.label.is_empty() {
+            draw.add_text(
+                [label_at[0] + 1.0, label_at[1] + 1.0],
+                OUTLINE_COLOR,
+                &self.label,
+            );
+            draw.add_text(label_at, MARKER_COLOR, &self.label);
+        }
+    }
+}
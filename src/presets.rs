@@ -0,0 +1,16 @@
+use crate::timer::Timer;
+use serde::Deserialize;
+use std::sync::OnceLock;
+
+#[derive(Debug, Deserialize)]
+pub struct Preset {
+    pub name: String,
+    pub timers: Vec<Timer>,
+}
+
+pub fn presets() -> &'static [Preset] {
+    static PRESETS: OnceLock<Vec<Preset>> = OnceLock::new();
+    PRESETS.get_or_init(|| {
+        serde_json::from_str(include_str!("presets.json")).expect("Embedded presets to be valid")
+    })
+}
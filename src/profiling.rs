@@ -0,0 +1,138 @@
+//! Per-subsystem timings, only collected when built with the `profiling` feature.
+
+#[cfg(feature = "profiling")]
+use crate::sync::LockExt;
+use nexus::imgui::Ui;
+use std::path::Path;
+
+/// Records the time until it's dropped under the given subsystem
+pub struct Scope {
+    #[cfg(feature = "profiling")]
+    name: &'static str,
+    #[cfg(feature = "profiling")]
+    start: std::time::Instant,
+}
+
+pub fn scope(_name: &'static str) -> Scope {
+    Scope {
+        #[cfg(feature = "profiling")]
+        name: _name,
+        #[cfg(feature = "profiling")]
+        start: std::time::Instant::now(),
+    }
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        #[cfg(feature = "profiling")]
+        imp::record(self.name, self.start.elapsed());
+    }
+}
+
+#[cfg(feature = "profiling")]
+mod imp {
+    use crate::sync::LockExt;
+    use std::collections::BTreeMap;
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant};
+
+    /// Samples are aggregated into one CSV row per subsystem per window
+    const WINDOW: Duration = Duration::from_secs(1);
+
+    #[derive(Debug, Default, Clone, Copy)]
+    pub struct Stat {
+        pub count: u64,
+        pub total: Duration,
+        pub max: Duration,
+    }
+
+    impl Stat {
+        fn add(&mut self, d: Duration) {
+            self.count += 1;
+            self.total += d;
+            self.max = self.max.max(d);
+        }
+
+        pub fn avg(&self) -> Duration {
+            self.total / self.count.max(1) as u32
+        }
+    }
+
+    pub struct Profile {
+        pub start: Instant,
+        pub totals: BTreeMap<&'static str, Stat>,
+        window_start: Instant,
+        window: BTreeMap<&'static str, Stat>,
+        pub rows: Vec<String>,
+    }
+
+    pub static PROFILE: Mutex<Option<Profile>> = Mutex::new(None);
+
+    pub fn record(name: &'static str, d: Duration) {
+        let now = Instant::now();
+        let mut profile = PROFILE.locked();
+        let profile = profile.get_or_insert_with(|| Profile {
+            start: now,
+            totals: BTreeMap::new(),
+            window_start: now,
+            window: BTreeMap::new(),
+            rows: Vec::new(),
+        });
+        profile.totals.entry(name).or_default().add(d);
+        profile.window.entry(name).or_default().add(d);
+        if now - profile.window_start >= WINDOW {
+            let t = (profile.window_start - profile.start).as_secs_f32();
+            for (name, stat) in std::mem::take(&mut profile.window) {
+                profile.rows.push(format!(
+                    "{:.1},{},{},{},{}",
+                    t,
+                    name,
+                    stat.count,
+                    stat.avg().as_micros(),
+                    stat.max.as_micros()
+                ));
+            }
+            profile.window_start = now;
+        }
+    }
+}
+
+pub fn render_panel(_ui: &Ui) {
+    #[cfg(feature = "profiling")]
+    {
+        let profile = imp::PROFILE.locked();
+        let Some(profile) = profile.as_ref() else {
+            return;
+        };
+        _ui.separator();
+        _ui.text("Profiling");
+        for (name, stat) in &profile.totals {
+            _ui.text(format!(
+                "{}: {} calls, avg {}us, max {}us",
+                name,
+                stat.count,
+                stat.avg().as_micros(),
+                stat.max.as_micros()
+            ));
+        }
+    }
+}
+
+/// Writes the collected samples as `time_s,subsystem,count,avg_us,max_us` rows
+pub fn write_csv(_path: &Path) {
+    #[cfg(feature = "profiling")]
+    {
+        let profile = imp::PROFILE.locked();
+        let Some(profile) = profile.as_ref() else {
+            return;
+        };
+        let mut csv = String::from("time_s,subsystem,count,avg_us,max_us\n");
+        for row in &profile.rows {
+            csv.push_str(row);
+            csv.push('\n');
+        }
+        if let Err(e) = std::fs::write(_path, csv) {
+            log::warn!("Failed to write {}: {}", _path.display(), e);
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! A shortcut in the Nexus quick access bar. Its tooltip lists the timers finishing next
+//! and a click opens the timers window.
+//!
+//! Nexus looks the tooltip up as a translation each time it is shown, so updating the
+//! translation updates the tooltip.
+
+use crate::format::TimeStyle;
+use crate::i18n::LANGS;
+use crate::keybinds;
+use crate::sync::LockExt;
+use crate::timer::Timers;
+use nexus::localization::set_translation;
+use nexus::quick_access::{add_quick_access, remove_quick_access};
+use nexus::texture::load_texture_from_memory;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const ID: &str = "QA_TIMERS";
+const TEXTURE: &str = "TEX_TIMERS_QUICK_ACCESS";
+const TEXTURE_HOVER: &str = "TEX_TIMERS_QUICK_ACCESS_HOVER";
+const TOOLTIP: &str = "TIMERS_QUICK_ACCESS_TOOLTIP";
+
+/// Timers listed in the tooltip
+const SHOWN: usize = 3;
+const REFRESH: Duration = Duration::from_secs(1);
+
+pub fn register() {
+    load_texture_from_memory(TEXTURE, include_bytes!("assets/quick_access.png"), None);
+    load_texture_from_memory(
+        TEXTURE_HOVER,
+        include_bytes!("assets/quick_access_hover.png"),
+        None,
+    );
+    set_tooltip("Timers");
+    add_quick_access(ID, TEXTURE, TEXTURE_HOVER, keybinds::TOGGLE_WINDOW, TOOLTIP)
+        .revert_on_unload();
+}
+
+pub fn unregister() {
+    remove_quick_access(ID);
+}
+
+fn set_tooltip(text: &str) {
+    for &l in LANGS {
+        set_translation(TOOLTIP, l, text);
+    }
+}
+
+fn tooltip(timers: &Timers, style: TimeStyle) -> String {
+    let next = timers.next_finishes();
+    let mut text = "Timers".to_string();
+    if next.is_empty() {
+        text.push_str("\nNone running");
+    }
+    for (timer, rest) in next.iter().take(SHOWN) {
+        // refreshed once a second, decimals would stand still
+        let style = timer.style(style).format.with_decimals(0);
+        text.push_str(&format!("\n{}  {}", timer.name, style.format(*rest)));
+    }
+    if next.len() > SHOWN {
+        text.push_str(&format!("\n+{} more", next.len() - SHOWN));
+    }
+    text
+}
+
+// When the tooltip was last set and to what
+static SHOWN_TOOLTIP: Mutex<Option<(Instant, String)>> = Mutex::new(None);
+
+/// The tooltip once `REFRESH` passed since it was last set, called every tick holding the
+/// timers
+pub fn collect(timers: &Timers, style: TimeStyle) -> Option<String> {
+    let due = SHOWN_TOOLTIP
+        .locked()
+        .as_ref()
+        .is_none_or(|(at, _)| at.elapsed() >= REFRESH);
+    due.then(|| tooltip(timers, style))
+}
+
+/// Sets the tooltip to `text` from [`collect`] if it changed
+pub fn publish(text: String) {
+    let mut shown = SHOWN_TOOLTIP.locked();
+    if shown.as_ref().is_none_or(|(_, old)| *old != text) {
+        set_tooltip(&text);
+    }
+    *shown = Some((Instant::now(), text));
+}
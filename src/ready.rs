@@ -0,0 +1,141 @@
+//! Timers started the moment a ready check completes, so pull timers don't depend on
+//! someone's reaction time.
+//!
+//! Nexus itself has no squad events, the roster comes from the Unofficial Extras addon
+//! which raises `EV_UNOFFICIAL_EXTRAS_SQUAD_UPDATE` with the members that changed,
+//! readiness included. Without it installed no ready check is ever seen.
+
+use crate::inbox::{self, Request};
+use crate::sync::LockExt;
+use crate::timer::Timer;
+use nexus::event::event_subscribe;
+use nexus::event_consume;
+use std::collections::BTreeMap;
+use std::ffi::{c_char, CStr};
+use std::sync::Mutex;
+
+pub const EV_SQUAD_UPDATE: &str = "EV_UNOFFICIAL_EXTRAS_SQUAD_UPDATE";
+
+/// `UserRole` of Unofficial Extras up to which someone is in the squad, the roles after
+/// it are invites, applications and those who left
+const ROLE_MEMBER: u8 = 2;
+
+/// Who is in the squad and whether they are ready
+#[derive(Debug, Default)]
+struct Roster {
+    ready: BTreeMap<String, bool>,
+    complete: bool,
+}
+
+impl Roster {
+    /// Applies the changed members of one update, returns whether everyone just became
+    /// ready
+    fn apply(&mut self, updates: impl IntoIterator<Item = (String, u8, bool)>) -> bool {
+        for (account, role, ready) in updates {
+            if role <= ROLE_MEMBER {
+                self.ready.insert(account, ready);
+            } else {
+                self.ready.remove(&account);
+            }
+        }
+        let complete = !self.ready.is_empty() && self.ready.values().all(|r| *r);
+        let completed = complete && !self.complete;
+        self.complete = complete;
+        completed
+    }
+}
+
+static ROSTER: Mutex<Roster> = Mutex::new(Roster {
+    ready: BTreeMap::new(),
+    complete: false,
+});
+
+/// The timers to start once a ready check completes
+pub fn triggered<'a>(timers: impl IntoIterator<Item = &'a Timer>) -> Vec<String> {
+    timers
+        .into_iter()
+        .filter(|t| t.enabled && t.ready_check)
+        .map(|t| t.name.clone())
+        .collect()
+}
+
+/// `UserInfo` of Unofficial Extras
+#[repr(C)]
+struct UserInfo {
+    account_name: *const c_char,
+    join_time: i64,
+    role: u8,
+    subgroup: u8,
+    ready_status: bool,
+    _unused1: u8,
+    _unused2: u32,
+}
+
+/// `SquadUpdate` of Unofficial Extras
+#[repr(C)]
+struct SquadUpdate {
+    users: *const UserInfo,
+    count: u64,
+}
+
+impl SquadUpdate {
+    fn members(&self) -> Vec<(String, u8, bool)> {
+        if self.users.is_null() {
+            return Vec::new();
+        }
+        // SAFETY: Extras passes `count` users, valid for the event
+        let users = unsafe { std::slice::from_raw_parts(self.users, self.count as usize) };
+        users
+            .iter()
+            .filter(|u| !u.account_name.is_null())
+            .map(|u| {
+                // SAFETY: account names are null terminated
+                let account = unsafe { CStr::from_ptr(u.account_name) };
+                (
+                    account.to_string_lossy().into_owned(),
+                    u.role,
+                    u.ready_status,
+                )
+            })
+            .collect()
+    }
+}
+
+pub fn register() {
+    event_subscribe(
+        EV_SQUAD_UPDATE,
+        event_consume!(<SquadUpdate> |data| {
+            let Some(update) = data else {
+                return;
+            };
+            if !ROSTER.locked().apply(update.members()) {
+                return;
+            }
+            log::info!("Ready check completed");
+            inbox::send(Request::ReadyCheck);
+        }),
+    )
+    .revert_on_unload();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn completes_once_everyone_is_ready() {
+        let member = |name: &str, role, ready| (name.to_string(), role, ready);
+        let mut roster = Roster::default();
+        assert!(!roster.apply([member("a.1234", 0, false), member("b.1234", 2, false)]));
+        assert!(!roster.apply([member("a.1234", 0, true)]));
+        // an invite doesn't hold up the check
+        assert!(!roster.apply([member("c.1234", 3, false)]));
+        assert!(roster.apply([member("b.1234", 2, true)]));
+        assert!(!roster.apply([member("a.1234", 0, true)]));
+        // the check ends and everyone is unready again
+        assert!(!roster.apply([member("a.1234", 0, false), member("b.1234", 2, false)]));
+        assert!(!roster.apply([member("a.1234", 0, true)]));
+        // someone leaving can complete it too
+        assert!(roster.apply([member("b.1234", 5, false)]));
+    }
+}
@@ -0,0 +1,121 @@
+//! Daily and weekly reset boundaries in UTC wall-clock time.
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const DAY: u64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Weekday {
+    #[default]
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    pub const ALL: [Weekday; 7] = [
+        Self::Monday,
+        Self::Tuesday,
+        Self::Wednesday,
+        Self::Thursday,
+        Self::Friday,
+        Self::Saturday,
+        Self::Sunday,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Monday => "Monday",
+            Self::Tuesday => "Tuesday",
+            Self::Wednesday => "Wednesday",
+            Self::Thursday => "Thursday",
+            Self::Friday => "Friday",
+            Self::Saturday => "Saturday",
+            Self::Sunday => "Sunday",
+        }
+    }
+
+    fn index(self) -> u64 {
+        self as u64
+    }
+}
+
+/// When a new day and a new week begin. Defaults to the game's resets.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResetSchedule {
+    /// UTC hour at which a new day begins
+    pub daily_hour: u8,
+    pub week_start: Weekday,
+    /// UTC time on `week_start` at which a new week begins
+    pub weekly_hour: u8,
+    pub weekly_minute: u8,
+}
+
+impl Default for ResetSchedule {
+    fn default() -> Self {
+        Self {
+            daily_hour: 0,
+            week_start: Weekday::Monday,
+            weekly_hour: 7,
+            weekly_minute: 30,
+        }
+    }
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+impl ResetSchedule {
+    /// Clamps values that were edited by hand into range
+    pub fn sanitize(&mut self) {
+        self.daily_hour = self.daily_hour.min(23);
+        self.weekly_hour = self.weekly_hour.min(23);
+        self.weekly_minute = self.weekly_minute.min(59);
+    }
+
+    /// The first daily reset after `now`
+    pub fn next_daily(&self, now: SystemTime) -> SystemTime {
+        let secs = unix_secs(now);
+        let mut next = secs - secs % DAY + self.daily_hour as u64 * 3600;
+        if next <= secs {
+            next += DAY;
+        }
+        UNIX_EPOCH + Duration::from_secs(next)
+    }
+
+    /// Which day unix time `unix` is in, counted in daily resets since the epoch
+    pub fn day(&self, unix: u64) -> u64 {
+        unix.saturating_sub(self.daily_hour as u64 * 3600) / DAY
+    }
+
+    /// Unix time of the last weekly reset at or before `now`
+    pub fn week_began(&self, now: SystemTime) -> u64 {
+        unix_secs(self.next_weekly(now)).saturating_sub(7 * DAY)
+    }
+
+    /// The first weekly reset after `now`
+    pub fn next_weekly(&self, now: SystemTime) -> SystemTime {
+        let secs = unix_secs(now);
+        let days = secs / DAY;
+        // the epoch was a thursday
+        let weekday = (days + 3) % 7;
+        let since_start = (weekday + 7 - self.week_start.index()) % 7;
+        let mut next = (days - since_start) * DAY
+            + self.weekly_hour as u64 * 3600
+            + self.weekly_minute as u64 * 60;
+        if next <= secs {
+            next += 7 * DAY;
+        }
+        UNIX_EPOCH + Duration::from_secs(next)
+    }
+}
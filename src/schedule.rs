@@ -0,0 +1,140 @@
+//! Fixed world boss and meta-event rotations, computed from UTC time and shown as
+//! read-only system timers counting down to the next spawn of subscribed events.
+
+use crate::sync::LockExt;
+use crate::timer::{TimerState, Timers};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Source of the system timers created here
+const SOURCE: &str = "Schedule";
+/// Resynced with the wall clock this often, in case it drifted from the frame clock
+const RESYNC: Duration = Duration::from_secs(60);
+const DAY_MINUTES: u64 = 24 * 60;
+
+enum Spawns {
+    /// Every `period` minutes starting at `first` minutes after midnight UTC
+    Every { first: u16, period: u16 },
+    /// At these minutes after midnight UTC
+    At(&'static [u16]),
+}
+
+pub struct Event {
+    pub name: &'static str,
+    spawns: Spawns,
+}
+
+const fn hm(hour: u16, minute: u16) -> u16 {
+    hour * 60 + minute
+}
+
+const fn every(name: &'static str, first: u16, period: u16) -> Event {
+    Event {
+        name,
+        spawns: Spawns::Every { first, period },
+    }
+}
+
+pub const EVENTS: &[Event] = &[
+    every("Taidha Covington", hm(0, 0), 180),
+    every("Svanir Shaman Chief", hm(0, 15), 120),
+    every("Megadestroyer", hm(0, 30), 180),
+    every("Fire Elemental", hm(0, 45), 120),
+    every("The Shatterer", hm(1, 0), 180),
+    every("Great Jungle Wurm", hm(1, 15), 120),
+    every("Modniir Ulgoth", hm(1, 30), 180),
+    every("Shadow Behemoth", hm(1, 45), 120),
+    every("Golem Mark II", hm(2, 0), 180),
+    every("Claw of Jormag", hm(2, 30), 180),
+    Event {
+        name: "Tequatl the Sunless",
+        spawns: Spawns::At(&[
+            hm(0, 0),
+            hm(3, 0),
+            hm(7, 0),
+            hm(11, 30),
+            hm(16, 0),
+            hm(19, 0),
+        ]),
+    },
+    Event {
+        name: "Triple Trouble",
+        spawns: Spawns::At(&[
+            hm(1, 0),
+            hm(4, 0),
+            hm(8, 0),
+            hm(12, 30),
+            hm(17, 0),
+            hm(20, 0),
+        ]),
+    },
+    Event {
+        name: "Karka Queen",
+        spawns: Spawns::At(&[
+            hm(2, 0),
+            hm(6, 0),
+            hm(10, 30),
+            hm(15, 0),
+            hm(18, 0),
+            hm(23, 0),
+        ]),
+    },
+    every("Chak Gerent", hm(0, 30), 120),
+    every("Dragon's Stand", hm(1, 30), 120),
+];
+
+impl Event {
+    /// The first spawn after `now`
+    pub fn next_spawn(&self, now: SystemTime) -> SystemTime {
+        let secs = now.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let day = secs / (DAY_MINUTES * 60);
+        let minute = secs / 60 % DAY_MINUTES;
+        let next = match self.spawns {
+            Spawns::Every { first, period } => {
+                let (first, period) = (first as u64, period as u64);
+                // every rotation divides a day, so it lines up the same every day
+                if minute < first {
+                    first
+                } else {
+                    first + ((minute - first) / period + 1) * period
+                }
+            }
+            Spawns::At(times) => times
+                .iter()
+                .map(|&t| t as u64)
+                .find(|&t| t > minute)
+                .unwrap_or(times[0] as u64 + DAY_MINUTES),
+        };
+        UNIX_EPOCH + Duration::from_secs((day * DAY_MINUTES + next) * 60)
+    }
+}
+
+// Subscriptions and time of the last sync
+static SYNCED: Mutex<Option<(Vec<String>, Instant)>> = Mutex::new(None);
+
+/// Keeps a timer to the next spawn of every subscribed event, called on every tick
+pub fn poll(subscribed: &[String], timers: &mut Timers) {
+    let spawned = timers
+        .list
+        .iter()
+        .any(|t| t.system == Some(SOURCE) && matches!(t.state, TimerState::Finished { .. }));
+    let mut synced = SYNCED.locked();
+    let due = match &*synced {
+        Some((names, at)) => names != subscribed || spawned || at.elapsed() >= RESYNC,
+        None => true,
+    };
+    if !due {
+        return;
+    }
+    let now = SystemTime::now();
+    let wanted: Vec<_> = EVENTS
+        .iter()
+        .filter(|e| subscribed.iter().any(|s| s == e.name))
+        .map(|e| {
+            let until = e.next_spawn(now).duration_since(now).unwrap_or_default();
+            (e.name.to_string(), until)
+        })
+        .collect();
+    timers.sync_system(SOURCE, &wanted);
+    *synced = Some((subscribed.to_vec(), Instant::now()));
+}
@@ -0,0 +1,273 @@
+//! Recording of everything that drives the timer engine, and deterministic replay of
+//! such recordings to reproduce timing bugs.
+
+use crate::clock::{Clock, ManualClock};
+use crate::store::Store;
+use crate::timer::{StartSource, Timer, TimerEvent, Timers};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Recordings kept in the sessions directory, older ones are deleted
+const KEEP_SESSIONS: usize = 5;
+/// Stop recording at some point so a session left running for days stays small
+const MAX_ENTRIES: usize = 100_000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Action {
+    Start {
+        name: String,
+        #[serde(default)]
+        source: StartSource,
+    },
+    Stop {
+        name: String,
+    },
+    TogglePause {
+        name: String,
+    },
+    AddTime {
+        name: String,
+        by: Duration,
+    },
+    SubtractTime {
+        name: String,
+        by: Duration,
+    },
+    Lap {
+        name: String,
+    },
+    Suspend {
+        away: Duration,
+        counted: Duration,
+    },
+    /// The game lost or regained focus, or a loading screen started or ended
+    Away {
+        away: bool,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionEntry {
+    /// Time since the recording started
+    pub at: Duration,
+    #[serde(flatten)]
+    pub action: Action,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Session {
+    /// The timers as they were configured when the recording started
+    pub timers: Vec<Timer>,
+    pub entries: Vec<SessionEntry>,
+}
+
+#[derive(Debug)]
+pub struct Recording {
+    started: Instant,
+    session: Session,
+}
+
+impl Recording {
+    pub fn new<'a>(now: Instant, timers: impl IntoIterator<Item = &'a Timer>) -> Self {
+        Self {
+            started: now,
+            session: Session {
+                timers: timers.into_iter().cloned().collect(),
+                entries: Vec::new(),
+            },
+        }
+    }
+
+    pub fn push(&mut self, now: Instant, action: Action) {
+        if self.session.entries.len() >= MAX_ENTRIES {
+            return;
+        }
+        self.session.entries.push(SessionEntry {
+            at: now.saturating_duration_since(self.started),
+            action,
+        });
+    }
+
+    pub fn into_session(self) -> Session {
+        self.session
+    }
+}
+
+/// Runs a session against a fresh engine, ticking every `step` like frames would.
+/// Returns every event with the time it happened at.
+pub fn replay(session: &Session, step: Duration) -> Vec<(Duration, TimerEvent)> {
+    let clock = ManualClock::new();
+    let start = clock.now();
+    let mut timers = Timers::with_clock(Box::new(clock.clone()));
+    timers.list = Store::new(session.timers.clone());
+    for timer in &mut timers.list {
+        timer.stop();
+    }
+    let added: Duration = session
+        .entries
+        .iter()
+        .filter_map(|e| match e.action {
+            Action::AddTime { by, .. } => Some(by),
+            _ => None,
+        })
+        .sum();
+    let end = session.entries.last().map(|e| e.at).unwrap_or_default()
+        + added
+        + timers
+            .list
+            .iter()
+            .map(|t| t.delay + t.duration)
+            .max()
+            .unwrap_or_default();
+    let mut events = Vec::new();
+    let mut entries = session.entries.iter().peekable();
+    let mut elapsed = Duration::ZERO;
+    while elapsed <= end {
+        while let Some(entry) = entries.next_if(|e| e.at <= elapsed) {
+            match &entry.action {
+                Action::Start { name, source } => timers.start(name, *source),
+                Action::Stop { name } => timers.stop(name),
+                Action::TogglePause { name } => timers.toggle_pause(name),
+                Action::AddTime { name, by } => timers.add_time(name, *by),
+                Action::SubtractTime { name, by } => timers.subtract_time(name, *by),
+                Action::Lap { name } => timers.lap(name),
+                Action::Suspend { away, counted } => {
+                    timers.resume_after_suspend(*away, *counted);
+                }
+                Action::Away { away } => timers.set_away(*away),
+            }
+        }
+        events.extend(timers.tick().into_iter().map(|e| (elapsed, e)));
+        clock.advance(step);
+        elapsed = clock.now() - start;
+    }
+    events
+}
+
+fn sessions_dir() -> PathBuf {
+    crate::config::addon_dir().join("sessions")
+}
+
+fn sorted_sessions(dir: &Path) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|e| e == "json"))
+                .collect()
+        })
+        .unwrap_or_default();
+    // file names are timestamps
+    paths.sort();
+    paths
+}
+
+pub fn save(session: &Session) {
+    if session.entries.is_empty() {
+        return;
+    }
+    let dir = sessions_dir();
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let path = dir.join(format!("{}.json", secs));
+    let json = serde_json::to_string(session).expect("Session to be serialized");
+    if let Err(e) = std::fs::create_dir_all(&dir).and_then(|()| std::fs::write(&path, json)) {
+        log::error!("Failed to save session to {}: {}", path.display(), e);
+        return;
+    }
+    log::info!("Saved session to {}", path.display());
+    let sessions = sorted_sessions(&dir);
+    for old in &sessions[..sessions.len().saturating_sub(KEEP_SESSIONS)] {
+        let _ = std::fs::remove_file(old);
+    }
+}
+
+/// The most recent recording and the file it was read from
+pub fn load_latest() -> Result<(PathBuf, Session), String> {
+    let path = sorted_sessions(&sessions_dir())
+        .pop()
+        .ok_or_else(|| "no recorded sessions".to_string())?;
+    let contents = std::fs::read(&path).map_err(|e| e.to_string())?;
+    let session = serde_json::from_slice(&contents).map_err(|e| e.to_string())?;
+    Ok((path, session))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEC: Duration = Duration::from_secs(1);
+
+    fn entry(secs: u64, action: Action) -> SessionEntry {
+        SessionEntry {
+            at: Duration::from_secs(secs),
+            action,
+        }
+    }
+
+    #[test]
+    fn replay_is_deterministic() {
+        let mut timer = Timer::new(1, "a".to_string(), 10 * SEC);
+        timer.marks = vec![5 * SEC];
+        let start = || Action::Start {
+            name: "a".to_string(),
+            source: StartSource::Keybind,
+        };
+        let name = || "a".to_string();
+        let session = Session {
+            timers: vec![timer],
+            entries: vec![
+                entry(1, start()),
+                entry(3, Action::TogglePause { name: name() }),
+                entry(5, Action::TogglePause { name: name() }),
+            ],
+        };
+        let step = Duration::from_millis(100);
+        let events = replay(&session, step);
+        assert_eq!(
+            events,
+            vec![
+                (SEC, TimerEvent::Started(1, StartSource::Keybind)),
+                (8 * SEC, TimerEvent::Mark(1, 5 * SEC)),
+                (13 * SEC, TimerEvent::Finished(1)),
+            ]
+        );
+        assert_eq!(replay(&session, step), events);
+    }
+
+    #[test]
+    fn replay_applies_suspends() {
+        let session = Session {
+            timers: vec![Timer::new(1, "a".to_string(), 60 * SEC)],
+            entries: vec![
+                entry(
+                    0,
+                    Action::Start {
+                        name: "a".to_string(),
+                        source: StartSource::Keybind,
+                    },
+                ),
+                entry(
+                    10,
+                    Action::Suspend {
+                        away: 40 * SEC,
+                        counted: Duration::ZERO,
+                    },
+                ),
+            ],
+        };
+        let events = replay(&session, SEC);
+        assert_eq!(
+            events,
+            vec![
+                (Duration::ZERO, TimerEvent::Started(1, StartSource::Keybind)),
+                (20 * SEC, TimerEvent::Finished(1))
+            ]
+        );
+    }
+}
@@ -0,0 +1,74 @@
+//! Spoken announcements through the Windows speech API (SAPI).
+
+use crate::worker::Worker;
+use std::cell::RefCell;
+use std::time::Duration;
+use windows::core::PCWSTR;
+use windows::Win32::Media::Speech::{ISpVoice, SpVoice, SPF_DEFAULT};
+use windows::Win32::System::Com::{
+    CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED,
+};
+
+struct Utterance {
+    text: String,
+    /// 0 to 100
+    volume: u8,
+}
+
+fn create_voice() -> windows::core::Result<ISpVoice> {
+    unsafe {
+        CoInitializeEx(None, COINIT_MULTITHREADED).ok()?;
+        CoCreateInstance(&SpVoice, None, CLSCTX_ALL)
+    }
+}
+
+fn speak(utterance: Utterance) {
+    thread_local! {
+        // COM objects belong to the thread that created them
+        static VOICE: RefCell<Option<ISpVoice>> = const { RefCell::new(None) };
+    }
+    VOICE.with_borrow_mut(|voice| {
+        if voice.is_none() {
+            match create_voice() {
+                Ok(v) => *voice = Some(v),
+                Err(e) => {
+                    log::error!("Failed to create voice: {}", e);
+                    return;
+                }
+            }
+        }
+        let voice = voice.as_ref().expect("Voice to be created");
+        let text: Vec<u16> = utterance.text.encode_utf16().chain([0]).collect();
+        let result = unsafe {
+            voice
+                .SetVolume(utterance.volume.min(100) as u16)
+                .and_then(|()| voice.Speak(PCWSTR(text.as_ptr()), SPF_DEFAULT.0 as u32, None))
+        };
+        if let Err(e) = result {
+            log::warn!("Failed to speak {:?}: {}", utterance.text, e);
+            let text = format!("failed to speak {:?}: {}", utterance.text, e);
+            crate::eventlog::error("", text);
+        }
+    });
+}
+
+static SPEECH: Worker<Utterance> = Worker::new("speech", Duration::from_secs(60), speak);
+
+/// A duration the way it would be said, e.g. `1 minute 30 seconds`
+pub fn spoken(d: Duration) -> String {
+    let secs = d.as_secs_f32().round() as u64;
+    let plural = |n: u64, unit: &str| format!("{} {}{}", n, unit, if n == 1 { "" } else { "s" });
+    match (secs / 60, secs % 60) {
+        (0, s) => plural(s, "second"),
+        (m, 0) => plural(m, "minute"),
+        (m, s) => format!("{} {}", plural(m, "minute"), plural(s, "second")),
+    }
+}
+
+/// Speaks `text` in the background, after anything that is already being said
+pub fn say(text: String, volume: u8) {
+    if volume == 0 {
+        return;
+    }
+    SPEECH.send(Utterance { text, volume });
+}
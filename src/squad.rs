@@ -0,0 +1,213 @@
+//! Opt-in sharing of timer starts with squad members over a relay server.
+//!
+//! The relay only keeps a short list of events per room:
+//! `POST {relay}/rooms/{room}` with `{"sender": .., "timer": ..}` appends one and
+//! `GET {relay}/rooms/{room}?after={seq}` returns the newer ones as
+//! `[{"seq": .., "sender": .., "timer": ..}]`. A start that comes in starts the shared
+//! timer of the same name here, so everyone has to use the same names.
+
+use crate::sync::LockExt;
+use crate::timer::{StartSource, Timers};
+use crate::worker::Worker;
+use serde::{Deserialize, Serialize};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const POLL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Room {
+    host: String,
+    /// Path of the room on the relay, `/base/rooms/{room}`
+    path: String,
+}
+
+impl Room {
+    /// `None` unless `relay` is an https URL and `code` isn't empty
+    fn new(relay: &str, code: &str) -> Option<Self> {
+        let rest = relay.trim().strip_prefix("https://")?;
+        let (host, base) = rest.split_once('/').unwrap_or((rest, ""));
+        let code: String = code
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+            .collect();
+        if host.is_empty() || code.is_empty() {
+            return None;
+        }
+        let base = base.trim_end_matches('/');
+        let path = if base.is_empty() {
+            format!("/rooms/{}", code)
+        } else {
+            format!("/{}/rooms/{}", base, code)
+        };
+        Some(Self {
+            host: host.to_string(),
+            path,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Event {
+    #[serde(default, skip_serializing)]
+    seq: u64,
+    sender: String,
+    timer: String,
+}
+
+enum Job {
+    Send(Room, String),
+    /// Everything after `after`, `None` only to learn the latest sequence number
+    Fetch(Room, Option<u64>),
+}
+
+struct State {
+    room: Option<Room>,
+    requested: Option<Instant>,
+    in_flight: bool,
+    /// Sequence number of the latest event seen
+    seen: Option<u64>,
+    /// Timers started by others, yet to be started here
+    received: Vec<String>,
+    status: String,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    room: None,
+    requested: None,
+    in_flight: false,
+    seen: None,
+    received: Vec::new(),
+    status: String::new(),
+});
+
+/// Tells our own events apart from those of others in the same room
+fn sender() -> &'static str {
+    static SENDER: OnceLock<String> = OnceLock::new();
+    SENDER.get_or_init(|| {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .subsec_nanos();
+        format!("{:x}{:08x}", std::process::id(), nanos)
+    })
+}
+
+fn run(job: Job) {
+    match job {
+        Job::Send(room, timer) => {
+            let event = Event {
+                seq: 0,
+                sender: sender().to_string(),
+                timer,
+            };
+            let body = serde_json::to_vec(&event).expect("Event to be serialized");
+            let result = crate::http::post(
+                &room.host,
+                &room.path,
+                "Content-Type: application/json",
+                &body,
+            );
+            if let Err(e) = result {
+                log::warn!("Failed to share start of {}: {}", event.timer, e);
+                let text = format!("sharing the start failed: {}", e);
+                crate::eventlog::error(&event.timer, text);
+                STATE.locked().status = format!("Sending failed: {}", e);
+            }
+        }
+        Job::Fetch(room, after) => {
+            let path = format!("{}?after={}", room.path, after.unwrap_or(0));
+            let result = crate::http::get(&room.host, &path, "").and_then(|body| {
+                serde_json::from_slice::<Vec<Event>>(&body).map_err(|e| e.to_string())
+            });
+            let mut state = STATE.locked();
+            state.in_flight = false;
+            // the room was changed in the meantime
+            if state.room.as_ref() != Some(&room) {
+                return;
+            }
+            match result {
+                Ok(events) => {
+                    let latest = events.iter().map(|e| e.seq).max();
+                    state.seen = state.seen.max(latest).or(Some(0));
+                    if after.is_some() {
+                        let received = events
+                            .into_iter()
+                            .filter(|e| e.sender != sender())
+                            .map(|e| e.timer);
+                        state.received.extend(received);
+                    }
+                    state.status = "Connected".to_string();
+                }
+                Err(e) => {
+                    log::warn!("Failed to fetch squad events: {}", e);
+                    state.status = format!("Update failed: {}", e);
+                }
+            }
+        }
+    }
+}
+
+static RELAY: Worker<Job> = Worker::new("squad", Duration::from_secs(30), run);
+
+/// Starts the timers others started and asks for new starts when due, called every tick
+pub fn poll(relay: &str, code: &str, timers: &mut Timers) {
+    let room = Room::new(relay, code);
+    let mut state = STATE.locked();
+    if state.room != room {
+        state.room = room.clone();
+        state.requested = None;
+        state.seen = None;
+        state.received.clear();
+        state.status.clear();
+    }
+    let Some(room) = room else {
+        return;
+    };
+    for name in std::mem::take(&mut state.received) {
+        let shared = timers
+            .list
+            .iter()
+            .any(|t| t.name == name && t.share && t.enabled);
+        if shared {
+            log::debug!("Timer {} started by the squad", name);
+            timers.start(&name, StartSource::Squad);
+        }
+    }
+    let due = state.requested.is_none_or(|at| at.elapsed() >= POLL);
+    if due && !state.in_flight {
+        state.requested = Some(Instant::now());
+        state.in_flight = true;
+        let after = state.seen;
+        drop(state);
+        RELAY.send(Job::Fetch(room, after));
+    }
+}
+
+/// Lets the room know a shared timer was started here
+pub fn broadcast(relay: &str, code: &str, timer: &str) {
+    if let Some(room) = Room::new(relay, code) {
+        RELAY.send(Job::Send(room, timer.to_string()));
+    }
+}
+
+/// Outcome of the last exchange with the relay, empty if there was none
+pub fn status() -> String {
+    STATE.locked().status.clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_room_paths() {
+        let room = Room::new("https://relay.example.com/timers/", "Raid night!").unwrap();
+        assert_eq!(room.host, "relay.example.com");
+        assert_eq!(room.path, "/timers/rooms/Raidnight");
+        let room = Room::new("https://relay.example.com", "abc").unwrap();
+        assert_eq!(room.path, "/rooms/abc");
+        assert_eq!(Room::new("http://relay.example.com", "abc"), None);
+        assert_eq!(Room::new("https://relay.example.com", "  "), None);
+    }
+}
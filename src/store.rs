@@ -0,0 +1,237 @@
+//! The timers keyed by id, in the order they are listed, with their names indexed.
+
+use crate::timer::Timer;
+use std::collections::{HashMap, HashSet};
+
+/// Timers by id, `order` listing every id once. A timer's name is fixed once it is in
+/// here, so the name index stays in step through mutable access.
+#[derive(Debug, Default)]
+pub struct Store {
+    timers: HashMap<u64, Timer>,
+    order: Vec<u64>,
+    names: HashMap<String, u64>,
+}
+
+impl Store {
+    /// Timers without an id or with a taken one get a new one, configs from before ids
+    /// existed (or edited by hand) may have those
+    pub fn new(list: Vec<Timer>) -> Self {
+        let mut seen = HashSet::new();
+        let mut next = list.iter().map(|t| t.id).max().unwrap_or(0) + 1;
+        let mut store = Self::default();
+        for mut timer in list {
+            if timer.id == 0 || !seen.insert(timer.id) {
+                timer.id = next;
+                next += 1;
+            }
+            store.push(timer);
+        }
+        store
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    /// The timers in list order
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Timer> + Clone + '_ {
+        self.order.iter().map(|id| &self.timers[id])
+    }
+
+    /// The timers in list order, not to be renamed
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Timer> + '_ {
+        let mut timers: HashMap<u64, &mut Timer> =
+            self.timers.iter_mut().map(|(&id, t)| (id, t)).collect();
+        let ordered: Vec<&mut Timer> = self
+            .order
+            .iter()
+            .filter_map(|id| timers.remove(id))
+            .collect();
+        ordered.into_iter()
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Timer> {
+        self.timers.get(&id)
+    }
+
+    /// Timer `id`, not to be renamed
+    pub fn get_mut(&mut self, id: u64) -> Option<&mut Timer> {
+        self.timers.get_mut(&id)
+    }
+
+    /// The id of timer `name`, the first listed if several share it
+    pub fn id_of(&self, name: &str) -> Option<u64> {
+        self.names.get(name).copied()
+    }
+
+    pub fn by_name(&self, name: &str) -> Option<&Timer> {
+        self.get(self.id_of(name)?)
+    }
+
+    pub fn by_name_mut(&mut self, name: &str) -> Option<&mut Timer> {
+        self.get_mut(self.id_of(name)?)
+    }
+
+    pub fn contains_name(&self, name: &str) -> bool {
+        self.names.contains_key(name)
+    }
+
+    /// One more than the highest id in use
+    pub fn next_id(&self) -> u64 {
+        self.timers.keys().max().copied().unwrap_or(0) + 1
+    }
+
+    /// Adds `timer` at the end, its id must not be taken
+    pub fn push(&mut self, timer: Timer) -> &mut Timer {
+        let id = timer.id;
+        debug_assert!(!self.timers.contains_key(&id), "Timer id {} is taken", id);
+        self.names.entry(timer.name.clone()).or_insert(id);
+        self.order.push(id);
+        self.timers.entry(id).insert_entry(timer).into_mut()
+    }
+
+    /// Puts `timer` in the place of the one with its id, returning that one
+    pub fn replace(&mut self, timer: Timer) -> Option<Timer> {
+        let old = self.timers.get_mut(&timer.id)?;
+        let old = std::mem::replace(old, timer);
+        self.renamed(&old.name);
+        let new = &self.timers[&old.id];
+        self.names.entry(new.name.clone()).or_insert(new.id);
+        Some(old)
+    }
+
+    pub fn remove(&mut self, id: u64) -> Option<Timer> {
+        let timer = self.timers.remove(&id)?;
+        self.order.retain(|&other| other != id);
+        self.renamed(&timer.name);
+        Some(timer)
+    }
+
+    pub fn retain(&mut self, mut keep: impl FnMut(&Timer) -> bool) {
+        let removed: Vec<u64> = self.iter().filter(|t| !keep(t)).map(|t| t.id).collect();
+        for id in removed {
+            self.remove(id);
+        }
+    }
+
+    /// Where timer `id` is listed
+    pub fn position(&self, id: u64) -> Option<usize> {
+        self.order.iter().position(|&other| other == id)
+    }
+
+    /// Swaps the places of the timers at positions `a` and `b`
+    pub fn swap(&mut self, a: usize, b: usize) {
+        self.order.swap(a, b);
+        // the first listed of timers sharing a name is the one it finds
+        for at in [a, b] {
+            let name = self.timers[&self.order[at]].name.clone();
+            self.renamed(&name);
+        }
+    }
+
+    /// Lists the timers in the order of `ids`, those left out after them as they were
+    pub fn reorder(&mut self, ids: &[u64]) {
+        let mut order: Vec<u64> = ids
+            .iter()
+            .copied()
+            .filter(|id| self.timers.contains_key(id))
+            .collect();
+        let listed: HashSet<u64> = order.iter().copied().collect();
+        order.extend(self.order.iter().filter(|id| !listed.contains(id)));
+        self.order = order;
+        self.reindex();
+    }
+
+    /// Takes out every timer, in list order
+    pub fn take(&mut self) -> Vec<Timer> {
+        let mut timers = std::mem::take(&mut self.timers);
+        self.names.clear();
+        std::mem::take(&mut self.order)
+            .into_iter()
+            .filter_map(|id| timers.remove(&id))
+            .collect()
+    }
+
+    /// Points the name `name` at the first listed timer that has it, after one with it
+    /// went away or moved
+    fn renamed(&mut self, name: &str) {
+        let first = self
+            .order
+            .iter()
+            .find(|id| self.timers[id].name == name)
+            .copied();
+        match first {
+            Some(id) => self.names.insert(name.to_string(), id),
+            None => self.names.remove(name),
+        };
+    }
+
+    fn reindex(&mut self) {
+        self.names.clear();
+        for id in &self.order {
+            let name = &self.timers[id].name;
+            self.names.entry(name.clone()).or_insert(*id);
+        }
+    }
+}
+
+impl From<Vec<Timer>> for Store {
+    fn from(list: Vec<Timer>) -> Self {
+        Self::new(list)
+    }
+}
+
+impl<'a> IntoIterator for &'a Store {
+    type Item = &'a Timer;
+    type IntoIter = Box<dyn Iterator<Item = &'a Timer> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.iter())
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Store {
+    type Item = &'a mut Timer;
+    type IntoIter = Box<dyn Iterator<Item = &'a mut Timer> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.iter_mut())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn timer(id: u64, name: &str) -> Timer {
+        Timer::new(id, name.to_string(), Duration::from_secs(10))
+    }
+
+    #[test]
+    fn keeps_order_and_names_in_step() {
+        let mut store = Store::new(vec![timer(1, "a"), timer(1, "b"), timer(0, "c")]);
+        let ids: Vec<_> = store.iter().map(|t| t.id).collect();
+        assert_eq!(ids, [1, 2, 3]);
+        assert_eq!(store.id_of("c"), Some(3));
+        store.swap(0, 2);
+        store.push(timer(7, "a"));
+        assert_eq!(store.id_of("a"), Some(1));
+        store.remove(1);
+        assert_eq!(store.id_of("a"), Some(7));
+        store.replace(timer(7, "d"));
+        assert!(!store.contains_name("a"));
+        assert_eq!(store.by_name("d").map(|t| t.id), Some(7));
+        store.retain(|t| t.name != "b");
+        let names: Vec<_> = store.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["c", "d"]);
+        store.reorder(&[7]);
+        let names: Vec<_> = store.take().into_iter().map(|t| t.name).collect();
+        assert_eq!(names, ["d", "c"]);
+        assert!(store.is_empty());
+    }
+}
@@ -0,0 +1,187 @@
+//! Running timers written to a file in the addon dir, for OBS text sources and other
+//! stream overlays that can read a file but not the game overlay.
+//!
+//! The file is rewritten at the configured interval from a background thread, only when
+//! its text changed, and swapped in by a rename so a reader never sees half of it.
+
+use crate::format::TimeStyle;
+use crate::sync::LockExt;
+use crate::timer::Timers;
+use crate::worker::Worker;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_INTERVAL_MS: u32 = 1000;
+pub const MIN_INTERVAL_MS: u32 = 100;
+pub const MAX_INTERVAL_MS: u32 = 60_000;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamFormat {
+    #[default]
+    Off,
+    /// `stream.txt`, one `Name  1:32` line per timer
+    Text,
+    /// `stream.json`, `[{"name": .., "remaining_secs": .., "remaining": ..}]`
+    Json,
+}
+
+impl StreamFormat {
+    pub const ALL: [StreamFormat; 3] = [Self::Off, Self::Text, Self::Json];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Off => "Off",
+            Self::Text => "Text (stream.txt)",
+            Self::Json => "JSON (stream.json)",
+        }
+    }
+
+    fn file(self) -> Option<&'static str> {
+        match self {
+            Self::Off => None,
+            Self::Text => Some("stream.txt"),
+            Self::Json => Some("stream.json"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Entry {
+    name: String,
+    remaining_secs: f64,
+    remaining: String,
+}
+
+fn text(entries: &[Entry]) -> String {
+    entries
+        .iter()
+        .map(|e| format!("{}  {}\n", e.name, e.remaining))
+        .collect()
+}
+
+fn render(format: StreamFormat, entries: &[Entry]) -> String {
+    match format {
+        StreamFormat::Json => {
+            serde_json::to_string_pretty(entries).expect("Stream entries to be serialized")
+        }
+        _ => text(entries),
+    }
+}
+
+struct Snapshot {
+    path: PathBuf,
+    contents: String,
+}
+
+fn write(snapshot: Snapshot) {
+    let tmp = snapshot.path.with_extension("tmp");
+    let result = std::fs::write(&tmp, &snapshot.contents)
+        .and_then(|()| std::fs::rename(&tmp, &snapshot.path));
+    if let Err(e) = result {
+        log::warn!("Failed to write {}: {}", snapshot.path.display(), e);
+    }
+}
+
+// a slow disk or a virus scanner holding the file would otherwise hold up the tick
+static WRITER: Worker<Snapshot> = Worker::new("stream", Duration::from_secs(10), write);
+
+struct Written {
+    at: Instant,
+    format: StreamFormat,
+    contents: String,
+}
+
+static WRITTEN: Mutex<Option<Written>> = Mutex::new(None);
+
+/// The running timers as the file shows them, taken holding the timers and written
+/// after by [`publish`]
+pub struct Update {
+    format: StreamFormat,
+    entries: Vec<Entry>,
+}
+
+/// The running timers once `interval_ms` passed since the last time or the format
+/// changed, `style` for the timers without their own. Called every tick.
+pub fn collect(
+    format: StreamFormat,
+    interval_ms: u32,
+    timers: &Timers,
+    style: TimeStyle,
+) -> Option<Update> {
+    let interval = Duration::from_millis(interval_ms.into());
+    let due = WRITTEN
+        .locked()
+        .as_ref()
+        .is_none_or(|w| w.format != format || Instant::now() >= w.at + interval);
+    if !due {
+        return None;
+    }
+    let entries = if format.file().is_some() {
+        timers
+            .next_finishes()
+            .into_iter()
+            .map(|(timer, rest)| Entry {
+                name: timer.name.clone(),
+                remaining_secs: rest.as_secs_f64(),
+                remaining: timer.style(style).format(rest),
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    Some(Update { format, entries })
+}
+
+/// Writes the file of `update` in the background if its text changed
+pub fn publish(update: Update) {
+    let Update { format, entries } = update;
+    let mut written = WRITTEN.locked();
+    let previous = written.take();
+    // don't leave the last timers up on stream once turned off or switched over
+    if let Some(old) = previous.as_ref().filter(|w| w.format != format) {
+        if let Some(file) = old.format.file() {
+            WRITER.send(Snapshot {
+                path: crate::config::addon_dir().join(file),
+                contents: String::new(),
+            });
+        }
+    }
+    let Some(file) = format.file() else {
+        return;
+    };
+    let contents = render(format, &entries);
+    let changed = previous.is_none_or(|w| w.format != format || w.contents != contents);
+    if changed {
+        WRITER.send(Snapshot {
+            path: crate::config::addon_dir().join(file),
+            contents: contents.clone(),
+        });
+    }
+    *written = Some(Written {
+        at: Instant::now(),
+        format,
+        contents,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_lines() {
+        let entry = |name: &str, remaining: &str| Entry {
+            name: name.to_string(),
+            remaining_secs: 0.0,
+            remaining: remaining.to_string(),
+        };
+        assert_eq!(text(&[]), "");
+        assert_eq!(
+            text(&[entry("Burn", "9.41"), entry("Portal", "1:32")]),
+            "Burn  9.41\nPortal  1:32\n"
+        );
+    }
+}
@@ -0,0 +1,53 @@
+//! Locks that survive a panic while they were held.
+//!
+//! A panic in a keybind handler or a render callback poisons the mutexes it held, after
+//! which every `lock().unwrap()` would panic as well and take the whole UI with it.
+//! The data is used as the panic left it instead, which at worst is a half-done change.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+static RECOVERED: AtomicBool = AtomicBool::new(false);
+
+pub trait LockExt<T> {
+    /// Locks the mutex, recovering it if a panic poisoned it
+    fn locked(&self) -> MutexGuard<'_, T>;
+}
+
+impl<T> LockExt<T> for Mutex<T> {
+    fn locked(&self) -> MutexGuard<'_, T> {
+        self.lock().unwrap_or_else(|poisoned| {
+            log::error!(
+                "Recovered the lock of {} after a panic while it was held",
+                std::any::type_name::<T>()
+            );
+            RECOVERED.store(true, Ordering::Relaxed);
+            self.clear_poison();
+            poisoned.into_inner()
+        })
+    }
+}
+
+/// Whether a lock had to be recovered since the last call
+pub fn take_recovered() -> bool {
+    RECOVERED.swap(false, Ordering::Relaxed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recovers_poisoned_lock() {
+        let lock = Mutex::new(1);
+        let _ = std::panic::catch_unwind(|| {
+            let mut value = lock.lock().unwrap();
+            *value = 2;
+            panic!("while holding the lock");
+        });
+        assert!(lock.is_poisoned());
+        assert_eq!(*lock.locked(), 2);
+        assert!(!lock.is_poisoned());
+        assert!(take_recovered());
+    }
+}
@@ -0,0 +1,131 @@
+//! Display templates of timers, like `{name}: {remaining} ({elapsed} elapsed)`, for
+//! overlay text that reads differently from the plain remaining time.
+//!
+//! `{{` and `}}` are a literal brace, anything else in braces has to be a placeholder.
+
+pub const PLACEHOLDERS: [&str; 5] = ["name", "remaining", "elapsed", "percent", "cycle"];
+
+/// What the placeholders of a template read
+pub struct Values<'a> {
+    pub name: &'a str,
+    /// The text shown without a template
+    pub remaining: &'a str,
+    pub elapsed: &'a str,
+    /// How much of the run is used up
+    pub percent: u32,
+    /// Which run since the addon was loaded, 1 for the first
+    pub cycle: u32,
+}
+
+enum Part<'a> {
+    Text(&'a str),
+    Name,
+    Remaining,
+    Elapsed,
+    Percent,
+    Cycle,
+}
+
+fn placeholder(name: &str) -> Option<Part<'static>> {
+    match name {
+        "name" => Some(Part::Name),
+        "remaining" => Some(Part::Remaining),
+        "elapsed" => Some(Part::Elapsed),
+        "percent" => Some(Part::Percent),
+        "cycle" => Some(Part::Cycle),
+        _ => None,
+    }
+}
+
+fn parse(template: &str) -> Result<Vec<Part<'_>>, String> {
+    let mut parts = Vec::new();
+    let mut rest = template;
+    while let Some(i) = rest.find(['{', '}']) {
+        if i > 0 {
+            parts.push(Part::Text(&rest[..i]));
+        }
+        let brace = &rest[i..i + 1];
+        rest = &rest[i + 1..];
+        if let Some(after) = rest.strip_prefix(brace) {
+            parts.push(Part::Text(brace));
+            rest = after;
+            continue;
+        }
+        if brace == "}" {
+            return Err("A lone } has to be written as }}".to_string());
+        }
+        let Some(end) = rest.find('}') else {
+            return Err("A { is never closed, a lone { has to be written as {{".to_string());
+        };
+        let name = rest[..end].trim();
+        let Some(part) = placeholder(name) else {
+            return Err(format!(
+                "Unknown placeholder {{{}}}, there are {{{}}}",
+                name,
+                PLACEHOLDERS.join("}, {")
+            ));
+        };
+        parts.push(part);
+        rest = &rest[end + 1..];
+    }
+    if !rest.is_empty() {
+        parts.push(Part::Text(rest));
+    }
+    Ok(parts)
+}
+
+/// Why `template` can't be used, if it can't
+pub fn validate(template: &str) -> Result<(), String> {
+    parse(template).map(|_| ())
+}
+
+/// Writes `template` filled in with `values` to `out`, which is left alone if it isn't
+/// valid
+pub fn render_into(template: &str, values: &Values, out: &mut String) -> Result<(), String> {
+    let parts = parse(template)?;
+    out.clear();
+    for part in parts {
+        match part {
+            Part::Text(text) => out.push_str(text),
+            Part::Name => out.push_str(values.name),
+            Part::Remaining => out.push_str(values.remaining),
+            Part::Elapsed => out.push_str(values.elapsed),
+            Part::Percent => out.push_str(&values.percent.to_string()),
+            Part::Cycle => out.push_str(&values.cycle.to_string()),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates_fill_in() {
+        let values = Values {
+            name: "Greens",
+            remaining: "1:32",
+            elapsed: "0:28",
+            percent: 23,
+            cycle: 2,
+        };
+        let render = |template| {
+            let mut out = "old".to_string();
+            render_into(template, &values, &mut out).map(|()| out)
+        };
+        assert_eq!(
+            render("{name}: {remaining} ({elapsed} elapsed)").as_deref(),
+            Ok("Greens: 1:32 (0:28 elapsed)")
+        );
+        assert_eq!(
+            render("#{ cycle } {percent}% {{done}}").as_deref(),
+            Ok("#2 23% {done}")
+        );
+        assert_eq!(render("").as_deref(), Ok(""));
+        assert!(render("{remains}").is_err());
+        assert!(render("{name").is_err());
+        assert!(render("name}").is_err());
+        assert!(validate("{name} {remaining}").is_ok());
+    }
+}
@@ -0,0 +1,168 @@
+//! Colors of the timer states, from a preset or the user's own theme in the settings.
+//!
+//! The color-blind presets take their hues from the Okabe-Ito palette, which stays apart
+//! for red-green color blindness, and tell states apart by brightness as well.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Palette {
+    pub running: [f32; 4],
+    pub paused: [f32; 4],
+    pub count_in: [f32; 4],
+    pub finished: [f32; 4],
+    pub overtime: [f32; 4],
+    /// Flash of a timer at an announcement mark or a step
+    pub ping: [f32; 4],
+    /// Final seconds of critical timers
+    pub warning: [f32; 4],
+    /// Progress behind the hotbar buttons, see-through
+    pub fill: [f32; 4],
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Preset::Default.palette()
+    }
+}
+
+impl Palette {
+    /// Every color with its name, for editing them one by one
+    pub fn colors_mut(&mut self) -> [(&'static str, &mut [f32; 4]); 8] {
+        [
+            ("Running", &mut self.running),
+            ("Paused", &mut self.paused),
+            ("Count-in", &mut self.count_in),
+            ("Finished", &mut self.finished),
+            ("Overtime", &mut self.overtime),
+            ("Flash", &mut self.ping),
+            ("Critical warning", &mut self.warning),
+            ("Hotbar progress", &mut self.fill),
+        ]
+    }
+
+    /// Replaces colors that aren't numbers and keeps the others in range
+    pub fn sanitize(&mut self) {
+        let mut fallback = Palette::default();
+        for ((_, color), (_, default)) in self.colors_mut().into_iter().zip(fallback.colors_mut()) {
+            if color.iter().all(|c| c.is_finite()) {
+                color.iter_mut().for_each(|c| *c = c.clamp(0.0, 1.0));
+            } else {
+                *color = *default;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Preset {
+    #[default]
+    Default,
+    HighContrast,
+    Deuteranopia,
+    Protanopia,
+    /// The theme saved in the settings
+    Custom,
+}
+
+const fn rgb(r: u8, g: u8, b: u8) -> [f32; 4] {
+    [r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, 1.0]
+}
+
+const FILL_ALPHA: f32 = 0.35;
+
+const fn fill([r, g, b, _]: [f32; 4]) -> [f32; 4] {
+    [r, g, b, FILL_ALPHA]
+}
+
+// Okabe-Ito
+const ORANGE: [f32; 4] = rgb(230, 159, 0);
+const SKY_BLUE: [f32; 4] = rgb(86, 180, 233);
+const BLUISH_GREEN: [f32; 4] = rgb(0, 158, 115);
+const YELLOW: [f32; 4] = rgb(240, 228, 66);
+const VERMILLION: [f32; 4] = rgb(213, 94, 0);
+const REDDISH_PURPLE: [f32; 4] = rgb(204, 121, 167);
+
+impl Preset {
+    pub const ALL: [Preset; 5] = [
+        Self::Default,
+        Self::HighContrast,
+        Self::Deuteranopia,
+        Self::Protanopia,
+        Self::Custom,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Default => "Default",
+            Self::HighContrast => "High contrast",
+            Self::Deuteranopia => "Deuteranopia safe",
+            Self::Protanopia => "Protanopia safe",
+            Self::Custom => "Custom",
+        }
+    }
+
+    /// The colors of the preset, those of the default for `Custom`
+    pub fn palette(self) -> Palette {
+        match self {
+            Self::Default | Self::Custom => Palette {
+                running: [0.3, 0.9, 0.4, 1.0],
+                paused: [1.0, 0.8, 0.2, 1.0],
+                count_in: [0.4, 0.7, 1.0, 1.0],
+                finished: [1.0, 0.3, 0.3, 1.0],
+                overtime: [1.0, 0.2, 0.2, 1.0],
+                ping: [1.0, 0.9, 0.2, 1.0],
+                warning: [1.0, 0.15, 0.15, 1.0],
+                fill: [0.2, 0.8, 0.3, FILL_ALPHA],
+            },
+            Self::HighContrast => Palette {
+                running: [0.0, 1.0, 0.0, 1.0],
+                paused: [1.0, 1.0, 0.0, 1.0],
+                count_in: [0.0, 1.0, 1.0, 1.0],
+                finished: [1.0, 0.0, 0.0, 1.0],
+                overtime: [1.0, 0.0, 1.0, 1.0],
+                ping: [1.0, 1.0, 1.0, 1.0],
+                warning: [1.0, 0.0, 0.0, 1.0],
+                fill: [0.0, 1.0, 0.0, 0.5],
+            },
+            Self::Deuteranopia => Palette {
+                running: SKY_BLUE,
+                paused: YELLOW,
+                count_in: REDDISH_PURPLE,
+                finished: ORANGE,
+                overtime: VERMILLION,
+                ping: YELLOW,
+                warning: VERMILLION,
+                fill: fill(SKY_BLUE),
+            },
+            Self::Protanopia => Palette {
+                running: SKY_BLUE,
+                paused: REDDISH_PURPLE,
+                count_in: BLUISH_GREEN,
+                finished: ORANGE,
+                overtime: YELLOW,
+                ping: YELLOW,
+                warning: YELLOW,
+                fill: fill(SKY_BLUE),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn broken_colors_fall_back() {
+        let mut palette = Preset::Protanopia.palette();
+        palette.running = [f32::NAN, 0.0, 0.0, 1.0];
+        palette.paused = [2.0, -1.0, 0.5, 1.0];
+        palette.sanitize();
+        assert_eq!(palette.running, Palette::default().running);
+        assert_eq!(palette.paused, [1.0, 0.0, 0.5, 1.0]);
+        assert_eq!(palette.finished, ORANGE);
+    }
+}
@@ -0,0 +1,36 @@
+//! Background thread advancing the timers between frames, so finish alerts still go
+//! off on time while the game renders slowly or not at all, e.g. when minimized.
+
+use crate::sync::LockExt;
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+const INTERVAL: Duration = Duration::from_millis(100);
+
+static THREAD: Mutex<Option<(Sender<()>, JoinHandle<()>)>> = Mutex::new(None);
+
+/// Calls `advance` every [`INTERVAL`] until [`stop`]ped
+pub fn start(advance: fn()) {
+    let (tx, rx) = channel::<()>();
+    let thread = std::thread::Builder::new()
+        .name("timers-ticker".to_string())
+        .spawn(move || loop {
+            match rx.recv_timeout(INTERVAL) {
+                Err(RecvTimeoutError::Timeout) => advance(),
+                Ok(()) | Err(RecvTimeoutError::Disconnected) => return,
+            }
+        })
+        .expect("Ticker thread to spawn");
+    *THREAD.locked() = Some((tx, thread));
+}
+
+pub fn stop() {
+    if let Some((tx, thread)) = THREAD.locked().take() {
+        drop(tx);
+        if thread.join().is_err() {
+            log::warn!("Ticker thread panicked");
+        }
+    }
+}
@@ -0,0 +1,79 @@
+//! Encounter timelines, one run that goes through a scripted sequence of labeled
+//! phases, e.g. the phases of a boss fight started by one keybind on pull.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Step {
+    pub label: String,
+    pub duration: Duration,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Timeline {
+    pub steps: Vec<Step>,
+}
+
+impl Timeline {
+    /// How long the whole timeline runs
+    pub fn total(&self) -> Duration {
+        self.steps.iter().map(|s| s.duration).sum()
+    }
+
+    /// When each step begins, from the start of the run
+    pub fn starts(&self) -> impl Iterator<Item = Duration> + '_ {
+        self.steps.iter().scan(Duration::ZERO, |at, step| {
+            let start = *at;
+            *at += step.duration;
+            Some(start)
+        })
+    }
+
+    /// The step `elapsed` is in and what is left of it, `None` once past the last one
+    pub fn current(&self, elapsed: Duration) -> Option<(usize, Duration)> {
+        self.starts()
+            .zip(&self.steps)
+            .enumerate()
+            .find(|(_, (start, step))| elapsed < *start + step.duration)
+            .map(|(i, (start, step))| (i, start + step.duration - elapsed))
+    }
+
+    /// The step that was entered between `prev` and `elapsed`, the first one is entered
+    /// by the start itself
+    pub fn entered(&self, prev: Duration, elapsed: Duration) -> Option<usize> {
+        let now = self.current(elapsed)?.0;
+        (now > 0 && self.current(prev).is_none_or(|(before, _)| before < now)).then_some(now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEC: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn steps_follow_each_other() {
+        let step = |label: &str, secs| Step {
+            label: label.to_string(),
+            duration: secs * SEC,
+        };
+        let timeline = Timeline {
+            steps: vec![step("Pull", 10), step("Split", 5), step("Burn", 20)],
+        };
+        assert_eq!(timeline.total(), 35 * SEC);
+        assert_eq!(
+            timeline.starts().collect::<Vec<_>>(),
+            [Duration::ZERO, 10 * SEC, 15 * SEC]
+        );
+        assert_eq!(timeline.current(Duration::ZERO), Some((0, 10 * SEC)));
+        assert_eq!(timeline.current(12 * SEC), Some((1, 3 * SEC)));
+        assert_eq!(timeline.current(35 * SEC), None);
+        assert_eq!(timeline.entered(9 * SEC, 11 * SEC), Some(1));
+        assert_eq!(timeline.entered(11 * SEC, 12 * SEC), None);
+        // a hitch can jump a whole step, the one ended up in is announced
+        assert_eq!(timeline.entered(9 * SEC, 16 * SEC), Some(2));
+    }
+}
@@ -0,0 +1,3188 @@
+use crate::alarm::Alarm;
+use crate::anchor::Anchor;
+use crate::audio::Priority;
+use crate::chat::ChatTrigger;
+use crate::clock::{Clock, Scaled, SystemClock};
+use crate::combat::CombatTrigger;
+use crate::format::{Rounding, TimeFormat, TimeStyle};
+use crate::marker::Marker;
+use crate::session::{Action, Recording};
+use crate::store::Store;
+use crate::timeline::Timeline;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// What happens to a running timer while the machine is in standby
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StandbyMode {
+    /// Time spent in standby counts towards the timer
+    #[default]
+    Elapse,
+    /// The timer is frozen while in standby
+    Pause,
+}
+
+/// What started a timer's current run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartSource {
+    Keybind,
+    RestartLast,
+    /// An event from another addon
+    Api,
+    #[default]
+    Ui,
+    Hotbar,
+    /// The menu of a timer window
+    Overlay,
+    /// Someone else in the squad room
+    Squad,
+    /// A keyword in squad or party chat
+    Chat,
+    /// A skill cast or buff seen by arcdps
+    Combat,
+    /// Everyone in the squad readied up
+    ReadyCheck,
+    /// The pull timer ran out
+    Pull,
+    /// It starts once the addon loads
+    Autostart,
+    /// An external tool through the control endpoint
+    Control,
+}
+
+impl StartSource {
+    pub fn icon(self) -> &'static str {
+        match self {
+            Self::Keybind => "[K]",
+            Self::RestartLast => "[R]",
+            Self::Api => "[E]",
+            Self::Ui => "[U]",
+            Self::Hotbar => "[H]",
+            Self::Overlay => "[O]",
+            Self::Squad => "[S]",
+            Self::Chat => "[C]",
+            Self::Combat => "[A]",
+            Self::ReadyCheck => "[Y]",
+            Self::Pull => "[P]",
+            Self::Autostart => "[L]",
+            Self::Control => "[T]",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Keybind => "keybind",
+            Self::RestartLast => "restart last keybind",
+            Self::Api => "addon event",
+            Self::Ui => "options",
+            Self::Hotbar => "hotbar",
+            Self::Overlay => "overlay menu",
+            Self::Squad => "squad",
+            Self::Chat => "chat",
+            Self::Combat => "combat",
+            Self::ReadyCheck => "ready check",
+            Self::Pull => "pull timer",
+            Self::Autostart => "addon load",
+            Self::Control => "control endpoint",
+        }
+    }
+}
+
+/// What an import does with a timer, see `Timers::merge_imported`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Merge {
+    Skip,
+    /// Replace the timer of the same name, keeping its usage
+    Overwrite,
+    /// Add it, under a copy's name if the name is taken
+    Add,
+}
+
+impl Merge {
+    pub const ALL: [Merge; 3] = [Self::Skip, Self::Overwrite, Self::Add];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Skip => "Skip",
+            Self::Overwrite => "Overwrite",
+            Self::Add => "Add as copy",
+        }
+    }
+}
+
+/// What pressing a timer's keybind does
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyAction {
+    /// Start it, from the beginning if it is already running
+    #[default]
+    Restart,
+    /// Start it, or stop it if it is running or finished
+    Toggle,
+    /// Start it unless it is already running
+    StartIfIdle,
+    /// Start it, or add `add_step` if it is running or finished
+    AddTime,
+}
+
+impl KeyAction {
+    pub const ALL: [KeyAction; 4] = [
+        Self::Restart,
+        Self::Toggle,
+        Self::StartIfIdle,
+        Self::AddTime,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Restart => "Restart",
+            Self::Toggle => "Start/stop",
+            Self::StartIfIdle => "Start if not running",
+            Self::AddTime => "Add time",
+        }
+    }
+}
+
+/// What a keybind press does to a timer, depending on its key action and state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Press {
+    Start,
+    /// Start it again from the beginning while it is running or paused
+    Restart,
+    Stop,
+    AddTime(Duration),
+    /// Not started again during its lockout
+    Locked,
+    Nothing,
+}
+
+impl Press {
+    /// What the press does, for the event log
+    pub fn describe(self) -> &'static str {
+        match self {
+            Self::Start => "starts it",
+            Self::Restart => "restarts it",
+            Self::Stop => "stops it",
+            Self::AddTime(_) => "adds time",
+            Self::Locked => "ignored during its lockout",
+            Self::Nothing => "does nothing, the timer is missing or already running",
+        }
+    }
+
+    /// Whether the press starts a run or ends the current one
+    pub fn starts_or_ends(self) -> bool {
+        matches!(self, Self::Start | Self::Restart | Self::Stop)
+    }
+}
+
+/// How crossing an announcement mark is signaled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarkCue {
+    /// Flash the timer's window
+    #[default]
+    Ping,
+    Sound,
+    Both,
+}
+
+impl MarkCue {
+    pub fn ping(self) -> bool {
+        self != Self::Sound
+    }
+
+    pub fn sound(self) -> bool {
+        self != Self::Ping
+    }
+}
+
+/// Ways a timer alerts that it is running out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    Sound,
+    Voice,
+    /// Flash the timer's window
+    Flash,
+}
+
+impl Channel {
+    pub const ALL: [Channel; 3] = [Self::Sound, Self::Voice, Self::Flash];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Sound => "Sound",
+            Self::Voice => "Voice",
+            Self::Flash => "Flash",
+        }
+    }
+}
+
+/// How long before the end each channel alerts, zero to alert on finish.
+/// Speech takes a while, so it usually wants more lead time than the rest.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertLeads {
+    pub sound: Duration,
+    pub voice: Duration,
+    pub flash: Duration,
+}
+
+impl AlertLeads {
+    pub fn get(&self, channel: Channel) -> Duration {
+        match channel {
+            Channel::Sound => self.sound,
+            Channel::Voice => self.voice,
+            Channel::Flash => self.flash,
+        }
+    }
+
+    pub fn get_mut(&mut self, channel: Channel) -> &mut Duration {
+        match channel {
+            Channel::Sound => &mut self.sound,
+            Channel::Voice => &mut self.voice,
+            Channel::Flash => &mut self.flash,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Color of the remaining time from `from` down to the next lower band
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ColorBand {
+    pub from: Duration,
+    pub color: [f32; 4],
+}
+
+impl ColorBand {
+    /// Green above 30s, yellow above 10s, red below
+    pub fn traffic_light() -> Vec<ColorBand> {
+        vec![
+            ColorBand {
+                from: Duration::from_secs(30),
+                color: [0.3, 0.9, 0.4, 1.0],
+            },
+            ColorBand {
+                from: Duration::from_secs(10),
+                color: [1.0, 0.85, 0.2, 1.0],
+            },
+            ColorBand {
+                from: Duration::ZERO,
+                color: [1.0, 0.3, 0.3, 1.0],
+            },
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FadeStyle {
+    /// The window background gets more opaque
+    #[default]
+    Background,
+    /// The text pulses more and more
+    Pulse,
+}
+
+impl FadeStyle {
+    pub const ALL: [FadeStyle; 2] = [Self::Background, Self::Pulse];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Background => "Background",
+            Self::Pulse => "Pulsing text",
+        }
+    }
+}
+
+/// In which order active timers are shown in the overlay
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimerOrder {
+    /// As they are listed in the options
+    Manual,
+    Name,
+    /// Soonest to finish first
+    #[default]
+    Remaining,
+}
+
+impl TimerOrder {
+    pub const ALL: [TimerOrder; 3] = [Self::Manual, Self::Name, Self::Remaining];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Manual => "As listed",
+            Self::Name => "By name",
+            Self::Remaining => "Soonest to finish first",
+        }
+    }
+
+    /// Sorts timers with their remaining time, ties keep the listed order
+    pub fn sort(self, timers: &mut [(&Timer, Duration)]) {
+        match self {
+            Self::Manual => {}
+            Self::Name => timers.sort_by_cached_key(|(t, _)| t.name.to_lowercase()),
+            Self::Remaining => timers.sort_by_key(|(_, rest)| *rest),
+        }
+    }
+}
+
+/// Draws attention to the final stretch of a run without a sound
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fade {
+    pub from: Duration,
+    #[serde(default)]
+    pub style: FadeStyle,
+}
+
+impl Default for Fade {
+    fn default() -> Self {
+        Self {
+            from: Duration::from_secs(10),
+            style: FadeStyle::default(),
+        }
+    }
+}
+
+impl Fade {
+    /// How far into the final stretch `rest` is, from 0 at its start to 1 at zero
+    pub fn urgency(&self, rest: Duration) -> Option<f32> {
+        if self.from.is_zero() || rest > self.from {
+            return None;
+        }
+        Some(1.0 - rest.as_secs_f32() / self.from.as_secs_f32())
+    }
+}
+
+/// Files from the sounds folder played instead of the tones, empty for the tone
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoundFiles {
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub start: String,
+    /// At the lead time of the sound channel
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub warning: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub finish: String,
+}
+
+impl SoundFiles {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Quantizes the start of a run so timers started at nearly the same time tick in sync,
+/// or so they line up with the wall clock like the server's schedules do
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartSnap {
+    #[default]
+    Off,
+    Second,
+    HalfSecond,
+    // UTC wall clock boundaries, the meta-event schedule runs on two hour cycles
+    Minute,
+    QuarterHour,
+    Hour,
+    TwoHours,
+}
+
+impl StartSnap {
+    pub const ALL: [StartSnap; 7] = [
+        Self::Off,
+        Self::Second,
+        Self::HalfSecond,
+        Self::Minute,
+        Self::QuarterHour,
+        Self::Hour,
+        Self::TwoHours,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Off => "Off",
+            Self::Second => "Whole second",
+            Self::HalfSecond => "Half second",
+            Self::Minute => "Clock minute",
+            Self::QuarterHour => "Quarter hour",
+            Self::Hour => "Full hour",
+            Self::TwoHours => "Meta cycle (2h)",
+        }
+    }
+
+    fn step(self) -> Option<Duration> {
+        match self {
+            Self::Off => None,
+            Self::Second => Some(Duration::from_secs(1)),
+            Self::HalfSecond => Some(Duration::from_millis(500)),
+            Self::Minute => Some(Duration::from_secs(60)),
+            Self::QuarterHour => Some(Duration::from_secs(15 * 60)),
+            Self::Hour => Some(Duration::from_secs(60 * 60)),
+            Self::TwoHours => Some(Duration::from_secs(2 * 60 * 60)),
+        }
+    }
+
+    /// Whether the grid is anchored to the wall clock instead of the addon's start
+    pub fn is_wall_clock(self) -> bool {
+        matches!(
+            self,
+            Self::Minute | Self::QuarterHour | Self::Hour | Self::TwoHours
+        )
+    }
+}
+
+/// Rounds `t` up to the next multiple of `step` after `epoch`, where `epoch` is
+/// `phase` past the start of the grid
+fn snap_up(t: Instant, epoch: Instant, phase: Duration, step: Duration) -> Instant {
+    let offset = (t.saturating_duration_since(epoch) + phase).as_nanos() % step.as_nanos();
+    if offset == 0 {
+        t
+    } else {
+        t.checked_add(step - Duration::from_nanos(offset as u64))
+            .unwrap_or(t)
+    }
+}
+
+/// Where a timer is in its run. All transitions take the current time instead of
+/// reading a clock, so they can be exercised without waiting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimerState {
+    #[default]
+    Idle,
+    /// Counting since `since`, which lies in the future during the count-in
+    Running { since: Instant },
+    /// Frozen at `at`, resuming moves `since` by the time spent paused
+    Paused { since: Instant, at: Instant },
+    /// Ran out and the finish actions fired, overtime keeps counting from `since`
+    Finished { since: Instant },
+}
+
+impl TimerState {
+    pub fn start(now: Instant, delay: Duration) -> Self {
+        // far enough out to overflow the clock it would never start anyway
+        Self::Running {
+            since: now.checked_add(delay).unwrap_or(now),
+        }
+    }
+
+    pub fn pause(&mut self, now: Instant) {
+        if let Self::Running { since } = *self {
+            *self = Self::Paused { since, at: now };
+        }
+    }
+
+    pub fn resume(&mut self, now: Instant) {
+        if let Self::Paused { since, at } = *self {
+            *self = Self::Running {
+                since: since + now.saturating_duration_since(at),
+            };
+        }
+    }
+
+    pub fn finish(&mut self) {
+        if let Self::Running { since } = *self {
+            *self = Self::Finished { since };
+        }
+    }
+
+    /// Finishes a running timer once `duration` is used up, returns true if it just did.
+    /// A finished timer whose duration was raised past its elapsed time runs again.
+    pub fn tick(&mut self, now: Instant, duration: Duration) -> bool {
+        match *self {
+            Self::Running { .. }
+                if self.count_in(now).is_none()
+                    && self.elapsed(now).is_some_and(|e| e >= duration) =>
+            {
+                self.finish();
+                return true;
+            }
+            Self::Finished { since } if self.elapsed(now).is_some_and(|e| e < duration) => {
+                *self = Self::Running { since };
+            }
+            _ => {}
+        }
+        false
+    }
+
+    fn since(&self) -> Option<Instant> {
+        match *self {
+            Self::Idle => None,
+            Self::Running { since } | Self::Paused { since, .. } | Self::Finished { since } => {
+                Some(since)
+            }
+        }
+    }
+
+    // time stands still while paused
+    fn frozen_now(&self, now: Instant) -> Instant {
+        match *self {
+            Self::Paused { at, .. } => at,
+            _ => now,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        *self != Self::Idle
+    }
+
+    pub fn is_paused(&self) -> bool {
+        matches!(self, Self::Paused { .. })
+    }
+
+    /// How long the timer has been counting, zero during the count-in
+    pub fn elapsed(&self, now: Instant) -> Option<Duration> {
+        Some(
+            self.frozen_now(now)
+                .saturating_duration_since(self.since()?),
+        )
+    }
+
+    /// How long until the timer begins, while it is still in its count-in
+    pub fn count_in(&self, now: Instant) -> Option<Duration> {
+        self.since()?
+            .checked_duration_since(self.frozen_now(now))
+            .filter(|d| !d.is_zero())
+    }
+
+    /// Moves the start of the run, e.g. to account for time spent in standby
+    fn shift(&mut self, f: impl FnOnce(Instant) -> Instant) {
+        match self {
+            Self::Idle => {}
+            Self::Running { since } | Self::Paused { since, .. } | Self::Finished { since } => {
+                *since = f(*since)
+            }
+        }
+    }
+}
+
+fn enabled_default() -> bool {
+    true
+}
+
+/// Source of one-off timers started from the quick timer prompt
+pub const QUICK: &str = "quick";
+/// How long a quick timer shows as done before it is removed
+const QUICK_HOLD: Duration = Duration::from_secs(5);
+/// Extra runs of a timer that allows several at once, removed once done like quick timers
+pub const INSTANCE: &str = "instance";
+/// Runs of one timer at once, against a stuck keybind spawning them without end
+pub const MAX_INSTANCES: usize = 20;
+/// The countdown to the start of a fight, see `Timers::toggle_pull`
+pub const PULL: &str = "pull";
+/// Final seconds of the pull timer with a beep each
+const PULL_BEEPS: u64 = 5;
+/// Righteous Indignation of a captured WvW objective, see `Timers::start_wvw`
+pub const WVW: &str = "wvw";
+
+/// How long a repeating alarm shows as finished before it is armed for the next time
+const ALARM_HOLD: Duration = Duration::from_secs(60);
+
+/// How often wall clock runs are compared against the wall clock
+const WALL_RESYNC: Duration = Duration::from_secs(60);
+/// Wall clock runs that are off by less than this are left alone
+const WALL_TOLERANCE: Duration = Duration::from_secs(1);
+
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+fn unix_secs(t: SystemTime) -> u64 {
+    t.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+fn add_step_default() -> Duration {
+    Duration::from_secs(10)
+}
+
+/// Length of the keybind id of a timer, hex digits
+pub const KEYBIND_ID_LEN: usize = 16;
+
+/// A keybind id for a new timer, random so a removed timer's keys don't carry over
+pub fn new_keybind_id() -> String {
+    use std::hash::BuildHasher;
+    // seeded randomly for each process and each call
+    let hash = std::hash::RandomState::new().hash_one(SystemTime::now());
+    format!("{:0width$x}", hash, width = KEYBIND_ID_LEN)
+}
+
+/// Whether `id` is one like [`new_keybind_id`] makes, the keybinds of a timer are only
+/// told apart by one
+pub fn is_keybind_id(id: &str) -> bool {
+    id.len() == KEYBIND_ID_LEN
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+/// The keybind id of a pack timer, the same on every load (FNV-1a of its name)
+fn pack_keybind_id(name: &str) -> String {
+    let hash = name.bytes().fold(0xcbf29ce484222325u64, |hash, b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x100000001b3)
+    });
+    format!("{:0width$x}", hash, width = KEYBIND_ID_LEN)
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Timer {
+    // Stable identifier, used to keep timers.json in a deterministic order
+    #[serde(default)]
+    pub id: u64,
+    pub name: String,
+    // Display names by language code, `name` is used for the languages missing here
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub names: BTreeMap<String, String>,
+    pub duration: Duration,
+    // The earliest the mechanic can happen for ones with variable timing, `duration`
+    // is the latest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub earliest: Option<Duration>,
+    // Disabled timers keep their config but have no keybind and are never shown
+    #[serde(default = "enabled_default")]
+    pub enabled: bool,
+    // Set aside in the archive, disabled and out of the timer list until restored
+    #[serde(default)]
+    pub archived: bool,
+    // Unix time of the last start, for suggesting timers to archive
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used: Option<u64>,
+    // Image shown next to the countdown, relative to the addon dir
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub icon: String,
+    // Free-form group name for organizing timers, empty if ungrouped
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub group: String,
+    // Only shown while playing one of these characters, on all of them if empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub characters: Vec<String>,
+    // Count-in before the timer itself starts
+    #[serde(default)]
+    pub delay: Duration,
+    // Counted as started this many milliseconds earlier, for the reaction time to an
+    // in-game cue, later if negative
+    #[serde(default)]
+    pub offset_ms: i32,
+    // Keep counting into negative time once the timer ran out
+    #[serde(default)]
+    pub overtime: bool,
+    // Final stretch shown as a big countdown in the middle of the screen, zero for none
+    #[serde(default, skip_serializing_if = "Duration::is_zero")]
+    pub countdown: Duration,
+    // How long a finished timer stays up before it's reset, `None` to stay until stopped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hold: Option<Duration>,
+    // Stops a run that went on this long, against one forgotten for hours skewing the
+    // history. `None` for no cap.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_run: Option<Duration>,
+    #[serde(default)]
+    pub standby: StandbyMode,
+    #[serde(default)]
+    pub snap: StartSnap,
+    #[serde(default)]
+    pub key_action: KeyAction,
+    // Time added or removed by the keybind action, the time keybinds and buttons
+    #[serde(default = "add_step_default")]
+    pub add_step: Duration,
+    // The keybind doesn't start the timer again for this long after it finished, against
+    // pressing it twice
+    #[serde(default, skip_serializing_if = "Duration::is_zero")]
+    pub lockout: Duration,
+    // The start keybind has to be held this long before it acts, against brushing the key
+    #[serde(default, skip_serializing_if = "Duration::is_zero")]
+    pub press_hold: Duration,
+    // Register keybinds that add or remove `add_step` while running
+    #[serde(default)]
+    pub time_keybinds: bool,
+    // Register a keybind that records a lap while running
+    #[serde(default)]
+    pub lap_keybind: bool,
+    // Show how often it finished today and this week in the overlay
+    #[serde(default)]
+    pub show_completions: bool,
+    // Started as soon as the addon loads, like a stopwatch of the session
+    #[serde(default)]
+    pub autostart: bool,
+    // Names its keybinds in Nexus, generated once so they keep their keys when renamed
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub keybind_id: String,
+    // Colors of the remaining time and the hotbar fill by how much is left, kept in layout.json
+    #[serde(default, skip_serializing)]
+    pub bands: Vec<ColorBand>,
+    // Fades the window in or pulses the text towards the end of a run, kept in layout.json
+    #[serde(default, skip_serializing)]
+    pub fade: Option<Fade>,
+    // Overrides the time format of the settings, kept in layout.json
+    #[serde(default, skip_serializing)]
+    pub format: Option<TimeFormat>,
+    // Overrides the decimals of the settings, kept in layout.json
+    #[serde(default, skip_serializing)]
+    pub decimals: Option<u8>,
+    // Overrides how the remaining time is rounded, kept in layout.json
+    #[serde(default, skip_serializing)]
+    pub rounding: Option<Rounding>,
+    // Overrides how often the overlay text is refreshed per second, kept in layout.json
+    #[serde(default, skip_serializing)]
+    pub refresh_hz: Option<u32>,
+    // Text shown instead of the remaining time, with placeholders, kept in layout.json
+    #[serde(default, skip_serializing)]
+    pub template: String,
+    // Size the overlay window was dragged to, `None` to fit it to the text, kept in
+    // layout.json
+    #[serde(default, skip_serializing)]
+    pub window_size: Option<[f32; 2]>,
+    // Hides the window once it sat finished or paused this long, zero for never, `None`
+    // for the setting, kept in layout.json
+    #[serde(default, skip_serializing)]
+    pub hide_idle: Option<Duration>,
+    // Starts are sent to and received from the squad room
+    #[serde(default)]
+    pub share: bool,
+    // Starting it while it runs starts another run next to it instead of restarting it
+    #[serde(default)]
+    pub instances: bool,
+    // Starting any timer of the same exclusive group stops the others, empty if in none
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub exclusive: String,
+    // Has a button in the hotbar
+    #[serde(default)]
+    pub favorite: bool,
+    // Runs out at a time of day rather than after `duration`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alarm: Option<Alarm>,
+    // Started by a keyword in squad or party chat
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chat: Option<ChatTrigger>,
+    // Started by a skill cast or buff application in combat
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub combat: Option<CombatTrigger>,
+    // Started when a ready check of the squad completes
+    #[serde(default)]
+    pub ready_check: bool,
+    // Runs through labeled steps, `duration` follows their total
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeline: Option<Timeline>,
+    // Keeps the window at a point of the screen instead of where it was dragged to, kept in layout.json
+    #[serde(default, skip_serializing)]
+    pub anchor: Option<Anchor>,
+    // Drawn on screen while the timer is running
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub marker: Option<Marker>,
+    // Shown when hovering the timer, e.g. what to do about the mechanic
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note: String,
+    // Page with more about the timer, e.g. the wiki page of the mechanic
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub url: String,
+    // https URL that gets a POST on start and finish, e.g. a Discord webhook
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub webhook: String,
+    // Ask for a note on the run when it is stopped by hand
+    #[serde(default)]
+    pub ask_note: bool,
+    // Copied to the clipboard when the timer finishes, e.g. a chat macro
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub finish_text: String,
+    // Play the tone of `priority` on finish
+    #[serde(default)]
+    pub sound: bool,
+    // Speak the name on start and finish and the announcement marks
+    #[serde(default)]
+    pub voice: bool,
+    // Flash the window on finish
+    #[serde(default)]
+    pub flash: bool,
+    #[serde(default, skip_serializing_if = "AlertLeads::is_zero")]
+    pub leads: AlertLeads,
+    #[serde(default, skip_serializing_if = "SoundFiles::is_empty")]
+    pub sounds: SoundFiles,
+    #[serde(default)]
+    pub priority: Priority,
+    // Remaining times at which to announce the countdown, e.g. 30s, 10s, 5s
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub marks: Vec<Duration>,
+    #[serde(default)]
+    pub mark_cue: MarkCue,
+    // Metadata of external tools and packs, kept as-is across load and save
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, serde_json::Value>,
+    #[serde(skip, default)]
+    pub state: TimerState,
+    #[serde(skip, default)]
+    pub started_by: Option<StartSource>,
+    // Time into the run at each lap of the current or last run
+    #[serde(skip, default)]
+    pub laps: Vec<Duration>,
+    // Runs since the addon was loaded, counting the current one
+    #[serde(skip, default)]
+    pub cycles: u32,
+    // Added to `duration` for the current run only
+    #[serde(skip, default)]
+    bonus: Duration,
+    // Remaining time at the previous tick, to notice marks being crossed
+    #[serde(skip, default)]
+    last_remaining: Option<Duration>,
+    // When the last run ran out, for the lockout
+    #[serde(skip, default)]
+    finished_at: Option<Instant>,
+    // Wall clock time the run ends at, for runs that are defined by one like alarms and
+    // resets. `Instant`s drift away from it over hours and across standby.
+    #[serde(skip, default)]
+    ends_at: Option<SystemTime>,
+    // Include file this timer was loaded from, those aren't written back to timers.json
+    #[serde(skip, default)]
+    pub pack: Option<String>,
+    // Subsystem that creates and updates this timer, those are read-only and never saved
+    #[serde(skip, default)]
+    pub system: Option<&'static str>,
+    // Id of the timer this is an extra run of
+    #[serde(skip)]
+    pub instance_of: Option<u64>,
+}
+
+impl Timer {
+    pub fn new(id: u64, name: String, duration: Duration) -> Self {
+        Self {
+            id,
+            name,
+            duration,
+            enabled: true,
+            add_step: add_step_default(),
+            keybind_id: new_keybind_id(),
+            ..Default::default()
+        }
+    }
+
+    /// How the timer's times are shown, `default` where it has no format of its own
+    pub fn style(&self, default: TimeStyle) -> TimeStyle {
+        self.format
+            .unwrap_or(default.format)
+            .with_decimals(self.decimals.unwrap_or(default.decimals))
+            .rounded(self.rounding.unwrap_or(default.rounding))
+    }
+
+    /// Whether starting it while it runs starts another run, alarms only ever have one
+    pub fn allows_instances(&self) -> bool {
+        self.instances && self.alarm.is_none()
+    }
+
+    pub fn display_name(&self, lang: &str) -> &str {
+        self.names.get(lang).map_or(&self.name, |n| n.as_str())
+    }
+
+    /// Starts a new run, snapped to the grid starting at `epoch` if enabled.
+    /// `wall` is the wall clock time at `now`.
+    pub fn start(&mut self, now: Instant, epoch: Instant, wall: SystemTime) {
+        self.last_used = Some(unix_secs(wall));
+        self.cycles += 1;
+        self.laps.clear();
+        self.finished_at = None;
+        self.ends_at = None;
+        if let Some(alarm) = self.alarm {
+            let ends_at = alarm.next(wall);
+            self.ends_at = Some(ends_at);
+            self.arm(now, ends_at.duration_since(wall).unwrap_or_default());
+            return;
+        }
+        if let Some(timeline) = &self.timeline {
+            self.duration = timeline.total();
+        }
+        self.state = TimerState::start(now, self.delay);
+        let offset = Duration::from_millis(u64::from(self.offset_ms.unsigned_abs()));
+        if self.offset_ms > 0 {
+            self.state
+                .shift(|since| since.checked_sub(offset).unwrap_or(since));
+        } else {
+            self.state
+                .shift(|since| since.checked_add(offset).unwrap_or(since));
+        }
+        self.bonus = Duration::ZERO;
+        let Some(step) = self.snap.step() else {
+            return;
+        };
+        let (epoch, phase) = if self.snap.is_wall_clock() {
+            (now, wall.duration_since(UNIX_EPOCH).unwrap_or_default())
+        } else {
+            (epoch, Duration::ZERO)
+        };
+        self.state.shift(|since| snap_up(since, epoch, phase, step));
+    }
+
+    /// Starts a run that ends in `until`, whatever the duration
+    fn arm(&mut self, now: Instant, until: Duration) {
+        self.bonus = until.saturating_sub(self.duration);
+        let since = now
+            .checked_sub(self.duration.saturating_sub(until))
+            .unwrap_or(now);
+        self.state = TimerState::Running { since };
+    }
+
+    /// Moves the end of a running wall clock run back to its wall clock time, returns
+    /// whether it was off by more than `WALL_TOLERANCE`
+    fn resync(&mut self, now: Instant, wall: SystemTime) -> bool {
+        let Some(ends_at) = self.ends_at else {
+            return false;
+        };
+        if !matches!(self.state, TimerState::Running { .. }) {
+            return false;
+        }
+        let Some(rest) = self.remaining(now) else {
+            return false;
+        };
+        let until = ends_at.duration_since(wall).unwrap_or_default();
+        if rest.abs_diff(until) < WALL_TOLERANCE {
+            return false;
+        }
+        self.arm(now, until);
+        true
+    }
+
+    /// Keeps a running wall clock run at its wall clock time, however fast the timers run
+    fn follow_wall(&mut self, now: Instant, wall: SystemTime) {
+        if let (Some(ends_at), TimerState::Running { .. }) = (self.ends_at, self.state) {
+            self.arm(now, ends_at.duration_since(wall).unwrap_or_default());
+        }
+    }
+
+    /// A repeating alarm that is idle or was finished for a while is armed again
+    fn wants_rearm(&self, now: Instant) -> bool {
+        if !self.enabled || !self.alarm.is_some_and(|a| a.repeat.is_repeating()) {
+            return false;
+        }
+        match self.state {
+            TimerState::Idle => true,
+            TimerState::Finished { .. } => self
+                .state
+                .elapsed(now)
+                .is_some_and(|e| e >= self.run_duration() + ALARM_HOLD),
+            _ => false,
+        }
+    }
+
+    /// The cap on its runs if the current one reached it, not yet finished or in its
+    /// count-in
+    fn capped(&self, now: Instant) -> Option<Duration> {
+        let max = self.max_run?;
+        let running = matches!(
+            self.state,
+            TimerState::Running { .. } | TimerState::Paused { .. }
+        );
+        (running && self.count_in(now).is_none() && self.state.elapsed(now)? >= max).then_some(max)
+    }
+
+    /// A finished timer was shown for its hold time and goes back to idle
+    fn hold_over(&self, now: Instant) -> bool {
+        let Some(hold) = self.hold else {
+            return false;
+        };
+        matches!(self.state, TimerState::Finished { .. })
+            && self
+                .state
+                .elapsed(now)
+                .is_some_and(|e| e >= self.run_duration() + hold)
+    }
+
+    /// How long it has been sitting finished or paused
+    pub fn idle_for(&self, now: Instant) -> Option<Duration> {
+        match self.state {
+            TimerState::Finished { .. } => self
+                .state
+                .elapsed(now)
+                .map(|e| e.saturating_sub(self.run_duration())),
+            TimerState::Paused { at, .. } => Some(now.saturating_duration_since(at)),
+            TimerState::Idle | TimerState::Running { .. } => None,
+        }
+    }
+
+    /// Whether its window is hidden for sitting idle, `default` unless it has its own
+    /// time. It shows again once started.
+    pub fn hidden_idle(&self, now: Instant, default: Duration) -> bool {
+        let after = self.hide_idle.unwrap_or(default);
+        !after.is_zero() && self.idle_for(now).is_some_and(|idle| idle >= after)
+    }
+
+    pub fn stop(&mut self) {
+        self.state = TimerState::Idle;
+        self.bonus = Duration::ZERO;
+        self.ends_at = None;
+        self.started_by = None;
+    }
+
+    /// Whether the timer is shown while playing `character`, all are without knowing who
+    pub fn shows_for(&self, character: Option<&str>) -> bool {
+        match character {
+            Some(character) if !self.characters.is_empty() => {
+                self.characters.iter().any(|c| c == character)
+            }
+            _ => true,
+        }
+    }
+
+    /// Moves the timer to the archive or restores it from there
+    pub fn set_archived(&mut self, archived: bool) {
+        self.archived = archived;
+        self.set_enabled(!archived);
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.stop();
+        }
+    }
+
+    /// Pauses a running timer or resumes a paused one
+    pub fn toggle_pause(&mut self, now: Instant) {
+        if self.state.is_paused() {
+            self.state.resume(now);
+        } else {
+            self.state.pause(now);
+        }
+    }
+
+    pub fn count_in(&self, now: Instant) -> Option<Duration> {
+        self.state.count_in(now)
+    }
+
+    /// The step of the timeline the run is in and what is left of it
+    pub fn step(&self, now: Instant) -> Option<(usize, Duration)> {
+        let timeline = self.timeline.as_ref()?;
+        if !matches!(
+            self.state,
+            TimerState::Running { .. } | TimerState::Paused { .. }
+        ) {
+            return None;
+        }
+        timeline.current(self.state.elapsed(now)?)
+    }
+
+    /// Time until the earliest point of a variable timer, `None` once it is reached
+    pub fn until_possible(&self, rest: Duration) -> Option<Duration> {
+        let possible_at = self.run_duration().checked_sub(self.earliest?)?;
+        rest.checked_sub(possible_at).filter(|d| !d.is_zero())
+    }
+
+    /// Whether the earliest point of a variable timer was reached since the last tick
+    fn became_possible(&self, rest: Option<Duration>) -> bool {
+        let (Some(prev), Some(rest)) = (self.last_remaining, rest) else {
+            return false;
+        };
+        self.until_possible(prev).is_some() && self.until_possible(rest).is_none()
+    }
+
+    /// The step of the timeline entered since the last tick
+    fn entered_step(&self, rest: Option<Duration>) -> Option<usize> {
+        let (timeline, prev, rest) = (self.timeline.as_ref()?, self.last_remaining?, rest?);
+        let run = self.run_duration();
+        timeline.entered(run.saturating_sub(prev), run.saturating_sub(rest))
+    }
+
+    /// What is left of the lockout after the last run finished
+    pub fn locked_out(&self, now: Instant) -> Option<Duration> {
+        (self.finished_at? + self.lockout)
+            .checked_duration_since(now)
+            .filter(|d| !d.is_zero())
+    }
+
+    /// Length of the current run, the duration plus any time added to it
+    pub fn run_duration(&self) -> Duration {
+        self.duration + self.bonus
+    }
+
+    /// Extends the current run by `by`, a finished timer runs again for `by`
+    pub fn add_time(&mut self, now: Instant, by: Duration) {
+        let Some(elapsed) = self.state.elapsed(now) else {
+            return;
+        };
+        let run = if matches!(self.state, TimerState::Finished { .. }) {
+            // overtime doesn't eat into the added time
+            elapsed.max(self.run_duration()) + by
+        } else {
+            self.run_duration() + by
+        };
+        self.bonus = run.saturating_sub(self.duration);
+    }
+
+    /// Shortens the current run by `by`, at most down to zero
+    pub fn subtract_time(&mut self, now: Instant, by: Duration) {
+        let Some(rest) = self.remaining(now) else {
+            return;
+        };
+        let by = by.min(rest);
+        let from_bonus = by.min(self.bonus);
+        self.bonus -= from_bonus;
+        let by = by - from_bonus;
+        if !by.is_zero() {
+            self.state
+                .shift(|since| since.checked_sub(by).unwrap_or(since));
+        }
+    }
+
+    /// The lowest mark passed since the last tick. Several marks can be passed at once
+    /// after a hitch or a duration edit, only the most urgent one is announced then.
+    fn crossed_mark(&self, rest: Option<Duration>) -> Option<Duration> {
+        let (prev, rest) = (self.last_remaining?, rest?);
+        self.marks
+            .iter()
+            .copied()
+            .filter(|m| !m.is_zero() && *m < prev && *m >= rest)
+            .min()
+    }
+
+    /// Channels whose lead time was passed since the last tick
+    fn crossed_leads(&self, rest: Option<Duration>) -> Vec<Channel> {
+        let (Some(prev), Some(rest)) = (self.last_remaining, rest) else {
+            return Vec::new();
+        };
+        Channel::ALL
+            .into_iter()
+            .filter(|&c| {
+                let lead = self.leads.get(c);
+                !lead.is_zero() && lead < prev && lead >= rest
+            })
+            .collect()
+    }
+
+    /// What is left while in the final stretch of the big countdown
+    pub fn countdown_left(&self, now: Instant) -> Option<Duration> {
+        if self.countdown.is_zero() || !matches!(self.state, TimerState::Running { .. }) {
+            return None;
+        }
+        if self.count_in(now).is_some() {
+            return None;
+        }
+        self.remaining(now)
+            .filter(|rest| !rest.is_zero() && *rest <= self.countdown)
+    }
+
+    /// Color of the band `rest` is in, if any
+    pub fn band_color(&self, rest: Duration) -> Option<[f32; 4]> {
+        self.bands
+            .iter()
+            .filter(|b| b.from <= rest)
+            .max_by_key(|b| b.from)
+            .map(|b| b.color)
+    }
+
+    /// How long ago the timer ran out, if it is set to keep counting into overtime
+    pub fn overtime_elapsed(&self, now: Instant) -> Option<Duration> {
+        if !self.overtime {
+            return None;
+        }
+        let elapsed = self.state.elapsed(now)?;
+        elapsed
+            .checked_sub(self.run_duration())
+            .filter(|d| !d.is_zero())
+    }
+
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        let elapsed = self.state.elapsed(now)?;
+        Some(self.run_duration().saturating_sub(elapsed))
+    }
+}
+
+/// Something that happened to a timer during a tick, identified by the timer's id
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerEvent {
+    Started(u64, StartSource),
+    /// Remaining time crossed one of the timer's announcement marks
+    Mark(u64, Duration),
+    /// Remaining time reached the lead time of an alert channel, those without one
+    /// alert on finish
+    Warn(u64, Channel),
+    /// Went on to the given step of its timeline
+    Step(u64, usize),
+    /// Reached the earliest point of its variable timing
+    Possible(u64),
+    Finished(u64),
+    /// Stopped before it ran out, after running for the given time
+    Stopped(u64, Duration),
+}
+
+impl TimerEvent {
+    pub fn id(self) -> u64 {
+        match self {
+            Self::Started(id, _)
+            | Self::Mark(id, _)
+            | Self::Warn(id, _)
+            | Self::Step(id, _)
+            | Self::Possible(id)
+            | Self::Finished(id)
+            | Self::Stopped(id, _) => id,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Timers {
+    pub list: Store,
+    // Name of the timer that was started most recently
+    pub last_started: Option<String>,
+    pub include: Vec<String>,
+    clock: Scaled,
+    // Common reference for start snapping
+    epoch: Instant,
+    recording: Option<Recording>,
+    // Events from outside of a tick, handed out with the next one
+    pending: Vec<TimerEvent>,
+    // The game is unfocused or loading, see `set_away`
+    away: bool,
+    // Timers paused while away, resumed once back
+    away_paused: Vec<u64>,
+    // When wall clock runs were last resynced
+    resynced: Instant,
+    // Started once the pull timer runs out
+    pull_then: Option<String>,
+}
+
+impl Default for Timers {
+    fn default() -> Self {
+        Self::with_clock(Box::new(SystemClock))
+    }
+}
+
+impl Timers {
+    pub fn new(list: Vec<Timer>, include: Vec<String>) -> Self {
+        Self {
+            list: Store::new(list),
+            include,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_clock(clock: Box<dyn Clock>) -> Self {
+        let now = clock.now();
+        Self {
+            list: Store::default(),
+            last_started: None,
+            include: Vec::new(),
+            epoch: now,
+            clock: Scaled::new(clock),
+            recording: None,
+            pending: Vec::new(),
+            away: false,
+            away_paused: Vec::new(),
+            pull_then: None,
+            resynced: now,
+        }
+    }
+
+    pub fn now(&self) -> Instant {
+        self.clock.now()
+    }
+
+    /// Runs the timers faster or slower, 1 for real time. Wall clock runs like alarms and
+    /// system timers keep to real time.
+    pub fn set_time_scale(&mut self, scale: f32) {
+        let before = self.clock.scale();
+        self.clock.set_scale(scale);
+        if self.clock.scale() != before {
+            log::info!("Timers run at {}x", self.clock.scale());
+        }
+    }
+
+    /// Records every action from now on, with the current timers as the starting point
+    pub fn start_recording(&mut self) {
+        self.recording = Some(Recording::new(self.now(), &self.list));
+    }
+
+    pub fn take_recording(&mut self) -> Option<Recording> {
+        self.recording.take()
+    }
+
+    fn record(&mut self, action: impl FnOnce() -> Action) {
+        let now = self.now();
+        if let Some(recording) = &mut self.recording {
+            recording.push(now, action());
+        }
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Timer> {
+        self.list.get(id)
+    }
+
+    pub fn get_mut(&mut self, id: u64) -> Option<&mut Timer> {
+        self.list.get_mut(id)
+    }
+
+    pub fn find_by_name(&self, name: &str) -> Option<&Timer> {
+        self.list.by_name(name)
+    }
+
+    pub fn find_by_name_mut(&mut self, name: &str) -> Option<&mut Timer> {
+        self.list.by_name_mut(name)
+    }
+
+    pub fn contains_name(&self, name: &str) -> bool {
+        self.list.contains_name(name)
+    }
+
+    /// The timer the runs of `timer` are kept under in the history, the one it was spawned
+    /// from for instances. None for those the addon keeps itself, like the pull timer.
+    pub fn history_name(&self, timer: &Timer) -> Option<String> {
+        match timer.system {
+            None => Some(timer.name.clone()),
+            Some(INSTANCE) => Some(self.get(timer.instance_of?)?.name.clone()),
+            Some(_) => None,
+        }
+    }
+
+    /// Running timers and what is left of them, the one finishing first first
+    pub fn next_finishes(&self) -> Vec<(&Timer, Duration)> {
+        let now = self.now();
+        let mut running: Vec<_> = self
+            .list
+            .iter()
+            .filter(|t| t.enabled && matches!(t.state, TimerState::Running { .. }))
+            .filter_map(|t| Some((t, t.remaining(now)?)))
+            .filter(|(_, rest)| !rest.is_zero())
+            .collect();
+        running.sort_by_key(|(_, rest)| *rest);
+        running
+    }
+
+    fn next_id(&self) -> u64 {
+        self.list.next_id()
+    }
+
+    pub fn add(&mut self, name: String, duration: Duration) -> &Timer {
+        let timer = Timer::new(self.next_id(), name, duration);
+        self.list.push(timer)
+    }
+
+    /// `name (copy)` or with the first number that isn't taken yet
+    fn copy_name(&self, name: &str) -> String {
+        (1..)
+            .map(|n| match n {
+                1 => format!("{} (copy)", name),
+                n => format!("{} (copy {})", name, n),
+            })
+            .find(|name| !self.contains_name(name))
+            .expect("A free name to exist")
+    }
+
+    /// Adds imported timers as chosen for each, overwritten ones are stopped. Those that
+    /// would overwrite a pack or system timer are added as copies. Returns the ids of
+    /// those added or overwritten, the latter with the timer they replaced.
+    pub fn merge_imported(&mut self, imported: Vec<(Timer, Merge)>) -> Vec<(u64, Option<Timer>)> {
+        let mut merged = Vec::new();
+        let mut next = self.next_id();
+        for (mut timer, merge) in imported {
+            let existing = self.list.by_name(&timer.name);
+            match (merge, existing) {
+                (Merge::Skip, _) => {}
+                // pack and system timers aren't ours to replace, those are added as copies
+                (Merge::Overwrite, Some(old)) if old.pack.is_none() && old.system.is_none() => {
+                    timer.id = old.id;
+                    timer.keybind_id = old.keybind_id.clone();
+                    timer.last_used = old.last_used;
+                    timer.cycles = old.cycles;
+                    let old = self.list.replace(timer).expect("Timer to be replaced");
+                    merged.push((old.id, Some(old)));
+                }
+                (merge, existing) => {
+                    if existing.is_some() {
+                        if merge == Merge::Overwrite {
+                            log::warn!(
+                                "Adding {} as a copy, the timer it would overwrite isn't yours",
+                                timer.name
+                            );
+                        }
+                        timer.name = self.copy_name(&timer.name);
+                    }
+                    timer.id = next;
+                    next += 1;
+                    timer.keybind_id = new_keybind_id();
+                    merged.push((timer.id, None));
+                    self.list.push(timer);
+                }
+            }
+        }
+        merged
+    }
+
+    /// Adds a copy of the configuration of timer `id` under the next free name like
+    /// "Boss (copy)", the copy is idle and belongs to no pack
+    pub fn duplicate(&mut self, id: u64) -> Option<&Timer> {
+        let original = self.get(id)?;
+        let name = self.copy_name(&original.name);
+        let mut copy = Timer {
+            id: self.next_id(),
+            name,
+            pack: None,
+            keybind_id: new_keybind_id(),
+            ..original.clone()
+        };
+        copy.stop();
+        copy.laps.clear();
+        copy.last_remaining = None;
+        copy.finished_at = None;
+        copy.last_used = None;
+        copy.cycles = 0;
+        Some(self.list.push(copy))
+    }
+
+    /// Timers not started for `days` days, worth suggesting to archive
+    pub fn unused(&self, days: u32) -> Vec<&Timer> {
+        let now = unix_secs(self.clock.wall());
+        let before = now.saturating_sub(u64::from(days) * DAY_SECS);
+        let mut unused: Vec<_> = self
+            .list
+            .iter()
+            .filter(|t| !t.archived && t.system.is_none() && t.pack.is_none())
+            .filter(|t| t.last_used.is_some_and(|used| used < before))
+            .collect();
+        unused.sort_by_key(|t| t.last_used);
+        unused
+    }
+
+    /// Timers from before last starts were kept count as used now, so they aren't all
+    /// suggested at once
+    pub fn seed_last_used(&mut self) {
+        let now = unix_secs(self.clock.wall());
+        for timer in self.list.iter_mut().filter(|t| t.system.is_none()) {
+            timer.last_used.get_or_insert(now);
+        }
+    }
+
+    /// Moves timer `id` one place up or down the list, returns whether it moved
+    pub fn move_by_one(&mut self, id: u64, up: bool) -> bool {
+        let Some(at) = self.list.position(id) else {
+            return false;
+        };
+        let to = if up { at.checked_sub(1) } else { Some(at + 1) };
+        match to.filter(|to| *to < self.list.len()) {
+            Some(to) => {
+                self.list.swap(at, to);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Adds already configured timers, skipping those whose name is taken.
+    /// Returns the ids of the timers that were added.
+    pub fn add_imported(&mut self, imported: Vec<Timer>) -> Vec<u64> {
+        let mut added = Vec::new();
+        for mut timer in imported {
+            if self.contains_name(&timer.name) {
+                continue;
+            }
+            timer.id = self.next_id();
+            timer.keybind_id = new_keybind_id();
+            added.push(self.list.push(timer).id);
+        }
+        added
+    }
+
+    /// Replaces our own timers with `restored`, returns the ones replaced. Pack and system
+    /// timers stay, restored ones of the same name as those are left out.
+    pub fn restore(&mut self, restored: Vec<Timer>) -> Vec<Timer> {
+        let (own, kept): (Vec<Timer>, Vec<Timer>) = self
+            .list
+            .take()
+            .into_iter()
+            .partition(|t| t.pack.is_none() && t.system.is_none());
+        let mut list: Vec<Timer> = restored
+            .into_iter()
+            .filter(|t| !kept.iter().any(|k| k.name == t.name))
+            .collect();
+        // backups from before keybind ids existed keep the keys of the timers they replace
+        for timer in list.iter_mut().filter(|t| t.keybind_id.is_empty()) {
+            if let Some(replaced) = own.iter().find(|o| o.name == timer.name) {
+                timer.keybind_id = replaced.keybind_id.clone();
+            }
+        }
+        list.extend(kept);
+        self.list = Store::new(list);
+        self.assign_keybind_ids();
+        if let Some(name) = &self.last_started {
+            if !self.contains_name(name) {
+                self.last_started = None;
+            }
+        }
+        own
+    }
+
+    pub fn remove(&mut self, id: u64) -> Option<Timer> {
+        let timer = self.list.remove(id)?;
+        if self.last_started.as_ref() == Some(&timer.name) {
+            self.last_started = None;
+        }
+        Some(timer)
+    }
+
+    /// Gives timers without a usable keybind id or with a taken one a new one, those of
+    /// packs one that follows from their name since packs aren't saved. Returns the ids of
+    /// the timers whose keybinds are still named otherwise in Nexus, with the keybind id
+    /// they had. It is empty for those that had none, their keybinds are named after them.
+    pub fn assign_keybind_ids(&mut self) -> Vec<(u64, String)> {
+        let mut seen = std::collections::HashSet::new();
+        let mut missing = Vec::new();
+        for timer in self.list.iter_mut() {
+            // edited by hand, its keybinds wouldn't be recognized as a timer's
+            if !is_keybind_id(&timer.keybind_id) {
+                missing.push((timer.id, std::mem::take(&mut timer.keybind_id)));
+                timer.keybind_id = match timer.pack {
+                    Some(_) => pack_keybind_id(&timer.name),
+                    None => new_keybind_id(),
+                };
+            }
+            while !seen.insert(timer.keybind_id.clone()) {
+                timer.keybind_id = new_keybind_id();
+            }
+        }
+        missing
+    }
+
+    pub fn start(&mut self, name: &str, source: StartSource) {
+        self.record(|| Action::Start {
+            name: name.to_string(),
+            source,
+        });
+        // a finished run is started again instead
+        let spawns = self.find_by_name(name).is_some_and(|t| {
+            t.enabled
+                && t.allows_instances()
+                && matches!(
+                    t.state,
+                    TimerState::Running { .. } | TimerState::Paused { .. }
+                )
+        });
+        if !spawns {
+            self.begin_run(name, source);
+        } else if let Some(instance) = self.spawn_instance(name) {
+            self.begin_run(&instance, source);
+        }
+    }
+
+    /// Adds another run of timer `name` next to the running one, returns its name
+    fn spawn_instance(&mut self, name: &str) -> Option<String> {
+        let original = self.find_by_name(name)?;
+        let running = self
+            .list
+            .iter()
+            .filter(|t| t.instance_of == Some(original.id))
+            .count();
+        if running + 1 >= MAX_INSTANCES {
+            log::warn!("Not starting more than {} runs of {}", MAX_INSTANCES, name);
+            return None;
+        }
+        let instance_name = (2..)
+            .map(|n| format!("{} #{}", name, n))
+            .find(|n| !self.contains_name(n))
+            .expect("A free name to exist");
+        let mut instance = Timer {
+            id: self.next_id(),
+            name: instance_name.clone(),
+            system: Some(INSTANCE),
+            instance_of: Some(original.id),
+            keybind_id: new_keybind_id(),
+            // finished runs go away like quick timers
+            hold: original.hold.or(Some(QUICK_HOLD)),
+            ..original.clone()
+        };
+        // placed under the original, started along with it, not by anything of its own
+        instance.instances = false;
+        instance.favorite = false;
+        instance.share = false;
+        instance.exclusive.clear();
+        instance.anchor = None;
+        instance.marker = None;
+        instance.chat = None;
+        instance.combat = None;
+        instance.ready_check = false;
+        instance.pack = None;
+        instance.stop();
+        instance.laps.clear();
+        instance.last_remaining = None;
+        instance.finished_at = None;
+        self.list.push(instance);
+        Some(instance_name)
+    }
+
+    /// Starts without recording it
+    fn begin_run(&mut self, name: &str, source: StartSource) {
+        let (now, epoch, wall) = (self.now(), self.epoch, self.clock.wall());
+        if let Some(timer) = self.find_by_name_mut(name).filter(|t| t.enabled) {
+            timer.start(now, epoch, wall);
+            timer.started_by = Some(source);
+            let (id, exclusive) = (timer.id, timer.exclusive.clone());
+            self.last_started = Some(timer.name.clone());
+            self.pending.push(TimerEvent::Started(id, source));
+            if !exclusive.is_empty() {
+                let others: Vec<String> = self
+                    .list
+                    .iter()
+                    .filter(|t| t.id != id && t.exclusive == exclusive && t.state.is_active())
+                    .map(|t| t.name.clone())
+                    .collect();
+                for other in others {
+                    self.end_run(&other);
+                }
+            }
+        }
+    }
+
+    /// Starts a one-off timer that isn't saved and is removed once it's done
+    pub fn start_quick(&mut self, duration: Duration) -> u64 {
+        let id = self.next_id();
+        let mut n = 1;
+        let name = loop {
+            let name = format!("Quick timer {}", n);
+            if !self.contains_name(&name) {
+                break name;
+            }
+            n += 1;
+        };
+        let mut timer = Timer::new(id, name.clone(), duration);
+        timer.system = Some(QUICK);
+        timer.hold = Some(QUICK_HOLD);
+        timer.sound = true;
+        timer.flash = true;
+        self.list.push(timer);
+        self.start(&name, StartSource::Keybind);
+        id
+    }
+
+    /// Starts the timer of a WvW objective's Righteous Indignation, from the beginning
+    /// if it is already running since the objective was taken again
+    pub fn start_wvw(&mut self, name: &str, duration: Duration) -> u64 {
+        let id = match self.find_by_name(name) {
+            Some(t) if t.system == Some(WVW) => t.id,
+            // a timer of the user's own has the name
+            Some(t) => return t.id,
+            None => {
+                let mut timer = Timer::new(self.next_id(), name.to_string(), duration);
+                timer.system = Some(WVW);
+                timer.hold = Some(QUICK_HOLD);
+                timer.sound = true;
+                timer.flash = true;
+                let id = timer.id;
+                self.list.push(timer);
+                id
+            }
+        };
+        self.start(name, StartSource::Ui);
+        id
+    }
+
+    /// Starts the pull timer, a big countdown that beeps through its final seconds and
+    /// starts `then` once it runs out. Cancels it instead if it is already counting down.
+    /// Returns whether it was started.
+    pub fn toggle_pull(&mut self, duration: Duration, then: Option<String>) -> bool {
+        let running = self
+            .list
+            .iter()
+            .find(|t| t.system == Some(PULL) && matches!(t.state, TimerState::Running { .. }))
+            .map(|t| t.name.clone());
+        if let Some(name) = running {
+            self.stop(&name);
+            self.pull_then = None;
+            return false;
+        }
+        self.list.retain(|t| t.system != Some(PULL));
+        let mut name = "Pull".to_string();
+        let mut n = 1;
+        while self.contains_name(&name) {
+            n += 1;
+            name = format!("Pull {}", n);
+        }
+        let mut timer = Timer::new(self.next_id(), name.clone(), duration);
+        timer.system = Some(PULL);
+        timer.hold = Some(QUICK_HOLD);
+        timer.countdown = duration;
+        timer.marks = (1..=PULL_BEEPS)
+            .map(Duration::from_secs)
+            .filter(|&mark| mark < duration)
+            .collect();
+        timer.mark_cue = MarkCue::Sound;
+        timer.sound = true;
+        self.list.push(timer);
+        self.pull_then = then;
+        self.start(&name, StartSource::Keybind);
+        true
+    }
+
+    pub fn stop(&mut self, name: &str) {
+        self.record(|| Action::Stop {
+            name: name.to_string(),
+        });
+        self.end_run(name);
+    }
+
+    /// Stops without recording it, for stops that follow from another action
+    fn end_run(&mut self, name: &str) {
+        let now = self.now();
+        let Some(timer) = self.find_by_name_mut(name) else {
+            return;
+        };
+        let running = matches!(
+            timer.state,
+            TimerState::Running { .. } | TimerState::Paused { .. }
+        );
+        // a run stopped during its count-in never began
+        let ran = timer
+            .state
+            .elapsed(now)
+            .filter(|_| running && timer.count_in(now).is_none());
+        let id = timer.id;
+        timer.stop();
+        if let Some(elapsed) = ran {
+            self.pending.push(TimerEvent::Stopped(id, elapsed));
+        }
+    }
+
+    /// Stops the timers of `group` if any of them is active, starts all of them otherwise
+    pub fn press_group(&mut self, group: &str) {
+        let of_group: Vec<(String, bool)> = self
+            .list
+            .iter()
+            .filter(|t| t.enabled && t.system.is_none() && t.group == group)
+            .map(|t| (t.name.clone(), t.state.is_active()))
+            .collect();
+        if of_group.iter().any(|(_, active)| *active) {
+            for (name, _) in of_group.iter().filter(|(_, active)| *active) {
+                self.stop(name);
+            }
+            log::info!("Stopped the timers of group {}", group);
+        } else {
+            for (name, _) in &of_group {
+                self.start(name, StartSource::Keybind);
+            }
+            log::info!("Started {} timers of group {}", of_group.len(), group);
+        }
+    }
+
+    /// What pressing the keybind of timer `name` does right now
+    pub fn press_outcome(&self, name: &str) -> Press {
+        let Some(timer) = self.find_by_name(name) else {
+            return Press::Nothing;
+        };
+        let active = timer.state.is_active();
+        let locked = timer.locked_out(self.now()).is_some();
+        let running = matches!(
+            timer.state,
+            TimerState::Running { .. } | TimerState::Paused { .. }
+        );
+        match timer.key_action {
+            _ if timer.allows_instances() && running && !locked => Press::Start,
+            KeyAction::Toggle if active => Press::Stop,
+            KeyAction::StartIfIdle if running => Press::Nothing,
+            KeyAction::AddTime if active => Press::AddTime(timer.add_step),
+            _ if locked => Press::Locked,
+            _ if running => Press::Restart,
+            _ => Press::Start,
+        }
+    }
+
+    /// Applies the timer's keybind action
+    pub fn press(&mut self, name: &str) {
+        match self.press_outcome(name) {
+            Press::Stop => self.stop(name),
+            Press::AddTime(step) => self.add_time(name, step),
+            Press::Locked => log::debug!("Not starting {} again during its lockout", name),
+            Press::Start | Press::Restart => self.start(name, StartSource::Keybind),
+            Press::Nothing => {}
+        }
+    }
+
+    pub fn add_time(&mut self, name: &str, by: Duration) {
+        self.record(|| Action::AddTime {
+            name: name.to_string(),
+            by,
+        });
+        let now = self.now();
+        if let Some(timer) = self.find_by_name_mut(name) {
+            timer.add_time(now, by);
+        }
+    }
+
+    pub fn subtract_time(&mut self, name: &str, by: Duration) {
+        self.record(|| Action::SubtractTime {
+            name: name.to_string(),
+            by,
+        });
+        let now = self.now();
+        if let Some(timer) = self.find_by_name_mut(name) {
+            timer.subtract_time(now, by);
+        }
+    }
+
+    pub fn toggle_pause(&mut self, name: &str) {
+        self.record(|| Action::TogglePause {
+            name: name.to_string(),
+        });
+        let now = self.now();
+        if let Some(timer) = self.find_by_name_mut(name) {
+            timer.toggle_pause(now);
+            let id = timer.id;
+            // paused or resumed by hand, coming back doesn't resume it anymore
+            self.away_paused.retain(|&p| p != id);
+        }
+    }
+
+    /// Records a lap of a running timer at the time into the run
+    pub fn lap(&mut self, name: &str) {
+        self.record(|| Action::Lap {
+            name: name.to_string(),
+        });
+        let now = self.now();
+        let Some(timer) = self.find_by_name_mut(name) else {
+            return;
+        };
+        let running = matches!(timer.state, TimerState::Running { .. });
+        if running && timer.count_in(now).is_none() {
+            if let Some(elapsed) = timer.state.elapsed(now) {
+                timer.laps.push(elapsed);
+            }
+        }
+    }
+
+    /// Keeps every running timer paused while `away` and resumes them once back.
+    /// Alarms and system timers follow the real world and keep running.
+    pub fn set_away(&mut self, away: bool) {
+        if away != self.away {
+            self.record(|| Action::Away { away });
+            self.away = away;
+        }
+        let now = self.now();
+        if !away {
+            for id in std::mem::take(&mut self.away_paused) {
+                if let Some(timer) = self.get_mut(id) {
+                    timer.state.resume(now);
+                }
+            }
+            return;
+        }
+        for timer in &mut self.list {
+            let running = matches!(timer.state, TimerState::Running { .. });
+            if running && timer.system.is_none() && timer.alarm.is_none() {
+                timer.state.pause(now);
+                self.away_paused.push(timer.id);
+            }
+        }
+    }
+
+    /// Whether the timer is held by [`Timers::set_away`] rather than paused by hand
+    pub fn is_frozen(&self, id: u64) -> bool {
+        self.away_paused.contains(&id)
+    }
+
+    /// Starts the enabled autostart timers that aren't running yet, returns their names
+    pub fn autostart(&mut self) -> Vec<String> {
+        let names: Vec<_> = self
+            .list
+            .iter()
+            .filter(|t| t.autostart && t.enabled && !t.state.is_active())
+            .map(|t| t.name.clone())
+            .collect();
+        for name in &names {
+            self.start(name, StartSource::Autostart);
+        }
+        names
+    }
+
+    pub fn restart_last(&mut self) {
+        if let Some(name) = self.last_started.take() {
+            self.start(&name, StartSource::RestartLast);
+        }
+    }
+
+    /// Replaces the system timers of `source` with `wanted` (name and remaining time),
+    /// each of them running from now. Names already taken by other timers are skipped.
+    pub fn sync_system(&mut self, source: &'static str, wanted: &[(String, Duration)]) {
+        self.list
+            .retain(|t| t.system != Some(source) || wanted.iter().any(|(name, _)| *name == t.name));
+        let (now, wall) = (self.now(), self.clock.wall());
+        for (name, remaining) in wanted {
+            let timer = match self.list.id_of(name) {
+                Some(id) if self.list.get(id).is_some_and(|t| t.system == Some(source)) => {
+                    self.list.get_mut(id).expect("Timer to be listed")
+                }
+                Some(_) => continue,
+                None => {
+                    let mut timer = Timer::new(self.next_id(), name.clone(), *remaining);
+                    timer.system = Some(source);
+                    self.list.push(timer)
+                }
+            };
+            timer.duration = *remaining;
+            timer.state = TimerState::start(now, Duration::ZERO);
+            timer.ends_at = Some(wall + *remaining);
+            timer.last_remaining = None;
+        }
+    }
+
+    /// Moves wall clock runs like alarms and resets back to their wall clock time after
+    /// the clock jumped or the machine was in standby, returns the timers that moved
+    pub fn resync_wall_clock(&mut self) -> Vec<String> {
+        let (now, wall) = (self.now(), self.clock.wall());
+        self.resynced = now;
+        let mut moved = Vec::new();
+        for timer in &mut self.list {
+            if timer.resync(now, wall) {
+                timer.last_remaining = None;
+                moved.push(timer.name.clone());
+            }
+        }
+        moved
+    }
+
+    /// Applies every timer's standby mode after a suspend, returns the affected timers
+    pub fn resume_after_suspend(&mut self, away: Duration, counted: Duration) -> Vec<String> {
+        self.record(|| Action::Suspend { away, counted });
+        let mut affected = Vec::new();
+        for timer in &mut self.list {
+            // paused timers don't move either way, wall clock runs are resynced instead
+            if !timer.state.is_active() || timer.state.is_paused() || timer.ends_at.is_some() {
+                continue;
+            }
+            match timer.standby {
+                StandbyMode::Elapse => timer
+                    .state
+                    .shift(|since| since.checked_sub(away - counted).unwrap_or(since)),
+                StandbyMode::Pause => timer
+                    .state
+                    .shift(|since| since.checked_add(counted).unwrap_or(since)),
+            }
+            affected.push(timer.name.clone());
+        }
+        affected
+    }
+
+    /// Advances the state of all timers, independent of whether anything is rendered
+    pub fn tick(&mut self) -> Vec<TimerEvent> {
+        if self.now().saturating_duration_since(self.resynced) >= WALL_RESYNC {
+            for name in self.resync_wall_clock() {
+                log::info!("Moved {} back to its wall clock time", name);
+            }
+        }
+        let now = self.now();
+        let wall = self.clock.wall();
+        if self.clock.scale() != 1.0 {
+            for timer in &mut self.list {
+                timer.follow_wall(now, wall);
+            }
+        }
+        let mut events = std::mem::take(&mut self.pending);
+        let mut pulled = false;
+        self.list.retain(|t| match t.system {
+            Some(QUICK | INSTANCE | PULL | WVW) => t.state.is_active(),
+            _ => true,
+        });
+        if self.list.iter().any(|t| t.instance_of.is_some()) {
+            let originals: Vec<u64> = self
+                .list
+                .iter()
+                .filter(|t| t.allows_instances())
+                .map(|t| t.id)
+                .collect();
+            self.list
+                .retain(|t| t.instance_of.is_none_or(|id| originals.contains(&id)));
+        }
+        for t in &mut self.list {
+            if let Some(ran) = t.capped(now) {
+                log::info!("Stopped {} after its longest run", t.name);
+                t.stop();
+                t.last_remaining = None;
+                events.push(TimerEvent::Stopped(t.id, ran));
+                continue;
+            }
+            if t.hold_over(now) {
+                t.stop();
+                t.last_remaining = None;
+            }
+            if t.wants_rearm(now) {
+                t.start(now, self.epoch, wall);
+                t.last_remaining = None;
+            }
+            let rest = t.remaining(now);
+            if let Some(mark) = t.crossed_mark(rest) {
+                events.push(TimerEvent::Mark(t.id, mark));
+            }
+            for channel in t.crossed_leads(rest) {
+                events.push(TimerEvent::Warn(t.id, channel));
+            }
+            if let Some(step) = t.entered_step(rest) {
+                events.push(TimerEvent::Step(t.id, step));
+            }
+            if t.became_possible(rest) {
+                events.push(TimerEvent::Possible(t.id));
+            }
+            t.last_remaining = rest;
+            if t.state.tick(now, t.run_duration()) {
+                t.finished_at = Some(now);
+                events.push(TimerEvent::Finished(t.id));
+                pulled |= t.system == Some(PULL);
+            }
+        }
+        if let Some(name) = self.pull_then.take_if(|_| pulled) {
+            self.start(&name, StartSource::Pull);
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alarm::Repeat;
+    use crate::clock::ManualClock;
+
+    const SEC: Duration = Duration::from_secs(1);
+    const MS: Duration = Duration::from_millis(1);
+
+    fn timers(clock: &ManualClock) -> Timers {
+        let mut timers = Timers::with_clock(Box::new(clock.clone()));
+        timers.add("a".to_string(), 10 * SEC);
+        timers
+    }
+
+    // start events are covered by their own test
+    fn tick(timers: &mut Timers) -> Vec<TimerEvent> {
+        let mut events = timers.tick();
+        events.retain(|e| !matches!(e, TimerEvent::Started(..)));
+        events
+    }
+
+    fn timer(timers: &mut Timers) -> &mut Timer {
+        timers.find_by_name_mut("a").expect("timer a to exist")
+    }
+
+    #[test]
+    fn finishes_once() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timers.start("a", StartSource::Keybind);
+        clock.advance(9 * SEC);
+        assert!(tick(&mut timers).is_empty());
+        assert_eq!(timer(&mut timers).remaining(clock.now()), Some(SEC));
+        clock.advance(SEC);
+        assert_eq!(tick(&mut timers), vec![TimerEvent::Finished(1)]);
+        clock.advance(SEC);
+        assert!(tick(&mut timers).is_empty());
+        assert_eq!(
+            timer(&mut timers).remaining(clock.now()),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn start_is_reported_with_the_next_tick() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timers.start("a", StartSource::Keybind);
+        timers.start("missing", StartSource::Keybind);
+        assert_eq!(
+            timers.tick(),
+            vec![TimerEvent::Started(1, StartSource::Keybind)]
+        );
+        assert!(timers.tick().is_empty());
+    }
+
+    #[test]
+    fn start_source_is_tracked() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timers.start("a", StartSource::Api);
+        assert_eq!(timer(&mut timers).started_by, Some(StartSource::Api));
+        timers.restart_last();
+        assert_eq!(
+            timer(&mut timers).started_by,
+            Some(StartSource::RestartLast)
+        );
+        timers.stop("a");
+        assert_eq!(timer(&mut timers).started_by, None);
+    }
+
+    #[test]
+    fn idle_timer_never_finishes() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        clock.advance(100 * SEC);
+        assert!(tick(&mut timers).is_empty());
+        assert_eq!(timer(&mut timers).remaining(clock.now()), None);
+    }
+
+    #[test]
+    fn count_in_delays_the_run() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timer(&mut timers).delay = 3 * SEC;
+        timers.start("a", StartSource::Keybind);
+        clock.advance(SEC);
+        let now = clock.now();
+        assert_eq!(timer(&mut timers).count_in(now), Some(2 * SEC));
+        assert_eq!(timer(&mut timers).remaining(now), Some(10 * SEC));
+        clock.advance(2 * SEC);
+        assert_eq!(timer(&mut timers).count_in(clock.now()), None);
+        clock.advance(9 * SEC);
+        assert!(tick(&mut timers).is_empty());
+        clock.advance(SEC);
+        assert_eq!(tick(&mut timers).len(), 1);
+        // one no clock can reach starts right away instead of panicking
+        let state = TimerState::start(clock.now(), Duration::MAX);
+        assert_eq!(state.count_in(clock.now()), None);
+    }
+
+    #[test]
+    fn offset_moves_the_start() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timer(&mut timers).offset_ms = 800;
+        timers.start("a", StartSource::Keybind);
+        let remaining = |timers: &mut Timers| timer(timers).remaining(clock.now());
+        assert_eq!(remaining(&mut timers), Some(9200 * MS));
+        clock.advance(9200 * MS);
+        assert_eq!(tick(&mut timers).len(), 1);
+        timer(&mut timers).offset_ms = -500;
+        timers.start("a", StartSource::Keybind);
+        clock.advance(500 * MS);
+        assert_eq!(remaining(&mut timers), Some(10 * SEC));
+    }
+
+    #[test]
+    fn instances_run_next_to_each_other() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timer(&mut timers).instances = true;
+        timers.press("a");
+        clock.advance(4 * SEC);
+        timers.press("a");
+        assert_eq!(timers.list.len(), 2);
+        let instance = timers.find_by_name_mut("a #2").unwrap();
+        assert_eq!(instance.instance_of, Some(1));
+        assert_eq!(instance.system, Some(INSTANCE));
+        assert_eq!(instance.remaining(clock.now()), Some(10 * SEC));
+        let instance = timers.find_by_name("a #2").unwrap();
+        assert_eq!(timers.history_name(instance).as_deref(), Some("a"));
+        clock.advance(6 * SEC);
+        assert_eq!(tick(&mut timers), vec![TimerEvent::Finished(1)]);
+        clock.advance(4 * SEC);
+        let finished = tick(&mut timers);
+        assert_eq!(finished.len(), 1);
+        assert_ne!(finished[0], TimerEvent::Finished(1));
+        // gone the tick after its hold, the timer itself stays
+        clock.advance(QUICK_HOLD);
+        tick(&mut timers);
+        tick(&mut timers);
+        assert_eq!(timers.list.len(), 1);
+        timers.press("a");
+        timers.press("a");
+        timer(&mut timers).instances = false;
+        tick(&mut timers);
+        assert_eq!(timers.list.len(), 1);
+    }
+
+    #[test]
+    fn lookups_follow_changes_to_the_list() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        let b = timers.add("b".to_string(), 10 * SEC).id;
+        assert_eq!(timers.get(b).map(|t| t.name.as_str()), Some("b"));
+        assert!(timers.move_by_one(b, true));
+        assert_eq!(timers.list.position(b), Some(0));
+        assert!(timers.remove(1).is_some());
+        assert!(timers.find_by_name_mut("a").is_none());
+        assert_eq!(timers.find_by_name("b").map(|t| t.id), Some(b));
+        timers.list.push(Timer::new(7, "a".to_string(), 10 * SEC));
+        assert_eq!(timers.find_by_name("a").map(|t| t.id), Some(7));
+    }
+
+    #[test]
+    fn finished_timers_restart_instead_of_adding_runs() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timer(&mut timers).instances = true;
+        timers.press("a");
+        clock.advance(10 * SEC);
+        assert_eq!(tick(&mut timers), vec![TimerEvent::Finished(1)]);
+        assert_eq!(timers.press_outcome("a"), Press::Start);
+        timers.press("a");
+        assert_eq!(timers.list.len(), 1);
+        assert_eq!(timer(&mut timers).remaining(clock.now()), Some(10 * SEC));
+    }
+
+    #[test]
+    fn pull_beeps_and_starts_the_encounter() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        assert!(timers.toggle_pull(10 * SEC, Some("a".to_string())));
+        let pull = timers.find_by_name_mut("Pull").unwrap();
+        assert_eq!(pull.countdown_left(clock.now()), Some(10 * SEC));
+        let id = pull.id;
+        tick(&mut timers);
+        let mut beeps = 0;
+        for _ in 0..9 {
+            clock.advance(SEC);
+            beeps += tick(&mut timers)
+                .iter()
+                .filter(|e| matches!(e, TimerEvent::Mark(..)))
+                .count();
+        }
+        assert_eq!(beeps, 5);
+        assert!(!timer(&mut timers).state.is_active());
+        clock.advance(SEC);
+        assert_eq!(tick(&mut timers), vec![TimerEvent::Finished(id)]);
+        assert_eq!(timer(&mut timers).started_by, Some(StartSource::Pull));
+        // pressed again while it counts down it is called off
+        timers.stop("a");
+        clock.advance(QUICK_HOLD);
+        tick(&mut timers);
+        assert!(timers.toggle_pull(10 * SEC, Some("a".to_string())));
+        assert!(!timers.toggle_pull(10 * SEC, Some("a".to_string())));
+        clock.advance(10 * SEC);
+        tick(&mut timers);
+        assert!(!timer(&mut timers).state.is_active());
+    }
+
+    #[test]
+    fn wvw_timers_restart_and_go_away() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        let id = timers.start_wvw("RI Bay", 300 * SEC);
+        clock.advance(100 * SEC);
+        assert_eq!(timers.start_wvw("RI Bay", 300 * SEC), id);
+        let ri = timers.get(id).unwrap();
+        assert_eq!(ri.remaining(clock.now()), Some(300 * SEC));
+        assert_eq!(timers.start_wvw("a", 300 * SEC), 1);
+        assert_eq!(timer(&mut timers).system, None);
+        clock.advance(300 * SEC);
+        assert_eq!(tick(&mut timers), vec![TimerEvent::Finished(id)]);
+        clock.advance(QUICK_HOLD);
+        tick(&mut timers);
+        tick(&mut timers);
+        assert!(timers.get(id).is_none());
+    }
+
+    #[test]
+    fn pause_freezes_remaining_time() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timers.start("a", StartSource::Keybind);
+        clock.advance(4 * SEC);
+        timer(&mut timers).toggle_pause(clock.now());
+        clock.advance(60 * SEC);
+        assert!(tick(&mut timers).is_empty());
+        assert_eq!(timer(&mut timers).remaining(clock.now()), Some(6 * SEC));
+        timer(&mut timers).toggle_pause(clock.now());
+        clock.advance(5 * SEC);
+        assert!(tick(&mut timers).is_empty());
+        clock.advance(SEC);
+        assert_eq!(tick(&mut timers).len(), 1);
+    }
+
+    #[test]
+    fn pause_during_count_in() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timer(&mut timers).delay = 3 * SEC;
+        timers.start("a", StartSource::Keybind);
+        clock.advance(SEC);
+        timer(&mut timers).toggle_pause(clock.now());
+        clock.advance(60 * SEC);
+        assert_eq!(timer(&mut timers).count_in(clock.now()), Some(2 * SEC));
+        timer(&mut timers).toggle_pause(clock.now());
+        clock.advance(2 * SEC);
+        assert_eq!(timer(&mut timers).count_in(clock.now()), None);
+        assert_eq!(timer(&mut timers).remaining(clock.now()), Some(10 * SEC));
+    }
+
+    #[test]
+    fn finished_timer_cannot_be_paused() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timers.start("a", StartSource::Keybind);
+        clock.advance(10 * SEC);
+        tick(&mut timers);
+        timer(&mut timers).toggle_pause(clock.now());
+        assert!(matches!(
+            timer(&mut timers).state,
+            TimerState::Finished { .. }
+        ));
+    }
+
+    #[test]
+    fn shortening_a_running_timer_finishes_it() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timers.start("a", StartSource::Keybind);
+        clock.advance(5 * SEC);
+        assert!(tick(&mut timers).is_empty());
+        timer(&mut timers).duration = 3 * SEC;
+        assert_eq!(tick(&mut timers).len(), 1);
+    }
+
+    #[test]
+    fn lengthening_a_running_timer_extends_it() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timers.start("a", StartSource::Keybind);
+        clock.advance(5 * SEC);
+        timer(&mut timers).duration = 20 * SEC;
+        clock.advance(10 * SEC);
+        assert!(tick(&mut timers).is_empty());
+        assert_eq!(timer(&mut timers).remaining(clock.now()), Some(5 * SEC));
+    }
+
+    #[test]
+    fn lengthening_a_finished_timer_rearms_it() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timers.start("a", StartSource::Keybind);
+        clock.advance(10 * SEC);
+        assert_eq!(tick(&mut timers).len(), 1);
+        timer(&mut timers).duration = 15 * SEC;
+        assert!(tick(&mut timers).is_empty());
+        assert!(matches!(
+            timer(&mut timers).state,
+            TimerState::Running { .. }
+        ));
+        clock.advance(5 * SEC);
+        assert_eq!(tick(&mut timers).len(), 1);
+    }
+
+    #[test]
+    fn restart_resets_a_finished_timer() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timers.start("a", StartSource::Keybind);
+        clock.advance(10 * SEC);
+        assert_eq!(tick(&mut timers).len(), 1);
+        timers.restart_last();
+        assert_eq!(timer(&mut timers).remaining(clock.now()), Some(10 * SEC));
+        clock.advance(10 * SEC);
+        assert_eq!(tick(&mut timers).len(), 1);
+    }
+
+    #[test]
+    fn exclusive_start_stops_the_others() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timers.add("b".to_string(), 10 * SEC);
+        timers.add("c".to_string(), 10 * SEC);
+        for name in ["a", "b"] {
+            timers.find_by_name_mut(name).unwrap().exclusive = "boss".to_string();
+        }
+        timers.start("a", StartSource::Keybind);
+        timers.start("c", StartSource::Keybind);
+        clock.advance(2 * SEC);
+        timers.start("b", StartSource::Keybind);
+        let active = |timers: &mut Timers, name| timers.find_by_name_mut(name).unwrap().state;
+        assert_eq!(active(&mut timers, "a"), TimerState::Idle);
+        assert!(active(&mut timers, "b").is_active());
+        assert!(active(&mut timers, "c").is_active());
+        assert_eq!(tick(&mut timers), vec![TimerEvent::Stopped(1, 2 * SEC)]);
+    }
+
+    #[test]
+    fn countdown_covers_the_final_seconds() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timer(&mut timers).countdown = 3 * SEC;
+        timers.start("a", StartSource::Keybind);
+        clock.advance(6 * SEC);
+        assert_eq!(timer(&mut timers).countdown_left(clock.now()), None);
+        clock.advance(2 * SEC);
+        assert_eq!(
+            timer(&mut timers).countdown_left(clock.now()),
+            Some(2 * SEC)
+        );
+        timers.toggle_pause("a");
+        assert_eq!(timer(&mut timers).countdown_left(clock.now()), None);
+        timers.toggle_pause("a");
+        clock.advance(2 * SEC);
+        tick(&mut timers);
+        assert_eq!(timer(&mut timers).countdown_left(clock.now()), None);
+    }
+
+    #[test]
+    fn fade_grows_towards_zero() {
+        let fade = Fade {
+            from: 10 * SEC,
+            style: FadeStyle::Pulse,
+        };
+        assert_eq!(fade.urgency(11 * SEC), None);
+        assert_eq!(fade.urgency(10 * SEC), Some(0.0));
+        assert_eq!(fade.urgency(5 * SEC), Some(0.5));
+        assert_eq!(fade.urgency(Duration::ZERO), Some(1.0));
+    }
+
+    #[test]
+    fn next_finishes_by_remaining_time() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timers.add("b".to_string(), 5 * SEC);
+        timers.add("c".to_string(), 20 * SEC);
+        timers.add("idle".to_string(), SEC);
+        for name in ["a", "b", "c"] {
+            timers.start(name, StartSource::Keybind);
+        }
+        timers.toggle_pause("c");
+        clock.advance(SEC);
+        let next: Vec<_> = timers
+            .next_finishes()
+            .into_iter()
+            .map(|(t, rest)| (t.name.as_str(), rest))
+            .collect();
+        assert_eq!(next, [("b", 4 * SEC), ("a", 9 * SEC)]);
+    }
+
+    #[test]
+    fn bands_color_by_remaining_time() {
+        let mut timer = Timer::new(1, "a".to_string(), 60 * SEC);
+        assert_eq!(timer.band_color(45 * SEC), None);
+        timer.bands = ColorBand::traffic_light();
+        let [green, yellow, red] = [0, 1, 2].map(|i| Some(timer.bands[i].color));
+        assert_eq!(timer.band_color(45 * SEC), green);
+        assert_eq!(timer.band_color(30 * SEC), green);
+        assert_eq!(timer.band_color(29 * SEC), yellow);
+        assert_eq!(timer.band_color(Duration::ZERO), red);
+        timer.bands.remove(2);
+        assert_eq!(timer.band_color(5 * SEC), None);
+    }
+
+    #[test]
+    fn laps_record_time_into_the_run() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timer(&mut timers).delay = 2 * SEC;
+        timers.start("a", StartSource::Keybind);
+        // during the count-in
+        timers.lap("a");
+        clock.advance(5 * SEC);
+        timers.lap("a");
+        timers.toggle_pause("a");
+        clock.advance(SEC);
+        timers.lap("a");
+        timers.toggle_pause("a");
+        clock.advance(2 * SEC);
+        timers.lap("a");
+        assert_eq!(timer(&mut timers).laps, vec![3 * SEC, 5 * SEC]);
+        timers.stop("a");
+        assert_eq!(timer(&mut timers).laps.len(), 2);
+        timers.start("a", StartSource::Keybind);
+        assert!(timer(&mut timers).laps.is_empty());
+    }
+
+    #[test]
+    fn away_pauses_running_timers() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timers.add("b".to_string(), 10 * SEC);
+        timers.start("a", StartSource::Keybind);
+        timers.start("b", StartSource::Keybind);
+        timers.toggle_pause("b");
+        clock.advance(2 * SEC);
+        timers.set_away(true);
+        clock.advance(5 * SEC);
+        // started while away
+        timers.add("c".to_string(), 10 * SEC);
+        timers.start("c", StartSource::Keybind);
+        timers.set_away(true);
+        let id = |timers: &mut Timers, name: &str| timers.find_by_name_mut(name).unwrap().id;
+        let (a, b) = (id(&mut timers, "a"), id(&mut timers, "b"));
+        assert!(timers.is_frozen(a));
+        assert!(!timers.is_frozen(b));
+        clock.advance(5 * SEC);
+        timers.set_away(false);
+        assert!(!timers.is_frozen(a));
+        clock.advance(SEC);
+        let remaining = |timers: &mut Timers, name: &str| {
+            let now = clock.now();
+            timers.find_by_name_mut(name).unwrap().remaining(now)
+        };
+        assert_eq!(remaining(&mut timers, "a"), Some(7 * SEC));
+        // paused by hand stays paused
+        assert!(timers.find_by_name_mut("b").unwrap().state.is_paused());
+        assert_eq!(remaining(&mut timers, "c"), Some(9 * SEC));
+    }
+
+    #[test]
+    fn quick_timer_is_removed_when_done() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        let id = timers.start_quick(3 * SEC);
+        assert_eq!(
+            timers.tick(),
+            vec![TimerEvent::Started(id, StartSource::Keybind)]
+        );
+        assert_eq!(
+            timers.get(id).map(|t| t.name.as_str()),
+            Some("Quick timer 1")
+        );
+        clock.advance(3 * SEC);
+        assert_eq!(tick(&mut timers), vec![TimerEvent::Finished(id)]);
+        clock.advance(QUICK_HOLD);
+        tick(&mut timers);
+        tick(&mut timers);
+        assert!(timers.get(id).is_none());
+        assert_eq!(timers.list.len(), 1);
+    }
+
+    #[test]
+    fn runs_stop_at_their_cap() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timer(&mut timers).duration = 3600 * SEC;
+        timer(&mut timers).max_run = Some(15 * SEC);
+        timers.start("a", StartSource::Ui);
+        tick(&mut timers);
+        clock.advance(14 * SEC);
+        assert!(tick(&mut timers).is_empty());
+        clock.advance(2 * SEC);
+        assert_eq!(tick(&mut timers), vec![TimerEvent::Stopped(1, 15 * SEC)]);
+        assert_eq!(timer(&mut timers).state, TimerState::Idle);
+        assert!(tick(&mut timers).is_empty());
+    }
+
+    #[test]
+    fn finished_timer_hides_after_hold() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timer(&mut timers).hold = Some(5 * SEC);
+        timers.start("a", StartSource::Keybind);
+        clock.advance(10 * SEC);
+        assert_eq!(tick(&mut timers), vec![TimerEvent::Finished(1)]);
+        clock.advance(4 * SEC);
+        assert!(tick(&mut timers).is_empty());
+        assert!(timer(&mut timers).state.is_active());
+        clock.advance(SEC);
+        assert!(tick(&mut timers).is_empty());
+        assert_eq!(timer(&mut timers).state, TimerState::Idle);
+        assert_eq!(timer(&mut timers).remaining(clock.now()), None);
+    }
+
+    #[test]
+    fn idle_windows_hide_until_started() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        let hidden = |timers: &mut Timers, default| timer(timers).hidden_idle(clock.now(), default);
+        timers.start("a", StartSource::Keybind);
+        clock.advance(10 * SEC);
+        tick(&mut timers);
+        clock.advance(59 * SEC);
+        assert!(!hidden(&mut timers, 60 * SEC));
+        clock.advance(SEC);
+        assert!(hidden(&mut timers, 60 * SEC));
+        assert!(!hidden(&mut timers, Duration::ZERO));
+        // its own time wins, zero never hides
+        timer(&mut timers).hide_idle = Some(Duration::ZERO);
+        assert!(!hidden(&mut timers, 60 * SEC));
+        timer(&mut timers).hide_idle = Some(30 * SEC);
+        assert!(hidden(&mut timers, Duration::ZERO));
+        timers.start("a", StartSource::Keybind);
+        assert!(!hidden(&mut timers, 60 * SEC));
+        timers.toggle_pause("a");
+        clock.advance(30 * SEC);
+        assert!(hidden(&mut timers, 60 * SEC));
+    }
+
+    #[test]
+    fn overtime_counts_past_zero() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timer(&mut timers).overtime = true;
+        timers.start("a", StartSource::Keybind);
+        clock.advance(10 * SEC);
+        assert_eq!(timer(&mut timers).overtime_elapsed(clock.now()), None);
+        clock.advance(3 * SEC);
+        assert_eq!(
+            timer(&mut timers).overtime_elapsed(clock.now()),
+            Some(3 * SEC)
+        );
+        // stopped once past its hold
+        timer(&mut timers).hold = Some(15 * SEC);
+        tick(&mut timers);
+        clock.advance(12 * SEC);
+        tick(&mut timers);
+        assert_eq!(timer(&mut timers).state, TimerState::Idle);
+    }
+
+    #[test]
+    fn standby_modes() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timers.add("b".to_string(), 10 * SEC);
+        timers
+            .find_by_name_mut("b")
+            .expect("timer b to exist")
+            .standby = StandbyMode::Pause;
+        timers.start("a", StartSource::Keybind);
+        timers.start("b", StartSource::Keybind);
+        // 2s of the 5s away were seen by the monotonic clock
+        clock.advance(2 * SEC);
+        let affected = timers.resume_after_suspend(5 * SEC, 2 * SEC);
+        assert_eq!(affected, vec!["a", "b"]);
+        let now = clock.now();
+        assert_eq!(timer(&mut timers).remaining(now), Some(5 * SEC));
+        assert_eq!(
+            timers.find_by_name_mut("b").unwrap().remaining(now),
+            Some(10 * SEC)
+        );
+    }
+
+    #[test]
+    fn wall_clock_runs_follow_the_wall_clock() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timers.sync_system("reset", &[("Reset".to_string(), 3600 * SEC)]);
+        let reset = |timers: &mut Timers| {
+            let now = timers.now();
+            let reset = timers.find_by_name_mut("Reset").expect("Reset to exist");
+            reset.remaining(now)
+        };
+        // `Instant`s missed 10 minutes of standby
+        clock.advance(SEC);
+        clock.shift_wall(600 * SEC);
+        assert!(timers.resume_after_suspend(601 * SEC, SEC).is_empty());
+        assert_eq!(timers.resync_wall_clock(), vec!["Reset"]);
+        assert_eq!(reset(&mut timers), Some(2999 * SEC));
+
+        // and slow drift is caught up with by the tick
+        clock.shift_wall(5 * SEC);
+        clock.advance(WALL_RESYNC);
+        tick(&mut timers);
+        assert_eq!(reset(&mut timers), Some(2934 * SEC));
+    }
+
+    #[test]
+    fn wall_clock_runs_ignore_the_time_scale() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timers.sync_system("reset", &[("Reset".to_string(), 3600 * SEC)]);
+        timers.start("a", StartSource::Keybind);
+        timers.set_time_scale(2.0);
+        clock.advance(2 * SEC);
+        tick(&mut timers);
+        let now = timers.now();
+        assert_eq!(timer(&mut timers).remaining(now), Some(6 * SEC));
+        let reset = timers.find_by_name_mut("Reset").expect("Reset to exist");
+        assert_eq!(reset.remaining(now), Some(3598 * SEC));
+    }
+
+    #[test]
+    fn snapped_timers_tick_in_sync() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timers.add("b".to_string(), 10 * SEC);
+        for timer in &mut timers.list {
+            timer.snap = StartSnap::Second;
+        }
+        clock.advance(Duration::from_millis(1200));
+        timers.start("a", StartSource::Keybind);
+        clock.advance(Duration::from_millis(300));
+        timers.start("b", StartSource::Keybind);
+        clock.advance(Duration::from_millis(500));
+        let now = clock.now();
+        assert_eq!(timer(&mut timers).remaining(now), Some(10 * SEC));
+        assert_eq!(
+            timers.find_by_name_mut("b").unwrap().remaining(now),
+            Some(10 * SEC)
+        );
+        clock.advance(10 * SEC);
+        assert_eq!(tick(&mut timers).len(), 2);
+    }
+
+    #[test]
+    fn wall_clock_snap_waits_for_the_boundary() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timer(&mut timers).snap = StartSnap::Minute;
+        clock.advance(80 * SEC);
+        timers.start("a", StartSource::Keybind);
+        assert_eq!(timer(&mut timers).count_in(clock.now()), Some(40 * SEC));
+        clock.advance(40 * SEC);
+        assert_eq!(timer(&mut timers).remaining(clock.now()), Some(10 * SEC));
+    }
+
+    #[test]
+    fn keybind_actions() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        let press = |timers: &mut Timers, action| {
+            timer(timers).key_action = action;
+            timers.press("a");
+        };
+        assert_eq!(timers.press_outcome("a"), Press::Start);
+        press(&mut timers, KeyAction::Toggle);
+        assert!(timer(&mut timers).state.is_active());
+        assert_eq!(timers.press_outcome("a"), Press::Stop);
+        press(&mut timers, KeyAction::Toggle);
+        assert_eq!(timer(&mut timers).state, TimerState::Idle);
+
+        press(&mut timers, KeyAction::StartIfIdle);
+        clock.advance(4 * SEC);
+        press(&mut timers, KeyAction::StartIfIdle);
+        assert_eq!(timer(&mut timers).remaining(clock.now()), Some(6 * SEC));
+        assert_eq!(timers.press_outcome("a"), Press::Nothing);
+        timer(&mut timers).key_action = KeyAction::Restart;
+        assert_eq!(timers.press_outcome("a"), Press::Restart);
+
+        press(&mut timers, KeyAction::AddTime);
+        assert_eq!(timer(&mut timers).remaining(clock.now()), Some(16 * SEC));
+        clock.advance(20 * SEC);
+        tick(&mut timers);
+        press(&mut timers, KeyAction::AddTime);
+        assert_eq!(timer(&mut timers).remaining(clock.now()), Some(10 * SEC));
+        assert_eq!(tick(&mut timers), vec![]);
+
+        press(&mut timers, KeyAction::Restart);
+        assert_eq!(timer(&mut timers).remaining(clock.now()), Some(10 * SEC));
+    }
+
+    #[test]
+    fn group_keybind_starts_and_stops_the_group() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timers.add("b".to_string(), SEC);
+        timers.add("other".to_string(), SEC);
+        for timer in timers.list.iter_mut().take(2) {
+            timer.group = "Vale".to_string();
+        }
+        let active = |timers: &Timers| {
+            timers
+                .list
+                .iter()
+                .map(|t| t.state.is_active())
+                .collect::<Vec<_>>()
+        };
+        timers.press_group("Vale");
+        assert_eq!(active(&timers), [true, true, false]);
+        timers.stop("a");
+        timers.press_group("Vale");
+        assert_eq!(active(&timers), [false, false, false]);
+        timers.press_group("Nothing");
+        assert_eq!(active(&timers), [false, false, false]);
+    }
+
+    #[test]
+    fn lockout_ignores_presses_after_finishing() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timer(&mut timers).lockout = 3 * SEC;
+        timers.press("a");
+        clock.advance(10 * SEC);
+        assert_eq!(tick(&mut timers), vec![TimerEvent::Finished(1)]);
+        clock.advance(2 * SEC);
+        assert_eq!(timer(&mut timers).locked_out(clock.now()), Some(SEC));
+        timers.press("a");
+        assert_eq!(
+            timer(&mut timers).remaining(clock.now()),
+            Some(Duration::ZERO)
+        );
+        // anything but the keybind still starts it
+        timers.start("a", StartSource::Overlay);
+        assert_eq!(timer(&mut timers).locked_out(clock.now()), None);
+        clock.advance(10 * SEC);
+        tick(&mut timers);
+        clock.advance(3 * SEC);
+        timers.press("a");
+        assert_eq!(timer(&mut timers).remaining(clock.now()), Some(10 * SEC));
+    }
+
+    #[test]
+    fn timeline_steps_are_reported() {
+        use crate::timeline::Step;
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        let step = |label: &str, secs| Step {
+            label: label.to_string(),
+            duration: secs * SEC,
+        };
+        timer(&mut timers).timeline = Some(Timeline {
+            steps: vec![step("Pull", 4), step("Split", 2)],
+        });
+        timers.start("a", StartSource::Keybind);
+        assert!(tick(&mut timers).is_empty());
+        assert_eq!(timer(&mut timers).step(clock.now()), Some((0, 4 * SEC)));
+        clock.advance(5 * SEC);
+        assert_eq!(tick(&mut timers), vec![TimerEvent::Step(1, 1)]);
+        assert_eq!(timer(&mut timers).step(clock.now()), Some((1, SEC)));
+        clock.advance(SEC);
+        assert_eq!(tick(&mut timers), vec![TimerEvent::Finished(1)]);
+        assert_eq!(timer(&mut timers).duration, 6 * SEC);
+    }
+
+    #[test]
+    fn variable_timers_become_possible() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timer(&mut timers).earliest = Some(6 * SEC);
+        timers.start("a", StartSource::Keybind);
+        tick(&mut timers);
+        let until = |timers: &mut Timers| {
+            let now = clock.now();
+            let timer = timer(timers);
+            timer.until_possible(timer.remaining(now).unwrap())
+        };
+        assert_eq!(until(&mut timers), Some(6 * SEC));
+        clock.advance(5 * SEC);
+        assert_eq!(tick(&mut timers), vec![]);
+        assert_eq!(until(&mut timers), Some(SEC));
+        clock.advance(SEC);
+        assert_eq!(tick(&mut timers), vec![TimerEvent::Possible(1)]);
+        assert_eq!(until(&mut timers), None);
+        clock.advance(4 * SEC);
+        assert_eq!(tick(&mut timers), vec![TimerEvent::Finished(1)]);
+    }
+
+    #[test]
+    fn duplicates_are_idle_under_a_new_name() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timer(&mut timers).sound = true;
+        timers.start("a", StartSource::Keybind);
+        let copy = timers.duplicate(1).expect("a to be copied");
+        assert_eq!((copy.id, copy.name.as_str()), (2, "a (copy)"));
+        assert!(copy.sound);
+        assert_eq!(copy.state, TimerState::Idle);
+        assert_eq!(
+            timers.duplicate(1).map(|t| t.name.as_str()),
+            Some("a (copy 2)")
+        );
+        assert!(timers.duplicate(7).is_none());
+    }
+
+    #[test]
+    fn keybind_ids_are_kept_and_unique() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        let kept = timer(&mut timers).keybind_id.clone();
+        let copy = timers
+            .duplicate(1)
+            .expect("a to be copied")
+            .keybind_id
+            .clone();
+        assert_ne!(copy, kept);
+        let mut from_pack = Timer::new(3, "p".to_string(), SEC);
+        from_pack.pack = Some("pack.json".to_string());
+        from_pack.keybind_id.clear();
+        timers.list.push(from_pack);
+        timers.list.push(Timer {
+            keybind_id: kept.clone(),
+            ..Timer::new(4, "b".to_string(), SEC)
+        });
+        timers.list.push(Timer {
+            keybind_id: "boss-cm".to_string(),
+            ..Timer::new(5, "c".to_string(), SEC)
+        });
+        assert_eq!(
+            timers.assign_keybind_ids(),
+            [(3, String::new()), (5, "boss-cm".to_string())]
+        );
+        assert_eq!(timer(&mut timers).keybind_id, kept);
+        assert_eq!(timers.get(3).unwrap().keybind_id, pack_keybind_id("p"));
+        assert_ne!(timers.get(4).unwrap().keybind_id, kept);
+        assert!(is_keybind_id(&timers.get(5).unwrap().keybind_id));
+        assert!(timers.assign_keybind_ids().is_empty());
+    }
+
+    #[test]
+    fn imports_are_merged_as_chosen() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timers.start("a", StartSource::Keybind);
+        let imported = |name: &str, secs| Timer::new(0, name.to_string(), secs * SEC);
+        let merged = timers.merge_imported(vec![
+            (imported("a", 20), Merge::Add),
+            (imported("b", 30), Merge::Skip),
+            (imported("c", 40), Merge::Add),
+        ]);
+        assert_eq!(merged.iter().map(|(id, _)| *id).collect::<Vec<_>>(), [2, 3]);
+        assert_eq!(timers.get(2).unwrap().name, "a (copy)");
+        assert!(!timers.contains_name("b"));
+        let merged = timers.merge_imported(vec![(imported("a", 50), Merge::Overwrite)]);
+        let (id, old) = &merged[0];
+        assert_eq!(*id, 1);
+        assert_eq!(old.as_ref().map(|t| t.duration), Some(10 * SEC));
+        assert_eq!(old.as_ref().map(|t| t.cycles), Some(1));
+        let a = timer(&mut timers);
+        assert_eq!((a.duration, a.cycles), (50 * SEC, 1));
+        assert_eq!(a.state, TimerState::Idle);
+        // a pack's timer stays, the import is added next to it
+        timer(&mut timers).pack = Some("p".to_string());
+        let merged = timers.merge_imported(vec![(imported("a", 60), Merge::Overwrite)]);
+        assert_eq!(merged.len(), 1);
+        assert!(merged[0].1.is_none());
+        assert_eq!(timer(&mut timers).duration, 50 * SEC);
+        assert_eq!(timers.get(merged[0].0).unwrap().name, "a (copy 2)");
+    }
+
+    #[test]
+    fn unused_timers_are_suggested() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timers.add("b".to_string(), SEC);
+        timers.seed_last_used();
+        clock.shift_wall(Duration::from_secs(10 * DAY_SECS));
+        timers.start("b", StartSource::Keybind);
+        let names = |timers: &Timers, days| {
+            timers
+                .unused(days)
+                .iter()
+                .map(|t| t.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&timers, 7), ["a"]);
+        assert!(names(&timers, 30).is_empty());
+        timer(&mut timers).set_archived(true);
+        assert!(names(&timers, 7).is_empty());
+        assert!(!timer(&mut timers).enabled);
+        timer(&mut timers).set_archived(false);
+        assert!(timer(&mut timers).enabled);
+    }
+
+    #[test]
+    fn restoring_keeps_packs() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timers.add("pack".to_string(), SEC);
+        timers
+            .find_by_name_mut("pack")
+            .expect("timer pack to exist")
+            .pack = Some("packs/raid.json".to_string());
+        // the id of the pack timer, from when the snapshot was taken
+        let restored = Timer::new(2, "old".to_string(), SEC);
+        let clash = Timer::new(3, "pack".to_string(), SEC);
+        let replaced = timers.restore(vec![restored, clash]);
+        assert_eq!(replaced.len(), 1);
+        assert_eq!(replaced[0].name, "a");
+        let names: Vec<_> = timers.list.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["old", "pack"]);
+        let old = timers.find_by_name("old").expect("timer old to exist");
+        let pack = timers.find_by_name("pack").expect("timer pack to exist");
+        assert!(pack.pack.is_some());
+        assert_ne!(old.id, pack.id);
+    }
+
+    #[test]
+    fn timers_in_order() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timers.add("C".to_string(), 5 * SEC);
+        timers.add("b".to_string(), 20 * SEC);
+        assert!(timers.move_by_one(3, true));
+        assert!(timers.move_by_one(3, true));
+        assert!(!timers.move_by_one(3, true));
+        assert!(!timers.move_by_one(2, false));
+        assert!(!timers.move_by_one(7, false));
+        let names = |order: TimerOrder| {
+            let mut shown: Vec<_> = timers.list.iter().map(|t| (t, t.duration)).collect();
+            order.sort(&mut shown);
+            shown
+                .iter()
+                .map(|(t, _)| t.name.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(TimerOrder::Manual), ["b", "a", "C"]);
+        assert_eq!(names(TimerOrder::Name), ["a", "b", "C"]);
+        assert_eq!(names(TimerOrder::Remaining), ["C", "a", "b"]);
+    }
+
+    #[test]
+    fn subtracting_time_clamps_at_zero() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timers.start("a", StartSource::Keybind);
+        timers.add_time("a", 5 * SEC);
+        timers.subtract_time("a", 8 * SEC);
+        assert_eq!(timer(&mut timers).remaining(clock.now()), Some(7 * SEC));
+        timers.subtract_time("a", 60 * SEC);
+        assert_eq!(
+            timer(&mut timers).remaining(clock.now()),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(tick(&mut timers), vec![TimerEvent::Finished(1)]);
+    }
+
+    #[test]
+    fn stopping_reports_the_run() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timers.start("a", StartSource::Keybind);
+        clock.advance(4 * SEC);
+        timers.stop("a");
+        assert_eq!(tick(&mut timers), vec![TimerEvent::Stopped(1, 4 * SEC)]);
+        // already idle
+        timers.stop("a");
+        assert!(tick(&mut timers).is_empty());
+
+        timer(&mut timers).delay = 5 * SEC;
+        timers.start("a", StartSource::Keybind);
+        clock.advance(4 * SEC);
+        timers.stop("a");
+        assert!(tick(&mut timers).is_empty());
+    }
+
+    #[test]
+    fn disabled_timer_cannot_start() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timers.start("a", StartSource::Keybind);
+        timer(&mut timers).set_enabled(false);
+        assert_eq!(timer(&mut timers).state, TimerState::Idle);
+        timers.restart_last();
+        assert_eq!(timer(&mut timers).state, TimerState::Idle);
+    }
+
+    #[test]
+    fn marks_fire_once_per_run() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timer(&mut timers).marks = vec![5 * SEC, 3 * SEC, 2 * SEC, Duration::ZERO];
+        timers.start("a", StartSource::Keybind);
+        let mut marks = Vec::new();
+        for _ in 0..12 {
+            for event in tick(&mut timers) {
+                if let TimerEvent::Mark(_, mark) = event {
+                    marks.push(mark);
+                }
+            }
+            clock.advance(SEC);
+        }
+        assert_eq!(marks, vec![5 * SEC, 3 * SEC, 2 * SEC]);
+    }
+
+    #[test]
+    fn only_the_lowest_skipped_mark_fires() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timer(&mut timers).marks = vec![8 * SEC, 6 * SEC, 4 * SEC];
+        timers.start("a", StartSource::Keybind);
+        assert!(tick(&mut timers).is_empty());
+        clock.advance(5 * SEC);
+        assert_eq!(tick(&mut timers), vec![TimerEvent::Mark(1, 6 * SEC)]);
+        // restarting doesn't count as crossing anything
+        timers.start("a", StartSource::Keybind);
+        assert!(tick(&mut timers).is_empty());
+    }
+
+    #[test]
+    fn alert_channels_fire_at_their_lead() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timer(&mut timers).leads = AlertLeads {
+            voice: 5 * SEC,
+            flash: 2 * SEC,
+            ..Default::default()
+        };
+        timers.start("a", StartSource::Keybind);
+        let mut events = Vec::new();
+        for _ in 0..12 {
+            events.extend(tick(&mut timers));
+            clock.advance(SEC);
+        }
+        assert_eq!(
+            events,
+            vec![
+                TimerEvent::Warn(1, Channel::Voice),
+                TimerEvent::Warn(1, Channel::Flash),
+                TimerEvent::Finished(1),
+            ]
+        );
+    }
+
+    #[test]
+    fn repeating_alarm_rearms_after_going_off() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timer(&mut timers).alarm = Some(Alarm {
+            hour: 1,
+            minute: 0,
+            utc: true,
+            repeat: Repeat::Daily,
+        });
+        let now = timers.now();
+        // armed without being started, the manual clock starts at midnight UTC
+        assert!(tick(&mut timers).is_empty());
+        assert_eq!(timer(&mut timers).remaining(now), Some(3600 * SEC));
+        clock.advance(3600 * SEC);
+        assert_eq!(tick(&mut timers), vec![TimerEvent::Finished(1)]);
+        clock.advance(ALARM_HOLD);
+        assert!(tick(&mut timers).is_empty());
+        let now = timers.now();
+        assert_eq!(
+            timer(&mut timers).remaining(now),
+            Some(24 * 3600 * SEC - ALARM_HOLD)
+        );
+    }
+
+    #[test]
+    fn stop_and_remove() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timers.start("a", StartSource::Keybind);
+        timers.stop("a");
+        clock.advance(10 * SEC);
+        assert_eq!(
+            tick(&mut timers),
+            vec![TimerEvent::Stopped(1, Duration::ZERO)]
+        );
+        let removed = timers.remove(1).expect("timer to be removed");
+        assert_eq!(removed.name, "a");
+        assert_eq!(timers.last_started, None);
+        timers.restart_last();
+        assert!(timers.list.is_empty());
+    }
+
+    #[test]
+    fn autostarts_idle_timers() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        timers.add("b".to_string(), 10 * SEC);
+        timers.add("c".to_string(), 10 * SEC);
+        for timer in &mut timers.list {
+            timer.autostart = timer.name != "c";
+        }
+        timers.start("b", StartSource::Keybind);
+        clock.advance(SEC);
+        // "b" keeps the run it already has
+        assert_eq!(timers.autostart(), ["a"]);
+        assert_eq!(timer(&mut timers).started_by, Some(StartSource::Autostart));
+        let b = timers.find_by_name_mut("b").expect("timer b to exist");
+        assert_eq!(b.remaining(clock.now()), Some(9 * SEC));
+        let c = timers.find_by_name("c").expect("timer c to exist");
+        assert!(!c.state.is_active());
+    }
+
+    #[test]
+    fn syncs_system_timers() {
+        let clock = ManualClock::new();
+        let mut timers = timers(&clock);
+        let wanted = |names: &[&str]| -> Vec<(String, Duration)> {
+            names.iter().map(|n| (n.to_string(), 60 * SEC)).collect()
+        };
+        timers.sync_system("test", &wanted(&["a", "b", "c"]));
+        timers.sync_system("other", &wanted(&["c", "d"]));
+        let names: Vec<_> = timers
+            .list
+            .iter()
+            .map(|t| (t.name.as_str(), t.system))
+            .collect();
+        // "a" is a configured timer and stays as it is, "c" is taken by the first source
+        assert_eq!(
+            names,
+            vec![
+                ("a", None),
+                ("b", Some("test")),
+                ("c", Some("test")),
+                ("d", Some("other"))
+            ]
+        );
+        assert!(!timer(&mut timers).state.is_active());
+
+        clock.advance(10 * SEC);
+        timers.sync_system("test", &wanted(&["c"]));
+        let names: Vec<_> = timers.list.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "c", "d"]);
+        let c = timers.find_by_name_mut("c").expect("timer c to exist");
+        assert_eq!(c.remaining(clock.now()), Some(60 * SEC));
+    }
+}
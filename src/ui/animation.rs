@@ -0,0 +1,134 @@
+//! Smoothing shared by the overlay, the compact list and the hotbar. Fills and colors
+//! glide to where they jump to, like when time is added or a band is reached, by the
+//! same share of the way per second at any frame rate.
+
+use nexus::imgui::Ui;
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Seconds for a smoothed value to get about two thirds of the way to where it jumped
+const TIME_CONSTANT: f32 = 0.08;
+/// Values not drawn for this long start over at where they are drawn next
+const FORGET: Duration = Duration::from_secs(1);
+
+/// What of a timer is smoothed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Channel {
+    Text,
+    Fill,
+    FillColor,
+}
+
+struct Smoothed {
+    value: [f32; 4],
+    drawn: Instant,
+}
+
+thread_local! {
+    static VALUES: RefCell<HashMap<(u64, Channel), Smoothed>> = RefCell::new(HashMap::new());
+    static PRUNED: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// `value` moved towards `target` for `dt` seconds
+fn approach(value: [f32; 4], target: [f32; 4], dt: f32) -> [f32; 4] {
+    let share = 1.0 - (-dt.max(0.0) / TIME_CONSTANT).exp();
+    mix(value, target, share)
+}
+
+/// `from` blended into `to` by `share`
+pub fn mix(from: [f32; 4], to: [f32; 4], share: f32) -> [f32; 4] {
+    std::array::from_fn(|i| from[i] + (to[i] - from[i]) * share)
+}
+
+/// Where `channel` of timer `id` is drawn this frame, on its way to `target`
+pub fn smooth(ui: &Ui, id: u64, channel: Channel, target: [f32; 4]) -> [f32; 4] {
+    let now = Instant::now();
+    let dt = ui.io().delta_time;
+    VALUES.with_borrow_mut(|values| {
+        if PRUNED.get().is_none_or(|at| now - at >= FORGET) {
+            values.retain(|_, s| now - s.drawn < FORGET);
+            PRUNED.set(Some(now));
+        }
+        let smoothed = values.entry((id, channel)).or_insert(Smoothed {
+            value: target,
+            drawn: now,
+        });
+        smoothed.value = if now - smoothed.drawn >= FORGET {
+            target
+        } else {
+            approach(smoothed.value, target, dt)
+        };
+        smoothed.drawn = now;
+        smoothed.value
+    })
+}
+
+/// `smooth` for a single number, like how full a bar is
+pub fn smooth_value(ui: &Ui, id: u64, channel: Channel, target: f32) -> f32 {
+    smooth(ui, id, channel, [target, 0.0, 0.0, 0.0])[0]
+}
+
+/// How the flash of a timer fades out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Easing {
+    /// On for the whole flash, then off
+    Hard,
+    Linear,
+    #[default]
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    pub const ALL: [Easing; 4] = [Self::Hard, Self::Linear, Self::EaseOut, Self::EaseInOut];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Hard => "Hard (no fade)",
+            Self::Linear => "Linear",
+            Self::EaseOut => "Ease out",
+            Self::EaseInOut => "Ease in and out",
+        }
+    }
+
+    /// How much of the flash is left `t` of the way through it, from 1 to 0
+    pub fn left(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Hard if t < 1.0 => 1.0,
+            Self::Hard => 0.0,
+            Self::Linear => 1.0 - t,
+            Self::EaseOut => (1.0 - t) * (1.0 - t),
+            Self::EaseInOut => 1.0 - t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoothing_ignores_the_frame_rate() {
+        let (from, to) = ([0.0; 4], [1.0, 0.5, 0.0, 1.0]);
+        let at_60 = approach(from, to, 1.0 / 60.0);
+        let at_120 = approach(approach(from, to, 1.0 / 120.0), to, 1.0 / 120.0);
+        for (a, b) in at_60.iter().zip(at_120) {
+            assert!((a - b).abs() < 1e-5);
+        }
+        assert_eq!(approach(from, to, 0.0), from);
+        // a long hitch lands on the target rather than past it
+        assert!(approach(from, to, 10.0)
+            .iter()
+            .zip(to)
+            .all(|(a, b)| (a - b).abs() < 1e-5));
+        for easing in Easing::ALL {
+            assert_eq!(easing.left(1.0), 0.0);
+            assert!(easing.left(0.5) > 0.0 && easing.left(0.5) <= 1.0);
+        }
+        assert_eq!(Easing::EaseOut.left(0.0), 1.0);
+    }
+}
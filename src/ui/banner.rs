@@ -0,0 +1,106 @@
+use crate::audio::Priority;
+use crate::sync::LockExt;
+use crate::{notice, NOTICES, SAFE_MODE, SETTINGS, TIMERS};
+use nexus::imgui::{Condition, Ui, Window};
+use std::cell::Cell;
+use std::time::Duration;
+
+const NOTICE_COLOR: [f32; 4] = [1.0, 0.15, 0.15, 1.0];
+
+/// Final seconds of critical timers, top center of the screen.
+///
+/// Registered separately from the overlay on purpose, nothing that hides or
+/// quiets the overlay applies here.
+pub fn render_banner(ui: &Ui) {
+    let (warn_secs, style, ui_scale, color) = {
+        let settings = SETTINGS.get().expect("Settings to be set").locked();
+        (
+            settings.critical_warning_secs,
+            settings.time_style(),
+            settings.ui_scale,
+            settings.palette().warning,
+        )
+    };
+    let timers = TIMERS.get().expect("Timers to be set").locked();
+    let now = timers.now();
+    let warnings: Vec<String> = timers
+        .list
+        .iter()
+        .filter(|t| t.enabled && t.priority == Priority::Critical)
+        .filter(|t| t.count_in(now).is_none())
+        .filter_map(|t| {
+            let rest = t.remaining(now)?;
+            (!rest.is_zero() && rest <= Duration::from_secs_f32(warn_secs)).then(|| {
+                let time = t.style(style).format(rest);
+                format!("{} in {}", t.name, time)
+            })
+        })
+        .collect();
+    drop(timers);
+    if warnings.is_empty() {
+        return;
+    }
+    let [width, _] = ui.io().display_size;
+    let scale = super::scale(ui, ui_scale);
+    let _padding = super::push_padding(ui, scale);
+    Window::new("##timers_critical_banner")
+        .position([width / 2.0, 40.0 * scale], Condition::Always)
+        .position_pivot([0.5, 0.0])
+        .no_decoration()
+        .no_inputs()
+        .always_auto_resize(true)
+        .focus_on_appearing(false)
+        .bg_alpha(0.6)
+        .build(ui, || {
+            ui.set_window_font_scale(2.0 * scale);
+            for warning in &warnings {
+                ui.text_colored(color, warning);
+            }
+        });
+}
+
+/// Problems that would otherwise only be in the log, until dismissed. The options show
+/// them too.
+pub fn render_notices(ui: &Ui) {
+    thread_local! {
+        static SAFE_MODE_SEEN: Cell<bool> = const { Cell::new(false) };
+    }
+    if crate::sync::take_recovered() {
+        notice(
+            "Timers recovered from an internal error, see the log. Reload the addon if \
+             timers misbehave."
+                .to_string(),
+        );
+    }
+    let safe_mode = SAFE_MODE.get().filter(|_| !SAFE_MODE_SEEN.get());
+    let mut notices = NOTICES.locked();
+    if notices.is_empty() && safe_mode.is_none() {
+        return;
+    }
+    let [width, _] = ui.io().display_size;
+    Window::new("Timers##timers_notices")
+        .position([width / 2.0, 120.0], Condition::FirstUseEver)
+        .position_pivot([0.5, 0.0])
+        .always_auto_resize(true)
+        .focus_on_appearing(false)
+        .build(ui, || {
+            if let Some(reason) = safe_mode {
+                ui.text_colored(
+                    NOTICE_COLOR,
+                    format!("Running in safe mode because {}, see the options", reason),
+                );
+                ui.same_line();
+                if ui.small_button("Dismiss##safe_mode") {
+                    SAFE_MODE_SEEN.set(true);
+                }
+            }
+            notices.retain(|msg| {
+                ui.text_colored(NOTICE_COLOR, msg);
+                ui.same_line();
+                !ui.small_button(format!("Dismiss##{}", msg))
+            });
+            if notices.len() > 1 && ui.button("Dismiss all") {
+                notices.clear();
+            }
+        });
+}
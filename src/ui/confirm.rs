@@ -0,0 +1,70 @@
+use crate::sync::LockExt;
+use crate::timer::Press;
+use crate::{SETTINGS, TIMERS};
+use nexus::imgui::{Condition, Key, Ui, Window};
+use std::sync::Mutex;
+
+// Asked for by keybind, which isn't called on the render thread
+static PENDING: Mutex<Option<String>> = Mutex::new(None);
+
+/// Asks before the keybind press of a long timer goes through
+pub fn confirm_press(name: String) {
+    *PENDING.locked() = Some(name);
+}
+
+pub fn render_press_confirmation(ui: &Ui) {
+    let mut pending = PENDING.locked();
+    let Some(name) = pending.as_deref() else {
+        return;
+    };
+    let style = SETTINGS
+        .get()
+        .expect("Settings to be set")
+        .locked()
+        .time_style();
+    let mut timers = TIMERS.get().expect("Timers to be set").locked();
+    let now = timers.now();
+    // checked again, the timer may have finished or been stopped in the meantime
+    let outcome = timers.press_outcome(name);
+    let action = match outcome {
+        Press::Start => "Start",
+        Press::Restart => "Restart",
+        Press::Stop => "Stop",
+        _ => {
+            *pending = None;
+            return;
+        }
+    };
+    let left = timers
+        .list
+        .iter()
+        .find(|t| t.name == name)
+        .and_then(|t| Some(t.style(style).format(t.remaining(now)?)));
+    let [width, height] = ui.io().display_size;
+    let mut answer = None;
+    Window::new("Confirm##timers_confirm_press")
+        .position([width / 2.0, height / 3.0], Condition::Appearing)
+        .position_pivot([0.5, 0.5])
+        .always_auto_resize(true)
+        .build(ui, || {
+            ui.text(format!("{} {}?", action, name));
+            if let Some(left) = left.filter(|_| outcome != Press::Start) {
+                ui.text_disabled(format!("{} left of the current run", left));
+            }
+            let enter = ui.is_key_pressed(Key::Enter) || ui.is_key_pressed(Key::KeypadEnter);
+            if ui.button(action) || enter {
+                answer = Some(true);
+            }
+            ui.same_line();
+            if ui.button("Cancel") || ui.is_key_pressed(Key::Escape) {
+                answer = Some(false);
+            }
+        });
+    let Some(confirmed) = answer else {
+        return;
+    };
+    if confirmed {
+        timers.press(name);
+    }
+    *pending = None;
+}
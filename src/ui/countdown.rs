@@ -0,0 +1,48 @@
+use crate::sync::LockExt;
+use crate::{SETTINGS, TIMERS};
+use nexus::imgui::{Condition, Ui, Window};
+
+const FONT_SCALE: f32 = 8.0;
+const NAME_SCALE: f32 = 2.0;
+
+/// The final seconds of timers with a big countdown, huge in the middle of the screen.
+/// Each second fades out until the next one comes up.
+pub fn render_countdown(ui: &Ui) {
+    let ui_scale = SETTINGS
+        .get()
+        .expect("Settings to be set")
+        .locked()
+        .ui_scale;
+    let timers = TIMERS.get().expect("Timers to be set").locked();
+    let now = timers.now();
+    let Some((name, rest)) = timers
+        .list
+        .iter()
+        .filter(|t| t.enabled)
+        .filter_map(|t| Some((t, t.countdown_left(now)?)))
+        .min_by_key(|(_, rest)| *rest)
+        .map(|(t, rest)| (t.name.clone(), rest))
+    else {
+        return;
+    };
+    drop(timers);
+    let secs = rest.as_secs_f32();
+    // fresh at the start of a second, faded by its end
+    let alpha = 0.2 + 0.8 * (secs - secs.floor());
+    let [width, height] = ui.io().display_size;
+    let scale = super::scale(ui, ui_scale);
+    Window::new("##timers_countdown")
+        .position([width / 2.0, height / 2.0], Condition::Always)
+        .position_pivot([0.5, 0.5])
+        .no_decoration()
+        .no_inputs()
+        .always_auto_resize(true)
+        .focus_on_appearing(false)
+        .bg_alpha(0.0)
+        .build(ui, || {
+            ui.set_window_font_scale(FONT_SCALE * scale);
+            ui.text_colored([1.0, 1.0, 1.0, alpha], format!("{}", secs.ceil()));
+            ui.set_window_font_scale(NAME_SCALE * scale);
+            ui.text_colored([1.0, 1.0, 1.0, alpha], &name);
+        });
+}
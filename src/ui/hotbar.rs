@@ -0,0 +1,96 @@
+use super::animation::{self, Channel};
+use crate::sync::LockExt;
+use crate::timer::{StartSource, TimerState};
+use crate::{maps, SETTINGS, TIMERS};
+use nexus::imgui::{Condition, Ui, Window};
+
+const BUTTON_SIZE: [f32; 2] = [72.0, 24.0];
+
+/// A button per favorite timer that starts or stops it, filled as the run progresses.
+/// Can be dragged anywhere, imgui remembers where.
+pub fn render_hotbar(ui: &Ui) {
+    let (groups, ui_scale, palette) = {
+        let settings = SETTINGS.get().expect("Settings to be set").locked();
+        if !settings.hotbar {
+            return;
+        }
+        (
+            maps::current_groups(&settings.maps),
+            settings.ui_scale,
+            settings.palette(),
+        )
+    };
+    // the fill's alpha applies to every color drawn behind the buttons
+    let alpha = palette.fill[3];
+    let character = crate::game::character();
+    let mut timers = TIMERS.get().expect("Timers to be set").locked();
+    let now = timers.now();
+    let mut clicked = None;
+    let favorites: Vec<_> = timers
+        .list
+        .iter()
+        .filter(|t| t.favorite && t.enabled && maps::shows(groups.as_deref(), t))
+        .filter(|t| t.shows_for(character.as_deref()))
+        .collect();
+    if favorites.is_empty() {
+        return;
+    }
+    let scale = super::scale(ui, ui_scale);
+    let button_size = BUTTON_SIZE.map(|side| side * scale);
+    let _padding = super::push_padding(ui, scale);
+    Window::new("##timers_hotbar")
+        .position([20.0, 200.0], Condition::FirstUseEver)
+        .no_decoration()
+        .always_auto_resize(true)
+        .bg_alpha(0.4)
+        .build(ui, || {
+            ui.set_window_font_scale(scale);
+            for (i, timer) in favorites.iter().enumerate() {
+                if i > 0 {
+                    ui.same_line();
+                }
+                let label = format!("{}##hotbar{}", timer.name, timer.id);
+                if ui.button_with_size(label, button_size) {
+                    clicked = Some((timer.name.clone(), timer.state.is_active()));
+                }
+                let (min, max) = (ui.item_rect_min(), ui.item_rect_max());
+                let (fill, color) = match timer.state {
+                    TimerState::Finished { .. } => {
+                        let [r, g, b, _] = palette.finished;
+                        (1.0, [r, g, b, alpha])
+                    }
+                    _ => {
+                        let elapsed = timer.state.elapsed(now).unwrap_or_default();
+                        let run = timer.run_duration().as_secs_f32();
+                        let fill = if run > 0.0 {
+                            elapsed.as_secs_f32() / run
+                        } else {
+                            0.0
+                        };
+                        let color = timer
+                            .remaining(now)
+                            .and_then(|r| timer.band_color(r))
+                            .map_or(palette.fill, |[r, g, b, a]| [r, g, b, a * alpha]);
+                        (fill.clamp(0.0, 1.0), color)
+                    }
+                };
+                let fill = animation::smooth_value(ui, timer.id, Channel::Fill, fill);
+                let color = animation::smooth(ui, timer.id, Channel::FillColor, color);
+                if fill > 0.0 {
+                    let right = min[0] + (max[0] - min[0]) * fill;
+                    ui.get_window_draw_list()
+                        .add_rect(min, [right, max[1]], color)
+                        .filled(true)
+                        .build();
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text(timer.name.as_str());
+                }
+            }
+        });
+    match clicked {
+        Some((name, true)) => timers.stop(&name),
+        Some((name, false)) => timers.start(&name, StartSource::Hotbar),
+        None => {}
+    }
+}
@@ -0,0 +1,98 @@
+mod animation;
+mod banner;
+mod confirm;
+mod countdown;
+mod hotbar;
+mod note;
+mod options;
+mod overlay;
+mod palette;
+mod panel;
+mod quick;
+mod search;
+mod snap;
+mod upnext;
+mod wizard;
+mod wvw;
+
+use crate::format::TimeStyle;
+use crate::i18n::tr;
+use crate::theme::Palette;
+use crate::timer::{Timer, TimerState};
+use nexus::imgui::{StyleVar, Ui};
+use std::time::Instant;
+
+/// How much bigger each interface size of the game is than "normal"
+const UI_SIZE_SCALES: [f32; 4] = [0.9, 1.0, 1.11, 1.22];
+/// Height the overlay is laid out for, larger screens scale it up
+const BASE_HEIGHT: f32 = 1080.0;
+
+/// Scale of the overlay windows, `manual` from the options or 0 to follow the interface
+/// size of the game at the screen's resolution
+fn scale(ui: &Ui, manual: f32) -> f32 {
+    if manual > 0.0 {
+        return manual;
+    }
+    let size = crate::game::ui_size().map_or(1.0, |s| UI_SIZE_SCALES[usize::from(s)]);
+    let [_, height] = ui.io().display_size;
+    size * (height / BASE_HEIGHT).max(1.0)
+}
+
+/// Window padding of the style at `scale`, pushed before the window begins
+fn push_padding(ui: &Ui, scale: f32) -> impl Drop + '_ {
+    let [x, y] = ui.clone_style().window_padding;
+    ui.push_style_var(StyleVar::WindowPadding([x * scale, y * scale]))
+}
+
+/// Live state of a timer, colored so the list can be read at a glance
+fn render_status(ui: &Ui, timer: &Timer, now: Instant, style: TimeStyle, palette: &Palette) {
+    let format = timer.style(style);
+    let remaining = || format.format(timer.remaining(now).unwrap_or_default());
+    match timer.state {
+        TimerState::Idle => ui.text_disabled(tr("idle")),
+        TimerState::Paused { .. } => {
+            ui.text_colored(palette.paused, format!("{} {}", tr("paused"), remaining()))
+        }
+        TimerState::Running { .. } => match timer.count_in(now) {
+            Some(c) => ui.text_colored(
+                palette.count_in,
+                format!("{} {}", tr("starting_in"), format.format(c)),
+            ),
+            None => ui.text_colored(palette.running, remaining()),
+        },
+        TimerState::Finished { .. } => match timer.overtime_elapsed(now) {
+            Some(over) => ui.text_colored(palette.finished, format!("-{}", format.format(over))),
+            None => ui.text_colored(palette.finished, tr("finished")),
+        },
+    }
+}
+
+/// A line per lap with the time into the run and the lap's own length
+fn format_laps(laps: &[std::time::Duration], format: crate::format::TimeStyle) -> String {
+    let mut previous = std::time::Duration::ZERO;
+    let mut lines = Vec::new();
+    for (i, &lap) in laps.iter().enumerate() {
+        lines.push(format!(
+            "Lap {}: {} (+{})",
+            i + 1,
+            format.format(lap),
+            format.format(lap.saturating_sub(previous))
+        ));
+        previous = lap;
+    }
+    lines.join("\n")
+}
+
+pub use animation::Easing;
+pub use banner::{render_banner, render_notices};
+pub use confirm::{confirm_press, render_press_confirmation};
+pub use countdown::render_countdown;
+pub use hotbar::render_hotbar;
+pub use note::{ask_note, render_note_prompt};
+pub use options::{open_editor, render_editor_window, render_options};
+pub use overlay::{ping, render_overlay, toggle_overlay, ClickModifier, FinishTime};
+pub use palette::{open_command_palette, render_command_palette};
+pub use panel::{render_timers_window, toggle_timers_window};
+pub use quick::{open_quick_timer, render_quick_timer};
+pub use upnext::render_up_next;
+pub use wvw::render_wvw_panel;
@@ -0,0 +1,58 @@
+use crate::history::HISTORY;
+use crate::sync::LockExt;
+use nexus::imgui::{Condition, Ui, Window};
+use std::sync::Mutex;
+
+struct Prompt {
+    run: u64,
+    timer: String,
+    note: String,
+}
+
+// Asked for from the tick thread too
+static PROMPT: Mutex<Option<Prompt>> = Mutex::new(None);
+
+/// Asks for a note on a run that was just stopped, replacing any earlier unanswered prompt
+pub fn ask_note(run: u64, timer: String) {
+    *PROMPT.locked() = Some(Prompt {
+        run,
+        timer,
+        note: String::new(),
+    });
+}
+
+pub fn render_note_prompt(ui: &Ui) {
+    let mut prompt = PROMPT.locked();
+    let Some(p) = &mut *prompt else {
+        return;
+    };
+    let [width, height] = ui.io().display_size;
+    let mut done = false;
+    Window::new("Note on the run##timers_note")
+        .position([width / 2.0, height / 3.0], Condition::Appearing)
+        .position_pivot([0.5, 0.5])
+        .always_auto_resize(true)
+        .build(ui, || {
+            ui.text(format!("{} stopped", p.timer));
+            if ui.is_window_appearing() {
+                ui.set_keyboard_focus_here();
+            }
+            let entered = ui
+                .input_text("##note", &mut p.note)
+                .hint("e.g. wipe at split 2")
+                .enter_returns_true(true)
+                .build();
+            if ui.button("Save") || entered {
+                let note = p.note.trim().to_string();
+                HISTORY.locked().set_note(p.run, note);
+                done = true;
+            }
+            ui.same_line();
+            if ui.button("Skip") {
+                done = true;
+            }
+        });
+    if done {
+        *prompt = None;
+    }
+}
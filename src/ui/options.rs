@@ -0,0 +1,2550 @@
+use super::search::{render_search_box, Search, Section};
+use super::{format_laps, render_status, ClickModifier, Easing, FinishTime};
+use crate::alarm::{self, Alarm, Repeat};
+use crate::anchor::{Anchor, AnchorPoint};
+use crate::audio::{self, Priority, SoundKind};
+use crate::backup::{self, Backup};
+use crate::blish;
+use crate::chat::{ChatChannel, ChatTrigger};
+use crate::combat::{CombatKind, CombatTrigger};
+use crate::duration::parse_duration;
+use crate::eventlog;
+use crate::format::{Rounding, TimeFormat, TimeStyle, MAX_DECIMALS};
+use crate::history::{Outcome, HISTORY};
+use crate::i18n::{tr, LANGS};
+use crate::maps::{self, MapRule};
+use crate::marker::{Marker, MarkerShape};
+use crate::presets::presets;
+use crate::recurrence::{ResetSchedule, Weekday};
+use crate::session;
+use crate::stream::{self, StreamFormat};
+use crate::sync::LockExt;
+use crate::template;
+use crate::theme::{Palette, Preset};
+use crate::timeline::{Step, Timeline};
+use crate::timer::{
+    Channel, ColorBand, Fade, FadeStyle, KeyAction, MarkCue, Merge, SoundFiles, StandbyMode,
+    StartSnap, StartSource, Timer, TimerEvent, TimerOrder, TimerState, Timers,
+};
+use crate::{
+    import, keybinds, layout, links, notice, schedule, webhook, NOTICES, SAFE_MODE, SETTINGS,
+    TIMERS,
+};
+use nexus::imgui::{ColorEdit, Key, TreeNodeFlags, Ui, Window};
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+pub fn render_options(ui: &Ui) {
+    let _profile = crate::profiling::scope("options");
+    if let Some(reason) = SAFE_MODE.get() {
+        ui.text_colored(
+            [1.0, 0.3, 0.3, 1.0],
+            format!("Timers is running in safe mode because {}.", reason),
+        );
+        ui.text_wrapped(format!(
+            "No timers or settings were loaded and nothing will be saved, your files in {} \
+             were left untouched. Check the log, fix or remove timers.json and settings.json \
+             and reload the addon.",
+            crate::config::addon_dir().display()
+        ));
+        ui.separator();
+    }
+    NOTICES.locked().retain(|msg| {
+        ui.text_colored([1.0, 0.3, 0.3, 1.0], msg);
+        ui.same_line();
+        !ui.small_button(format!("Dismiss##{}", msg))
+    });
+    if super::wizard::render_wizard(ui) {
+        return;
+    }
+    let search = render_search_box(ui);
+    // for the pull timer, taken first so the timers aren't locked under the settings
+    let timer_names: Vec<String> = TIMERS
+        .get()
+        .expect("Timers to be set")
+        .locked()
+        .list
+        .iter()
+        .filter(|t| t.system.is_none() && !t.archived)
+        .map(|t| t.name.clone())
+        .collect();
+    {
+        let mut settings = SETTINGS.get().expect("Settings to be set").locked();
+        if let Some(cost) = super::overlay::over_budget() {
+            ui.text_colored(
+                [1.0, 0.6, 0.0, 1.0],
+                format!(
+                    "Overlay render cost ({:.2}ms) exceeds the frame budget, updates are throttled",
+                    cost
+                ),
+            );
+        }
+        search.group(ui, "General", |section| {
+            section.item("Frame budget (ms)", |label| {
+                ui.input_float(label, &mut settings.frame_budget_ms).build();
+                settings.frame_budget_ms = settings.frame_budget_ms.max(0.1);
+            });
+            section.item("Only show timers in combat", |label| {
+                ui.checkbox(label, &mut settings.combat_only);
+            });
+            section.item("Snap timer windows to a grid of (px, 0 = none)", |label| {
+                let mut grid = settings.snap_grid as i32;
+                if ui.input_int(label, &mut grid).build() {
+                    let max = crate::config::MAX_SNAP_GRID as i32;
+                    settings.snap_grid = grid.clamp(0, max) as u32;
+                }
+            });
+            section.item("Snap timer windows to each other", |label| {
+                ui.checkbox(label, &mut settings.snap_to_windows);
+            });
+            section.extra(|| {
+                if ui.button("Arrange timer windows") {
+                    super::snap::arrange();
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text("Tiles the windows of the shown timers from the top left");
+                }
+            });
+            section.item(
+                "Pause timers while the game is unfocused or loading",
+                |label| {
+                    ui.checkbox(label, &mut settings.pause_when_away);
+                },
+            );
+            section.item(
+                "Freeze timers on loading screens and the character select",
+                |label| {
+                    ui.checkbox(label, &mut settings.freeze_while_loading);
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text(
+                            "Running timers stay shown, dimmed, and go on once you're back in the game",
+                        );
+                    }
+                },
+            );
+            section.item(
+                "Hide timers finished or paused for (minutes, 0 = never)",
+                |label| {
+                    let mut mins = settings.hide_idle_mins as i32;
+                    if ui.input_int(label, &mut mins).build() {
+                        settings.hide_idle_mins = mins.clamp(0, 24 * 60) as u32;
+                    }
+                },
+            );
+            section.item("Ask before deleting timers", |label| {
+                ui.checkbox(label, &mut settings.confirm_delete);
+            });
+            section.item(
+                "Ask before a keybind starts or stops timers of at least (minutes, 0 = never)",
+                |label| {
+                    let mut mins = settings.confirm_long_mins as i32;
+                    if ui.input_int(label, &mut mins).build() {
+                        settings.confirm_long_mins = mins.clamp(0, 24 * 60) as u32;
+                    }
+                },
+            );
+            section.item("Shortcut in the Nexus quick access bar", |label| {
+                if ui.checkbox(label, &mut settings.quick_access) {
+                    if settings.quick_access {
+                        crate::quick_access::register();
+                    } else {
+                        crate::quick_access::unregister();
+                    }
+                }
+            });
+            section.item("Show hotbar of favorite timers", |label| {
+                ui.checkbox(label, &mut settings.hotbar);
+            });
+            section.item("WvW panel for Righteous Indignation timers", |label| {
+                ui.checkbox(label, &mut settings.wvw_panel);
+                if ui.is_item_hovered() {
+                    ui.tooltip_text("Shown on WvW maps, a button per objective starts its 5 minutes");
+                }
+            });
+            section.item("Up next window with this many timers (0 = off)", |label| {
+                let mut count = settings.up_next as i32;
+                if ui.input_int(label, &mut count).build() {
+                    settings.up_next = count.clamp(0, 20) as usize;
+                }
+            });
+            section.item("Compact list from this many timers (0 = never)", |label| {
+                let mut after = settings.compact_after as i32;
+                if ui.input_int(label, &mut after).build() {
+                    settings.compact_after = after.clamp(0, 100) as usize;
+                }
+            });
+            section.item("Order of timers", |label| {
+                let orders = TimerOrder::ALL.map(TimerOrder::name);
+                let mut order = settings.timer_order as usize;
+                if ui.combo_simple_string(label, &mut order, &orders) {
+                    settings.timer_order = TimerOrder::ALL[order];
+                }
+            });
+            section.item("Time format", |label| {
+                let formats = TimeFormat::ALL.map(TimeFormat::name);
+                let mut format = settings.time_format as usize;
+                if ui.combo_simple_string(label, &mut format, &formats) {
+                    settings.time_format = TimeFormat::ALL[format];
+                }
+            });
+            section.item("Decimals of seconds", |label| {
+                let mut decimals = settings.decimals as usize;
+                if ui.combo_simple_string(label, &mut decimals, &DECIMALS) {
+                    settings.decimals = decimals as u8;
+                }
+            });
+            section.item("Round the remaining time", |label| {
+                let names = Rounding::ALL.map(Rounding::name);
+                let mut rounding = settings.rounding as usize;
+                if ui.combo_simple_string(label, &mut rounding, &names) {
+                    settings.rounding = Rounding::ALL[rounding];
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text("Up shows 3 until the timer reaches 2.0");
+                }
+            });
+            section.item("Refresh the overlay text", |label| {
+                let names = REFRESH_RATES.map(|(name, _)| name);
+                let mut rate = REFRESH_RATES
+                    .iter()
+                    .position(|(_, hz)| *hz == settings.refresh_hz)
+                    .unwrap_or(0);
+                if ui.combo_simple_string(label, &mut rate, &names) {
+                    settings.refresh_hz = REFRESH_RATES[rate].1;
+                }
+            });
+            section.item(
+                "Click timers in the overlay (left pauses, middle resets)",
+                |label| {
+                    ui.checkbox(label, &mut settings.overlay_clicks);
+                },
+            );
+            if settings.overlay_clicks {
+                section.item("Only while holding", |label| {
+                    let names = ClickModifier::ALL.map(ClickModifier::name);
+                    let mut modifier = settings.click_modifier as usize;
+                    if ui.combo_simple_string(label, &mut modifier, &names) {
+                        settings.click_modifier = ClickModifier::ALL[modifier];
+                    }
+                });
+            }
+            section.item("Show when running timers finish", |label| {
+                let names = FinishTime::ALL.map(FinishTime::name);
+                let mut shown = settings.finish_time as usize;
+                if ui.combo_simple_string(label, &mut shown, &names) {
+                    settings.finish_time = FinishTime::ALL[shown];
+                }
+            });
+            section.item("Fade of the flash", |label| {
+                let names = Easing::ALL.map(Easing::name);
+                let mut easing = settings.flash_easing as usize;
+                if ui.combo_simple_string(label, &mut easing, &names) {
+                    settings.flash_easing = Easing::ALL[easing];
+                }
+            });
+            section.item(
+                "Suggest archiving timers unused for (days, 0 = never)",
+                |label| {
+                    let mut days = settings.archive_after_days as i32;
+                    if ui.input_int(label, &mut days).build() {
+                        settings.archive_after_days = days.clamp(0, 3650) as u32;
+                    }
+                },
+            );
+            section.item("Backups of the timers to keep (0 = none)", |label| {
+                let mut keep = settings.backups as i32;
+                if ui.input_int(label, &mut keep).build() {
+                    settings.backups = keep.clamp(0, backup::MAX_KEEP as i32) as u32;
+                }
+            });
+            section.item("Overlay scale (0 = follow the game)", |label| {
+                let max = crate::config::MAX_UI_SCALE;
+                ui.slider(label, 0.0, max, &mut settings.ui_scale);
+                // a slider can be typed into past its range
+                settings.ui_scale = settings.ui_scale.clamp(0.0, max);
+            });
+            section.item("Critical warning (s)", |label| {
+                ui.input_float(label, &mut settings.critical_warning_secs)
+                    .build();
+                settings.critical_warning_secs = settings.critical_warning_secs.clamp(0.0, 3600.0);
+            });
+            section.item("Write running timers to a file for OBS", |label| {
+                let formats = StreamFormat::ALL.map(StreamFormat::name);
+                let mut format = settings.stream_file as usize;
+                if ui.combo_simple_string(label, &mut format, &formats) {
+                    settings.stream_file = StreamFormat::ALL[format];
+                }
+            });
+            if settings.stream_file != StreamFormat::Off {
+                section.item("Rewrite the file every (ms)", |label| {
+                    let mut interval = settings.stream_interval_ms as i32;
+                    if ui.input_int(label, &mut interval).build() {
+                        settings.stream_interval_ms = (interval.max(0) as u32)
+                            .clamp(stream::MIN_INTERVAL_MS, stream::MAX_INTERVAL_MS);
+                    }
+                });
+            }
+            section.item("Share running timers with other addons and tools", |label| {
+                ui.checkbox(label, &mut settings.data_link);
+                if ui.is_item_hovered() {
+                    ui.tooltip_text(format!(
+                        "Through the Nexus data link {}, rewritten every {}ms",
+                        crate::datalink::ID,
+                        crate::datalink::INTERVAL.as_millis()
+                    ));
+                }
+            });
+            section.item("Take requests from tools on this computer", |label| {
+                if ui.checkbox(label, &mut settings.control) && settings.control_token.is_empty() {
+                    settings.control_token = crate::control::new_token();
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text(
+                        "Listens on 127.0.0.1 for GET /timers and POST /timers/{name}/start \
+                         or /stop, with the token as \"Authorization: Bearer <token>\"",
+                    );
+                }
+            });
+            if settings.control {
+                section.item("Port", |label| {
+                    let mut port = i32::from(settings.control_port);
+                    if ui.input_int(label, &mut port).build() {
+                        let min = i32::from(crate::control::MIN_PORT);
+                        settings.control_port = port.clamp(min, i32::from(u16::MAX)) as u16;
+                    }
+                });
+                section.extra(|| {
+                    ui.text_disabled("Token");
+                    ui.same_line();
+                    if ui.small_button("Copy##control_token") {
+                        ui.set_clipboard_text(&settings.control_token);
+                    }
+                    ui.same_line();
+                    if ui.small_button("New##control_token") {
+                        settings.control_token = crate::control::new_token();
+                    }
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Tools using the old token are turned away");
+                    }
+                });
+            }
+        });
+        search.section(ui, "Pull timer", |section| {
+            section.extra(|| {
+                ui.text_disabled(
+                    "A big countdown to the pull that beeps through its final seconds, \
+                     started and called off by its keybind",
+                );
+            });
+            section.item("Length (seconds)", |label| {
+                let mut secs = settings.pull_secs as i32;
+                if ui.input_int(label, &mut secs).build() {
+                    settings.pull_secs = secs.clamp(1, crate::config::MAX_PULL_SECS as i32) as u32;
+                }
+            });
+            section.item("Then start", |label| {
+                let mut names = vec!["Nothing"];
+                names.extend(timer_names.iter().map(String::as_str));
+                let mut then = timer_names
+                    .iter()
+                    .position(|name| *name == settings.pull_then)
+                    .map_or(0, |i| i + 1);
+                if ui.combo_simple_string(label, &mut then, &names) {
+                    settings.pull_then = timer_names
+                        .get(then.wrapping_sub(1))
+                        .cloned()
+                        .unwrap_or_default();
+                }
+            });
+            if !settings.pull_then.is_empty() && !timer_names.contains(&settings.pull_then) {
+                section.extra(|| {
+                    ui.text_colored(
+                        [1.0, 0.6, 0.0, 1.0],
+                        format!("No timer named {}", settings.pull_then),
+                    );
+                });
+            }
+        });
+        search.section(ui, "Theme", |section| {
+            section.item("Colors of the timer states", |label| {
+                let names = Preset::ALL.map(Preset::name);
+                let mut preset = settings.theme as usize;
+                if ui.combo_simple_string(label, &mut preset, &names) {
+                    settings.theme = Preset::ALL[preset];
+                }
+            });
+            // editing a preset's color starts a custom theme from the preset
+            let mut palette = settings.palette();
+            let mut changed = false;
+            for (name, color) in palette.colors_mut() {
+                section.item(name, |label| {
+                    changed |= ColorEdit::new(label, color).inputs(false).build(ui);
+                });
+            }
+            if changed {
+                settings.custom_theme = palette;
+                settings.theme = Preset::Custom;
+            }
+            if settings.theme == Preset::Custom {
+                section.extra(|| {
+                    if ui.button("Reset to the default colors") {
+                        settings.custom_theme = Palette::default();
+                    }
+                });
+            }
+        });
+        search.section(ui, "Sound", |section| {
+            section.item("Mute all sounds and speech", |label| {
+                ui.checkbox(label, &mut settings.muted);
+            });
+            let volumes = &mut settings.volumes;
+            let sliders = [
+                ("Master volume", &mut volumes.master),
+                ("Start sounds", &mut volumes.start),
+                ("Warning sounds", &mut volumes.warning),
+                ("Finish sounds", &mut volumes.finish),
+                ("Countdown ticks", &mut volumes.mark),
+            ];
+            for (name, volume) in sliders {
+                section.item(name, |label| input_volume(ui, label, volume));
+            }
+            section.item("Voice volume", |label| {
+                input_volume(ui, label, &mut settings.voice_volume);
+            });
+        });
+        search.section(ui, "GW2 API", |section| {
+            section.item("API key", |label| {
+                // every key typed on the way would be fetched with
+                input_secret_on_leave(ui, label, &mut settings.api_key);
+            });
+            section.extra(|| {
+                ui.text_disabled(
+                    "Needs the account and progression permissions. Daily crafting cooldowns \
+                     of the account are shown as timers.",
+                );
+                let status = crate::gw2api::status();
+                if !status.is_empty() {
+                    ui.text_disabled(status);
+                }
+            });
+        });
+        search.section(ui, "Squad", |section| {
+            section.item("Relay", |label| {
+                ui.input_text(label, &mut settings.squad_relay)
+                    .hint("https://...")
+                    .build();
+            });
+            section.item("Room", |label| {
+                ui.input_text(label, &mut settings.squad_room).build();
+            });
+            section.extra(|| {
+                ui.text_disabled(
+                    "Starts of timers set to be shared are sent to everyone in the room, \
+                     and their starts start the shared timer of the same name here.",
+                );
+                let status = crate::squad::status();
+                if !status.is_empty() {
+                    ui.text_disabled(status);
+                }
+            });
+        });
+        search.section(ui, "Maps", |section| {
+            section.extra(|| render_map_rules(ui, &mut settings.maps));
+        });
+        search.section(ui, "World bosses and metas", |section| {
+            render_events(ui, section, &mut settings.events);
+        });
+        search.section(ui, "Resets", |section| {
+            render_resets(ui, section, &mut settings.resets);
+        });
+        search.section(ui, "Debugging", |section| {
+            section.item("Record sessions (from the next start)", |label| {
+                ui.checkbox(label, &mut settings.record_sessions);
+            });
+            section.item("Replay latest session", |label| render_replay(ui, label));
+            section.item("Time scale (not saved)", |label| {
+                let (min, max) = (crate::clock::MIN_SCALE, crate::clock::MAX_SCALE);
+                ui.slider(label, min, max, &mut settings.time_scale);
+                settings.time_scale = settings.time_scale.clamp(min, max);
+                if settings.time_scale != 1.0 {
+                    ui.same_line();
+                    if ui.small_button("Real time") {
+                        settings.time_scale = 1.0;
+                    }
+                    ui.text_colored([1.0, 0.6, 0.0, 1.0], "Timers aren't running in real time");
+                }
+            });
+        });
+        search.section(ui, "History", |section| {
+            section.extra(|| render_history(ui, &settings.resets));
+        });
+        search.section(ui, "Log", |section| {
+            section.extra(|| render_event_log(ui));
+        });
+    }
+    let (style, palette, confirm_delete, manual_order, archive_after_days, backups, group_keybinds) = {
+        let settings = SETTINGS.get().expect("Settings to be set").locked();
+        (
+            settings.time_style(),
+            settings.palette(),
+            settings.confirm_delete,
+            settings.timer_order == TimerOrder::Manual,
+            settings.archive_after_days,
+            settings.backups,
+            settings.group_keybinds.clone(),
+        )
+    };
+    let mut timers = TIMERS.get().expect("Timers to be set").locked();
+    thread_local! {
+        static EDITING: Cell<Option<u64>> = const { Cell::new(None) };
+        static SELECTED: RefCell<HashSet<u64>> = RefCell::new(HashSet::new());
+        static CURRENT_CHARACTER: Cell<bool> = const { Cell::new(false) };
+        static PAGE: Cell<usize> = const { Cell::new(0) };
+    }
+    let character = crate::game::character();
+    if let Some(character) = &character {
+        let mut only = CURRENT_CHARACTER.get();
+        if ui.checkbox(
+            format!("Only timers shown on {}##current_character", character),
+            &mut only,
+        ) {
+            CURRENT_CHARACTER.set(only);
+        }
+    }
+    let only_for = character.filter(|_| CURRENT_CHARACTER.get());
+    let mut to_remove = Vec::new();
+    // asked about first
+    let mut to_delete = Vec::new();
+    let mut to_toggle = Vec::new();
+    let mut to_duplicate = Vec::new();
+    // (id, whether to move it up)
+    let mut to_move = Vec::new();
+    // (name, whether to add or remove time)
+    let mut to_adjust = Vec::new();
+    let now = timers.now();
+    let shown: Vec<u64> = timers
+        .list
+        .iter()
+        .filter(|t| !t.archived && t.shows_for(only_for.as_deref()))
+        .filter(|t| !search.is_active() || search.matches(&t.name) || search.matches(&t.group))
+        .map(|t| t.id)
+        .collect();
+    // only a page of rows is drawn, hundreds of them would take too long every frame
+    let pages = shown.len().div_ceil(PAGE_SIZE).max(1);
+    let page = PAGE.get().min(pages - 1);
+    if pages > 1 {
+        if ui.small_button("<##page") && page > 0 {
+            PAGE.set(page - 1);
+        }
+        ui.same_line();
+        ui.text(format!("Page {} of {}", page + 1, pages));
+        ui.same_line();
+        if ui.small_button(">##page") && page + 1 < pages {
+            PAGE.set(page + 1);
+        }
+        ui.same_line();
+        ui.text_disabled(format!("({} timers)", shown.len()));
+    }
+    let start = page * PAGE_SIZE;
+    let on_page = &shown[start..(start + PAGE_SIZE).min(shown.len())];
+    let table = ui.begin_table("timer_options", 4);
+    if table.is_none() {
+        log_table_fallback();
+    }
+    let columns = Columns {
+        ui,
+        table: table.is_some(),
+    };
+    // without a table, inputs would take up the whole line
+    let _width = table
+        .is_none()
+        .then(|| ui.push_item_width(FALLBACK_INPUT_WIDTH));
+    keybinds::refresh_binds();
+    for &id in on_page {
+        let Some(timer) = timers.list.get_mut(id) else {
+            continue;
+        };
+        let _id = ui.push_id(timer.name.as_str());
+        columns.row();
+        if let Some(source) = timer.system {
+            render_system_row(ui, &columns, timer, source, now, style, &palette);
+            continue;
+        }
+        SELECTED.with_borrow_mut(|selected| {
+            let mut checked = selected.contains(&timer.id);
+            if ui.checkbox("##select", &mut checked) {
+                if checked {
+                    selected.insert(timer.id);
+                } else {
+                    selected.remove(&timer.id);
+                }
+            }
+        });
+        ui.same_line();
+        if timer.enabled {
+            ui.text(timer.name.as_str());
+        } else {
+            ui.text_disabled(format!("{} ({})", timer.name, tr("disabled")));
+        }
+        if !timer.note.is_empty() && ui.is_item_hovered() {
+            ui.tooltip_text(&timer.note);
+        }
+        if !timer.group.is_empty() {
+            ui.same_line();
+            ui.text_disabled(format!("[{}]", timer.group));
+        }
+        if let Some(pack) = &timer.pack {
+            ui.same_line();
+            ui.text_disabled(format!("({}, changes aren't saved)", pack));
+        }
+        if let Some(bind) = timer.enabled.then(|| keybinds::start_bind(timer)).flatten() {
+            ui.same_line();
+            ui.text_disabled(&bind.label);
+            if !bind.conflicts.is_empty() {
+                ui.same_line();
+                ui.text_colored([1.0, 0.6, 0.0, 1.0], "key in use");
+                if ui.is_item_hovered() {
+                    ui.tooltip_text(format!(
+                        "{} is also bound to {}, only one of them may fire",
+                        bind.label,
+                        bind.conflicts.join(", ")
+                    ));
+                }
+            }
+        }
+        columns.next();
+        if let Some(alarm) = &timer.alarm {
+            ui.text(format!("at {}", alarm.label()));
+        } else if let Some(timeline) = &timer.timeline {
+            ui.text(format!("{} steps", timeline.steps.len()));
+        } else {
+            let label = format!("##duration{}", timer.id);
+            if timer.state.is_active() {
+                // changing it under a running timer would jump the countdown
+                let mut text = format_seconds(timer.duration);
+                ui.input_text(label, &mut text).read_only(true).build();
+            } else {
+                input_duration_on_leave(ui, &label, &mut timer.duration);
+            }
+        }
+        columns.next();
+        render_status(ui, timer, now, style, &palette);
+        columns.next();
+        let editing = EDITING.get() == Some(timer.id);
+        if ui.button(tr(if editing { "close" } else { "edit" })) {
+            EDITING.set((!editing).then_some(timer.id));
+        }
+        if !timer.url.is_empty() {
+            ui.same_line();
+            if ui.button(tr("open_link")) {
+                links::open(&timer.url);
+            }
+        }
+        if matches!(
+            timer.state,
+            TimerState::Running { .. } | TimerState::Paused { .. }
+        ) {
+            ui.same_line();
+            if ui.button(tr(if timer.state.is_paused() {
+                "resume"
+            } else {
+                "pause"
+            })) {
+                to_toggle.push(timer.name.clone());
+            }
+            let step = timer.add_step.as_secs_f32();
+            ui.same_line();
+            if ui.small_button(format!("+{}s", step)) {
+                to_adjust.push((timer.name.clone(), true));
+            }
+            ui.same_line();
+            if ui.small_button(format!("-{}s", step)) {
+                to_adjust.push((timer.name.clone(), false));
+            }
+        }
+        ui.same_line();
+        if ui.button(tr(if timer.enabled { "disable" } else { "enable" })) {
+            set_enabled(timer, !timer.enabled);
+        }
+        ui.same_line();
+        if ui.button(tr("duplicate")) {
+            to_duplicate.push(timer.id);
+        }
+        ui.same_line();
+        if ui.button(tr("delete")) {
+            to_delete.push(timer.id);
+        }
+        // the order only matters to the overlay when it is shown as listed
+        if manual_order && !search.is_active() {
+            ui.same_line();
+            if ui.small_button("^") {
+                to_move.push((timer.id, true));
+            }
+            ui.same_line();
+            if ui.small_button("v") {
+                to_move.push((timer.id, false));
+            }
+        }
+    }
+    for (id, up) in to_move {
+        timers.move_by_one(id, up);
+    }
+    for id in to_duplicate {
+        if let Some(copy) = timers.duplicate(id) {
+            keybinds::register(copy);
+            EDITING.set(Some(copy.id));
+        }
+    }
+    for name in to_toggle {
+        timers.toggle_pause(&name);
+    }
+    for (name, add) in to_adjust {
+        let Some(step) = timers.find_by_name_mut(&name).map(|t| t.add_step) else {
+            continue;
+        };
+        if add {
+            timers.add_time(&name, step);
+        } else {
+            timers.subtract_time(&name, step);
+        }
+    }
+    if !search.is_active() {
+        render_add_row(ui, &columns, &mut timers);
+    }
+    drop(_width);
+    drop(table);
+
+    SELECTED.with_borrow_mut(|selected| {
+        selected.retain(|id| timers.get(*id).is_some());
+        if !selected.is_empty() {
+            render_bulk_actions(ui, &mut timers, selected, &mut to_delete);
+        }
+    });
+    let (confirmed, stop_asking) = if confirm_delete {
+        confirm_deletion(ui, &timers, to_delete)
+    } else {
+        (to_delete, false)
+    };
+    to_remove.extend(confirmed);
+    for id in to_remove {
+        if let Some(timer) = timers.remove(id) {
+            keybinds::unregister(&timer);
+        }
+    }
+
+    if let Some(timer) = EDITING.get().and_then(|id| timers.get_mut(id)) {
+        ui.separator();
+        render_timer_editor(ui, timer);
+        ui.separator();
+    }
+
+    let toggled = render_group_keybinds(ui, &search, &timers, &group_keybinds);
+    render_archive(ui, &search, &mut timers, archive_after_days);
+    render_backups(ui, &search, &mut timers, backups);
+    render_presets_and_import(ui, &search, &mut timers, &group_keybinds);
+
+    if !search.is_active() || search.matches("profiling") {
+        crate::profiling::render_panel(ui);
+    }
+    if stop_asking || toggled.is_some() {
+        drop(timers);
+        let mut settings = SETTINGS.get().expect("Settings to be set").locked();
+        if stop_asking {
+            settings.confirm_delete = false;
+        }
+        if let Some((group, on)) = toggled {
+            if on {
+                keybinds::register_group(&group);
+                settings.group_keybinds.push(group);
+            } else {
+                keybinds::unregister_group(&group);
+                settings.group_keybinds.retain(|g| *g != group);
+            }
+        }
+    }
+}
+
+/// A checkbox per group for its start/stop keybind, returns the one toggled
+fn render_group_keybinds(
+    ui: &Ui,
+    search: &Search,
+    timers: &Timers,
+    with_keybind: &[String],
+) -> Option<(String, bool)> {
+    let mut groups: Vec<&str> = timers
+        .list
+        .iter()
+        .filter(|t| t.system.is_none())
+        .map(|t| t.group.as_str())
+        .chain(with_keybind.iter().map(String::as_str))
+        .filter(|g| !g.is_empty())
+        .collect();
+    groups.sort_unstable();
+    groups.dedup();
+    let mut toggled = None;
+    search.section(ui, "Group keybinds", |section| {
+        if groups.is_empty() {
+            section.extra(|| ui.text_disabled("No timers are in a group"));
+        }
+        for &group in &groups {
+            section.item(group, |label| {
+                let _id = ui.push_id("group_keybind");
+                let mut on = with_keybind.iter().any(|g| g == group);
+                if ui.checkbox(label, &mut on) {
+                    toggled = Some((group.to_string(), on));
+                }
+            });
+        }
+        section.extra(|| {
+            ui.text_disabled(
+                "Starts every timer of the group, or stops them if any is running. \
+                 The key is assigned in the Nexus keybinds.",
+            );
+        });
+    });
+    toggled
+}
+
+const DELETE_POPUP: &str = "Delete timers?##timers_delete";
+
+struct Deletion {
+    ids: Vec<u64>,
+    dont_ask: bool,
+}
+
+/// Asks before deleting `ids`, returns the timers confirmed to be deleted and whether to
+/// stop asking
+fn confirm_deletion(ui: &Ui, timers: &Timers, ids: Vec<u64>) -> (Vec<u64>, bool) {
+    thread_local! {
+        static PENDING: RefCell<Option<Deletion>> = const { RefCell::new(None) };
+    }
+    PENDING.with_borrow_mut(|pending| {
+        if !ids.is_empty() {
+            *pending = Some(Deletion {
+                ids,
+                dont_ask: false,
+            });
+            ui.open_popup(DELETE_POPUP);
+        }
+        let mut confirmed = (Vec::new(), false);
+        let mut close = false;
+        ui.popup_modal(DELETE_POPUP)
+            .always_auto_resize(true)
+            .build(ui, || {
+                let Some(deletion) = pending.as_mut() else {
+                    close = true;
+                    return;
+                };
+                let names: Vec<&str> = deletion
+                    .ids
+                    .iter()
+                    .filter_map(|id| timers.get(*id))
+                    .map(|t| t.name.as_str())
+                    .collect();
+                match names.as_slice() {
+                    [name] => ui.text(format!("Delete {} and its keybinds?", name)),
+                    names => ui.text(format!("Delete {} timers and their keybinds?", names.len())),
+                }
+                ui.checkbox("Don't ask again", &mut deletion.dont_ask);
+                let enter = ui.is_key_pressed(Key::Enter) || ui.is_key_pressed(Key::KeypadEnter);
+                if ui.button(tr("delete")) || enter {
+                    confirmed = (std::mem::take(&mut deletion.ids), deletion.dont_ask);
+                    close = true;
+                }
+                ui.same_line();
+                if ui.button("Cancel") || ui.is_key_pressed(Key::Escape) {
+                    close = true;
+                }
+                if close {
+                    ui.close_current_popup();
+                }
+            });
+        if close {
+            *pending = None;
+        }
+        confirmed
+    })
+}
+
+fn render_events(ui: &Ui, section: &Section, subscribed: &mut Vec<String>) {
+    let now = SystemTime::now();
+    for event in schedule::EVENTS {
+        section.item(event.name, |label| {
+            let mut checked = subscribed.iter().any(|s| s == event.name);
+            if ui.checkbox(label, &mut checked) {
+                if checked {
+                    subscribed.push(event.name.to_string());
+                } else {
+                    subscribed.retain(|s| s != event.name);
+                }
+            }
+            ui.same_line();
+            let until = event
+                .next_spawn(now)
+                .duration_since(now)
+                .unwrap_or_default();
+            ui.text_disabled(format!("next spawn in {}", format_until(until)));
+        });
+    }
+}
+
+const FALLBACK_INPUT_WIDTH: f32 = 120.0;
+/// Rows of the timers table drawn at once
+const PAGE_SIZE: usize = 50;
+
+const DECIMALS: [&str; MAX_DECIMALS as usize + 1] = ["None (92)", "One (92.4)", "Two (92.41)"];
+/// Choices for how often the overlay text is refreshed, in Hz
+const REFRESH_RATES: [(&str, u32); 5] = [
+    ("Every frame", 0),
+    ("30 times a second", 30),
+    ("10 times a second", 10),
+    ("4 times a second", 4),
+    ("Once a second", 1),
+];
+
+/// Cells of the timer list, laid out one after another on a line when the table
+/// can't be created (seen with some Nexus versions and very narrow option areas)
+struct Columns<'a> {
+    ui: &'a Ui,
+    table: bool,
+}
+
+impl Columns<'_> {
+    fn row(&self) {
+        if self.table {
+            self.ui.table_next_row();
+            self.ui.table_next_column();
+        }
+    }
+
+    fn next(&self) {
+        if self.table {
+            self.ui.table_next_column();
+        } else {
+            self.ui.same_line();
+        }
+    }
+}
+
+fn log_table_fallback() {
+    thread_local! {
+        static LOGGED: Cell<bool> = const { Cell::new(false) };
+    }
+    if !LOGGED.replace(true) {
+        log::warn!("Failed to create the options table, falling back to a simple layout");
+    }
+}
+
+fn render_system_row(
+    ui: &Ui,
+    columns: &Columns,
+    timer: &Timer,
+    source: &str,
+    now: Instant,
+    style: TimeStyle,
+    palette: &Palette,
+) {
+    ui.text(timer.name.as_str());
+    ui.same_line();
+    ui.text_disabled(format!("({})", source));
+    columns.next();
+    columns.next();
+    render_status(ui, timer, now, style, palette);
+}
+
+fn render_add_row(ui: &Ui, columns: &Columns, timers: &mut Timers) {
+    columns.row();
+    thread_local! {
+        static NEW_NAME: RefCell<String> = const { RefCell::new(String::new()) };
+        static NEW_DURATION: RefCell<String> = const { RefCell::new(String::new()) };
+        // why the last add was refused, until the fields change
+        static ADD_ERROR: RefCell<Option<String>> = const { RefCell::new(None) };
+        // back into the name field after adding with enter, for adding several in a row
+        static REFOCUS: Cell<bool> = const { Cell::new(false) };
+    }
+    let mut edited = false;
+    let entered = NEW_NAME.with_borrow_mut(|nn| {
+        if REFOCUS.replace(false) {
+            ui.set_keyboard_focus_here();
+        }
+        let entered = ui
+            .input_text(tr("name"), nn)
+            .enter_returns_true(true)
+            .build();
+        edited |= ui.is_item_edited();
+        entered
+    });
+    columns.next();
+    let entered = NEW_DURATION.with_borrow_mut(|nd| {
+        let entered = ui
+            .input_text(tr("seconds"), nd)
+            .hint("90, 1:30, 0.5, 2m30s")
+            .enter_returns_true(true)
+            .build();
+        edited |= ui.is_item_edited();
+        entered
+    }) || entered;
+    if edited {
+        ADD_ERROR.set(None);
+    }
+    ADD_ERROR.with_borrow(|error| {
+        if let Some(error) = error {
+            ui.text_colored([1.0, 0.3, 0.3, 1.0], error);
+        }
+    });
+    columns.next();
+    columns.next();
+    if ui.button(tr("add")) || entered {
+        REFOCUS.set(entered);
+        let name = NEW_NAME.with_borrow(|nn| nn.trim().to_string());
+        let duration = NEW_DURATION.with_borrow(|nd| parse_duration(nd));
+        let error = match duration {
+            _ if name.is_empty() => Some("Give the timer a name".to_string()),
+            Ok(d) if d.is_zero() => Some("The duration has to be longer than 0".to_string()),
+            Ok(d) if d > import::MAX_DURATION => Some("That duration is too long".to_string()),
+            Ok(d) => {
+                let timer = timers.add(name, d);
+                keybinds::register(timer);
+                NEW_NAME.with_borrow_mut(String::clear);
+                NEW_DURATION.with_borrow_mut(String::clear);
+                None
+            }
+            Err(e) => Some(format!("Can't read the duration: {}", e)),
+        };
+        ADD_ERROR.set(error);
+    }
+}
+
+/// Timers suggested for the archive and the archived ones, which keep their config but
+/// have no keybinds and aren't listed with the others
+fn render_archive(ui: &Ui, search: &Search, timers: &mut Timers, after_days: u32) {
+    // (id, whether to archive or restore it)
+    let mut to_archive = Vec::new();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    search.section(ui, "Archive", |section| {
+        section.extra(|| {
+            let unused = match after_days {
+                0 => Vec::new(),
+                days => timers.unused(days),
+            };
+            if !unused.is_empty() {
+                ui.text(format!("Not started for over {} days:", after_days));
+                for timer in &unused {
+                    let _id = ui.push_id(timer.name.as_str());
+                    let days = now.saturating_sub(timer.last_used.unwrap_or(now)) / 86_400;
+                    ui.text(format!("{} ({} days)", timer.name, days));
+                    ui.same_line();
+                    if ui.small_button("Archive") {
+                        to_archive.push((timer.id, true));
+                    }
+                }
+                if unused.len() > 1 && ui.button("Archive all of them") {
+                    to_archive.extend(unused.iter().map(|t| (t.id, true)));
+                }
+                ui.separator();
+            }
+            let mut archived = timers.list.iter().filter(|t| t.archived).peekable();
+            if archived.peek().is_none() {
+                ui.text_disabled("No archived timers");
+            }
+            for timer in archived {
+                let _id = ui.push_id(timer.name.as_str());
+                ui.text(timer.name.as_str());
+                ui.same_line();
+                if ui.small_button("Restore") {
+                    to_archive.push((timer.id, false));
+                }
+            }
+        });
+    });
+    for (id, archive) in to_archive {
+        let Some(timer) = timers.get_mut(id) else {
+            continue;
+        };
+        timer.set_archived(archive);
+        // the keys stay assigned in Nexus, so they are back once restored
+        if archive {
+            keybinds::unregister(timer);
+        } else {
+            keybinds::register(timer);
+        }
+    }
+}
+
+const RESTORE_POPUP: &str = "Restore backup?##timers_restore";
+
+fn render_backups(ui: &Ui, search: &Search, timers: &mut Timers, keep: u32) {
+    thread_local! {
+        // read once the section is shown and again after a restore or on refresh
+        static LISTED: RefCell<Option<Vec<Backup>>> = const { RefCell::new(None) };
+        static CHOSEN: RefCell<Option<Backup>> = const { RefCell::new(None) };
+    }
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    search.section(ui, "Backups", |section| {
+        section.extra(|| {
+            if keep == 0 {
+                ui.text_disabled("Backups are turned off in the general settings");
+            }
+            LISTED.with_borrow_mut(|listed| {
+                let backups = listed.get_or_insert_with(backup::list);
+                if backups.is_empty() {
+                    ui.text_disabled(
+                        "No backups yet, one is taken every 15 minutes the timers changed in",
+                    );
+                }
+                for b in backups.iter() {
+                    let _id = ui.push_id(b.path.to_string_lossy().as_ref());
+                    ui.text(format!("{}, {} timers", ago(now, b.at), b.timers));
+                    ui.same_line();
+                    if ui.small_button("Restore") {
+                        CHOSEN.set(Some(b.clone()));
+                        ui.open_popup(RESTORE_POPUP);
+                    }
+                }
+                if ui.button("Refresh") {
+                    *listed = None;
+                }
+            });
+        });
+    });
+    let mut restore = None;
+    ui.popup_modal(RESTORE_POPUP)
+        .always_auto_resize(true)
+        .build(ui, || {
+            CHOSEN.with_borrow(|chosen| {
+                if let Some(b) = chosen {
+                    ui.text(format!(
+                        "Replace the timers with the {} backed up {}?",
+                        b.timers,
+                        ago(now, b.at)
+                    ));
+                    ui.text_disabled("The current ones are backed up first, pack timers stay");
+                }
+            });
+            if ui.button("Restore") {
+                restore = CHOSEN.take();
+                ui.close_current_popup();
+            }
+            ui.same_line();
+            if ui.button("Cancel") || ui.is_key_pressed(Key::Escape) {
+                CHOSEN.set(None);
+                ui.close_current_popup();
+            }
+        });
+    let Some(chosen) = restore else {
+        return;
+    };
+    let restored = match backup::read(&chosen.path) {
+        Ok(restored) => restored,
+        Err(e) => {
+            notice(format!("Failed to read {}: {}", chosen.path.display(), e));
+            return;
+        }
+    };
+    // so restoring the wrong one can be undone, even with backups turned off
+    let keep = match keep {
+        0 => backup::MAX_KEEP,
+        keep => keep,
+    };
+    backup::take_now(backup::Pending::of(timers), keep);
+    for timer in timers.restore(restored) {
+        keybinds::unregister(&timer);
+    }
+    for timer in timers.list.iter().filter(|t| t.pack.is_none()) {
+        keybinds::register(timer);
+    }
+    LISTED.set(None);
+    notice(format!(
+        "Restored the {} timers backed up {}",
+        chosen.timers,
+        ago(now, chosen.at)
+    ));
+}
+
+fn render_presets_and_import(
+    ui: &Ui,
+    search: &Search,
+    timers: &mut Timers,
+    group_keybinds: &[String],
+) {
+    thread_local! {
+        static PRESET: Cell<usize> = const { Cell::new(0) };
+    }
+    search.group(ui, "Presets and import", |section| {
+        section.item("Preset", |label| {
+            let names: Vec<&str> = presets().iter().map(|p| p.name.as_str()).collect();
+            let mut preset = PRESET.get();
+            ui.combo_simple_string(label, &mut preset, &names);
+            PRESET.set(preset);
+            ui.same_line();
+            if ui.button("Add from preset") {
+                let preset = &presets()[preset];
+                let source = format!("the preset {}", preset.name);
+                preview_import(source, preset.timers.clone(), 0, timers);
+            }
+        });
+        section.item("Import from clipboard", |label| {
+            if ui.button(label) {
+                import_clipboard(ui, timers);
+            }
+        });
+        section.item("Import Blish HUD timers", |label| {
+            let dir = crate::config::addon_dir().join("blish");
+            if ui.button(label) {
+                import_blish(&dir, timers);
+            }
+            ui.same_line();
+            if ui.button("Export Blish HUD timers") {
+                match blish::export_dir(&dir, &timers.list) {
+                    Ok(n) => notice(format!("Exported {} encounters to {}", n, dir.display())),
+                    Err(e) => notice(format!("Blish HUD export failed: {}", e)),
+                }
+            }
+        });
+        section.item("Export timers", |label| {
+            let path = crate::config::addon_dir().join("timers-export.json");
+            if ui.button(label) {
+                match crate::config::export_timers(&path, timers) {
+                    Ok(n) => notice(format!("Exported {} timers to {}", n, path.display())),
+                    Err(e) => notice(format!("Timer export failed: {}", e)),
+                }
+            }
+            ui.same_line();
+            if ui.button("Import timers") {
+                import_file(&path, timers);
+            }
+        });
+        section.item("Export layout", |label| {
+            let path = crate::config::addon_dir().join("layout-export.json");
+            if ui.button(label) {
+                match layout::export(&path, &timers.list) {
+                    Ok(n) => notice(format!(
+                        "Exported the layout of {} timers to {}",
+                        n,
+                        path.display()
+                    )),
+                    Err(e) => notice(format!("Layout export failed: {}", e)),
+                }
+            }
+            ui.same_line();
+            if ui.button("Import layout") {
+                match layout::import(&path, &mut timers.list) {
+                    Ok(n) => notice(format!(
+                        "Imported the layout of {} timers from {}, timers missing here were skipped",
+                        n,
+                        path.display()
+                    )),
+                    Err(e) => notice(format!("Layout import failed: {}", e)),
+                }
+            }
+        });
+        section.item("Export keybinds", |label| {
+            let path = crate::config::addon_dir().join("keybinds.json");
+            if ui.button(label) {
+                match keybinds::export(&path, timers) {
+                    Ok(n) => notice(format!("Exported {} keybinds to {}", n, path.display())),
+                    Err(e) => notice(format!("Keybind export failed: {}", e)),
+                }
+            }
+            ui.same_line();
+            if ui.button("Import keybinds") {
+                match keybinds::import(&path, timers, group_keybinds) {
+                    Ok(n) => notice(format!(
+                        "Imported {} keybinds from {}, keys already assigned here were kept",
+                        n,
+                        path.display()
+                    )),
+                    Err(e) => notice(format!("Keybind import failed: {}", e)),
+                }
+            }
+        });
+    });
+    render_import_preview(ui, timers);
+}
+
+fn import_blish(dir: &std::path::Path, timers: &Timers) {
+    match blish::import_dir(dir) {
+        Ok(import) => {
+            for warning in &import.warnings {
+                log::warn!("Blish HUD import: {}", warning);
+            }
+            let source = dir.display().to_string();
+            preview_import(source, import.timers, import.warnings.len(), timers);
+        }
+        Err(e) => notice(format!(
+            "Blish HUD import from {} failed: {}",
+            dir.display(),
+            e
+        )),
+    }
+}
+
+fn import_file(path: &std::path::Path, timers: &Timers) {
+    match import::from_file(path) {
+        Ok(import) => {
+            for warning in &import.warnings {
+                log::warn!("Timer import: {}", warning);
+            }
+            let source = path.display().to_string();
+            preview_import(source, import.timers, import.warnings.len(), timers);
+        }
+        Err(e) => notice(format!("Timer import failed: {}", e)),
+    }
+}
+
+fn import_clipboard(ui: &Ui, timers: &Timers) {
+    let text = ui.clipboard_text().unwrap_or_default();
+    match import::from_slice(text.as_bytes()) {
+        Ok(import) => {
+            for warning in &import.warnings {
+                log::warn!("Clipboard import: {}", warning);
+            }
+            let source = "the clipboard".to_string();
+            preview_import(source, import.timers, import.warnings.len(), timers);
+        }
+        Err(e) => notice(format!("Clipboard import failed: {}", e)),
+    }
+}
+
+const IMPORT_POPUP: &str = "Import timers##timers_import";
+
+/// Imported timers waiting for what to do with each of them
+struct ImportPreview {
+    source: String,
+    timers: Vec<Timer>,
+    choices: Vec<Merge>,
+    warnings: usize,
+    opened: bool,
+}
+
+thread_local! {
+    static PREVIEW: RefCell<Option<ImportPreview>> = const { RefCell::new(None) };
+}
+
+/// Asks what to do with the timers `imported` from `source` before anything is added
+fn preview_import(source: String, imported: Vec<Timer>, warnings: usize, timers: &Timers) {
+    if imported.is_empty() {
+        notice(format!("No timers to import from {}", source));
+        return;
+    }
+    // names are unique, replacing a timer is opted into
+    let choices = imported
+        .iter()
+        .map(|t| {
+            if timers.contains_name(&t.name) {
+                Merge::Skip
+            } else {
+                Merge::Add
+            }
+        })
+        .collect();
+    PREVIEW.set(Some(ImportPreview {
+        source,
+        timers: imported,
+        choices,
+        warnings,
+        opened: false,
+    }));
+}
+
+fn render_import_preview(ui: &Ui, timers: &mut Timers) {
+    let mut apply = None;
+    PREVIEW.with_borrow_mut(|preview| {
+        let Some(preview) = preview.as_mut() else {
+            return;
+        };
+        if !preview.opened {
+            ui.open_popup(IMPORT_POPUP);
+            preview.opened = true;
+        }
+        let mut close = false;
+        ui.popup_modal(IMPORT_POPUP)
+            .always_auto_resize(true)
+            .build(ui, || {
+                ui.text(format!(
+                    "{} timers from {}",
+                    preview.timers.len(),
+                    preview.source
+                ));
+                if preview.warnings > 0 {
+                    ui.text_disabled(format!("{} warnings, see the log", preview.warnings));
+                }
+                let existing: Vec<bool> = preview
+                    .timers
+                    .iter()
+                    .map(|t| timers.contains_name(&t.name))
+                    .collect();
+                if existing.iter().any(|e| *e) {
+                    ui.text("For all timers that exist here:");
+                    for merge in Merge::ALL {
+                        ui.same_line();
+                        if ui.small_button(merge.name()) {
+                            for (choice, _) in preview
+                                .choices
+                                .iter_mut()
+                                .zip(&existing)
+                                .filter(|(_, e)| **e)
+                            {
+                                *choice = merge;
+                            }
+                        }
+                    }
+                }
+                ui.separator();
+                let names = Merge::ALL.map(Merge::name);
+                for (i, (timer, choice)) in preview
+                    .timers
+                    .iter()
+                    .zip(preview.choices.iter_mut())
+                    .enumerate()
+                {
+                    let _id = ui.push_id(i);
+                    if existing[i] {
+                        let _width = ui.push_item_width(FALLBACK_INPUT_WIDTH);
+                        let mut merge = *choice as usize;
+                        if ui.combo_simple_string("##merge", &mut merge, &names) {
+                            *choice = Merge::ALL[merge];
+                        }
+                    } else {
+                        let mut add = *choice != Merge::Skip;
+                        if ui.checkbox("##add", &mut add) {
+                            *choice = if add { Merge::Add } else { Merge::Skip };
+                        }
+                    }
+                    ui.same_line();
+                    ui.text(&timer.name);
+                    ui.same_line();
+                    ui.text_disabled(format!("{}s", format_seconds(timer.duration)));
+                    if existing[i] {
+                        ui.same_line();
+                        ui.text_disabled("(exists)");
+                    }
+                }
+                ui.separator();
+                if ui.button("Import") {
+                    apply = Some((
+                        std::mem::take(&mut preview.source),
+                        std::mem::take(&mut preview.timers),
+                        std::mem::take(&mut preview.choices),
+                    ));
+                    close = true;
+                }
+                ui.same_line();
+                if ui.button("Cancel") || ui.is_key_pressed(Key::Escape) {
+                    close = true;
+                }
+                if close {
+                    ui.close_current_popup();
+                }
+            });
+        if close {
+            PREVIEW.set(None);
+        }
+    });
+    let Some((source, imported, choices)) = apply else {
+        return;
+    };
+    let merged = timers.merge_imported(imported.into_iter().zip(choices).collect());
+    for (id, old) in &merged {
+        if let Some(old) = old {
+            keybinds::unregister(old);
+        }
+        if let Some(timer) = timers.get(*id) {
+            keybinds::register(timer);
+        }
+    }
+    let overwritten = merged.iter().filter(|(_, old)| old.is_some()).count();
+    notice(format!(
+        "Imported {} timers from {} ({} overwritten)",
+        merged.len(),
+        source,
+        overwritten
+    ));
+}
+
+fn set_enabled(timer: &mut Timer, enabled: bool) {
+    if timer.enabled == enabled {
+        return;
+    }
+    timer.set_enabled(enabled);
+    // Nexus remembers the key assigned to an id, so it's back once registered again
+    if enabled {
+        keybinds::register(timer);
+    } else {
+        keybinds::unregister(timer);
+    }
+}
+
+fn render_bulk_actions(
+    ui: &Ui,
+    timers: &mut Timers,
+    selected: &mut HashSet<u64>,
+    to_delete: &mut Vec<u64>,
+) {
+    thread_local! {
+        static GROUP: RefCell<String> = const { RefCell::new(String::new()) };
+    }
+    let names: Vec<String> = selected
+        .iter()
+        .filter_map(|id| timers.get(*id))
+        .map(|t| t.name.clone())
+        .collect();
+    ui.text(format!("{} selected:", selected.len()));
+    ui.same_line();
+    if ui.button("Start") {
+        names
+            .iter()
+            .for_each(|name| timers.start(name, StartSource::Ui));
+    }
+    ui.same_line();
+    if ui.button("Stop") {
+        names.iter().for_each(|name| timers.stop(name));
+    }
+    for (label, enabled) in [("Enable", true), ("Disable", false)] {
+        ui.same_line();
+        if ui.button(label) {
+            for timer in timers.list.iter_mut().filter(|t| selected.contains(&t.id)) {
+                set_enabled(timer, enabled);
+            }
+        }
+    }
+    ui.same_line();
+    if ui.button("Delete selected") {
+        to_delete.extend(selected.iter().copied());
+        return;
+    }
+    ui.same_line();
+    if ui.button("Clear selection") {
+        selected.clear();
+    }
+    GROUP.with_borrow_mut(|group| {
+        ui.input_text("##group", group).hint("Group").build();
+        ui.same_line();
+        if ui.button("Assign group") {
+            for timer in timers.list.iter_mut().filter(|t| selected.contains(&t.id)) {
+                timer.group = group.trim().to_string();
+            }
+        }
+    });
+}
+
+/// Text field accepting durations like `90`, `1:30` or `1 min 30 s` in any supported language
+fn input_duration(ui: &Ui, label: &str, value: &mut Duration) {
+    // imgui keeps its own buffer while the field is active, so this is only the initial text
+    let mut text = format_seconds(*value);
+    if ui.input_text(label, &mut text).build() {
+        if let Ok(d) = parse_duration(&text) {
+            *value = d;
+        }
+    }
+}
+
+/// Like `input_duration`, but only applied once the field is left so the timer isn't
+/// changed with every key typed
+fn input_duration_on_leave(ui: &Ui, label: &str, value: &mut Duration) {
+    thread_local! {
+        // (label, text) of the field being typed into
+        static TYPED: RefCell<Option<(String, String)>> = const { RefCell::new(None) };
+    }
+    let mut text = TYPED
+        .with_borrow(|typed| {
+            typed
+                .as_ref()
+                .filter(|(l, _)| l == label)
+                .map(|(_, t)| t.clone())
+        })
+        .unwrap_or_else(|| format_seconds(*value));
+    if ui.input_text(label, &mut text).build() {
+        TYPED.set(Some((label.to_string(), text)));
+    }
+    if ui.is_item_deactivated() {
+        let typed = TYPED.with_borrow_mut(|typed| typed.take_if(|(l, _)| l == label));
+        if let Some(d) = typed.and_then(|(_, text)| parse_duration(&text).ok()) {
+            *value = d;
+        }
+    }
+}
+
+/// A masked text input that only changes `value` once it is left
+fn input_secret_on_leave(ui: &Ui, label: &str, value: &mut String) {
+    thread_local! {
+        // (label, text) of the field being typed into
+        static TYPED: RefCell<Option<(String, String)>> = const { RefCell::new(None) };
+    }
+    let mut text = TYPED
+        .with_borrow(|typed| {
+            typed
+                .as_ref()
+                .filter(|(l, _)| l == label)
+                .map(|(_, t)| t.clone())
+        })
+        .unwrap_or_else(|| value.clone());
+    if ui.input_text(label, &mut text).password(true).build() {
+        TYPED.set(Some((label.to_string(), text)));
+    }
+    if ui.is_item_deactivated() {
+        let typed = TYPED.with_borrow_mut(|typed| typed.take_if(|(l, _)| l == label));
+        if let Some((_, text)) = typed {
+            *value = text.trim().to_string();
+        }
+    }
+}
+
+/// Seconds with up to millisecond fractions, which `parse_duration` reads back
+fn format_seconds(d: Duration) -> String {
+    let text = format!("{}.{:03}", d.as_secs(), d.subsec_millis());
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+fn input_volume(ui: &Ui, label: &str, value: &mut u8) {
+    let mut volume = i32::from(*value);
+    if ui.slider(label, 0, 100, &mut volume) {
+        *value = volume.clamp(0, 100) as u8;
+    }
+}
+
+fn input_u8(ui: &Ui, label: &str, value: &mut u8) {
+    let mut v = *value as i32;
+    if ui.input_int(label, &mut v).build() {
+        *value = v.clamp(0, u8::MAX as i32) as u8;
+    }
+}
+
+fn format_until(d: Duration) -> String {
+    let mins = d.as_secs() / 60;
+    let (days, hours, mins) = (mins / 1440, mins / 60 % 24, mins % 60);
+    if days > 0 {
+        format!("{}d {}h {}m", days, hours, mins)
+    } else {
+        format!("{}h {}m", hours, mins)
+    }
+}
+
+fn render_map_rules(ui: &Ui, rules: &mut Vec<MapRule>) {
+    ui.text_disabled(
+        "On a map with a rule only the timers of its groups are shown, e.g. the encounters \
+         of a raid wing.",
+    );
+    let current = maps::current();
+    match current {
+        Some(map) => ui.text(format!("Current map: {}", map)),
+        None => ui.text_disabled("Current map: unknown"),
+    }
+    let mut to_remove = None;
+    for (i, rule) in rules.iter_mut().enumerate() {
+        let _id = ui.push_id(i);
+        let mut map = rule.map as i32;
+        if ui.input_int("Map id", &mut map).build() {
+            rule.map = map.max(0) as u32;
+        }
+        ui.input_text("Name", &mut rule.name)
+            .hint("e.g. Spirit Vale")
+            .build();
+        let mut groups = rule.groups.join(", ");
+        if ui
+            .input_text("Groups", &mut groups)
+            .hint("comma separated")
+            .build()
+        {
+            rule.groups = groups
+                .split(',')
+                .map(|g| g.trim().to_string())
+                .filter(|g| !g.is_empty())
+                .collect();
+        }
+        if ui.button(tr("delete")) {
+            to_remove = Some(i);
+        }
+        ui.separator();
+    }
+    if let Some(i) = to_remove {
+        rules.remove(i);
+    }
+    if let Some(map) = current {
+        if ui.button("Add rule for the current map") {
+            rules.push(MapRule {
+                map,
+                ..MapRule::default()
+            });
+        }
+    }
+}
+
+fn render_resets(ui: &Ui, section: &Section, resets: &mut ResetSchedule) {
+    section.item("Daily reset hour (UTC)", |label| {
+        input_u8(ui, label, &mut resets.daily_hour)
+    });
+    section.item("Week starts on", |label| {
+        let names = Weekday::ALL.map(Weekday::name);
+        let mut day = resets.week_start as usize;
+        if ui.combo_simple_string(label, &mut day, &names) {
+            resets.week_start = Weekday::ALL[day];
+        }
+    });
+    section.item("Weekly reset hour (UTC)", |label| {
+        input_u8(ui, label, &mut resets.weekly_hour)
+    });
+    section.item("Weekly reset minute", |label| {
+        input_u8(ui, label, &mut resets.weekly_minute)
+    });
+    resets.sanitize();
+    section.extra(|| {
+        let now = SystemTime::now();
+        let until = |t: SystemTime| format_until(t.duration_since(now).unwrap_or_default());
+        ui.text_disabled(format!(
+            "Next daily reset in {}, next weekly reset in {}",
+            until(resets.next_daily(now)),
+            until(resets.next_weekly(now))
+        ));
+    });
+}
+
+/// Announcement marks as a list like `60; 30; 10; 5; 4; 3; 2; 1`
+fn input_marks(ui: &Ui, marks: &mut Vec<Duration>) {
+    let mut text = marks
+        .iter()
+        .map(|m| m.as_secs_f64().to_string())
+        .collect::<Vec<_>>()
+        .join("; ");
+    let changed = ui
+        .input_text("Announce at", &mut text)
+        .hint("60; 30; 10; 5; 4; 3; 2; 1")
+        .build();
+    if changed {
+        // ',' is a decimal separator for parse_duration, so only ';' separates
+        if let Ok(mut parsed) = text
+            .split(';')
+            .filter(|m| !m.trim().is_empty())
+            .map(parse_duration)
+            .collect::<Result<Vec<_>, _>>()
+        {
+            parsed.sort_unstable_by(|a, b| b.cmp(a));
+            parsed.dedup();
+            *marks = parsed;
+        }
+    }
+}
+
+/// Replays the latest recorded session and lists what happened when
+/// Runs shown in the options, the rest are only kept in history.json
+const SHOWN_RUNS: usize = 20;
+/// Size a window starts at once it is set to be resized by dragging
+const DEFAULT_WINDOW_SIZE: [f32; 2] = [200.0, 60.0];
+/// Latest log entries listed, the rest only gets copied
+const SHOWN_LOG: usize = 100;
+
+/// How long before `now` the unix time `at` was
+fn ago(now: u64, at: u64) -> String {
+    match now.saturating_sub(at) / 60 {
+        0 => "just now".to_string(),
+        m if m < 60 => format!("{}m ago", m),
+        m if m < 24 * 60 => format!("{}h ago", m / 60),
+        m => format!("{}d ago", m / (24 * 60)),
+    }
+}
+
+fn render_history(ui: &Ui, resets: &ResetSchedule) {
+    let history = HISTORY.locked();
+    if history.runs.is_empty() {
+        ui.text_disabled("No runs yet");
+        return;
+    }
+    ui.text("Per timer");
+    let wall = SystemTime::now();
+    for stats in history.stats() {
+        ui.text(format!(
+            "  {}: {} runs, {} finished, {:.1}s on average",
+            stats.timer,
+            stats.runs,
+            stats.finished,
+            stats.average.as_secs_f32()
+        ));
+        if stats.finished > 0 {
+            ui.same_line();
+            let completions = history.completions(&stats.timer, resets, wall);
+            ui.text_disabled(completions.label());
+        }
+    }
+    ui.text("Latest runs");
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    for run in history.runs.iter().rev().take(SHOWN_RUNS) {
+        let outcome = match run.outcome {
+            Outcome::Finished => "finished",
+            Outcome::Stopped => "stopped",
+        };
+        ui.text(format!(
+            "  {} {} after {:.1}s, {}",
+            run.timer,
+            outcome,
+            run.length.as_secs_f32(),
+            ago(now, run.ended)
+        ));
+        if !run.note.is_empty() {
+            ui.same_line();
+            ui.text_disabled(format!("\"{}\"", run.note));
+        }
+        if !run.laps.is_empty() {
+            ui.same_line();
+            ui.text_disabled(format!("{} laps", run.laps.len()));
+            if ui.is_item_hovered() {
+                ui.tooltip_text(format_laps(&run.laps, TimeStyle::default()));
+            }
+        }
+    }
+}
+
+fn render_event_log(ui: &Ui) {
+    thread_local! {
+        static FILTER: RefCell<String> = const { RefCell::new(String::new()) };
+    }
+    FILTER.with_borrow_mut(|filter| {
+        ui.input_text("Timer##event_log_filter", filter)
+            .hint("only entries of timers with this in their name")
+            .build();
+        let filter = filter.trim().to_lowercase();
+        let offset = alarm::local_offset();
+        let mut log = eventlog::LOG.locked();
+        let lines: Vec<String> = log
+            .entries
+            .iter()
+            .rev()
+            .filter(|e| filter.is_empty() || e.timer.to_lowercase().contains(&filter))
+            .map(|e| e.line(offset))
+            .collect();
+        if ui.button("Copy to clipboard##event_log") {
+            ui.set_clipboard_text(lines.join("\n"));
+        }
+        ui.same_line();
+        if ui.button("Clear##event_log") {
+            log.entries.clear();
+        }
+        if lines.is_empty() {
+            ui.text_disabled("Nothing logged yet");
+        }
+        for line in lines.iter().take(SHOWN_LOG) {
+            ui.text(line);
+        }
+        if lines.len() > SHOWN_LOG {
+            ui.text_disabled(format!(
+                "{} older entries, copy them to the clipboard",
+                lines.len() - SHOWN_LOG
+            ));
+        }
+    });
+}
+
+fn render_replay(ui: &Ui, label: &str) {
+    thread_local! {
+        static REPLAY: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+    if ui.button(label) {
+        let lines = match session::load_latest() {
+            Ok((path, recorded)) => {
+                let events = session::replay(&recorded, Duration::from_millis(16));
+                let name = |id| {
+                    recorded
+                        .timers
+                        .iter()
+                        .find(|t| t.id == id)
+                        .map_or("?", |t| t.name.as_str())
+                };
+                std::iter::once(format!(
+                    "{}: {} actions",
+                    path.display(),
+                    recorded.entries.len()
+                ))
+                .chain(events.into_iter().map(|(at, event)| match event {
+                    TimerEvent::Mark(id, mark) => format!(
+                        "{:>9.3}s  {} at {}s",
+                        at.as_secs_f32(),
+                        name(id),
+                        mark.as_secs_f32()
+                    ),
+                    TimerEvent::Started(id, source) => format!(
+                        "{:>9.3}s  {} started by {}",
+                        at.as_secs_f32(),
+                        name(id),
+                        source.name()
+                    ),
+                    TimerEvent::Warn(id, channel) => format!(
+                        "{:>9.3}s  {} {} alert",
+                        at.as_secs_f32(),
+                        name(id),
+                        channel.name()
+                    ),
+                    TimerEvent::Step(id, step) => format!(
+                        "{:>9.3}s  {} on to step {}",
+                        at.as_secs_f32(),
+                        name(id),
+                        step + 1
+                    ),
+                    TimerEvent::Possible(id) => {
+                        format!("{:>9.3}s  {} possible", at.as_secs_f32(), name(id))
+                    }
+                    TimerEvent::Finished(id) => {
+                        format!("{:>9.3}s  {} finished", at.as_secs_f32(), name(id))
+                    }
+                    TimerEvent::Stopped(id, ran) => format!(
+                        "{:>9.3}s  {} stopped after {}s",
+                        at.as_secs_f32(),
+                        name(id),
+                        ran.as_secs_f32()
+                    ),
+                }))
+                .collect()
+            }
+            Err(e) => vec![format!("Replay failed: {}", e)],
+        };
+        REPLAY.set(lines);
+    }
+    REPLAY.with_borrow(|lines| {
+        for line in lines {
+            ui.text_disabled(line);
+        }
+    });
+}
+
+fn render_alarm_editor(ui: &Ui, alarm: &mut Option<Alarm>) {
+    let mut enabled = alarm.is_some();
+    if ui.checkbox("Alarm at a time of day instead of a duration", &mut enabled) {
+        *alarm = enabled.then(Alarm::default);
+    }
+    let Some(alarm) = alarm else {
+        return;
+    };
+    input_u8(ui, "Hour##alarm", &mut alarm.hour);
+    input_u8(ui, "Minute##alarm", &mut alarm.minute);
+    alarm.sanitize();
+    ui.checkbox("UTC instead of local time", &mut alarm.utc);
+    let repeats = ["Once, when started", "Daily", "Weekly"];
+    let mut repeat = match alarm.repeat {
+        Repeat::Once => 0,
+        Repeat::Daily => 1,
+        Repeat::Weekly(_) => 2,
+    };
+    if ui.combo_simple_string("Repeat", &mut repeat, &repeats) {
+        alarm.repeat = match repeat {
+            0 => Repeat::Once,
+            1 => Repeat::Daily,
+            _ => Repeat::Weekly(Weekday::default()),
+        };
+    }
+    if let Repeat::Weekly(weekday) = &mut alarm.repeat {
+        let days = Weekday::ALL.map(Weekday::name);
+        let mut day = *weekday as usize;
+        if ui.combo_simple_string("Day", &mut day, &days) {
+            *weekday = Weekday::ALL[day];
+        }
+    }
+}
+
+fn render_chat_editor(ui: &Ui, chat: &mut Option<ChatTrigger>) {
+    let mut enabled = chat.is_some();
+    if ui.checkbox("Start on a keyword in chat", &mut enabled) {
+        *chat = enabled.then(ChatTrigger::default);
+    }
+    let Some(chat) = chat else {
+        return;
+    };
+    ui.input_text("Keyword##chat", &mut chat.keyword)
+        .hint("e.g. burn in")
+        .build();
+    ui.same_line();
+    ui.checkbox("Regex##chat", &mut chat.regex);
+    if let Some(error) = chat.error() {
+        ui.text_colored([1.0, 0.3, 0.3, 1.0], error);
+    }
+    let channels = ChatChannel::ALL.map(ChatChannel::name);
+    let mut channel = chat.channel as usize;
+    if ui.combo_simple_string("Channel##chat", &mut channel, &channels) {
+        chat.channel = ChatChannel::ALL[channel];
+    }
+    ui.text_disabled("Needs the Unofficial Extras addon, which forwards squad and party chat");
+}
+
+fn render_combat_editor(ui: &Ui, combat: &mut Option<CombatTrigger>) {
+    let mut enabled = combat.is_some();
+    if ui.checkbox("Start on a skill or buff in combat", &mut enabled) {
+        *combat = enabled.then(CombatTrigger::default);
+    }
+    let Some(combat) = combat else {
+        return;
+    };
+    let kinds = CombatKind::ALL.map(CombatKind::name);
+    let mut kind = combat.kind as usize;
+    if ui.combo_simple_string("Event##combat", &mut kind, &kinds) {
+        combat.kind = CombatKind::ALL[kind];
+    }
+    let mut ids = combat
+        .ids
+        .iter()
+        .map(u32::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+    if ui
+        .input_text("Skill or buff ids##combat", &mut ids)
+        .hint("comma separated, e.g. 34664")
+        .build()
+    {
+        combat.ids = ids
+            .split(',')
+            .filter_map(|id| id.trim().parse().ok())
+            .collect();
+    }
+    ui.text_disabled("Needs arcdps, the ids are on the wiki and in arcdps logs");
+}
+
+/// Characters the timer is shown on, e.g. for class cooldowns. Returns whether they
+/// changed.
+fn render_characters_editor(ui: &Ui, characters: &mut Vec<String>) -> bool {
+    let mut list = characters.join(", ");
+    let mut changed = false;
+    if ui
+        .input_text("Characters", &mut list)
+        .hint("comma separated, shown on all if empty")
+        .build()
+    {
+        *characters = list
+            .split(',')
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .map(str::to_string)
+            .collect();
+        changed = true;
+    }
+    let Some(current) = crate::game::character() else {
+        return changed;
+    };
+    if !characters.contains(&current) {
+        ui.same_line();
+        if ui.small_button(format!("Add {}", current)) {
+            characters.push(current);
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// What the overlay reads instead of the remaining time
+fn render_template_editor(ui: &Ui, template: &mut String) {
+    ui.input_text("Display template", template)
+        .hint("e.g. {name}: {remaining} ({elapsed} elapsed)")
+        .build();
+    if template.is_empty() {
+        return;
+    }
+    match template::validate(template) {
+        Ok(()) => ui.text_disabled(format!(
+            "Placeholders: {{{}}}",
+            template::PLACEHOLDERS.join("}, {")
+        )),
+        Err(e) => ui.text_colored([1.0, 0.6, 0.0, 1.0], e),
+    }
+}
+
+/// Whether the overlay window fits its text or keeps the size it is dragged to
+fn render_window_size_editor(ui: &Ui, window_size: &mut Option<[f32; 2]>) {
+    let choices = ["Fit the text", "Drag to resize"];
+    let mut chosen = usize::from(window_size.is_some());
+    if ui.combo_simple_string("Window size", &mut chosen, &choices) {
+        *window_size = (chosen == 1).then_some(DEFAULT_WINDOW_SIZE);
+    }
+    if window_size.is_some() {
+        ui.same_line();
+        ui.text_disabled("(drag its corner in the overlay, kept from then on)");
+    }
+}
+
+/// Own time after which the window of a finished or paused timer hides
+fn render_hide_idle_editor(ui: &Ui, hide_idle: &mut Option<Duration>) {
+    let choices = ["Default", "Never", "After"];
+    let mut chosen = match hide_idle {
+        None => 0,
+        Some(after) if after.is_zero() => 1,
+        Some(_) => 2,
+    };
+    if ui.combo_simple_string("Hide while finished or paused", &mut chosen, &choices) {
+        *hide_idle = match chosen {
+            0 => None,
+            1 => Some(Duration::ZERO),
+            _ => Some(Duration::from_secs(10 * 60)),
+        };
+    }
+    if let Some(after) = hide_idle.as_mut().filter(|a| !a.is_zero()) {
+        input_duration(ui, "Hide after", after);
+    }
+}
+
+/// Variable timing, from the earliest point up to the duration
+fn render_earliest_editor(ui: &Ui, earliest: &mut Option<Duration>, duration: Duration) {
+    let mut enabled = earliest.is_some();
+    if ui.checkbox("Varies, can happen from", &mut enabled) {
+        *earliest = enabled.then_some(duration / 2);
+    }
+    if let Some(earliest) = earliest {
+        ui.same_line();
+        input_duration(ui, "##earliest", earliest);
+        *earliest = (*earliest).min(duration);
+        ui.same_line();
+        ui.text_disabled(format!("up to {}s", format_seconds(duration)));
+    }
+}
+
+/// Steps of a timeline, the duration of the timer follows their total
+fn render_timeline_editor(ui: &Ui, timeline: &mut Option<Timeline>, duration: &mut Duration) {
+    let mut enabled = timeline.is_some();
+    if ui.checkbox("Timeline of phases", &mut enabled) {
+        *timeline = enabled.then(|| Timeline {
+            steps: vec![Step {
+                label: "Phase 1".to_string(),
+                duration: *duration,
+            }],
+        });
+    }
+    let Some(timeline) = timeline else {
+        return;
+    };
+    let mut remove = None;
+    for (i, step) in timeline.steps.iter_mut().enumerate() {
+        ui.input_text(format!("##step{}", i), &mut step.label)
+            .hint("Phase")
+            .build();
+        input_duration(ui, &format!("##step_duration{}", i), &mut step.duration);
+        ui.same_line();
+        if ui.small_button(format!("Remove##step{}", i)) {
+            remove = Some(i);
+        }
+    }
+    if let Some(i) = remove {
+        timeline.steps.remove(i);
+    }
+    if ui.button("Add step") {
+        let label = format!("Phase {}", timeline.steps.len() + 1);
+        timeline.steps.push(Step {
+            label,
+            duration: Duration::from_secs(30),
+        });
+    }
+    *duration = timeline.total();
+}
+
+fn render_anchor_editor(ui: &Ui, anchor: &mut Option<Anchor>) {
+    let mut anchored = anchor.is_some();
+    if ui.checkbox("Anchor the window to the screen", &mut anchored) {
+        *anchor = anchored.then(Anchor::default);
+    }
+    let Some(anchor) = anchor else {
+        return;
+    };
+    let points = AnchorPoint::ALL.map(AnchorPoint::name);
+    let mut point = anchor.point as usize;
+    if ui.combo_simple_string("Anchor", &mut point, &points) {
+        anchor.point = AnchorPoint::ALL[point];
+    }
+    ui.input_float2("Anchor offset (px)", &mut anchor.offset)
+        .build();
+    anchor.sanitize();
+}
+
+fn render_fade_editor(ui: &Ui, fade: &mut Option<Fade>) {
+    let mut enabled = fade.is_some();
+    if ui.checkbox("Draw attention as it runs out", &mut enabled) {
+        *fade = enabled.then(Fade::default);
+    }
+    let Some(fade) = fade else {
+        return;
+    };
+    input_duration(ui, "From##fade", &mut fade.from);
+    let styles = FadeStyle::ALL.map(FadeStyle::name);
+    let mut style = fade.style as usize;
+    if ui.combo_simple_string("Style##fade", &mut style, &styles) {
+        fade.style = FadeStyle::ALL[style];
+    }
+}
+
+fn render_bands_editor(ui: &Ui, bands: &mut Vec<ColorBand>) {
+    if !ui.collapsing_header("Color bands", TreeNodeFlags::empty()) {
+        return;
+    }
+    ui.text_disabled("Color of the remaining time from each time down to the next band");
+    let mut to_remove = None;
+    for (i, band) in bands.iter_mut().enumerate() {
+        let _id = ui.push_id(i);
+        input_duration(ui, "From", &mut band.from);
+        ui.same_line();
+        ColorEdit::new("##color", &mut band.color)
+            .inputs(false)
+            .build(ui);
+        ui.same_line();
+        if ui.small_button(tr("delete")) {
+            to_remove = Some(i);
+        }
+    }
+    if let Some(i) = to_remove {
+        bands.remove(i);
+    }
+    if ui.button("Add band") {
+        bands.push(ColorBand {
+            from: Duration::ZERO,
+            color: [1.0, 1.0, 1.0, 1.0],
+        });
+    }
+    ui.same_line();
+    if ui.button("Green, yellow, red") {
+        *bands = ColorBand::traffic_light();
+    }
+}
+
+fn render_sound_files(ui: &Ui, sounds: &mut SoundFiles) {
+    thread_local! {
+        // scanned when the header is opened, not every frame
+        static FILES: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+    }
+    if !ui.collapsing_header("Sound files", TreeNodeFlags::empty()) {
+        FILES.set(None);
+        return;
+    }
+    FILES.with_borrow_mut(|files| {
+        let files = files.get_or_insert_with(audio::sound_files);
+        ui.text_disabled("WAV files in the sounds folder, played instead of the tone");
+        if files.is_empty() {
+            ui.text_disabled("No sounds found");
+        }
+        let events = [
+            ("On start", &mut sounds.start, SoundKind::Start),
+            ("On warning", &mut sounds.warning, SoundKind::Warning),
+            ("On finish", &mut sounds.finish, SoundKind::Finish),
+        ];
+        for (label, file, kind) in events {
+            let _id = ui.push_id(label);
+            let mut names = vec!["Tone"];
+            names.extend(files.iter().map(String::as_str));
+            let mut picked = files.iter().position(|f| f == file).map_or(0, |i| i + 1);
+            if ui.combo_simple_string(label, &mut picked, &names) {
+                *file = names[picked].to_string();
+                if picked == 0 {
+                    file.clear();
+                }
+            }
+            if !file.is_empty() {
+                ui.same_line();
+                if ui.button("Preview") {
+                    audio::play_file(file, kind);
+                }
+            }
+        }
+    });
+    if ui.button("Rescan sounds") {
+        FILES.set(None);
+    }
+}
+
+fn render_marker_editor(ui: &Ui, marker: &mut Option<Marker>) {
+    let mut shown = marker.is_some();
+    if ui.checkbox("Show a marker on screen while running", &mut shown) {
+        *marker = shown.then(|| Marker {
+            position: ui.io().display_size.map(|d| d / 2.0),
+            ..Marker::default()
+        });
+    }
+    let Some(marker) = marker else {
+        return;
+    };
+    let shapes = MarkerShape::ALL.map(MarkerShape::name);
+    let mut shape = marker.shape as usize;
+    if ui.combo_simple_string("Marker shape", &mut shape, &shapes) {
+        marker.shape = MarkerShape::ALL[shape];
+    }
+    ui.input_float2("Marker position (px)", &mut marker.position)
+        .build();
+    ui.input_float("Marker size (px)", &mut marker.size).build();
+    marker.size = marker.size.clamp(1.0, crate::marker::MAX_SIZE);
+    ui.input_text("Marker label", &mut marker.label).build();
+}
+
+fn render_names_editor(ui: &Ui, timer: &mut Timer) {
+    if !ui.collapsing_header("Names per language", TreeNodeFlags::empty()) {
+        return;
+    }
+    let mut changed = false;
+    for &lang in LANGS {
+        let mut name = timer.names.get(lang).cloned().unwrap_or_default();
+        if ui
+            .input_text(format!("Name ({})", lang), &mut name)
+            .hint(&timer.name)
+            .build()
+        {
+            let name = name.trim();
+            if name.is_empty() {
+                timer.names.remove(lang);
+            } else {
+                timer.names.insert(lang.to_string(), name.to_string());
+            }
+            changed = true;
+        }
+    }
+    if changed {
+        keybinds::localize_timer(timer);
+    }
+}
+
+thread_local! {
+    // Timer whose settings are shown in a window of their own
+    static EDITOR_WINDOW: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// Shows the settings of a timer outside of the options, e.g. from the overlay
+pub fn open_editor(id: u64) {
+    EDITOR_WINDOW.set(Some(id));
+}
+
+pub fn render_editor_window(ui: &Ui) {
+    let Some(id) = EDITOR_WINDOW.get() else {
+        return;
+    };
+    let mut timers = TIMERS.get().expect("Timers to be set").locked();
+    let Some(timer) = timers.get_mut(id).filter(|t| t.system.is_none()) else {
+        EDITOR_WINDOW.set(None);
+        return;
+    };
+    let mut close = false;
+    Window::new("Timer settings##timers_editor")
+        .always_auto_resize(true)
+        .build(ui, || {
+            render_timer_editor(ui, timer);
+            close = ui.button(tr("close"));
+        });
+    if close {
+        EDITOR_WINDOW.set(None);
+    }
+}
+
+fn render_timer_editor(ui: &Ui, timer: &mut Timer) {
+    ui.text(format!("Settings for {}", timer.name));
+    render_names_editor(ui, timer);
+    ui.input_text("Group", &mut timer.group).build();
+    if render_characters_editor(ui, &mut timer.characters) {
+        keybinds::rescope(timer);
+    }
+    ui.input_text("Exclusive group", &mut timer.exclusive)
+        .hint("starting one stops the others")
+        .build();
+    ui.input_text_multiline("Note", &mut timer.note, [0.0, ui.text_line_height() * 3.0])
+        .build();
+    ui.checkbox("Favorite (button in the hotbar)", &mut timer.favorite);
+    ui.checkbox("Share starts with the squad", &mut timer.share);
+    render_alarm_editor(ui, &mut timer.alarm);
+    render_chat_editor(ui, &mut timer.chat);
+    render_combat_editor(ui, &mut timer.combat);
+    ui.checkbox("Start when a ready check completes", &mut timer.ready_check);
+    if timer.ready_check {
+        ui.text_disabled("Needs Unofficial Extras, the count-in can wait for the pull");
+    }
+    render_timeline_editor(ui, &mut timer.timeline, &mut timer.duration);
+    if timer.alarm.is_none() && timer.timeline.is_none() {
+        render_earliest_editor(ui, &mut timer.earliest, timer.duration);
+    }
+    input_duration(ui, "Count-in", &mut timer.delay);
+    if timer.alarm.is_none() {
+        ui.checkbox("Allow several runs at once", &mut timer.instances);
+        if ui.is_item_hovered() {
+            ui.tooltip_text(
+                "Starting it while it runs starts another run under it, e.g. for adds that spawn at different times. Runs are stopped from the overlay.",
+            );
+        }
+        let max = import::MAX_OFFSET_MS;
+        if ui
+            .input_int("Start offset (ms)", &mut timer.offset_ms)
+            .build()
+        {
+            timer.offset_ms = timer.offset_ms.clamp(-max, max);
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text(
+                "Counts the timer as started this much earlier, for your reaction time to the cue. Negative starts it later.",
+            );
+        }
+    }
+    ui.checkbox("Keep counting past zero", &mut timer.overtime);
+    input_duration(ui, "Big countdown for the last", &mut timer.countdown);
+    ui.same_line();
+    ui.text_disabled("(0 for none)");
+    let mut hides = timer.hold.is_some();
+    if ui.checkbox("Hide once finished", &mut hides) {
+        timer.hold = hides.then_some(Duration::from_secs(5));
+    }
+    if let Some(hold) = &mut timer.hold {
+        input_duration(ui, "Show \"Done\" for", hold);
+    } else {
+        ui.same_line();
+        ui.text_disabled("(stays until stopped)");
+    }
+    let mut capped = timer.max_run.is_some();
+    if ui.checkbox("Stop runs that go on too long", &mut capped) {
+        timer.max_run = capped.then_some(Duration::from_secs(15 * 60));
+    }
+    if ui.is_item_hovered() {
+        ui.tooltip_text(
+            "Stops a run left going once it reaches this, counted as stopped in the history",
+        );
+    }
+    if let Some(max_run) = &mut timer.max_run {
+        input_duration(ui, "Stop runs after", max_run);
+        *max_run = (*max_run).max(import::MIN_RUN);
+    }
+    render_hide_idle_editor(ui, &mut timer.hide_idle);
+    ui.input_text("Icon", &mut timer.icon)
+        .hint("image in the addon folder, e.g. icons/greens.png")
+        .build();
+    render_anchor_editor(ui, &mut timer.anchor);
+    render_window_size_editor(ui, &mut timer.window_size);
+    render_marker_editor(ui, &mut timer.marker);
+    ui.input_text("Link", &mut timer.url)
+        .hint("e.g. the wiki page, https://...")
+        .build();
+    if !timer.url.is_empty() && !links::is_valid(&timer.url) {
+        ui.same_line();
+        ui.text_colored([1.0, 0.6, 0.0, 1.0], "not a web link");
+    }
+    ui.input_text("Webhook", &mut timer.webhook)
+        .hint("posted to on start and finish, https://...")
+        .build();
+    if !timer.webhook.is_empty() && !webhook::is_valid(&timer.webhook) {
+        ui.same_line();
+        ui.text_colored([1.0, 0.6, 0.0, 1.0], "not an https URL");
+    }
+    ui.checkbox("Ask for a note when stopped", &mut timer.ask_note);
+    ui.input_text("Finish text", &mut timer.finish_text)
+        .hint("copied to the clipboard on finish")
+        .build();
+    let mut pause = timer.standby == StandbyMode::Pause;
+    if ui.checkbox("Pause while in standby", &mut pause) {
+        timer.standby = if pause {
+            StandbyMode::Pause
+        } else {
+            StandbyMode::Elapse
+        };
+    }
+    ui.checkbox("Play sound on finish", &mut timer.sound);
+    ui.checkbox("Speak name and announcements", &mut timer.voice);
+    ui.checkbox("Flash on finish", &mut timer.flash);
+    if ui.collapsing_header("Alert lead times", TreeNodeFlags::empty()) {
+        ui.text_disabled("How long before the end each alert goes off, 0 for on finish");
+        for channel in Channel::ALL {
+            input_duration(ui, channel.name(), timer.leads.get_mut(channel));
+        }
+    }
+    let priorities = Priority::ALL.map(Priority::name);
+    let mut priority = timer.priority as usize;
+    if ui.combo_simple_string("Priority", &mut priority, &priorities) {
+        timer.priority = Priority::ALL[priority];
+    }
+    ui.same_line();
+    if ui.button("Test") {
+        audio::cue(timer.priority, SoundKind::Finish);
+    }
+    render_sound_files(ui, &mut timer.sounds);
+    render_bands_editor(ui, &mut timer.bands);
+    render_fade_editor(ui, &mut timer.fade);
+    input_marks(ui, &mut timer.marks);
+    let cues = ["Flash", "Sound", "Flash and sound"];
+    let mut cue = timer.mark_cue as usize;
+    if ui.combo_simple_string("Announce marks with", &mut cue, &cues) {
+        timer.mark_cue = [MarkCue::Ping, MarkCue::Sound, MarkCue::Both][cue];
+    }
+    let formats: Vec<&str> = ["Default"]
+        .into_iter()
+        .chain(TimeFormat::ALL.map(TimeFormat::name))
+        .collect();
+    let mut format = timer.format.map_or(0, |f| f as usize + 1);
+    if ui.combo_simple_string("Time format", &mut format, &formats) {
+        timer.format = format.checked_sub(1).map(|f| TimeFormat::ALL[f]);
+    }
+    let decimals: Vec<&str> = ["Default"].into_iter().chain(DECIMALS).collect();
+    let mut chosen = timer.decimals.map_or(0, |d| d as usize + 1);
+    if ui.combo_simple_string("Decimals of seconds", &mut chosen, &decimals) {
+        timer.decimals = chosen.checked_sub(1).map(|d| d as u8);
+    }
+    let roundings: Vec<&str> = ["Default"]
+        .into_iter()
+        .chain(Rounding::ALL.map(Rounding::name))
+        .collect();
+    let mut rounding = timer.rounding.map_or(0, |r| r as usize + 1);
+    if ui.combo_simple_string("Round the remaining time", &mut rounding, &roundings) {
+        timer.rounding = rounding.checked_sub(1).map(|r| Rounding::ALL[r]);
+    }
+    let rates: Vec<&str> = ["Default"]
+        .into_iter()
+        .chain(REFRESH_RATES.map(|(name, _)| name))
+        .collect();
+    let mut rate = timer.refresh_hz.map_or(0, |hz| {
+        REFRESH_RATES
+            .iter()
+            .position(|(_, r)| *r == hz)
+            .map_or(0, |i| i + 1)
+    });
+    if ui.combo_simple_string("Refresh the text", &mut rate, &rates) {
+        timer.refresh_hz = rate.checked_sub(1).map(|r| REFRESH_RATES[r].1);
+    }
+    render_template_editor(ui, &mut timer.template);
+    let actions = KeyAction::ALL.map(KeyAction::name);
+    let mut action = timer.key_action as usize;
+    if ui.combo_simple_string("Keybind action", &mut action, &actions) {
+        timer.key_action = KeyAction::ALL[action];
+    }
+    input_duration(ui, "Time step", &mut timer.add_step);
+    input_duration(
+        ui,
+        "Ignore the keybind after finishing for",
+        &mut timer.lockout,
+    );
+    ui.same_line();
+    ui.text_disabled("(0 for never)");
+    input_duration(ui, "Hold the keybind to start for", &mut timer.press_hold);
+    if ui.is_item_hovered() {
+        ui.tooltip_text("It only acts once held this long, 0 to act on the press");
+    }
+    if ui.checkbox("Keybinds to add and remove time", &mut timer.time_keybinds) {
+        if timer.time_keybinds {
+            keybinds::register(timer);
+        } else {
+            keybinds::unregister_time_keybinds(timer);
+        }
+    }
+    if ui.checkbox("Keybind to record laps", &mut timer.lap_keybind) {
+        if timer.lap_keybind {
+            keybinds::register(timer);
+        } else {
+            keybinds::unregister_lap_keybind(timer);
+        }
+    }
+    ui.checkbox("Start when the addon loads", &mut timer.autostart);
+    if ui.is_item_hovered() {
+        ui.tooltip_text("Unless it is still running from before");
+    }
+    ui.checkbox("Show how often it finished", &mut timer.show_completions);
+    if ui.is_item_hovered() {
+        ui.tooltip_text("Today and this week, by the daily and weekly reset");
+    }
+    let snaps = StartSnap::ALL.map(StartSnap::name);
+    let mut snap = timer.snap as usize;
+    if ui.combo_simple_string("Snap start to", &mut snap, &snaps) {
+        timer.snap = StartSnap::ALL[snap];
+    }
+    if !timer.extra.is_empty() {
+        ui.text_disabled("Extra metadata:");
+        for (key, value) in &timer.extra {
+            ui.text_disabled(format!("  {}: {}", key, value));
+        }
+    }
+}
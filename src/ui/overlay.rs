@@ -0,0 +1,887 @@
+use crate::format::{time_of_day, Rounding, TimeStyle};
+use crate::history::{Completions, HISTORY};
+use crate::sync::LockExt;
+use crate::theme::Palette;
+use crate::timeline::Timeline;
+use crate::timer::{FadeStyle, StartSource, Timer, TimerState};
+use crate::{icons, links, maps, template, SETTINGS, TIMERS};
+use nexus::imgui::{Condition, Io, MouseButton, StyleColor, Ui, Window};
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use super::animation::{self, Channel, Easing};
+use super::format_laps;
+use super::snap;
+
+const PING_DURATION: Duration = Duration::from_millis(400);
+
+/// Background alpha at the start of a fade, fully opaque at zero
+const FADE_BG_ALPHA: f32 = 0.2;
+const PULSE_HZ: f32 = 2.0;
+/// How far the text fades at the bottom of a pulse at zero
+const PULSE_DEPTH: f32 = 0.8;
+/// Text alpha of a timer whose keybind is locked out
+const LOCKOUT_ALPHA: f32 = 0.4;
+/// Text alpha of a timer frozen while the game is loading
+const FROZEN_ALPHA: f32 = 0.5;
+
+/// Latest laps shown under the remaining time
+const SHOWN_LAPS: usize = 3;
+
+/// How often timer text is refreshed while the overlay is degraded
+const DEGRADED_UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+
+thread_local! {
+    // Smoothed cost of `render_overlay` in milliseconds
+    static FRAME_COST: Cell<f32> = const { Cell::new(0.0) };
+    static DEGRADED: Cell<bool> = const { Cell::new(false) };
+    // Formatted text per timer id
+    static LABELS: RefCell<HashMap<u64, Label>> = RefCell::new(HashMap::new());
+}
+
+/// What a label reads, it is only formatted again once this changes
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Shown {
+    CountIn(u64),
+    Locked(u64),
+    Overtime(u64),
+    Done,
+    Alarm(u64),
+    /// Until the earliest and the latest point of a variable timer, still to come
+    Range(u64, u64),
+    /// Until the latest point of a variable timer, it can happen any moment now
+    Possible(u64),
+    Rest(u64),
+}
+
+#[derive(Default)]
+struct Label {
+    text: String,
+    shown: Option<(Shown, TimeStyle, bool, bool)>,
+    /// When it was last refreshed
+    updated: Option<Instant>,
+    /// The latest laps, formatted again once there is another
+    laps: String,
+    lap_count: Option<(usize, TimeStyle)>,
+    /// Template and name the text was filled in with
+    template: String,
+    name: String,
+    /// `Finishes at 21:43:10`, formatted again once the second changes
+    finish: String,
+    finish_at: Option<u64>,
+    /// How often it finished lately, formatted again once that changes
+    completions: String,
+    completed: Option<Completions>,
+}
+
+impl Label {
+    /// Formats the text again if it would read differently, returns whether it did.
+    /// `frozen` if it is paused for a loading screen.
+    fn update(
+        &mut self,
+        timer: &Timer,
+        now: Instant,
+        rest: Duration,
+        format: TimeStyle,
+        frozen: bool,
+    ) -> bool {
+        // snapping to the engine's grid is a count-in of under a second, don't
+        // announce that, waiting for the wall clock can take much longer
+        let count_in = timer
+            .count_in(now)
+            .filter(|_| !timer.delay.is_zero() || timer.snap.is_wall_clock());
+        let over = timer.overtime_elapsed(now);
+        // counting up, so the time past zero and the elapsed time aren't rounded up
+        let counting = format.rounded(Rounding::Down);
+        // a stopped timer is only still shown for its lockout
+        let locked = timer.locked_out(now).filter(|_| !timer.state.is_active());
+        let shown = if let Some(c) = count_in {
+            Shown::CountIn(c.as_secs_f32().ceil() as u64)
+        } else if let Some(l) = locked {
+            Shown::Locked(l.as_secs_f32().ceil() as u64)
+        } else if let Some(over) = over {
+            Shown::Overtime(counting.tick(over))
+        } else if timer.hold.is_some() && matches!(timer.state, TimerState::Finished { .. }) {
+            Shown::Done
+        } else if timer.alarm.is_some() {
+            Shown::Alarm(format.tick(rest))
+        } else if timer.earliest.is_some() {
+            match timer.until_possible(rest) {
+                Some(until) => Shown::Range(format.tick(until), format.tick(rest)),
+                None => Shown::Possible(format.tick(rest)),
+            }
+        } else {
+            Shown::Rest(format.tick(rest))
+        };
+        let key = Some((shown, format, timer.state.is_paused(), frozen));
+        if self.shown == key && self.template == timer.template && self.name == timer.name {
+            return false;
+        }
+        self.shown = key;
+        self.template.clone_from(&timer.template);
+        self.name.clone_from(&timer.name);
+        let text = &mut self.text;
+        match shown {
+            Shown::CountIn(secs) => {
+                text.clear();
+                let _ = write!(text, "Starting in {}…", secs);
+            }
+            Shown::Locked(secs) => {
+                text.clear();
+                let _ = write!(text, "Locked for {}s", secs);
+            }
+            Shown::Overtime(_) => {
+                counting.format_into(over.unwrap_or_default(), text);
+                text.insert(0, '-');
+            }
+            Shown::Done => {
+                text.clear();
+                text.push_str("Done");
+            }
+            Shown::Alarm(_) => {
+                format.format_into(rest, text);
+                if let Some(alarm) = &timer.alarm {
+                    text.insert_str(0, &format!("{} in ", alarm.label()));
+                }
+            }
+            Shown::Range(..) => {
+                let until = timer.until_possible(rest).unwrap_or_default();
+                format.format_into(until, text);
+                text.insert_str(0, "from ");
+                let _ = write!(text, ", latest {}", format.format(rest));
+            }
+            Shown::Possible(_) => {
+                format.format_into(rest, text);
+                text.insert_str(0, "possible now, latest ");
+            }
+            Shown::Rest(_) => format.format_into(rest, text),
+        }
+        if !timer.template.is_empty() {
+            let elapsed = timer.state.elapsed(now).unwrap_or_default();
+            let run = timer.run_duration().as_secs_f32();
+            let percent = if run > 0.0 {
+                (elapsed.as_secs_f32() / run * 100.0).min(100.0) as u32
+            } else {
+                100
+            };
+            let remaining = text.clone();
+            let values = template::Values {
+                name: &timer.name,
+                remaining: &remaining,
+                elapsed: &counting.format(elapsed),
+                percent,
+                cycle: timer.cycles,
+            };
+            // validated in the editor and on load, shows the plain text otherwise
+            let _ = template::render_into(&timer.template, &values, text);
+        }
+        if frozen {
+            text.push_str(" (paused, loading)");
+        } else if timer.state.is_paused() {
+            text.push_str(" (paused)");
+        }
+        true
+    }
+
+    fn update_finish(&mut self, at: Option<u64>, offset: i64) {
+        if self.finish_at == at {
+            return;
+        }
+        self.finish_at = at;
+        self.finish.clear();
+        if let Some(at) = at {
+            let _ = write!(self.finish, "Finishes at {}", time_of_day(at, offset));
+        }
+    }
+
+    fn update_completions(&mut self, completions: Option<Completions>) {
+        if self.completed == completions {
+            return;
+        }
+        self.completed = completions;
+        self.completions = completions.map(Completions::label).unwrap_or_default();
+    }
+
+    fn update_laps(&mut self, laps: &[Duration], format: TimeStyle) {
+        let key = Some((laps.len(), format));
+        if self.lap_count == key {
+            return;
+        }
+        self.lap_count = key;
+        let skip = laps.len().saturating_sub(SHOWN_LAPS);
+        self.laps = format_laps(laps, format)
+            .lines()
+            .skip(skip)
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+}
+
+// When each timer was last flashed, set from the tick thread too
+static PINGS: Mutex<BTreeMap<u64, Instant>> = Mutex::new(BTreeMap::new());
+
+/// Flashes the window of the timer for a moment
+pub fn ping(id: u64) {
+    PINGS.locked().insert(id, Instant::now());
+}
+
+// Toggled by keybind, which isn't called on the render thread
+static VISIBLE: AtomicBool = AtomicBool::new(true);
+
+pub fn toggle_overlay() {
+    let visible = !VISIBLE.fetch_xor(true, Ordering::Relaxed);
+    log::debug!("Overlay {}", if visible { "shown" } else { "hidden" });
+}
+
+/// The smoothed render cost in milliseconds if it is over budget
+pub fn over_budget() -> Option<f32> {
+    DEGRADED.get().then(|| FRAME_COST.get())
+}
+
+fn record_frame_cost(cost: Duration, budget: f32) {
+    let avg = FRAME_COST.get() * 0.95 + cost.as_secs_f32() * 1000.0 * 0.05;
+    FRAME_COST.set(avg);
+    // some hysteresis so we don't flip between modes every other frame
+    if !DEGRADED.get() && avg > budget {
+        log::warn!(
+            "Render cost {:.2}ms exceeds budget of {:.2}ms, degrading overlay",
+            avg,
+            budget
+        );
+        DEGRADED.set(true);
+    } else if DEGRADED.get() && avg < budget * 0.8 {
+        log::info!("Render cost back within budget");
+        DEGRADED.set(false);
+    }
+}
+
+/// Key to hold for clicks on the overlay to pause and reset timers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClickModifier {
+    #[default]
+    None,
+    Ctrl,
+    Alt,
+    Shift,
+}
+
+impl ClickModifier {
+    pub const ALL: [ClickModifier; 4] = [Self::None, Self::Ctrl, Self::Alt, Self::Shift];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::None => "None",
+            Self::Ctrl => "Ctrl",
+            Self::Alt => "Alt",
+            Self::Shift => "Shift",
+        }
+    }
+
+    fn held(self, io: &Io) -> bool {
+        match self {
+            Self::None => true,
+            Self::Ctrl => io.key_ctrl,
+            Self::Alt => io.key_alt,
+            Self::Shift => io.key_shift,
+        }
+    }
+}
+
+/// Where the wall clock time a running timer finishes at is shown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FinishTime {
+    Never,
+    #[default]
+    OnHover,
+    Always,
+}
+
+impl FinishTime {
+    pub const ALL: [FinishTime; 3] = [Self::Never, Self::OnHover, Self::Always];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Never => "Never",
+            Self::OnHover => "On hover",
+            Self::Always => "Always",
+        }
+    }
+}
+
+/// The note of a hovered timer and when it finishes, if it is only shown on hover
+fn tooltip(ui: &Ui, note: &str, finish: &str, finish_time: FinishTime) {
+    let finish = if finish_time == FinishTime::OnHover {
+        finish
+    } else {
+        ""
+    };
+    match (note.is_empty(), finish.is_empty()) {
+        (true, true) => {}
+        (false, true) => ui.tooltip_text(note),
+        (true, false) => ui.tooltip_text(finish),
+        (false, false) => ui.tooltip_text(format!("{}\n{}", finish, note)),
+    }
+}
+
+/// Unix time a running timer finishes at in real time
+fn finishes_at(timer: &Timer, now: Instant, rest: Duration) -> Option<u64> {
+    if !matches!(timer.state, TimerState::Running { .. }) {
+        return None;
+    }
+    let left = timer.count_in(now).unwrap_or_default() + rest;
+    let at = SystemTime::now() + left;
+    // rounded, the sum of the clock and what is left wavers by a little every frame
+    Some(at.duration_since(UNIX_EPOCH).ok()?.as_secs_f64().round() as u64)
+}
+
+enum MenuAction {
+    Stop,
+    Restart,
+    TogglePause,
+    AddTime,
+    Settings,
+    OpenLink,
+}
+
+/// Right click menu of a timer window or row of the compact list
+fn render_context_menu(ui: &Ui, timer: &Timer, hovered: bool) -> Option<MenuAction> {
+    if hovered && ui.is_mouse_clicked(MouseButton::Right) {
+        ui.open_popup("context");
+    }
+    let mut action = None;
+    ui.popup("context", || {
+        if ui.menu_item("Stop") {
+            action = Some(MenuAction::Stop);
+        }
+        if ui.menu_item("Restart") {
+            action = Some(MenuAction::Restart);
+        }
+        if matches!(
+            timer.state,
+            TimerState::Running { .. } | TimerState::Paused { .. }
+        ) {
+            let pause = if timer.state.is_paused() {
+                "Resume"
+            } else {
+                "Pause"
+            };
+            if ui.menu_item(pause) {
+                action = Some(MenuAction::TogglePause);
+            }
+        }
+        if ui.menu_item(format!("+{}s", timer.add_step.as_secs_f32())) {
+            action = Some(MenuAction::AddTime);
+        }
+        if timer.system.is_none() && ui.menu_item("Open settings") {
+            action = Some(MenuAction::Settings);
+        }
+        if !timer.url.is_empty() && ui.menu_item("Open link") {
+            action = Some(MenuAction::OpenLink);
+        }
+    });
+    action
+}
+
+/// Left click toggles pause and middle click resets a timer while `hovered`
+fn clicked(ui: &Ui, hovered: bool, modifier: ClickModifier) -> Option<MenuAction> {
+    if !hovered || !modifier.held(ui.io()) {
+        return None;
+    }
+    // on release, so dragging the window somewhere else isn't taken as a click
+    if ui.is_mouse_released(MouseButton::Left)
+        && ui.mouse_drag_delta_with_button(MouseButton::Left) == [0.0, 0.0]
+    {
+        return Some(MenuAction::TogglePause);
+    }
+    ui.is_mouse_clicked(MouseButton::Middle)
+        .then_some(MenuAction::Stop)
+}
+
+/// How much of the flash of timer `id` is left, `None` once it is over
+fn flash_left(id: u64, easing: Easing) -> Option<f32> {
+    let mut pings = PINGS.locked();
+    let t = pings.get(&id)?.elapsed().as_secs_f32() / PING_DURATION.as_secs_f32();
+    if t >= 1.0 {
+        pings.remove(&id);
+        return None;
+    }
+    Some(easing.left(t))
+}
+
+/// Color of the remaining time, `None` for the default text color. Smoothed into a
+/// new color and flashed unless `degraded`.
+fn label_color(
+    ui: &Ui,
+    timer: &Timer,
+    now: Instant,
+    palette: &Palette,
+    degraded: bool,
+    by_state: bool,
+    easing: Easing,
+) -> Option<[f32; 4]> {
+    let flash = flash_left(timer.id, easing);
+    if degraded {
+        return flash.map(|_| palette.ping);
+    }
+    let color =
+        state_color(timer, now, palette, by_state).unwrap_or(ui.style_color(StyleColor::Text));
+    let color = animation::smooth(ui, timer.id, Channel::Text, color);
+    Some(match flash {
+        Some(left) => animation::mix(color, palette.ping, left),
+        None => color,
+    })
+}
+
+/// Color of the remaining time by how much is left. Rows of the compact list are
+/// colored by state so they can be told apart at a glance.
+fn state_color(timer: &Timer, now: Instant, palette: &Palette, by_state: bool) -> Option<[f32; 4]> {
+    if timer.overtime_elapsed(now).is_some() {
+        return Some(palette.overtime);
+    }
+    if let Some(color) = timer.remaining(now).and_then(|r| timer.band_color(r)) {
+        return Some(color);
+    }
+    if !by_state {
+        return None;
+    }
+    match timer.state {
+        TimerState::Idle => None,
+        TimerState::Paused { .. } => Some(palette.paused),
+        TimerState::Running { .. } if timer.count_in(now).is_some() => Some(palette.count_in),
+        TimerState::Running { .. } => Some(palette.running),
+        TimerState::Finished { .. } => Some(palette.finished),
+    }
+}
+
+/// How far a running timer is into the final stretch of its fade
+fn urgency(timer: &Timer, now: Instant, rest: Duration) -> Option<(FadeStyle, f32)> {
+    let fade = timer.fade?;
+    if !matches!(timer.state, TimerState::Running { .. }) || timer.count_in(now).is_some() {
+        return None;
+    }
+    Some((fade.style, fade.urgency(rest)?))
+}
+
+/// Text alpha of a pulse that gets deeper with `urgency`
+fn pulse_alpha(rest: Duration, urgency: f32) -> f32 {
+    let wave = 0.5 + 0.5 * (rest.as_secs_f32() * PULSE_HZ * std::f32::consts::TAU).cos();
+    1.0 - PULSE_DEPTH * urgency * wave
+}
+
+/// `color` or the text color with its alpha scaled by `alpha`
+fn faded(ui: &Ui, color: Option<[f32; 4]>, alpha: f32) -> Option<[f32; 4]> {
+    let mut color = color.unwrap_or(ui.style_color(StyleColor::Text));
+    color[3] *= alpha;
+    Some(color)
+}
+
+/// The steps of a timeline, the current one highlighted and those after it with when
+/// they begin
+fn render_steps(
+    ui: &Ui,
+    timeline: &Timeline,
+    current: Option<(usize, Duration)>,
+    format: TimeStyle,
+    palette: &Palette,
+) {
+    let mut eta = current.map_or(Duration::ZERO, |(_, left)| left);
+    for (i, step) in timeline.steps.iter().enumerate() {
+        match current {
+            Some((at, left)) if i == at => ui.text_colored(
+                palette.running,
+                format!("> {}  {}", step.label, format.format(left)),
+            ),
+            Some((at, _)) if i > at => {
+                ui.text(format!("  {}  in {}", step.label, format.format(eta)));
+                eta += step.duration;
+            }
+            _ => ui.text_disabled(format!("  {}", step.label)),
+        }
+    }
+}
+
+/// Time between refreshes of the text at `hz`, zero to refresh every frame
+fn refresh_interval(hz: u32) -> Duration {
+    match hz {
+        0 => Duration::ZERO,
+        hz => Duration::from_secs(1) / hz,
+    }
+}
+
+fn next_column(ui: &Ui, table: bool) {
+    if table {
+        ui.table_next_column();
+    } else {
+        ui.same_line();
+    }
+}
+
+pub fn render_overlay(ui: &Ui) {
+    let _profile = crate::profiling::scope("render");
+    let (
+        budget,
+        combat_only,
+        style,
+        refresh_hz,
+        compact_after,
+        groups,
+        clicks,
+        order,
+        ui_scale,
+        hide_idle,
+        finish_time,
+        palette,
+        easing,
+        resets,
+        (snap_grid, snap_to_windows),
+    ) = {
+        let settings = SETTINGS.get().expect("Settings to be set").locked();
+        (
+            settings.frame_budget_ms,
+            settings.combat_only,
+            settings.time_style(),
+            settings.refresh_hz,
+            settings.compact_after,
+            maps::current_groups(&settings.maps),
+            settings.overlay_clicks.then_some(settings.click_modifier),
+            settings.timer_order,
+            settings.ui_scale,
+            Duration::from_secs(u64::from(settings.hide_idle_mins) * 60),
+            settings.finish_time,
+            settings.palette(),
+            settings.flash_easing,
+            settings.resets.clone(),
+            (settings.snap_grid, settings.snap_to_windows),
+        )
+    };
+    if !VISIBLE.load(Ordering::Relaxed) || (combat_only && !crate::game::in_combat()) {
+        return;
+    }
+    snap::begin(ui, snap_grid);
+    let frame_start = Instant::now();
+    let degraded = DEGRADED.get();
+    let scale = super::scale(ui, ui_scale);
+    let character = crate::game::character();
+    let loading = crate::game::is_loading();
+    let offset = match finish_time {
+        FinishTime::Never => 0,
+        _ => crate::alarm::local_offset(),
+    };
+    let mut timers = TIMERS.get().expect("Timers to be set").locked();
+    let now = timers.now();
+    let mut chosen = None;
+    let mut resized = Vec::new();
+    LABELS.with_borrow_mut(|labels| {
+        let mut shown = Vec::new();
+        let here =
+            |t: &&Timer| maps::shows(groups.as_deref(), t) && t.shows_for(character.as_deref());
+        for timer in timers.list.iter().filter(|t| t.enabled).filter(here) {
+            let rest = match timer.remaining(now) {
+                Some(rest) => rest,
+                None if timer.locked_out(now).is_some() => Duration::ZERO,
+                None => continue,
+            };
+            if timer.hidden_idle(now, hide_idle) {
+                continue;
+            }
+            let label = labels.entry(timer.id).or_default();
+            let mut interval = refresh_interval(timer.refresh_hz.unwrap_or(refresh_hz));
+            if degraded {
+                interval = interval.max(DEGRADED_UPDATE_INTERVAL);
+            }
+            let stale = label.updated.is_none_or(|u| frame_start - u >= interval);
+            if stale {
+                let format = timer.style(style);
+                let frozen = loading && timers.is_frozen(timer.id);
+                label.update(timer, now, rest, format, frozen);
+                label.update_laps(&timer.laps, format.rounded(Rounding::Down));
+                let completions = timer.show_completions.then(|| {
+                    let wall = SystemTime::now();
+                    HISTORY.locked().completions(&timer.name, &resets, wall)
+                });
+                label.update_completions(completions);
+                label.updated = Some(frame_start);
+            }
+            if finish_time != FinishTime::Never {
+                label.update_finish(finishes_at(timer, now, rest), offset);
+            }
+            if let Some(marker) = &timer.marker {
+                let running = matches!(timer.state, TimerState::Running { .. });
+                if running && timer.count_in(now).is_none() {
+                    marker.draw(ui);
+                }
+            }
+            shown.push((timer, rest));
+        }
+        order.sort(&mut shown);
+        // extra runs go under the window of their timer
+        shown.sort_by_key(|(t, _)| t.instance_of.is_some());
+        let compact = compact_after > 0 && shown.len() >= compact_after;
+        if compact {
+            let mut window = Window::new("Timers##timers_list");
+            if clicks.is_none() {
+                window = window.no_inputs();
+            }
+            let _padding = super::push_padding(ui, scale);
+            window.build(ui, || {
+                ui.set_window_font_scale(scale);
+                let table = ui.begin_table("timers_list", 3);
+                for (timer, _) in &shown {
+                    if table.is_some() {
+                        ui.table_next_row();
+                        ui.table_next_column();
+                    }
+                    let _id = ui.push_id(timer.name.as_str());
+                    ui.text(timer.name.as_str());
+                    let mut hovered = ui.is_item_hovered();
+                    let label = &labels[&timer.id];
+                    if hovered {
+                        tooltip(ui, &timer.note, &label.finish, finish_time);
+                    }
+                    if let Some(completions) = label.completed {
+                        ui.same_line();
+                        ui.text_disabled(format!("{}×", completions.today));
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text(&label.completions);
+                        }
+                    }
+                    next_column(ui, table.is_some());
+                    let (text, finish) = (&label.text, &label.finish);
+                    let mut color = label_color(ui, timer, now, &palette, degraded, true, easing);
+                    if timer.locked_out(now).is_some() {
+                        color = faded(ui, color, LOCKOUT_ALPHA);
+                    }
+                    if loading && timers.is_frozen(timer.id) {
+                        color = faded(ui, color, FROZEN_ALPHA);
+                    }
+                    match color {
+                        Some(color) => ui.text_colored(color, text),
+                        None => ui.text(text),
+                    }
+                    hovered |= ui.is_item_hovered();
+                    if finish_time == FinishTime::Always && !finish.is_empty() {
+                        ui.same_line();
+                        ui.text_disabled(finish);
+                    }
+                    if let Some(source) = timer.started_by {
+                        next_column(ui, table.is_some());
+                        ui.text_disabled(source.icon());
+                    }
+                    let action = render_context_menu(ui, timer, hovered)
+                        .or_else(|| clicked(ui, hovered, clicks?));
+                    if let Some(action) = action {
+                        chosen = Some((timer.name.clone(), timer.id, action));
+                    }
+                }
+            });
+            return;
+        }
+        // Bottom left of the latest window of each timer with several runs
+        let mut below: HashMap<u64, [f32; 2]> = HashMap::new();
+        for (timer, rest) in shown {
+            let label = &labels[&timer.id];
+            let mut window = Window::new(timer.name.as_str());
+            let stack = timer.instance_of.unwrap_or(timer.id);
+            if let Some(&position) = timer.instance_of.and_then(|id| below.get(&id)) {
+                window = window.position(position, Condition::Always);
+            }
+            if clicks.is_none() {
+                window = window.no_inputs();
+            }
+            let urgency = urgency(timer, now, rest).filter(|_| !degraded);
+            if let Some((FadeStyle::Background, u)) = urgency {
+                window = window.bg_alpha(FADE_BG_ALPHA + (1.0 - FADE_BG_ALPHA) * u);
+            }
+            if let Some(anchor) = &timer.anchor {
+                let (position, pivot) = anchor.place(ui.io().display_size);
+                window = window
+                    .position(position, Condition::Always)
+                    .position_pivot(pivot);
+            }
+            let movable = timer.anchor.is_none() && timer.instance_of.is_none();
+            if let Some(position) = snap::placed(timer.id).filter(|_| movable) {
+                window = window.position(position, Condition::Always);
+            }
+            match timer.window_size {
+                None => window = window.always_auto_resize(true),
+                Some(size) => {
+                    // never narrower than the text, so nothing gets cut off
+                    let [pad, _] = ui.clone_style().window_padding;
+                    let text = ui.calc_text_size(&label.text)[0];
+                    let title = ui.calc_text_size(&timer.name)[0];
+                    let min = (text.max(title) + 2.0 * pad) * scale;
+                    window = window
+                        .size(size, Condition::Appearing)
+                        .size_constraints([min, 0.0], [f32::MAX, f32::MAX]);
+                }
+            }
+            let _padding = super::push_padding(ui, scale);
+            window.build(ui, || {
+                ui.set_window_font_scale(scale);
+                if let Some(size) = timer.window_size {
+                    let dragged = ui.window_size();
+                    let moved = dragged.iter().zip(size).any(|(a, b)| (a - b).abs() >= 1.0);
+                    if moved {
+                        resized.push((timer.id, dragged));
+                    }
+                }
+                if !timer.icon.is_empty() && icons::draw(ui, &timer.icon, ui.text_line_height()) {
+                    ui.same_line();
+                }
+                let mut color = label_color(ui, timer, now, &palette, degraded, false, easing);
+                if let Some((FadeStyle::Pulse, u)) = urgency {
+                    color = faded(ui, color, pulse_alpha(rest, u));
+                }
+                if timer.locked_out(now).is_some() {
+                    color = faded(ui, color, LOCKOUT_ALPHA);
+                }
+                if loading && timers.is_frozen(timer.id) {
+                    color = faded(ui, color, FROZEN_ALPHA);
+                }
+                match color {
+                    Some(color) => ui.text_colored(color, &label.text),
+                    None => ui.text(&label.text),
+                }
+                let mut on_source = false;
+                if let Some(source) = timer.started_by {
+                    ui.same_line();
+                    ui.text_disabled(source.icon());
+                    on_source = ui.is_item_hovered();
+                    if on_source {
+                        ui.tooltip_text(format!("Started by {}", source.name()));
+                    }
+                }
+                snap::drawn(ui, timer.id, movable, snap_grid, snap_to_windows);
+                if let Some(timeline) = &timer.timeline {
+                    render_steps(ui, timeline, timer.step(now), timer.style(style), &palette);
+                }
+                for line in label.laps.lines() {
+                    ui.text_disabled(line);
+                }
+                if !label.completions.is_empty() {
+                    ui.text_disabled(&label.completions);
+                }
+                if finish_time == FinishTime::Always && !label.finish.is_empty() {
+                    ui.text_disabled(&label.finish);
+                }
+                if timer.allows_instances() || timer.instance_of.is_some() {
+                    let [x, y] = ui.window_pos();
+                    below.insert(stack, [x, y + ui.window_size()[1]]);
+                }
+                let hovered = ui.is_window_hovered();
+                if hovered && !on_source {
+                    tooltip(ui, &timer.note, &label.finish, finish_time);
+                }
+                let action = render_context_menu(ui, timer, hovered)
+                    .or_else(|| clicked(ui, hovered, clicks?));
+                if let Some(action) = action {
+                    chosen = Some((timer.name.clone(), timer.id, action));
+                }
+            });
+        }
+    });
+    for (id, size) in resized {
+        if let Some(timer) = timers.get_mut(id) {
+            timer.window_size = Some(size);
+        }
+    }
+    if let Some((name, id, action)) = chosen {
+        match action {
+            MenuAction::Stop => timers.stop(&name),
+            MenuAction::Restart => timers.start(&name, StartSource::Overlay),
+            MenuAction::TogglePause => timers.toggle_pause(&name),
+            MenuAction::AddTime => {
+                if let Some(step) = timers.get(id).map(|t| t.add_step) {
+                    timers.add_time(&name, step);
+                }
+            }
+            MenuAction::Settings => super::open_editor(id),
+            MenuAction::OpenLink => {
+                if let Some(timer) = timers.get(id) {
+                    links::open(&timer.url);
+                }
+            }
+        }
+    }
+    drop(timers);
+    record_frame_cost(frame_start.elapsed(), budget);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{Clock, ManualClock};
+    use crate::format::TimeFormat;
+    use crate::timer::Timers;
+
+    const FRAME: Duration = Duration::from_millis(16);
+
+    fn running(count: usize) -> (ManualClock, Timers) {
+        let clock = ManualClock::new();
+        let mut timers = Timers::with_clock(Box::new(clock.clone()));
+        for i in 0..count {
+            let name = format!("Timer {}", i);
+            timers.add(name.clone(), Duration::from_secs(600));
+            timers.start(&name, StartSource::Ui);
+        }
+        (clock, timers)
+    }
+
+    #[test]
+    fn labels_only_change_with_the_text() {
+        let (clock, timers) = running(1);
+        let timer = timers.list.iter().next().expect("a timer to be running");
+        let mut label = Label::default();
+        let update = |label: &mut Label, format: TimeFormat, decimals| {
+            let now = clock.now();
+            let rest = timer.remaining(now).unwrap();
+            label.update(timer, now, rest, format.with_decimals(decimals), false)
+        };
+        clock.advance(Duration::from_millis(100));
+        assert!(update(&mut label, TimeFormat::MinSec, 2));
+        clock.advance(FRAME);
+        assert!(!update(&mut label, TimeFormat::MinSec, 2));
+        assert!(update(&mut label, TimeFormat::Seconds, 2));
+        assert_eq!(label.text, "599.88");
+        assert!(update(&mut label, TimeFormat::Seconds, 1));
+        assert_eq!(label.text, "599.8");
+    }
+
+    /// `cargo test --release -- --ignored --nocapture overlay::tests::bench`
+    #[test]
+    #[ignore = "benchmark"]
+    fn bench_labels_of_100_timers() {
+        const FRAMES: u32 = 10_000;
+        let (clock, timers) = running(100);
+        let mut cached: HashMap<u64, Label> = HashMap::new();
+        let mut run = |every_frame: bool| {
+            let start = Instant::now();
+            for _ in 0..FRAMES {
+                clock.advance(FRAME);
+                let now = clock.now();
+                for timer in &timers.list {
+                    let rest = timer.remaining(now).unwrap_or_default();
+                    let label = cached.entry(timer.id).or_default();
+                    if every_frame {
+                        label.shown = None;
+                    }
+                    label.update(timer, now, rest, TimeStyle::default(), false);
+                }
+            }
+            start.elapsed() / FRAMES
+        };
+        let uncached = run(true);
+        let cached = run(false);
+        println!(
+            "100 timers: {:?} per frame formatting every frame, {:?} cached",
+            uncached, cached
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use crate::command::{self, Command};
+use crate::sync::LockExt;
+use crate::timer::Timers;
+use crate::TIMERS;
+use nexus::imgui::{Condition, Ui, Window};
+use std::sync::Mutex;
+
+struct Prompt {
+    input: String,
+    error: Option<String>,
+}
+
+// Opened by keybind, which isn't called on the render thread
+static PROMPT: Mutex<Option<Prompt>> = Mutex::new(None);
+
+/// Asks for a command like `start greens`
+pub fn open_command_palette() {
+    *PROMPT.locked() = Some(Prompt {
+        input: String::new(),
+        error: None,
+    });
+}
+
+/// Names commands can refer to, system timers can't be controlled
+fn names(timers: &Timers) -> Vec<&str> {
+    timers
+        .list
+        .iter()
+        .filter(|t| t.enabled && t.system.is_none())
+        .map(|t| t.name.as_str())
+        .collect()
+}
+
+pub fn render_command_palette(ui: &Ui) {
+    let mut prompt = PROMPT.locked();
+    let Some(p) = &mut *prompt else {
+        return;
+    };
+    let [width, height] = ui.io().display_size;
+    let mut done = false;
+    Window::new("Timer command##timers_palette")
+        .position([width / 2.0, height / 3.0], Condition::Appearing)
+        .position_pivot([0.5, 0.5])
+        .always_auto_resize(true)
+        .build(ui, || {
+            if ui.is_window_appearing() {
+                ui.set_keyboard_focus_here();
+            }
+            let entered = ui
+                .input_text("##command", &mut p.input)
+                .hint("e.g. start greens")
+                .enter_returns_true(true)
+                .build();
+            if ui.is_item_edited() {
+                p.error = None;
+            }
+            let mut timers = TIMERS.get().expect("Timers to be set").locked();
+            let parsed = command::parse(&p.input, &names(&timers));
+            // what will happen, so a fuzzy match can be checked before it runs
+            match &parsed {
+                Ok(Command::Start(name)) => ui.text_disabled(format!("Start {}", name)),
+                Ok(Command::Stop(name)) => ui.text_disabled(format!("Stop {}", name)),
+                Ok(Command::StopAll) => ui.text_disabled("Stop all timers"),
+                Ok(Command::Pause(name)) => ui.text_disabled(format!("Pause or resume {}", name)),
+                Ok(Command::Add(by, name)) => {
+                    ui.text_disabled(format!("Add {}s to {}", by.as_secs_f32(), name))
+                }
+                Ok(Command::New(name, duration)) => {
+                    ui.text_disabled(format!("New timer {} of {}s", name, duration.as_secs_f32()))
+                }
+                Err(_) if p.input.trim().is_empty() => ui.text_disabled(command::HELP),
+                Err(_) => {}
+            }
+            if ui.button("Run") || entered {
+                match parsed {
+                    Ok(command) => {
+                        log::info!("{}", command::run(command, &mut timers));
+                        done = true;
+                    }
+                    Err(e) => p.error = Some(e),
+                }
+            }
+            ui.same_line();
+            if ui.button("Cancel") {
+                done = true;
+            }
+            if let Some(error) = &p.error {
+                ui.text_colored([1.0, 0.3, 0.3, 1.0], error);
+            }
+        });
+    if done {
+        *prompt = None;
+    }
+}
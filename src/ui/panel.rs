@@ -0,0 +1,80 @@
+use super::render_status;
+use crate::sync::LockExt;
+use crate::timer::StartSource;
+use crate::{SETTINGS, TIMERS};
+use nexus::imgui::{Condition, Ui, Window};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Toggled by keybind and the quick access shortcut, which aren't called on the render thread
+static OPEN: AtomicBool = AtomicBool::new(false);
+
+pub fn toggle_timers_window() {
+    OPEN.fetch_xor(true, Ordering::Relaxed);
+}
+
+enum Clicked {
+    Start,
+    Stop,
+    Pause,
+}
+
+/// Every timer with buttons to start, stop and pause it
+pub fn render_timers_window(ui: &Ui) {
+    if !OPEN.load(Ordering::Relaxed) {
+        return;
+    }
+    let (style, palette) = {
+        let settings = SETTINGS.get().expect("Settings to be set").locked();
+        (settings.time_style(), settings.palette())
+    };
+    let mut timers = TIMERS.get().expect("Timers to be set").locked();
+    let now = timers.now();
+    let mut open = true;
+    let mut clicked = None;
+    Window::new("Timers##timers_window")
+        .size([320.0, 360.0], Condition::FirstUseEver)
+        .opened(&mut open)
+        .build(ui, || {
+            let mut shown = timers.list.iter().filter(|t| t.enabled).peekable();
+            if shown.peek().is_none() {
+                ui.text_disabled("No timers yet, add them in the addon options");
+            }
+            for timer in shown {
+                let _id = ui.push_id(timer.name.as_str());
+                // system timers run on their own
+                if timer.system.is_none() {
+                    if !timer.state.is_active() {
+                        if ui.small_button("Start") {
+                            clicked = Some((timer.name.clone(), Clicked::Start));
+                        }
+                    } else {
+                        if ui.small_button("Stop") {
+                            clicked = Some((timer.name.clone(), Clicked::Stop));
+                        }
+                        ui.same_line();
+                        let pause = if timer.state.is_paused() {
+                            "Resume"
+                        } else {
+                            "Pause"
+                        };
+                        if ui.small_button(pause) {
+                            clicked = Some((timer.name.clone(), Clicked::Pause));
+                        }
+                    }
+                    ui.same_line();
+                }
+                ui.text(timer.name.as_str());
+                ui.same_line();
+                render_status(ui, timer, now, style, &palette);
+            }
+        });
+    if !open {
+        OPEN.store(false, Ordering::Relaxed);
+    }
+    match clicked {
+        Some((name, Clicked::Start)) => timers.start(&name, StartSource::Ui),
+        Some((name, Clicked::Stop)) => timers.stop(&name),
+        Some((name, Clicked::Pause)) => timers.toggle_pause(&name),
+        None => {}
+    }
+}
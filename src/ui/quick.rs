@@ -0,0 +1,65 @@
+use crate::duration::parse_duration;
+use crate::sync::LockExt;
+use crate::TIMERS;
+use nexus::imgui::{Condition, Ui, Window};
+use std::sync::Mutex;
+
+struct Prompt {
+    input: String,
+    error: Option<String>,
+}
+
+// Opened by keybind, which isn't called on the render thread
+static PROMPT: Mutex<Option<Prompt>> = Mutex::new(None);
+
+/// Asks for the duration of a one-off timer
+pub fn open_quick_timer() {
+    *PROMPT.locked() = Some(Prompt {
+        input: String::new(),
+        error: None,
+    });
+}
+
+pub fn render_quick_timer(ui: &Ui) {
+    let mut prompt = PROMPT.locked();
+    let Some(p) = &mut *prompt else {
+        return;
+    };
+    let [width, height] = ui.io().display_size;
+    let mut done = false;
+    Window::new("Quick timer##timers_quick")
+        .position([width / 2.0, height / 3.0], Condition::Appearing)
+        .position_pivot([0.5, 0.5])
+        .always_auto_resize(true)
+        .build(ui, || {
+            if ui.is_window_appearing() {
+                ui.set_keyboard_focus_here();
+            }
+            let entered = ui
+                .input_text("##duration", &mut p.input)
+                .hint("e.g. 90 or 1:30")
+                .enter_returns_true(true)
+                .build();
+            if ui.button("Start") || entered {
+                match parse_duration(&p.input) {
+                    Ok(duration) if !duration.is_zero() => {
+                        let mut timers = TIMERS.get().expect("Timers to be set").locked();
+                        timers.start_quick(duration);
+                        done = true;
+                    }
+                    Ok(_) => p.error = Some("The duration can't be zero".to_string()),
+                    Err(e) => p.error = Some(e.to_string()),
+                }
+            }
+            ui.same_line();
+            if ui.button("Cancel") {
+                done = true;
+            }
+            if let Some(error) = &p.error {
+                ui.text_colored([1.0, 0.3, 0.3, 1.0], error);
+            }
+        });
+    if done {
+        *prompt = None;
+    }
+}
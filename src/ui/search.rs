@@ -0,0 +1,114 @@
+//! Filtering of the options by a search query.
+
+use nexus::imgui::{StyleColor, TreeNodeFlags, Ui};
+use std::cell::{Cell, RefCell};
+
+const HIGHLIGHT_COLOR: [f32; 4] = [1.0, 0.85, 0.3, 1.0];
+
+thread_local! {
+    static QUERY: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+pub struct Search {
+    /// Lowercase, empty if not searching
+    query: String,
+}
+
+/// Draws the search box and returns the current query
+pub fn render_search_box(ui: &Ui) -> Search {
+    QUERY.with_borrow_mut(|query| {
+        ui.input_text("##search", query)
+            .hint("Search options")
+            .build();
+        if !query.is_empty() {
+            ui.same_line();
+            if ui.small_button("Clear##search") {
+                query.clear();
+            }
+        }
+        Search {
+            query: query.trim().to_lowercase(),
+        }
+    })
+}
+
+impl Search {
+    pub fn is_active(&self) -> bool {
+        !self.query.is_empty()
+    }
+
+    pub fn matches(&self, text: &str) -> bool {
+        text.to_lowercase().contains(&self.query)
+    }
+
+    /// A collapsible group of options. While searching it is expanded and only shows
+    /// the items that match, or all of them if the title matches.
+    pub fn section(&self, ui: &Ui, title: &str, body: impl FnOnce(&Section)) {
+        self.draw(ui, title, true, body);
+    }
+
+    /// Like `section`, but always expanded and without a header unless searching
+    pub fn group(&self, ui: &Ui, title: &str, body: impl FnOnce(&Section)) {
+        self.draw(ui, title, false, body);
+    }
+
+    fn draw(&self, ui: &Ui, title: &str, collapsible: bool, body: impl FnOnce(&Section)) {
+        if self.is_active() {
+            body(&Section {
+                ui,
+                title,
+                query: Some(&self.query),
+                show_all: self.matches(title),
+                title_drawn: Cell::new(false),
+            });
+        } else if !collapsible || ui.collapsing_header(title, TreeNodeFlags::empty()) {
+            body(&Section {
+                ui,
+                title,
+                query: None,
+                show_all: true,
+                title_drawn: Cell::new(true),
+            });
+        }
+    }
+}
+
+pub struct Section<'a> {
+    ui: &'a Ui,
+    title: &'a str,
+    query: Option<&'a str>,
+    show_all: bool,
+    // the title is drawn in front of the first item shown while searching
+    title_drawn: Cell<bool>,
+}
+
+impl Section<'_> {
+    fn show_title(&self) {
+        if !self.title_drawn.replace(true) {
+            self.ui.separator();
+            self.ui.text_disabled(self.title);
+        }
+    }
+
+    /// A control labeled `label`, hidden if it doesn't match the search
+    pub fn item(&self, label: &str, draw: impl FnOnce(&str)) {
+        let Some(query) = self.query else {
+            return draw(label);
+        };
+        let matched = label.to_lowercase().contains(query);
+        if !matched && !self.show_all {
+            return;
+        }
+        self.show_title();
+        let _color = matched.then(|| self.ui.push_style_color(StyleColor::Text, HIGHLIGHT_COLOR));
+        draw(label);
+    }
+
+    /// Anything that isn't a control, shown only while the section is
+    pub fn extra(&self, draw: impl FnOnce()) {
+        if self.title_drawn.get() || self.show_all {
+            self.show_title();
+            draw();
+        }
+    }
+}
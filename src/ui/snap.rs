@@ -0,0 +1,207 @@
+//! Snapping of timer windows to a grid and to each other once they are let go, and
+//! tiling them all at once from the options.
+
+use nexus::imgui::{MouseButton, Ui};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+
+/// Windows let go this close to another one line up with it
+const DISTANCE: f32 = 8.0;
+/// Where tiling starts, clear of the Nexus bar
+const ORIGIN: [f32; 2] = [16.0, 64.0];
+/// Space between tiled windows
+const GAP: f32 = 4.0;
+
+/// Top left and size of a window
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Rect {
+    pos: [f32; 2],
+    size: [f32; 2],
+}
+
+impl Rect {
+    fn end(&self, axis: usize) -> f32 {
+        self.pos[axis] + self.size[axis]
+    }
+
+    /// Whether the window is next to `other` along `axis`, so it can line up with it on the
+    /// other one
+    fn beside(&self, other: &Rect, axis: usize) -> bool {
+        self.pos[axis] <= other.end(axis) + DISTANCE && other.pos[axis] <= self.end(axis) + DISTANCE
+    }
+}
+
+thread_local! {
+    static DRAWN: RefCell<HashMap<u64, Rect>> = RefCell::new(HashMap::new());
+    static PREVIOUS: RefCell<HashMap<u64, Rect>> = RefCell::new(HashMap::new());
+    /// Windows dragged since the mouse went down
+    static MOVING: RefCell<HashSet<u64>> = RefCell::new(HashSet::new());
+    /// Where windows go the next time they are drawn
+    static PLACE: RefCell<HashMap<u64, [f32; 2]>> = RefCell::new(HashMap::new());
+    static ARRANGE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Tiles the timer windows the next time they are drawn
+pub fn arrange() {
+    ARRANGE.set(true);
+}
+
+/// Starts a frame of the overlay, `grid` spacing if tiling lines up with a grid
+pub fn begin(ui: &Ui, grid: u32) {
+    let drawn = DRAWN.take();
+    if ARRANGE.replace(false) {
+        let mut windows: Vec<_> = drawn.iter().map(|(&id, &rect)| (id, rect)).collect();
+        // column by column as they are now
+        windows.sort_by(|(a_id, a), (b_id, b)| {
+            let [ax, ay] = a.pos;
+            let [bx, by] = b.pos;
+            ax.total_cmp(&bx)
+                .then(ay.total_cmp(&by))
+                .then(a_id.cmp(b_id))
+        });
+        let height = ui.io().display_size[1];
+        PLACE.with_borrow_mut(|place| place.extend(tile(&windows, grid as f32, height)));
+    }
+    PREVIOUS.set(drawn);
+}
+
+/// Where window `id` is to be moved before it is drawn
+pub fn placed(id: u64) -> Option<[f32; 2]> {
+    PLACE.with_borrow_mut(|place| place.remove(&id))
+}
+
+/// Records the window being drawn for timer `id`, and snaps it once it was dragged and
+/// let go. Windows placed by an anchor or under their timer aren't `movable`.
+pub fn drawn(ui: &Ui, id: u64, movable: bool, grid: u32, to_windows: bool) {
+    let rect = Rect {
+        pos: ui.window_pos(),
+        size: ui.window_size(),
+    };
+    let previous = PREVIOUS.with_borrow(|previous| previous.get(&id).copied());
+    DRAWN.with_borrow_mut(|drawn| drawn.insert(id, rect));
+    if !movable || (grid == 0 && !to_windows) {
+        return;
+    }
+    if ui.is_mouse_down(MouseButton::Left) {
+        if previous.is_some_and(|p| p.pos != rect.pos) {
+            MOVING.with_borrow_mut(|moving| moving.insert(id));
+        }
+        return;
+    }
+    if !MOVING.with_borrow_mut(|moving| moving.remove(&id)) {
+        return;
+    }
+    let others: Vec<_> = PREVIOUS.with_borrow(|previous| {
+        previous
+            .iter()
+            .filter(|(&other, _)| other != id)
+            .map(|(_, &r)| r)
+            .collect()
+    });
+    let others = if to_windows { others } else { Vec::new() };
+    let pos = snapped(rect, &others, grid as f32);
+    if pos != rect.pos {
+        PLACE.with_borrow_mut(|place| place.insert(id, pos));
+    }
+}
+
+/// Where `rect` goes, lined up with the closest edge of `others` within `DISTANCE`, else
+/// on the `grid` if there is one
+fn snapped(rect: Rect, others: &[Rect], grid: f32) -> [f32; 2] {
+    std::array::from_fn(|axis| {
+        let across = 1 - axis;
+        let at = rect.pos[axis];
+        let size = rect.size[axis];
+        others
+            .iter()
+            .filter(|other| rect.beside(other, across))
+            .flat_map(|other| {
+                let (start, end) = (other.pos[axis], other.end(axis));
+                [start, end, start - size, end - size]
+            })
+            .filter(|edge| (edge - at).abs() <= DISTANCE)
+            .min_by(|a, b| (a - at).abs().total_cmp(&(b - at).abs()))
+            .unwrap_or(if grid > 0.0 {
+                (at / grid).round() * grid
+            } else {
+                at
+            })
+    })
+}
+
+/// Positions of `windows` in columns from the top left, each column as high as fits in
+/// `height`
+fn tile(windows: &[(u64, Rect)], grid: f32, height: f32) -> Vec<(u64, [f32; 2])> {
+    let step = |v: f32| {
+        if grid > 0.0 {
+            (v / grid).ceil() * grid
+        } else {
+            v
+        }
+    };
+    let [left, top] = ORIGIN.map(step);
+    let (mut x, mut y, mut width) = (left, top, 0.0_f32);
+    let mut placed = Vec::new();
+    for &(id, rect) in windows {
+        if y > top && y + rect.size[1] > height {
+            x = step(x + width + GAP);
+            (y, width) = (top, 0.0);
+        }
+        placed.push((id, [x, y]));
+        y = step(y + rect.size[1] + GAP);
+        width = width.max(rect.size[0]);
+    }
+    placed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: f32, y: f32, w: f32, h: f32) -> Rect {
+        Rect {
+            pos: [x, y],
+            size: [w, h],
+        }
+    }
+
+    #[test]
+    fn snaps_to_windows_then_the_grid() {
+        let other = rect(100.0, 100.0, 80.0, 40.0);
+        // let go just right of and a bit below it, lines up to its right and its top
+        assert_eq!(
+            snapped(rect(185.0, 103.0, 60.0, 30.0), &[other], 0.0),
+            [180.0, 100.0]
+        );
+        // far away from it only the grid counts
+        assert_eq!(
+            snapped(rect(413.0, 318.0, 60.0, 30.0), &[other], 20.0),
+            [420.0, 320.0]
+        );
+        assert_eq!(
+            snapped(rect(413.0, 318.0, 60.0, 30.0), &[], 0.0),
+            [413.0, 318.0]
+        );
+        // right under it, lines up with its bottom and its left edge
+        assert_eq!(
+            snapped(rect(96.0, 145.0, 60.0, 30.0), &[other], 50.0),
+            [100.0, 140.0]
+        );
+    }
+
+    #[test]
+    fn tiles_in_columns() {
+        let windows = [
+            (1, rect(0.0, 0.0, 100.0, 300.0)),
+            (2, rect(0.0, 0.0, 120.0, 300.0)),
+            (3, rect(0.0, 0.0, 50.0, 300.0)),
+        ];
+        let placed = tile(&windows, 0.0, 700.0);
+        assert_eq!(
+            placed,
+            [(1, [16.0, 64.0]), (2, [16.0, 368.0]), (3, [140.0, 64.0])]
+        );
+        let on_grid = tile(&windows[..1], 10.0, 700.0);
+        assert_eq!(on_grid, [(1, [20.0, 70.0])]);
+    }
+}
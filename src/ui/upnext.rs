@@ -0,0 +1,55 @@
+use crate::sync::LockExt;
+use crate::{maps, SETTINGS, TIMERS};
+use nexus::imgui::{Condition, Ui, Window};
+
+/// The timers finishing next across everything running, so what's imminent can be
+/// seen without looking through every window. Read-only, there is nothing to click.
+pub fn render_up_next(ui: &Ui) {
+    let (count, style, groups, ui_scale) = {
+        let settings = SETTINGS.get().expect("Settings to be set").locked();
+        if settings.up_next == 0 {
+            return;
+        }
+        (
+            settings.up_next,
+            settings.time_style(),
+            maps::current_groups(&settings.maps),
+            settings.ui_scale,
+        )
+    };
+    let character = crate::game::character();
+    let timers = TIMERS.get().expect("Timers to be set").locked();
+    let next: Vec<_> = timers
+        .next_finishes()
+        .into_iter()
+        .filter(|(t, _)| maps::shows(groups.as_deref(), t) && t.shows_for(character.as_deref()))
+        .take(count)
+        .collect();
+    if next.is_empty() {
+        return;
+    }
+    let scale = super::scale(ui, ui_scale);
+    let _padding = super::push_padding(ui, scale);
+    Window::new("Up next##timers_up_next")
+        .position([20.0, 300.0], Condition::FirstUseEver)
+        .always_auto_resize(true)
+        .focus_on_appearing(false)
+        .bg_alpha(0.4)
+        .build(ui, || {
+            ui.set_window_font_scale(scale);
+            let table = ui.begin_table("up_next", 2);
+            for (timer, rest) in next {
+                if table.is_some() {
+                    ui.table_next_row();
+                    ui.table_next_column();
+                }
+                ui.text(timer.name.as_str());
+                if table.is_some() {
+                    ui.table_next_column();
+                } else {
+                    ui.same_line();
+                }
+                ui.text(timer.style(style).format(rest));
+            }
+        });
+}
@@ -0,0 +1,236 @@
+//! A short introduction shown in place of the options until it is finished or skipped.
+//! It sets up starter timers and how times are shown, and saves them right away.
+
+use crate::alarm::{Alarm, Repeat};
+use crate::config::{self, MAX_PULL_SECS};
+use crate::format::TimeFormat;
+use crate::presets::presets;
+use crate::sync::LockExt;
+use crate::theme::Preset;
+use crate::timer::Timer;
+use crate::{keybinds, notice, SETTINGS, TIMERS};
+use nexus::imgui::Ui;
+use std::cell::{Cell, RefCell};
+use std::time::Duration;
+
+/// Everyday timers for the kitchen or a break
+const KITCHEN: [(&str, Duration); 3] = [
+    ("Tea", Duration::from_secs(3 * 60)),
+    ("5 minutes", Duration::from_secs(5 * 60)),
+    ("15 minutes", Duration::from_secs(15 * 60)),
+];
+const DAILY_RESET: &str = "Daily reset";
+const STEPS: usize = 4;
+
+#[derive(Clone, Copy)]
+struct Starters {
+    kitchen: bool,
+    daily_reset: bool,
+    pull: bool,
+}
+
+thread_local! {
+    static STEP: Cell<usize> = const { Cell::new(0) };
+    // indices into `presets()`
+    static CHOSEN: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) };
+    static STARTERS: Cell<Starters> = const {
+        Cell::new(Starters {
+            kitchen: true,
+            daily_reset: false,
+            pull: false,
+        })
+    };
+}
+
+/// Draws the wizard if this is the first run, returns whether it was drawn
+pub fn render_wizard(ui: &Ui) -> bool {
+    if SETTINGS
+        .get()
+        .expect("Settings to be set")
+        .locked()
+        .onboarded
+    {
+        return false;
+    }
+    if !TIMERS
+        .get()
+        .expect("Timers to be set")
+        .locked()
+        .list
+        .is_empty()
+    {
+        // configured before the wizard existed
+        finish();
+        return false;
+    }
+
+    let step = STEP.get();
+    ui.text(format!("Welcome to Timers ({}/{})", step + 1, STEPS));
+    ui.separator();
+    match step {
+        0 => {
+            ui.text_wrapped(
+                "Pick any timers you'd like to start with, you can change or remove them later.",
+            );
+            let mut starters = STARTERS.get();
+            let kitchen: Vec<_> = KITCHEN.iter().map(|(name, _)| *name).collect();
+            ui.checkbox(
+                format!("Kitchen timers ({})", kitchen.join(", ")),
+                &mut starters.kitchen,
+            );
+            ui.checkbox(
+                "Daily reset, goes off when a new day begins in the game",
+                &mut starters.daily_reset,
+            );
+            ui.checkbox(
+                "Pull timer, a big countdown to the start of a fight",
+                &mut starters.pull,
+            );
+            if starters.pull {
+                let mut settings = SETTINGS.get().expect("Settings to be set").locked();
+                let mut secs = settings.pull_secs as i32;
+                ui.indent();
+                if ui.input_int("Seconds until the pull", &mut secs).build() {
+                    settings.pull_secs = secs.clamp(1, MAX_PULL_SECS as i32) as u32;
+                }
+                ui.unindent();
+            }
+            STARTERS.set(starters);
+            ui.spacing();
+            ui.text_disabled("Encounters");
+            CHOSEN.with_borrow_mut(|chosen| {
+                for (i, preset) in presets().iter().enumerate() {
+                    let mut checked = chosen.contains(&i);
+                    if ui.checkbox(&preset.name, &mut checked) {
+                        if checked {
+                            chosen.push(i);
+                        } else {
+                            chosen.retain(|c| *c != i);
+                        }
+                    }
+                }
+            });
+        }
+        1 => {
+            ui.text_wrapped("How should times be shown? This too can be changed later.");
+            let mut settings = SETTINGS.get().expect("Settings to be set").locked();
+            let formats = TimeFormat::ALL.map(TimeFormat::name);
+            let mut format = settings.time_format as usize;
+            if ui.combo_simple_string("Time format", &mut format, &formats) {
+                settings.time_format = TimeFormat::ALL[format];
+            }
+            let themes = Preset::ALL.map(Preset::name);
+            // the custom theme is made in the options
+            let mut theme = settings.theme as usize;
+            if ui.combo_simple_string("Colors", &mut theme, &themes[..themes.len() - 1]) {
+                settings.theme = Preset::ALL[theme];
+            }
+        }
+        2 => {
+            ui.text_wrapped(
+                "Every timer gets a keybind to start it. Assign them in the Nexus options \
+                 under Keybinds, listed with the timer's name.",
+            );
+            ui.text_wrapped(
+                "There is also a keybind to restart the timer that was started last and one \
+                 to show or hide all timers.",
+            );
+            if STARTERS.get().pull {
+                ui.text_wrapped("The pull timer is started and called off by its own keybind.");
+            }
+        }
+        _ => {
+            ui.text_wrapped(
+                "Running timers show up as small windows you can move anywhere. Each timer \
+                 has more settings behind its Edit button, like a count-in or sounds.",
+            );
+        }
+    }
+    ui.separator();
+    if step > 0 {
+        if ui.button("Back") {
+            STEP.set(step - 1);
+        }
+        ui.same_line();
+    }
+    if step < STEPS - 1 {
+        if ui.button("Next") {
+            STEP.set(step + 1);
+        }
+    } else if ui.button("Done") {
+        // saved along with the settings
+        finish();
+        apply();
+    }
+    ui.same_line();
+    if ui.button("Skip") {
+        finish();
+    }
+    true
+}
+
+/// A starter timer that alerts with a sound and a flash
+fn starter(name: &str, duration: Duration) -> Timer {
+    let mut timer = Timer::new(0, name.to_string(), duration);
+    timer.sound = true;
+    timer.flash = true;
+    timer
+}
+
+fn starter_timers(starters: Starters, daily_hour: u8) -> Vec<Timer> {
+    let mut timers = Vec::new();
+    if starters.kitchen {
+        timers.extend(KITCHEN.iter().map(|(name, d)| starter(name, *d)));
+    }
+    if starters.daily_reset {
+        let mut timer = starter(DAILY_RESET, Duration::ZERO);
+        timer.alarm = Some(Alarm {
+            hour: daily_hour,
+            minute: 0,
+            utc: true,
+            repeat: Repeat::Daily,
+        });
+        timers.push(timer);
+    }
+    timers
+}
+
+/// Adds the chosen timers and writes the first config, instead of waiting for the
+/// addon to unload
+fn apply() {
+    let daily_hour = SETTINGS
+        .get()
+        .expect("Settings to be set")
+        .locked()
+        .resets
+        .daily_hour;
+    let mut timers = TIMERS.get().expect("Timers to be set").locked();
+    let mut imported = starter_timers(STARTERS.get(), daily_hour);
+    let chosen = CHOSEN.take();
+    imported.extend(
+        chosen
+            .iter()
+            .flat_map(|&i| presets()[i].timers.iter().cloned()),
+    );
+    let added = timers.add_imported(imported);
+    for timer in added.into_iter().filter_map(|id| timers.get(id)) {
+        keybinds::register(timer);
+    }
+    let saved = config::save_timers(&timers);
+    drop(timers);
+    let settings = SETTINGS.get().expect("Settings to be set").locked();
+    let saved = saved.and(config::save_settings(&settings));
+    drop(settings);
+    if let Err(e) = saved {
+        log::error!("{}", e);
+        notice(e.to_string());
+    }
+}
+
+fn finish() {
+    SETTINGS
+        .get()
+        .expect("Settings to be set")
+        .locked()
+        .onboarded = true;
+}
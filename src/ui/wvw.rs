@@ -0,0 +1,73 @@
+use crate::sync::LockExt;
+use crate::timer::WVW;
+use crate::wvw::{self, MAPS, RIGHTEOUS_INDIGNATION};
+use crate::{SETTINGS, TIMERS};
+use nexus::imgui::{Condition, Ui, Window};
+use std::cell::Cell;
+
+/// Objective buttons on one line
+const PER_LINE: usize = 3;
+
+/// Buttons that start a Righteous Indignation timer per objective of a WvW map, and the
+/// running ones by when they run out. Shown on WvW maps and while any are running.
+pub fn render_wvw_panel(ui: &Ui) {
+    thread_local! {
+        // index into `MAPS`, follows the map that is played until one is picked
+        static PICKED: Cell<Option<usize>> = const { Cell::new(None) };
+    }
+    let (style, palette, ui_scale) = {
+        let settings = SETTINGS.get().expect("Settings to be set").locked();
+        if !settings.wvw_panel {
+            return;
+        }
+        (settings.time_style(), settings.palette(), settings.ui_scale)
+    };
+    let current = crate::game::map_id().and_then(wvw::map);
+    let mut timers = TIMERS.get().expect("Timers to be set").locked();
+    let now = timers.now();
+    let mut running: Vec<_> = timers
+        .list
+        .iter()
+        .filter(|t| t.system == Some(WVW) && t.state.is_active())
+        .filter_map(|t| Some((t.name.clone(), t.remaining(now)?)))
+        .collect();
+    if current.is_none() && running.is_empty() {
+        return;
+    }
+    running.sort_by_key(|(_, rest)| *rest);
+    let map = PICKED.get().or(current).unwrap_or(0);
+    let scale = super::scale(ui, ui_scale);
+    let _padding = super::push_padding(ui, scale);
+    let mut clicked = None;
+    Window::new("WvW##timers_wvw")
+        .position([20.0, 400.0], Condition::FirstUseEver)
+        .always_auto_resize(true)
+        .build(ui, || {
+            ui.set_window_font_scale(scale);
+            let names = MAPS.map(|m| m.name);
+            let mut picked = map;
+            if ui.combo_simple_string("##wvw_map", &mut picked, &names) {
+                PICKED.set(Some(picked).filter(|&p| Some(p) != current));
+            }
+            let map = &MAPS[picked];
+            for (i, objective) in map.objectives.iter().enumerate() {
+                if i % PER_LINE != 0 {
+                    ui.same_line();
+                }
+                if ui.small_button(objective) {
+                    clicked = Some(wvw::timer_name(map, objective));
+                }
+            }
+            if !running.is_empty() {
+                ui.separator();
+            }
+            for (name, rest) in &running {
+                ui.text(name);
+                ui.same_line();
+                ui.text_colored(palette.running, style.format(*rest));
+            }
+        });
+    if let Some(name) = clicked {
+        timers.start_wvw(&name, RIGHTEOUS_INDIGNATION);
+    }
+}
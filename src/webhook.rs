@@ -0,0 +1,118 @@
+//! Webhooks of timers, an HTTPS POST on start and finish for integrations like Discord
+//! bots and stream overlays.
+//!
+//! The body is `{"timer": .., "event": "start" | "finish", "timestamp": ..}` with the
+//! unix time in seconds, and a `content` line so Discord webhooks take it as it is.
+
+use crate::worker::Worker;
+use serde::Serialize;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookEvent {
+    Start,
+    Finish,
+}
+
+#[derive(Debug, Serialize)]
+struct Payload {
+    timer: String,
+    event: HookEvent,
+    timestamp: u64,
+    content: String,
+}
+
+struct Hook {
+    host: String,
+    path: String,
+    payload: Payload,
+}
+
+/// Host and path of an https URL, the only kind the HTTP client speaks
+fn split(url: &str) -> Option<(&str, &str)> {
+    let rest = url.trim().strip_prefix("https://")?;
+    let (host, path) = match rest.find('/') {
+        Some(i) => rest.split_at(i),
+        None => (rest, "/"),
+    };
+    (!host.is_empty() && !url.contains(char::is_whitespace)).then_some((host, path))
+}
+
+pub fn is_valid(url: &str) -> bool {
+    split(url).is_some()
+}
+
+fn post(hook: Hook) {
+    let body = serde_json::to_vec(&hook.payload).expect("Payload to be serialized");
+    let result = crate::http::post(
+        &hook.host,
+        &hook.path,
+        "Content-Type: application/json",
+        &body,
+    );
+    if let Err(e) = result {
+        log::warn!("Webhook of {} failed: {}", hook.payload.timer, e);
+        crate::eventlog::error(&hook.payload.timer, format!("webhook failed: {}", e));
+    }
+}
+
+// a slow endpoint would otherwise hold up the tick
+static HOOKS: Worker<Hook> = Worker::new("webhook", Duration::from_secs(30), post);
+
+fn payload(timer: &str, event: HookEvent, at: SystemTime) -> Payload {
+    let content = match event {
+        HookEvent::Start => format!("{} started", timer),
+        HookEvent::Finish => format!("{} finished", timer),
+    };
+    Payload {
+        timer: timer.to_string(),
+        event,
+        timestamp: at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+        content,
+    }
+}
+
+/// Calls the webhook at `url` in the background
+pub fn send(url: &str, timer: &str, event: HookEvent) {
+    let Some((host, path)) = split(url) else {
+        log::warn!(
+            "Not calling the webhook of {}, it isn't an https URL",
+            timer
+        );
+        return;
+    };
+    HOOKS.send(Hook {
+        host: host.to_string(),
+        path: path.to_string(),
+        payload: payload(timer, event, SystemTime::now()),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn urls_and_payload() {
+        assert_eq!(
+            split("https://discord.com/api/webhooks/1/abc"),
+            Some(("discord.com", "/api/webhooks/1/abc"))
+        );
+        assert_eq!(split("https://example.com"), Some(("example.com", "/")));
+        assert_eq!(split("http://example.com/hook"), None);
+        assert_eq!(split("https:///hook"), None);
+
+        let at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let json = serde_json::to_value(payload("Burn", HookEvent::Finish, at)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "timer": "Burn",
+                "event": "finish",
+                "timestamp": 1_700_000_000u64,
+                "content": "Burn finished",
+            })
+        );
+    }
+}
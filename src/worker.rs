@@ -0,0 +1,116 @@
+use crate::sync::LockExt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender, TryRecvError};
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// A background thread that is spawned on the first job, stops itself after
+/// being idle for `idle` and is spawned again when the next job comes in.
+pub struct Worker<T: Send + 'static> {
+    name: &'static str,
+    idle: Duration,
+    handler: fn(T),
+    state: Mutex<Option<Running<T>>>,
+    // Set while stopping, jobs still queued are dropped instead of run
+    stopping: AtomicBool,
+}
+
+struct Running<T> {
+    tx: Sender<T>,
+    thread: JoinHandle<()>,
+}
+
+trait Stop: Sync {
+    fn stop(&self);
+}
+
+// Every worker that was ever spawned, so `unload` can stop all of them
+static SPAWNED: Mutex<Vec<&'static dyn Stop>> = Mutex::new(Vec::new());
+
+impl<T: Send + 'static> Worker<T> {
+    pub const fn new(name: &'static str, idle: Duration, handler: fn(T)) -> Self {
+        Self {
+            name,
+            idle,
+            handler,
+            state: Mutex::new(None),
+            stopping: AtomicBool::new(false),
+        }
+    }
+
+    fn run(&self, job: T) {
+        if !self.stopping.load(Ordering::Relaxed) {
+            (self.handler)(job);
+        }
+    }
+
+    pub fn send(&'static self, job: T) {
+        let mut state = self.state.locked();
+        let job = match state.as_ref() {
+            Some(running) => match running.tx.send(job) {
+                Ok(()) => return,
+                Err(e) => e.0,
+            },
+            None => job,
+        };
+        let (tx, rx) = channel();
+        tx.send(job).expect("Receiver to be alive");
+        log::debug!("Starting {} worker", self.name);
+        let thread = std::thread::Builder::new()
+            .name(format!("timers-{}", self.name))
+            .spawn(move || loop {
+                match rx.recv_timeout(self.idle) {
+                    Ok(job) => self.run(job),
+                    Err(RecvTimeoutError::Disconnected) => return,
+                    Err(RecvTimeoutError::Timeout) => {
+                        // Re-check under the lock so a job sent right now isn't lost
+                        let mut state = self.state.locked();
+                        match rx.try_recv() {
+                            Ok(job) => {
+                                drop(state);
+                                self.run(job);
+                            }
+                            Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => {
+                                log::debug!("Stopping idle {} worker", self.name);
+                                *state = None;
+                                return;
+                            }
+                        }
+                    }
+                }
+            })
+            .expect("Worker thread to spawn");
+        // the handle of a previous run that exited (if any) is dropped here
+        *state = Some(Running { tx, thread });
+        drop(state);
+
+        let mut spawned = SPAWNED.locked();
+        if !spawned.iter().any(|w| std::ptr::addr_eq(*w, self)) {
+            spawned.push(self);
+        }
+    }
+}
+
+impl<T: Send + 'static> Stop for Worker<T> {
+    /// Waits for the job at hand, the queued ones are dropped
+    fn stop(&self) {
+        self.stopping.store(true, Ordering::Relaxed);
+        let running = self.state.locked().take();
+        if let Some(Running { tx, thread }) = running {
+            drop(tx);
+            if thread.join().is_err() {
+                log::warn!("{} worker panicked", self.name);
+            }
+        }
+        self.stopping.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Stops every worker thread, called on unload so no thread outlives the addon.
+pub fn shutdown_all() {
+    let spawned = std::mem::take(&mut *SPAWNED.locked());
+    for worker in spawned {
+        worker.stop();
+    }
+}
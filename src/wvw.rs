@@ -0,0 +1,108 @@
+//! Objectives of the WvW maps, for timers of their Righteous Indignation. A captured
+//! objective can't be taken again for as long as it has the buff.
+
+use std::time::Duration;
+
+/// How long Righteous Indignation lasts
+pub const RIGHTEOUS_INDIGNATION: Duration = Duration::from_secs(5 * 60);
+
+pub struct WvwMap {
+    pub name: &'static str,
+    pub map_id: u32,
+    /// Of a borderland, told apart by it since they share objective names
+    pub color: Option<&'static str>,
+    pub objectives: &'static [&'static str],
+}
+
+const ALPINE: &[&str] = &[
+    "Garrison",
+    "Bay",
+    "Hills",
+    "North-west tower",
+    "North-east tower",
+    "South-west tower",
+    "South-east tower",
+    "North camp",
+    "North-west camp",
+    "North-east camp",
+    "South-west camp",
+    "South-east camp",
+    "South camp",
+];
+
+pub const MAPS: [WvwMap; 4] = [
+    WvwMap {
+        name: "Eternal Battlegrounds",
+        map_id: 38,
+        color: None,
+        objectives: &[
+            "Stonemist Castle",
+            "Overlook",
+            "Valley",
+            "Lowlands",
+            "Mendon's Gap",
+            "Veloka Slope",
+            "Anzalias Pass",
+            "Ogrewatch Cut",
+            "Quentin Lake",
+            "Durios Gulch",
+            "Bravost Escarpment",
+            "Langor Gulch",
+            "Aldon's Ledge",
+            "Wildcreek Run",
+            "Jerrifer's Slough",
+            "Klovan Gully",
+            "Speldan Clearcut",
+            "Pangloss Rise",
+            "Danelon Passage",
+            "Umberglade Woods",
+            "Golanta Clearing",
+            "Rogue's Quarry",
+        ],
+    },
+    WvwMap {
+        name: "Red Desert Borderlands",
+        map_id: 1099,
+        color: Some("Red"),
+        objectives: &[
+            "Fire Keep",
+            "Air Keep",
+            "Earth Keep",
+            "North-west tower",
+            "North-east tower",
+            "South-west tower",
+            "South-east tower",
+            "North camp",
+            "North-west camp",
+            "North-east camp",
+            "South-west camp",
+            "South-east camp",
+            "South camp",
+        ],
+    },
+    WvwMap {
+        name: "Blue Alpine Borderlands",
+        map_id: 96,
+        color: Some("Blue"),
+        objectives: ALPINE,
+    },
+    WvwMap {
+        name: "Green Alpine Borderlands",
+        map_id: 95,
+        color: Some("Green"),
+        objectives: ALPINE,
+    },
+];
+
+/// The WvW map of `map_id`, if it is one
+pub fn map(map_id: u32) -> Option<usize> {
+    MAPS.iter().position(|m| m.map_id == map_id)
+}
+
+/// Name of the timer of an objective on `map`
+pub fn timer_name(map: &WvwMap, objective: &str) -> String {
+    match map.color {
+        Some(color) => format!("RI {} {}", color, objective),
+        None => format!("RI {}", objective),
+    }
+}